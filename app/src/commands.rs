@@ -1,15 +1,20 @@
-use self::{attestation::AttestationCmd, elc::ELCCmd, enclave::EnclaveCmd, service::ServiceCmd};
+use self::{
+    attestation::AttestationCmd, elc::ELCCmd, enclave::EnclaveCmd, message::MessageCmd,
+    service::ServiceCmd, store::StoreCmd,
+};
+use crate::store::{into_host_store, AppStore};
 use crate::{enclave::build_enclave_loader, opts::Opts};
 use anyhow::Result;
 use clap::Parser;
-use host::environment::store::{host::HostStore, rocksdb::RocksDBStore};
 use host::environment::Environment;
 use std::sync::{Arc, RwLock};
 
 mod attestation;
 mod elc;
 mod enclave;
+mod message;
 mod service;
+mod store;
 
 /// Cli Subcommands
 #[allow(clippy::upper_case_acronyms)]
@@ -23,6 +28,10 @@ pub enum CliCmd {
     ELC(ELCCmd),
     #[clap(subcommand, display_order = 4, about = "Service subcommands")]
     Service(ServiceCmd),
+    #[clap(subcommand, display_order = 5, about = "Message subcommands")]
+    Message(MessageCmd),
+    #[clap(subcommand, display_order = 6, about = "Store subcommands")]
+    Store(StoreCmd),
 }
 
 impl CliCmd {
@@ -31,20 +40,22 @@ impl CliCmd {
         match self {
             CliCmd::Enclave(cmd) => {
                 Self::setup_read_only_env(opts);
-                cmd.run(opts, build_enclave_loader::<RocksDBStore>())
+                cmd.run(opts, build_enclave_loader::<AppStore>())
             }
             CliCmd::Attestation(cmd) => {
                 Self::setup_read_only_env(opts);
-                cmd.run(opts, build_enclave_loader::<RocksDBStore>())
+                cmd.run(opts, build_enclave_loader::<AppStore>())
             }
             CliCmd::Service(cmd) => {
                 Self::setup_env(opts);
-                cmd.run(opts, build_enclave_loader::<RocksDBStore>())
+                cmd.run(opts, build_enclave_loader::<AppStore>())
             }
             CliCmd::ELC(cmd) => {
                 Self::setup_env(opts);
-                cmd.run(opts, build_enclave_loader::<RocksDBStore>())
+                cmd.run(opts, build_enclave_loader::<AppStore>())
             }
+            CliCmd::Message(cmd) => cmd.run(),
+            CliCmd::Store(cmd) => cmd.run(opts),
         }
     }
 
@@ -58,13 +69,13 @@ impl CliCmd {
     }
 
     fn setup_env(opts: &Opts) {
-        let store = HostStore::RocksDB(RocksDBStore::open(opts.get_state_store_path()));
+        let store = into_host_store(AppStore::open(opts.get_state_store_path()));
         let env = Environment::new(opts.get_home(), Arc::new(RwLock::new(store)));
         host::set_environment(env).unwrap();
     }
 
     fn setup_read_only_env(opts: &Opts) {
-        let store = HostStore::RocksDB(RocksDBStore::open_read_only(opts.get_state_store_path()));
+        let store = into_host_store(AppStore::open_read_only(opts.get_state_store_path()));
         let env = Environment::new(opts.get_home(), Arc::new(RwLock::new(store)));
         host::set_environment(env).unwrap();
     }
@@ -0,0 +1,272 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as Base64Std, Engine};
+use clap::Parser;
+use commitments::{
+    EmittedState, MessageDomain, ProxyMessage, ValidationContext, MESSAGE_SCHEMA_VERSION,
+    MESSAGE_SCHEMA_VERSION_V2,
+};
+use crypto::verify_signature_address;
+use lcp_types::proto::google::protobuf::Any as RawAny;
+use lcp_types::proto::ibc::lightclients::lcp::v1::UpdateClientMessage as RawUpdateClientMessage;
+use lcp_types::Any;
+use prost::Message;
+use serde_json::{json, Value};
+
+const LCP_UPDATE_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.lightclients.lcp.v1.UpdateClientMessage";
+
+// `message` subcommand
+#[derive(Debug, Parser)]
+pub enum MessageCmd {
+    #[clap(
+        about = "Decode a ProxyMessage or an UpdateClientMessage and print it as JSON",
+        display_order = 1
+    )]
+    Decode(Decode),
+    #[clap(
+        about = "Print the differences between two messages",
+        display_order = 2
+    )]
+    Diff(Diff),
+}
+
+impl MessageCmd {
+    pub fn run(&self) -> Result<()> {
+        match self {
+            Self::Decode(cmd) => run_decode(cmd),
+            Self::Diff(cmd) => run_diff(cmd),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct Decode {
+    /// Hex or base64 encoded message
+    #[clap(help = "Hex or base64 encoded ProxyMessage or UpdateClientMessage")]
+    pub message: String,
+    /// Signatures of a bare ProxyMessage
+    #[clap(
+        long = "signature",
+        help = "Hex or base64 encoded signature of the ProxyMessage to recover the signer address"
+    )]
+    pub signatures: Vec<String>,
+}
+
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct Diff {
+    #[clap(help = "Hex or base64 encoded ProxyMessage or UpdateClientMessage")]
+    pub left: String,
+    #[clap(help = "Hex or base64 encoded ProxyMessage or UpdateClientMessage")]
+    pub right: String,
+}
+
+fn run_decode(cmd: &Decode) -> Result<()> {
+    let (proxy_message, mut signatures) = parse_message(decode_input(&cmd.message)?);
+    for signature in cmd.signatures.iter() {
+        signatures.push(decode_input(signature)?);
+    }
+    let decoded = message_to_json(&proxy_message, &signatures)?;
+    println!("{}", serde_json::to_string_pretty(&decoded)?);
+    Ok(())
+}
+
+fn run_diff(cmd: &Diff) -> Result<()> {
+    let (left, left_signatures) = parse_message(decode_input(&cmd.left)?);
+    let (right, right_signatures) = parse_message(decode_input(&cmd.right)?);
+    let mut diffs = vec![];
+    diff_json(
+        "",
+        &message_to_json(&left, &left_signatures)?,
+        &message_to_json(&right, &right_signatures)?,
+        &mut diffs,
+    );
+    println!("{}", serde_json::to_string_pretty(&diffs)?);
+    Ok(())
+}
+
+/// decode_input decodes a hex string (with or without `0x` prefix) or a base64 string
+fn decode_input(input: &str) -> Result<Vec<u8>> {
+    let input = input.trim();
+    if let Ok(bz) = hex::decode(input.strip_prefix("0x").unwrap_or(input)) {
+        return Ok(bz);
+    }
+    Base64Std
+        .decode(input)
+        .map_err(|e| anyhow!("the input is neither hex nor base64: {}", e))
+}
+
+/// parse_message returns the proxy message and its signatures
+///
+/// The input is either a proxy message, or an UpdateClientMessage that may be wrapped in Any.
+fn parse_message(bz: Vec<u8>) -> (Vec<u8>, Vec<Vec<u8>>) {
+    if ProxyMessage::from_bytes_with_encoding(&bz).is_ok() {
+        return (bz, vec![]);
+    }
+    let value = match RawAny::decode(bz.as_slice()) {
+        Ok(any) if any.type_url == LCP_UPDATE_CLIENT_MESSAGE_TYPE_URL => any.value,
+        _ => bz.clone(),
+    };
+    match RawUpdateClientMessage::decode(value.as_slice()) {
+        Ok(msg) if !msg.proxy_message.is_empty() => (msg.proxy_message, msg.signatures),
+        // the error of decoding the bytes as a proxy message is reported later
+        _ => (bz, vec![]),
+    }
+}
+
+fn message_to_json(bz: &[u8], signatures: &[Vec<u8>]) -> Result<Value> {
+    let (message, domain, encoding) = ProxyMessage::from_bytes_with_encoding(bz)
+        .map_err(|e| anyhow!("failed to decode the proxy message: {}", e))?;
+    let version = if domain.is_some() {
+        MESSAGE_SCHEMA_VERSION_V2
+    } else {
+        MESSAGE_SCHEMA_VERSION
+    };
+    let signatures: Vec<Value> = signatures
+        .iter()
+        .map(|signature| {
+            // an operator without a signature is represented as an empty signature
+            let signer = if signature.is_empty() {
+                Value::Null
+            } else {
+                match verify_signature_address(bz, signature) {
+                    Ok(signer) => json!(signer.to_hex_string()),
+                    Err(e) => json!({ "error": e.to_string() }),
+                }
+            };
+            json!({
+                "signature": hex_string(signature),
+                "signer": signer,
+            })
+        })
+        .collect();
+    Ok(json!({
+        "header": {
+            "version": version,
+            "type": message.message_type(),
+            "encoding": encoding.to_string(),
+        },
+        "domain": domain.as_ref().map(domain_to_json),
+        "message": proxy_message_to_json(&message),
+        "signatures": signatures,
+    }))
+}
+
+fn proxy_message_to_json(message: &ProxyMessage) -> Value {
+    match message {
+        ProxyMessage::UpdateState(m) => json!({
+            "UpdateState": {
+                "prev_height": m.prev_height.map(|h| h.to_string()),
+                "prev_state_id": m.prev_state_id.map(|id| id.to_string()),
+                "post_height": m.post_height.to_string(),
+                "post_state_id": m.post_state_id.to_string(),
+                "timestamp": m.timestamp.to_string(),
+                "context": context_to_json(&m.context),
+                "emitted_states": m.emitted_states.iter().map(emitted_state_to_json).collect::<Vec<_>>(),
+                "emitted_states_root": m.emitted_states_root.map(|root| hex_string(&root)),
+            }
+        }),
+        ProxyMessage::VerifyMembership(m) => json!({
+            "VerifyMembership": {
+                "prefix": hex_string(&m.prefix),
+                "path": m.path,
+                "value": m.value.map(|v| hex_string(&v)),
+                "height": m.height.to_string(),
+                "state_id": m.state_id.to_string(),
+            }
+        }),
+        ProxyMessage::Misbehaviour(m) => json!({
+            "Misbehaviour": {
+                "prev_states": m.prev_states.iter().map(|s| json!({
+                    "height": s.height.to_string(),
+                    "state_id": s.state_id.to_string(),
+                })).collect::<Vec<_>>(),
+                "context": context_to_json(&m.context),
+                "client_message": any_to_json(&m.client_message),
+            }
+        }),
+        ProxyMessage::VerifyMembershipBatch(m) => json!({
+            "VerifyMembershipBatch": {
+                "prefix": hex_string(&m.prefix),
+                "entries": m.entries.iter().map(|e| json!({
+                    "path": e.path,
+                    "value": e.value.map(|v| hex_string(&v)),
+                })).collect::<Vec<_>>(),
+                "height": m.height.to_string(),
+                "state_id": m.state_id.to_string(),
+            }
+        }),
+    }
+}
+
+fn context_to_json(context: &ValidationContext) -> Value {
+    match context {
+        ValidationContext::Empty => json!({ "type": "Empty" }),
+        ValidationContext::TrustingPeriod(ctx) => json!({
+            "type": "TrustingPeriod",
+            "trusting_period": format!("{:?}", ctx.trusting_period()),
+            "clock_drift": format!("{:?}", ctx.clock_drift()),
+            "untrusted_header_timestamp": ctx.untrusted_header_timestamp().to_string(),
+            "trusted_state_timestamp": ctx.trusted_state_timestamp().to_string(),
+        }),
+        ValidationContext::HostHeight(ctx) => json!({
+            "type": "HostHeight",
+            "valid_until": ctx.valid_until().to_string(),
+        }),
+    }
+}
+
+fn emitted_state_to_json(state: &EmittedState) -> Value {
+    json!({
+        "height": state.0.to_string(),
+        "state": any_to_json(&state.1),
+    })
+}
+
+fn domain_to_json(domain: &MessageDomain) -> Value {
+    json!({
+        "chain_id": domain.chain_id,
+        "client_id": domain.client_id,
+    })
+}
+
+fn any_to_json(any: &Any) -> Value {
+    json!({
+        "type_url": any.type_url,
+        "value": hex_string(&any.value),
+    })
+}
+
+fn hex_string(bz: &[u8]) -> String {
+    format!("0x{}", hex::encode(bz))
+}
+
+/// diff_json collects the values that differ between `left` and `right` with their JSON pointer paths
+fn diff_json(path: &str, left: &Value, right: &Value, diffs: &mut Vec<Value>) {
+    match (left, right) {
+        (Value::Object(l), Value::Object(r)) => {
+            for key in l.keys().chain(r.keys().filter(|k| !l.contains_key(*k))) {
+                diff_json(
+                    &format!("{}/{}", path, key),
+                    l.get(key).unwrap_or(&Value::Null),
+                    r.get(key).unwrap_or(&Value::Null),
+                    diffs,
+                );
+            }
+        }
+        (Value::Array(l), Value::Array(r)) => {
+            for i in 0..l.len().max(r.len()) {
+                diff_json(
+                    &format!("{}/{}", path, i),
+                    l.get(i).unwrap_or(&Value::Null),
+                    r.get(i).unwrap_or(&Value::Null),
+                    diffs,
+                );
+            }
+        }
+        (l, r) if l != r => diffs.push(json!({
+            "path": path,
+            "left": l,
+            "right": r,
+        })),
+        _ => {}
+    }
+}
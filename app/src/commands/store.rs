@@ -0,0 +1,562 @@
+use crate::opts::{EnclaveOpts, Opts};
+use crate::store::AppStore;
+use anyhow::{anyhow, bail, Result};
+use clap::Parser;
+use crypto::Keccak256;
+use host::store::{
+    transaction::{CommitStore, CreatedTx, Tx, TxAccessor},
+    KVPair,
+};
+use keymanager::{EnclaveKeyManager, KEY_MANAGER_DB};
+use lcp_types::{Height, Mrenclave, Time};
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use tiny_keccak::{Hasher, Keccak};
+
+const ARCHIVE_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const STATE_ENTRY: &str = "state.bin";
+const KEY_MANAGER_ENTRY: &str = KEY_MANAGER_DB;
+
+// `store` subcommand
+#[derive(Debug, Parser)]
+pub enum StoreCmd {
+    #[clap(
+        about = "Export the state store and the key manager to an archive",
+        display_order = 1
+    )]
+    Export(Export),
+    #[clap(
+        about = "Import an archive into a fresh home directory",
+        display_order = 2
+    )]
+    Import(Import),
+}
+
+impl StoreCmd {
+    pub fn run(&self, opts: &Opts) -> Result<()> {
+        match self {
+            Self::Export(cmd) => run_export(opts, cmd),
+            Self::Import(cmd) => run_import(opts, cmd),
+        }
+    }
+}
+
+/// `Manifest` describes the contents of an archive
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    /// Unix timestamp in seconds
+    created_at: u64,
+    /// MRENCLAVE of the enclave that owns the state
+    mrenclave: String,
+    clients: Vec<ClientInfo>,
+    /// Number of the key-value pairs in the state
+    entries: usize,
+    /// Keccak256 of the state entry
+    state_checksum: String,
+    /// Keccak256 of the key manager entry
+    key_manager_checksum: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct ClientInfo {
+    client_id: String,
+    /// None if the client has no consensus state or its keys are not readable from the host
+    latest_height: Option<Height>,
+}
+
+/// This command exports the state store and the key manager of the home directory to an archive.
+///
+/// The state is read from a snapshot of the database, so the command can be run while the service is running.
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct Export {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Path to the archive to create
+    #[clap(long = "output", help = "Path to the archive to create")]
+    pub output: PathBuf,
+}
+
+fn run_export(opts: &Opts, cmd: &Export) -> Result<()> {
+    let mrenclave = get_mrenclave(opts, &cmd.enclave)?;
+    let manifest = export_archive(
+        &opts.get_home(),
+        &opts.get_state_store_path(),
+        &mrenclave,
+        &cmd.output,
+    )?;
+    info!(
+        "exported {} entries of {} clients to {:?}",
+        manifest.entries,
+        manifest.clients.len(),
+        cmd.output
+    );
+    println!("{}", serde_json::to_string(&manifest)?);
+    Ok(())
+}
+
+/// `export_archive` writes the state store and the key manager in `home` to an archive at `output`
+///
+/// The state is streamed into a temporary file from a snapshot of the state store,
+/// and then the key manager is backed up. This order must be kept:
+/// the key manager copy is never older than the state, so it contains every enclave key generated before the snapshot.
+fn export_archive(
+    home: &Path,
+    state_path: &Path,
+    mrenclave: &Mrenclave,
+    output: &Path,
+) -> Result<Manifest> {
+    if output.exists() {
+        bail!("the output already exists: {:?}", output);
+    }
+    if !state_path.exists() {
+        bail!("the state store does not exist: {:?}", state_path);
+    }
+    if !home.join(KEY_MANAGER_DB).exists() {
+        bail!("the key manager does not exist: {:?}", home);
+    }
+
+    let state = TempFile::new(output, "state")?;
+    let summary = dump_state(state_path, &state.0)?;
+    let key_manager = backup_key_manager(home, output)?;
+    let manifest = Manifest {
+        version: ARCHIVE_VERSION,
+        created_at: Time::now().as_unix_timestamp_secs(),
+        mrenclave: mrenclave.to_hex_string(),
+        clients: summary.clients,
+        entries: summary.entries,
+        state_checksum: hex::encode(summary.checksum),
+        key_manager_checksum: hex::encode(key_manager.keccak256()),
+    };
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(output)?;
+    let mut builder = tar::Builder::new(file);
+    let manifest_bz = serde_json::to_vec_pretty(&manifest)?;
+    append_entry(
+        &mut builder,
+        MANIFEST_ENTRY,
+        manifest_bz.len() as u64,
+        manifest_bz.as_slice(),
+    )?;
+    let state_file = File::open(&state.0)?;
+    let state_size = state_file.metadata()?.len();
+    append_entry(&mut builder, STATE_ENTRY, state_size, state_file)?;
+    append_entry(
+        &mut builder,
+        KEY_MANAGER_ENTRY,
+        key_manager.len() as u64,
+        key_manager.as_slice(),
+    )?;
+    builder.into_inner()?.sync_all()?;
+    Ok(manifest)
+}
+
+/// This command imports an archive created by `export` into a fresh home directory.
+///
+/// The archive is rejected if its checksums do not match or if it was exported for another enclave.
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct Import {
+    /// Options for enclave
+    #[clap(flatten)]
+    pub enclave: EnclaveOpts,
+    /// Path to the archive to import
+    #[clap(long = "input", help = "Path to the archive to import")]
+    pub input: PathBuf,
+}
+
+fn run_import(opts: &Opts, cmd: &Import) -> Result<()> {
+    let mrenclave = get_mrenclave(opts, &cmd.enclave)?;
+    let manifest = import_archive(
+        &opts.get_home(),
+        &opts.get_state_store_path(),
+        &mrenclave,
+        &cmd.input,
+    )?;
+    info!(
+        "imported {} entries of {} clients into {:?}",
+        manifest.entries,
+        manifest.clients.len(),
+        opts.get_home()
+    );
+    Ok(())
+}
+
+/// `import_archive` imports the archive at `input` into the fresh `home`
+fn import_archive(
+    home: &Path,
+    state_path: &Path,
+    mrenclave: &Mrenclave,
+    input: &Path,
+) -> Result<Manifest> {
+    let km_path = home.join(KEY_MANAGER_DB);
+    let state_exists =
+        state_path.is_file() || (state_path.is_dir() && state_path.read_dir()?.next().is_some());
+    if km_path.exists() || state_exists {
+        bail!("the home directory is not fresh: {:?}", home);
+    }
+
+    let (manifest, state, key_manager) = read_archive(input)?;
+    if manifest.version != ARCHIVE_VERSION {
+        bail!("unsupported archive version: {}", manifest.version);
+    }
+    if hex::encode(state.keccak256()) != manifest.state_checksum {
+        bail!("state checksum mismatch");
+    }
+    if hex::encode(key_manager.keccak256()) != manifest.key_manager_checksum {
+        bail!("key manager checksum mismatch");
+    }
+    if mrenclave.to_hex_string() != manifest.mrenclave {
+        bail!(
+            "mrenclave mismatch: expected={} actual={}",
+            manifest.mrenclave,
+            mrenclave
+        );
+    }
+    let entries = decode_state(&state)?;
+    let mut clients = ClientCollector::default();
+    entries.iter().for_each(|(key, _)| clients.add(key));
+    if entries.len() != manifest.entries || clients.finish() != manifest.clients {
+        bail!("the state does not match the manifest");
+    }
+
+    fs::create_dir_all(home)?;
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&km_path)?
+        .write_all(&key_manager)?;
+    EnclaveKeyManager::new(home)?.all_keys()?;
+
+    let store = write_state(state_path, entries)?;
+    let mut imported = 0;
+    let mut clients = ClientCollector::default();
+    store.for_each(|key, _| -> Result<()> {
+        imported += 1;
+        clients.add(key);
+        Ok(())
+    })?;
+    if imported != manifest.entries || clients.finish() != manifest.clients {
+        bail!("the imported state does not match the manifest");
+    }
+    Ok(manifest)
+}
+
+/// `write_state` writes the key-value pairs into the state store in a single transaction
+fn write_state(state_path: &Path, entries: Vec<KVPair>) -> Result<AppStore> {
+    let mut store = AppStore::open(state_path);
+    let tx = store
+        .create_transaction(Some("import".to_owned()))?
+        .prepare()?;
+    store.begin(&tx)?;
+    let batch = entries.into_iter().map(|(k, v)| (k, Some(v))).collect();
+    if let Err(e) = store.tx_write_batch(tx.get_id(), batch) {
+        store.rollback(tx);
+        return Err(e.into());
+    }
+    store.commit(tx)?;
+    Ok(store)
+}
+
+fn get_mrenclave(opts: &Opts, enclave: &EnclaveOpts) -> Result<Mrenclave> {
+    let metadata = host::sgx_get_metadata(
+        enclave
+            .path
+            .clone()
+            .unwrap_or_else(|| opts.default_enclave()),
+    )?;
+    Ok(Mrenclave::from(metadata.enclave_css.body.enclave_hash.m))
+}
+
+/// `StateSummary` describes the state written by `dump_state`
+struct StateSummary {
+    entries: usize,
+    checksum: [u8; 32],
+    clients: Vec<ClientInfo>,
+}
+
+/// `dump_state` streams all key-value pairs in a snapshot of the state store to `path`
+///
+/// The pairs are encoded as a sequence of length-prefixed keys and values.
+fn dump_state(state_path: &Path, path: &Path) -> Result<StateSummary> {
+    let store = AppStore::open_read_only(state_path);
+    let file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    let mut writer = HashWriter::new(BufWriter::new(file));
+    let mut entries = 0;
+    let mut clients = ClientCollector::default();
+    store.for_each(|key, value| -> Result<()> {
+        for data in [key, value] {
+            writer.write_all(&(data.len() as u32).to_be_bytes())?;
+            writer.write_all(data)?;
+        }
+        entries += 1;
+        clients.add(key);
+        Ok(())
+    })?;
+    let (writer, checksum) = writer.finish();
+    writer.into_inner()?.sync_all()?;
+    Ok(StateSummary {
+        entries,
+        checksum,
+        clients: clients.finish(),
+    })
+}
+
+/// `HashWriter` computes the Keccak256 of the data written to the inner writer
+struct HashWriter<W> {
+    inner: W,
+    hasher: Keccak,
+}
+
+impl<W: Write> HashWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Keccak::v256(),
+        }
+    }
+
+    fn finish(self) -> (W, [u8; 32]) {
+        let mut hash = [0u8; 32];
+        self.hasher.finalize(&mut hash);
+        (self.inner, hash)
+    }
+}
+
+impl<W: Write> Write for HashWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// `TempFile` is a temporary file next to the output, which is removed when dropped
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(output: &Path, suffix: &str) -> Result<Self> {
+        let file_name = output
+            .file_name()
+            .ok_or_else(|| anyhow!("invalid output: {:?}", output))?;
+        Ok(Self(output.with_file_name(format!(
+            ".{}.{}.tmp",
+            file_name.to_string_lossy(),
+            suffix
+        ))))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.0) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!(
+                    "failed to remove a temporary file: path={:?} err={}",
+                    self.0, e
+                );
+            }
+        }
+    }
+}
+
+/// `backup_key_manager` returns a consistent copy of the key manager database
+///
+/// The copy is written to a temporary file next to `output` and removed after it is read.
+fn backup_key_manager(home: &Path, output: &Path) -> Result<Vec<u8>> {
+    let tmp = TempFile::new(output, "km")?;
+    EnclaveKeyManager::new(home)?.backup(&tmp.0)?;
+    Ok(fs::read(&tmp.0)?)
+}
+
+/// `ClientCollector` collects the clients found in the state with their latest consensus heights
+#[derive(Default)]
+struct ClientCollector(BTreeMap<String, Option<Height>>);
+
+impl ClientCollector {
+    fn add(&mut self, key: &[u8]) {
+        let Some((client_id, path)) = std::str::from_utf8(key)
+            .ok()
+            .and_then(|key| key.strip_prefix("clients/"))
+            .and_then(|key| key.split_once('/'))
+        else {
+            return;
+        };
+        let height = path
+            .strip_prefix("consensusStates/")
+            .and_then(|height| height.split_once('-'))
+            .and_then(|(epoch, height)| {
+                Some(Height::new(epoch.parse().ok()?, height.parse().ok()?))
+            });
+        let latest_height = self.0.entry(client_id.to_owned()).or_default();
+        if height > *latest_height {
+            *latest_height = height;
+        }
+    }
+
+    fn finish(self) -> Vec<ClientInfo> {
+        self.0
+            .into_iter()
+            .map(|(client_id, latest_height)| ClientInfo {
+                client_id,
+                latest_height,
+            })
+            .collect()
+    }
+}
+
+fn decode_state(mut bz: &[u8]) -> Result<Vec<KVPair>> {
+    fn take(bz: &mut &[u8]) -> Result<Vec<u8>> {
+        if bz.len() < 4 {
+            bail!("truncated state");
+        }
+        let (len, rest) = bz.split_at(4);
+        let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+        if rest.len() < len {
+            bail!("truncated state");
+        }
+        let (data, rest) = rest.split_at(len);
+        *bz = rest;
+        Ok(data.to_vec())
+    }
+
+    let mut entries: Vec<KVPair> = Vec::new();
+    while !bz.is_empty() {
+        let key = take(&mut bz)?;
+        let value = take(&mut bz)?;
+        if matches!(entries.last(), Some((last, _)) if *last >= key) {
+            bail!("the keys in the state are not sorted");
+        }
+        entries.push((key, value));
+    }
+    Ok(entries)
+}
+
+fn append_entry(
+    builder: &mut tar::Builder<File>,
+    name: &str,
+    size: u64,
+    data: impl Read,
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(size);
+    header.set_mode(0o600);
+    builder.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+/// `read_archive` returns the manifest, the state and the key manager in the archive
+fn read_archive(path: &Path) -> Result<(Manifest, Vec<u8>, Vec<u8>)> {
+    let mut archive = tar::Archive::new(File::open(path)?);
+    let mut files = BTreeMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().to_string();
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        if files.insert(name.clone(), data).is_some() {
+            bail!("duplicate entry in the archive: {}", name);
+        }
+    }
+    let mut take = |name: &str| {
+        files
+            .remove(name)
+            .ok_or_else(|| anyhow!("missing entry in the archive: {}", name))
+    };
+    let manifest = serde_json::from_slice(&take(MANIFEST_ENTRY)?)?;
+    let state = take(STATE_ENTRY)?;
+    let key_manager = take(KEY_MANAGER_ENTRY)?;
+    if let Some(name) = files.keys().next() {
+        bail!("unexpected entry in the archive: {}", name);
+    }
+    Ok((manifest, state, key_manager))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::STATE_STORE_NAME;
+
+    #[test]
+    fn test_export_import() {
+        let src = tempfile::tempdir().unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        let mrenclave = Mrenclave::from([1u8; 32]);
+        let entries: Vec<KVPair> = [
+            ("clients/07-tendermint-0/clientState", "cs"),
+            ("clients/07-tendermint-0/consensusStates/0-10", "cs10"),
+            ("clients/07-tendermint-0/consensusStates/0-9", "cs9"),
+            ("clients/07-tendermint-1/clientState", "cs"),
+            ("nextClientSequence", "2"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.as_bytes().to_vec(), v.as_bytes().to_vec()))
+        .collect();
+
+        let src_state = src.path().join(STATE_STORE_NAME);
+        drop(write_state(&src_state, entries.clone()).unwrap());
+        EnclaveKeyManager::new(src.path()).unwrap();
+
+        let archive = src.path().join("archive.tar");
+        let exported = export_archive(src.path(), &src_state, &mrenclave, &archive).unwrap();
+        assert_eq!(exported.entries, entries.len());
+        assert_eq!(
+            exported.clients,
+            vec![
+                ClientInfo {
+                    client_id: "07-tendermint-0".to_owned(),
+                    latest_height: Some(Height::new(0, 10)),
+                },
+                ClientInfo {
+                    client_id: "07-tendermint-1".to_owned(),
+                    latest_height: None,
+                },
+            ]
+        );
+        // the temporary files are removed
+        assert!(fs::read_dir(src.path()).unwrap().all(|entry| !entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .ends_with(".tmp")));
+        // the output must not exist
+        assert!(export_archive(src.path(), &src_state, &mrenclave, &archive).is_err());
+
+        // the archive is rejected by another enclave
+        let dst_state = dst.path().join(STATE_STORE_NAME);
+        let other = Mrenclave::from([2u8; 32]);
+        assert!(import_archive(dst.path(), &dst_state, &other, &archive).is_err());
+
+        let imported = import_archive(dst.path(), &dst_state, &mrenclave, &archive).unwrap();
+        assert_eq!(imported.clients, exported.clients);
+        let mut pairs = Vec::new();
+        AppStore::open_read_only(&dst_state)
+            .for_each(|key, value| -> Result<()> {
+                pairs.push((key.to_vec(), value.to_vec()));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(pairs, entries);
+        assert_eq!(
+            fs::read(dst.path().join(KEY_MANAGER_DB))
+                .unwrap()
+                .keccak256(),
+            hex::decode(&exported.key_manager_checksum).unwrap()[..]
+        );
+
+        // the home directory must be fresh
+        assert!(import_archive(dst.path(), &dst_state, &mrenclave, &archive).is_err());
+    }
+}
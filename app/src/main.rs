@@ -6,6 +6,7 @@ mod cli;
 mod commands;
 mod enclave;
 mod opts;
+mod store;
 
 fn main() -> Result<()> {
     Cli::parse().run()
@@ -1,3 +1,4 @@
+use crate::store::STATE_STORE_NAME;
 use anyhow::{anyhow, Result};
 use clap::Parser;
 use log::LevelFilter;
@@ -35,7 +36,7 @@ impl Opts {
     }
 
     pub fn get_state_store_path(&self) -> PathBuf {
-        self.get_home().join("state")
+        self.get_home().join(STATE_STORE_NAME)
     }
 
     pub fn get_log_level_filter(&self) -> Result<Option<LevelFilter>> {
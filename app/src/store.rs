@@ -0,0 +1,30 @@
+use host::environment::store::host::HostStore;
+
+#[cfg(not(any(feature = "rocksdb", feature = "sqlite")))]
+compile_error!("either the `rocksdb` or `sqlite` feature must be enabled");
+
+/// `AppStore` is the state store of the host
+///
+/// `SQLiteStore` is selected if the `sqlite` feature is enabled, otherwise `RocksDBStore` is selected.
+#[cfg(feature = "sqlite")]
+pub type AppStore = host::environment::store::sqlite::SQLiteStore;
+#[cfg(all(feature = "rocksdb", not(feature = "sqlite")))]
+pub type AppStore = host::environment::store::rocksdb::RocksDBStore;
+
+/// The name of the state store in the home directory
+#[cfg(feature = "sqlite")]
+pub const STATE_STORE_NAME: &str = "state.sqlite";
+#[cfg(all(feature = "rocksdb", not(feature = "sqlite")))]
+pub const STATE_STORE_NAME: &str = "state";
+
+/// `into_host_store` converts the state store into `HostStore`
+#[cfg(feature = "sqlite")]
+pub fn into_host_store(store: AppStore) -> HostStore {
+    HostStore::SQLite(store)
+}
+
+/// `into_host_store` converts the state store into `HostStore`
+#[cfg(all(feature = "rocksdb", not(feature = "sqlite")))]
+pub fn into_host_store(store: AppStore) -> HostStore {
+    HostStore::RocksDB(store)
+}
@@ -4,7 +4,10 @@ use context::Context;
 use crypto::{EnclavePublicKey, Signer, Verifier};
 use ecall_commands::{AggregateMessagesInput, AggregateMessagesResponse, LightClientResponse};
 use light_client::{
-    commitments::{self, prove_commitment, ProxyMessage, UpdateStateProxyMessage},
+    commitments::{
+        self, prove_commitment_with_encoding, MessageDomain, MessageEncoding,
+        MisbehaviourProxyMessage, ProxyMessage, UpdateStateProxyMessage,
+    },
     HostContext, LightClientResolver,
 };
 use store::KVStore;
@@ -30,31 +33,84 @@ pub fn aggregate_messages<R: LightClientResolver, S: KVStore, K: Signer>(
     let messages = input
         .messages
         .into_iter()
-        .map(|m| ProxyMessage::from_bytes(&m)?.try_into())
-        .collect::<Result<Vec<_>, _>>()?
+        .map(|m| ProxyMessage::from_bytes_with_encoding(&m))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // all messages must be bound to the same domain and share the same encoding, and the aggregated message inherits both
+    let (domain, encoding) = (messages[0].1.clone(), messages[0].2);
+    if messages.iter().any(|(_, d, _)| *d != domain) {
+        return Err(Error::invalid_argument(
+            "messages must be bound to the same domain".into(),
+        ));
+    }
+    if messages.iter().any(|(_, _, e)| *e != encoding) {
+        return Err(Error::invalid_argument(
+            "messages must have the same encoding".into(),
+        ));
+    }
+
+    let messages = messages
         .into_iter()
         .zip(input.signatures.iter())
-        .map(|(m, s)| -> Result<_, Error> {
-            verify_message(&pk, &m, s)?;
-            m.context.validate(ctx.host_timestamp())?;
+        .map(|((m, _, _), s)| -> Result<_, Error> {
+            verify_message(&pk, &m, domain.clone(), encoding, s)?;
             Ok(m)
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    let message = ProxyMessage::from(commitments::aggregate_messages(messages)?);
-    let proof = prove_commitment(ek, message)?;
+    // the messages are aggregated according to the type of the first message
+    //
+    // NOTE: the enclave doesn't know the height of the counterparty chain, so a host height bound is checked by the LCP client
+    let first = messages[0].clone();
+    let (message, emitted_states_root_proofs) = match first {
+        ProxyMessage::UpdateState(_) => {
+            let messages = messages
+                .into_iter()
+                .map(|m| -> Result<_, Error> {
+                    let m: UpdateStateProxyMessage = m.try_into()?;
+                    m.context
+                        .validate(ctx.host_timestamp(), ctx.host_height())?;
+                    Ok(m)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let (message, proofs) = commitments::aggregate_messages_with_proofs(messages)?;
+            (ProxyMessage::from(message), proofs)
+        }
+        ProxyMessage::Misbehaviour(_) => {
+            let messages = messages
+                .into_iter()
+                .map(|m| -> Result<_, Error> {
+                    let m: MisbehaviourProxyMessage = m.try_into()?;
+                    m.context
+                        .validate(ctx.host_timestamp(), ctx.host_height())?;
+                    Ok(m)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let message = commitments::aggregate_misbehaviour_messages(messages)?;
+            (ProxyMessage::from(message), vec![])
+        }
+        _ => {
+            return Err(Error::invalid_argument(format!(
+                "unsupported message type for aggregation: {}",
+                first.message_type()
+            )))
+        }
+    };
+    let proof = prove_commitment_with_encoding(ek, message, domain, encoding)?;
 
     Ok(LightClientResponse::AggregateMessages(
-        AggregateMessagesResponse(proof),
+        AggregateMessagesResponse(proof, emitted_states_root_proofs),
     ))
 }
 
 fn verify_message(
     verifier: &EnclavePublicKey,
-    message: &UpdateStateProxyMessage,
+    message: &ProxyMessage,
+    domain: Option<MessageDomain>,
+    encoding: MessageEncoding,
     signature: &[u8],
 ) -> Result<(), Error> {
-    let message_bytes = ProxyMessage::UpdateState(message.clone()).to_bytes();
+    let message_bytes = message.clone().to_bytes_with_encoding(domain, encoding);
     verifier
         .verify(&message_bytes, signature)
         .map_err(Error::crypto)?;
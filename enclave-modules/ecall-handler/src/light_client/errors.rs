@@ -23,6 +23,27 @@ define_error! {
             format_args!("client already exists: client_id={}", e.client_id)
         },
 
+        InconsistentBatchStateId
+        {
+            expected: light_client::commitments::StateID,
+            actual: light_client::commitments::StateID
+        }
+        |e| {
+            format_args!("inconsistent state_id in batch: expected={} actual={}", e.expected, e.actual)
+        },
+
+        CountersignMessageMismatch
+        {
+            expected: Vec<u8>,
+            actual: Vec<u8>
+        }
+        |e| {
+            format_args!("countersign message mismatch: expected={:?} actual={:?}", e.expected, e.actual)
+        },
+
+        CountersignUnprovenMessage
+        |_| { "the light client doesn't prove the message, so it cannot be countersigned" },
+
         LightClient
         [light_client::Error]
         |_| { "LightClient error" },
@@ -39,6 +60,10 @@ define_error! {
         [crypto::Error]
         |_| { "Crypto error" },
 
+        Store
+        [store::Error]
+        |_| { "Store error" },
+
         LcpType
         {}
         [lcp_types::TypeError]
@@ -3,8 +3,8 @@ pub use errors::Error;
 pub use init_client::init_client;
 pub use query::query_client;
 pub use router::dispatch;
-pub use update_client::update_client;
-pub use verify_state::{verify_membership, verify_non_membership};
+pub use update_client::{countersign_update_client, update_client};
+pub use verify_state::{verify_membership, verify_membership_batch, verify_non_membership};
 
 mod aggregate_messages;
 mod errors;
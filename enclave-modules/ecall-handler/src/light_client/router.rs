@@ -1,6 +1,6 @@
 use crate::light_client::{
-    aggregate_messages, init_client, query_client, update_client, verify_membership,
-    verify_non_membership, Error,
+    aggregate_messages, countersign_update_client, init_client, query_client, update_client,
+    verify_membership, verify_membership_batch, verify_non_membership, Error,
 };
 use context::Context;
 use crypto::NopSigner;
@@ -27,13 +27,19 @@ pub fn dispatch<E: Env>(
                 &sealed_ek,
                 cctx.current_timestamp,
             );
-            match cmd {
-                InitClient(input) => init_client(&mut ctx, input)?,
-                UpdateClient(input) => update_client(&mut ctx, input)?,
-                AggregateMessages(input) => aggregate_messages(&mut ctx, input)?,
-                VerifyMembership(input) => verify_membership(&mut ctx, input)?,
-                VerifyNonMembership(input) => verify_non_membership(&mut ctx, input)?,
-            }
+            let res = match cmd {
+                InitClient(input) => init_client(&mut ctx, input),
+                UpdateClient(input) => update_client(&mut ctx, input),
+                AggregateMessages(input) => aggregate_messages(&mut ctx, input),
+                VerifyMembership(input) => verify_membership(&mut ctx, input),
+                VerifyNonMembership(input) => verify_non_membership(&mut ctx, input),
+                VerifyMembershipBatch(input) => verify_membership_batch(&mut ctx, input),
+                CountersignUpdateClient(input) => countersign_update_client(&mut ctx, input),
+            };
+            ctx.into_store()
+                .finalize(res.is_ok())
+                .map_err(Error::store)?;
+            res?
         }
         LightClientCommand::Query(cmd) => {
             use LightClientQueryCommand::*;
@@ -43,9 +49,13 @@ pub fn dispatch<E: Env>(
                 &NopSigner,
                 cctx.current_timestamp,
             );
-            match cmd {
-                QueryClient(input) => query_client(&mut ctx, input)?,
-            }
+            let res = match cmd {
+                QueryClient(input) => query_client(&mut ctx, input),
+            };
+            ctx.into_store()
+                .finalize(res.is_ok())
+                .map_err(Error::store)?;
+            res?
         }
     };
     Ok(CommandResponse::LightClient(res))
@@ -3,8 +3,13 @@ use crate::light_client::Error;
 use crate::prelude::*;
 use context::Context;
 use crypto::Signer;
-use ecall_commands::{LightClientResponse, UpdateClientInput, UpdateClientResponse};
-use light_client::commitments::{prove_commitment, CommitmentProof, EmittedState, ProxyMessage};
+use ecall_commands::{
+    CountersignUpdateClientInput, CountersignUpdateClientResponse, LightClientResponse,
+    UpdateClientInput, UpdateClientResponse,
+};
+use light_client::commitments::{
+    prove_commitment_with_encoding, CommitmentProof, EmittedState, ProxyMessage,
+};
 use light_client::{ClientKeeper, LightClientResolver, UpdateClientResult};
 use store::KVStore;
 
@@ -12,17 +17,54 @@ pub fn update_client<R: LightClientResolver, S: KVStore, K: Signer>(
     ctx: &mut Context<R, S, K>,
     input: UpdateClientInput,
 ) -> Result<LightClientResponse, Error> {
+    Ok(LightClientResponse::UpdateClient(execute_update_client(
+        ctx, input,
+    )?))
+}
+
+/// countersign_update_client re-runs the update processed by another LCP node and signs the resulting message
+///
+/// The message is signed only if it is identical to the message produced by the other node,
+/// so the signatures of multiple nodes can be combined into a multi-signature `UpdateClientMessage`.
+pub fn countersign_update_client<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: CountersignUpdateClientInput,
+) -> Result<LightClientResponse, Error> {
+    let UpdateClientResponse(proof, _) = execute_update_client(ctx, input.update)?;
+    if proof.message != input.message {
+        return Err(Error::countersign_message_mismatch(
+            input.message,
+            proof.message,
+        ));
+    }
+    if !proof.is_proven() {
+        return Err(Error::countersign_unproven_message());
+    }
+    Ok(LightClientResponse::CountersignUpdateClient(
+        CountersignUpdateClientResponse(proof),
+    ))
+}
+
+fn execute_update_client<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: UpdateClientInput,
+) -> Result<UpdateClientResponse, Error> {
+    // the consensus states stored before the height index was introduced are indexed at the first update
+    ctx.index_consensus_heights(&input.client_id)?;
     let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
     let ek = ctx.get_enclave_key();
     match lc.update_client(ctx, input.client_id.clone(), input.any_header)? {
         UpdateClientResult::UpdateState(mut data) => {
-            let message: ProxyMessage = {
-                if input.include_state && data.message.emitted_states.is_empty() {
-                    data.message.emitted_states =
-                        vec![EmittedState(data.height, data.new_any_client_state.clone())];
-                }
-                data.message.into()
+            if input.include_state && data.message.emitted_states.is_empty() {
+                data.message.emitted_states =
+                    vec![EmittedState(data.height, data.new_any_client_state.clone())];
+            }
+            let emitted_state_proofs = if input.commit_emitted_states_root {
+                data.message.commit_emitted_states()?
+            } else {
+                vec![]
             };
+            let message: ProxyMessage = data.message.into();
 
             ctx.store_any_client_state(input.client_id.clone(), data.new_any_client_state)?;
             ctx.store_any_consensus_state(
@@ -32,21 +74,24 @@ pub fn update_client<R: LightClientResolver, S: KVStore, K: Signer>(
             )?;
 
             let proof = if data.prove {
-                prove_commitment(ek, message)?
+                prove_commitment_with_encoding(ek, message, input.domain, input.encoding)?
             } else {
-                CommitmentProof::new_with_no_signature(message.to_bytes())
+                CommitmentProof::new_with_no_signature(
+                    message.to_bytes_with_encoding(input.domain, input.encoding),
+                )
             };
-            Ok(LightClientResponse::UpdateClient(UpdateClientResponse(
-                proof,
-            )))
+            Ok(UpdateClientResponse(proof, emitted_state_proofs))
         }
         UpdateClientResult::Misbehaviour(data) => {
             ctx.store_any_client_state(input.client_id, data.new_any_client_state)?;
 
-            let proof = prove_commitment(ek, data.message.into())?;
-            Ok(LightClientResponse::UpdateClient(UpdateClientResponse(
-                proof,
-            )))
+            let proof = prove_commitment_with_encoding(
+                ek,
+                data.message.into(),
+                input.domain,
+                input.encoding,
+            )?;
+            Ok(UpdateClientResponse(proof, vec![]))
         }
     }
 }
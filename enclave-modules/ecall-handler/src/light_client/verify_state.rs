@@ -1,12 +1,16 @@
 use super::registry::get_light_client_by_client_id;
 use crate::light_client::Error;
+use crate::prelude::*;
 use context::Context;
 use crypto::Signer;
 use ecall_commands::{
-    LightClientResponse, VerifyMembershipInput, VerifyMembershipResponse, VerifyNonMembershipInput,
+    LightClientResponse, VerifyMembershipBatchInput, VerifyMembershipBatchResponse,
+    VerifyMembershipInput, VerifyMembershipResponse, VerifyNonMembershipInput,
     VerifyNonMembershipResponse,
 };
-use light_client::commitments::prove_commitment;
+use light_client::commitments::{
+    prove_commitment_with_encoding, MembershipEntry, VerifyMembershipBatchProxyMessage,
+};
 use light_client::LightClientResolver;
 use store::KVStore;
 
@@ -28,7 +32,12 @@ pub fn verify_membership<R: LightClientResolver, S: KVStore, K: Signer>(
     )?;
 
     Ok(LightClientResponse::VerifyMembership(
-        VerifyMembershipResponse(prove_commitment(ek, res.message.into())?),
+        VerifyMembershipResponse(prove_commitment_with_encoding(
+            ek,
+            res.message.into(),
+            input.domain,
+            input.encoding,
+        )?),
     ))
 }
 
@@ -49,6 +58,57 @@ pub fn verify_non_membership<R: LightClientResolver, S: KVStore, K: Signer>(
     )?;
 
     Ok(LightClientResponse::VerifyNonMembership(
-        VerifyNonMembershipResponse(prove_commitment(ek, res.message.into())?),
+        VerifyNonMembershipResponse(prove_commitment_with_encoding(
+            ek,
+            res.message.into(),
+            input.domain,
+            input.encoding,
+        )?),
+    ))
+}
+
+pub fn verify_membership_batch<R: LightClientResolver, S: KVStore, K: Signer>(
+    ctx: &mut Context<R, S, K>,
+    input: VerifyMembershipBatchInput,
+) -> Result<LightClientResponse, Error> {
+    let ek = ctx.get_enclave_key();
+    let lc = get_light_client_by_client_id(ctx, &input.client_id)?;
+
+    let mut state_id = None;
+    let mut entries = Vec::with_capacity(input.items.len());
+    for item in input.items {
+        let res = lc.verify_membership(
+            ctx,
+            input.client_id.clone(),
+            input.prefix.clone(),
+            item.path,
+            item.value,
+            input.proof_height,
+            item.proof,
+        )?;
+        match state_id {
+            Some(expected) if expected != res.message.state_id => {
+                return Err(Error::inconsistent_batch_state_id(
+                    expected,
+                    res.message.state_id,
+                ));
+            }
+            _ => state_id = Some(res.message.state_id),
+        }
+        entries.push(MembershipEntry::new(res.message.path, res.message.value));
+    }
+    let state_id =
+        state_id.ok_or_else(|| Error::invalid_argument("items must be non-empty".to_string()))?;
+
+    let message =
+        VerifyMembershipBatchProxyMessage::new(input.prefix, entries, input.proof_height, state_id);
+    message.validate()?;
+    Ok(LightClientResponse::VerifyMembershipBatch(
+        VerifyMembershipBatchResponse(prove_commitment_with_encoding(
+            ek,
+            message.into(),
+            input.domain,
+            input.encoding,
+        )?),
     ))
 }
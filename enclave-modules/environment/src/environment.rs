@@ -1,16 +1,16 @@
 use crate::prelude::*;
 use alloc::sync::Arc;
 use light_client::LightClientResolver;
-use store::{KVStore, TxId};
+use store::{TxId, TxKVStore};
 
 pub trait Env: Sync + Send {
-    fn new_store(&self, tx_id: TxId) -> Box<dyn KVStore>;
+    fn new_store(&self, tx_id: TxId) -> Box<dyn TxKVStore + '_>;
 
     fn get_lc_registry(&self) -> Arc<dyn LightClientResolver>;
 }
 
 impl Env for &Box<dyn Env> {
-    fn new_store(&self, tx_id: TxId) -> Box<dyn KVStore> {
+    fn new_store(&self, tx_id: TxId) -> Box<dyn TxKVStore + '_> {
         self.as_ref().new_store(tx_id)
     }
 
@@ -1,11 +1,22 @@
 use crate::{prelude::*, Env};
 use alloc::sync::Arc;
+use crypto::sgx::aead::Aes128Key;
 use host_api::store::new_enclave_store;
 use light_client::{LightClient, LightClientResolver, MapLightClientRegistry};
-use store::{KVStore, TxId};
+use store::authenticated::{
+    AuthenticationConfig, Hash, RootRecord, RootRegistry, RootSealer, RootTable,
+};
+use store::encrypted::{EncryptionConfig, StoreCipher, StoreKeyring};
+use store::protected::{StoreAuthentication, StoreEncryption, StoreProtection};
+use store::{TxId, TxKVStore};
+use zeroize::Zeroizing;
 
 pub struct Environment {
     lc_registry: Arc<MapLightClientRegistry>,
+    root_registry: EnclaveRootRegistry,
+    store_keyring: EnclaveStoreKeyring,
+    store_authentication: Option<AuthenticationConfig>,
+    store_encryption: Option<EncryptionConfig>,
 }
 
 impl Environment {
@@ -17,6 +28,45 @@ impl Environment {
             // Thread-safe: `lc_registry` is sealed and immutable after initialization
             #[allow(clippy::arc_with_non_send_sync)]
             lc_registry: Arc::new(lc_registry),
+            root_registry: Default::default(),
+            store_keyring: Default::default(),
+            store_authentication: None,
+            store_encryption: None,
+        }
+    }
+
+    /// `with_store_authentication` enables the authentication of the values read from the host's store
+    ///
+    /// The values written before enabling it can be read only if `config.migrate_existing` is true.
+    ///
+    /// NOTE: This detects the values tampered with by the host, but not a rollback of the whole store across an enclave restart.
+    /// The latest roots are kept only in the enclave memory, so after a restart the host can present any root sealed by the enclave before.
+    /// See `store::authenticated::AuthenticatedKVS` for the details.
+    pub fn with_store_authentication(mut self, config: AuthenticationConfig) -> Self {
+        self.store_authentication = Some(config);
+        self
+    }
+
+    /// `with_store_encryption` enables the encryption of the values written to the host's store
+    ///
+    /// The encryption key is generated on the first use and stored in the host's store after being sealed to MRENCLAVE,
+    /// so the encrypted values cannot be read by other enclave builds.
+    pub fn with_store_encryption(mut self, config: EncryptionConfig) -> Self {
+        self.store_encryption = Some(config);
+        self
+    }
+
+    fn store_protection(&self) -> StoreProtection<'_> {
+        StoreProtection {
+            encryption: self.store_encryption.map(|config| StoreEncryption {
+                config,
+                keyring: &self.store_keyring,
+            }),
+            authentication: self.store_authentication.map(|config| StoreAuthentication {
+                config,
+                registry: &self.root_registry,
+                sealer: &EnclaveRootSealer,
+            }),
         }
     }
 }
@@ -28,8 +78,8 @@ impl LightClientResolver for Environment {
 }
 
 impl Env for Environment {
-    fn new_store(&self, tx_id: TxId) -> Box<dyn KVStore> {
-        new_enclave_store(tx_id)
+    fn new_store(&self, tx_id: TxId) -> Box<dyn TxKVStore + '_> {
+        self.store_protection().wrap(new_enclave_store(tx_id))
     }
 
     fn get_lc_registry(&self) -> Arc<dyn LightClientResolver> {
@@ -39,3 +89,103 @@ impl Env for Environment {
 
 unsafe impl Sync for Environment {}
 unsafe impl Send for Environment {}
+
+/// `EnclaveRootRegistry` keeps the latest and pending roots of the authenticated store in the enclave memory
+///
+/// The roots are lost when the enclave restarts, so a rollback across restarts is not detected.
+#[derive(Default)]
+struct EnclaveRootRegistry(spin::Mutex<RootTable>);
+
+impl RootRegistry for EnclaveRootRegistry {
+    fn load(&self, stored: RootRecord) -> Result<RootRecord, store::Error> {
+        self.0.lock().load(stored)
+    }
+
+    fn prepare(&self, updates: Vec<(RootRecord, RootRecord)>) -> Result<(), store::Error> {
+        self.0.lock().prepare(updates)
+    }
+}
+
+/// `EnclaveRootSealer` seals the roots of the authenticated store with the enclave's sealing key
+struct EnclaveRootSealer;
+
+impl RootSealer for EnclaveRootSealer {
+    fn seal(&self, digest: &Hash) -> Result<Vec<u8>, store::Error> {
+        crypto::sgx::sealing::seal_digest(digest)
+            .map_err(|e| store::Error::seal_root(format!("{:?}", e)))
+    }
+
+    fn unseal(&self, sealed: &[u8]) -> Result<Hash, store::Error> {
+        crypto::sgx::sealing::unseal_digest(sealed)
+            .map_err(|e| store::Error::unseal_root(format!("{:?}", e)))
+    }
+}
+
+/// `EnclaveStoreKeyring` keeps the data encryption key of the store after loading it
+#[derive(Default)]
+struct EnclaveStoreKeyring(spin::Once<(EnclaveStoreCipher, Vec<u8>)>);
+
+impl StoreKeyring for EnclaveStoreKeyring {
+    fn load(&self, sealed: Option<&[u8]>) -> Result<(&dyn StoreCipher, &[u8]), store::Error> {
+        let (cipher, sealed) = self
+            .0
+            .try_call_once(|| match sealed {
+                Some(sealed) => Ok((EnclaveStoreCipher::unseal(sealed)?, sealed.to_vec())),
+                None => EnclaveStoreCipher::generate(),
+            })
+            .map_err(|e: crypto::Error| store::Error::cipher(format!("{:?}", e)))?;
+        Ok((cipher, sealed.as_slice()))
+    }
+}
+
+/// `EnclaveStoreCipher` encrypts the values with AES-128-GCM and MACs the keys with AES-128-CMAC
+///
+/// The two keys are the halves of a random secret sealed by the enclave.
+struct EnclaveStoreCipher {
+    value_key: Aes128Key,
+    mac_key: Aes128Key,
+}
+
+impl EnclaveStoreCipher {
+    /// `generate` returns a cipher with new random keys and the sealed secret
+    fn generate() -> Result<(Self, Vec<u8>), crypto::Error> {
+        let mut secret = Zeroizing::new([0u8; 32]);
+        crypto::sgx::rand::rand_slice(&mut secret[..])?;
+        let sealed = crypto::sgx::sealing::seal_secret(&secret)?;
+        Ok((Self::from_secret(&secret), sealed))
+    }
+
+    fn unseal(sealed: &[u8]) -> Result<Self, crypto::Error> {
+        let secret = crypto::sgx::sealing::unseal_secret(sealed)?;
+        Ok(Self::from_secret(&secret))
+    }
+
+    fn from_secret(secret: &[u8; 32]) -> Self {
+        let key = |bz: &[u8]| Aes128Key::new(Zeroizing::new(bz.try_into().unwrap()));
+        Self {
+            value_key: key(&secret[..16]),
+            mac_key: key(&secret[16..]),
+        }
+    }
+}
+
+impl StoreCipher for EnclaveStoreCipher {
+    fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, store::Error> {
+        self.value_key
+            .encrypt(plaintext, aad)
+            .map_err(|e| store::Error::cipher(format!("{:?}", e)))
+    }
+
+    fn decrypt(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, store::Error> {
+        self.value_key
+            .decrypt(ciphertext, aad)
+            .map_err(|e| store::Error::cipher(format!("{:?}", e)))
+    }
+
+    fn mac(&self, key: &[u8]) -> Result<Vec<u8>, store::Error> {
+        self.mac_key
+            .cmac(key)
+            .map(|mac| mac.to_vec())
+            .map_err(|e| store::Error::cipher(format!("{:?}", e)))
+    }
+}
@@ -22,6 +22,8 @@ mod prelude {
 
 /// re-export
 pub use light_client::MapLightClientRegistry;
+pub use store::authenticated::AuthenticationConfig;
+pub use store::encrypted::EncryptionConfig;
 
 pub use environment::Env;
 #[cfg(feature = "environment_impl")]
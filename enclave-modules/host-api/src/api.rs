@@ -55,7 +55,7 @@ mod tests {
     use store::{
         host::IntoCommitStore,
         transaction::{CommitStore, Tx},
-        KVStore,
+        KVStore, Order,
     };
     use tempfile::TempDir;
 
@@ -104,11 +104,98 @@ mod tests {
                 res.unwrap(),
                 CommandResult::Store(StoreResult::Get(Some(b"v0".to_vec())))
             );
+            let res = execute_command(
+                StoreCommand::Range(
+                    tx.get_id(),
+                    Some(b"k".to_vec()),
+                    None,
+                    Order::Ascending,
+                    None,
+                )
+                .into(),
+            );
+            assert!(res.is_ok());
+            assert_eq!(
+                res.unwrap(),
+                CommandResult::Store(StoreResult::Range(
+                    vec![(b"k0".to_vec(), b"v0".to_vec())],
+                    false
+                ))
+            );
+            let res = execute_command(
+                StoreCommand::WriteBatch(
+                    tx.get_id(),
+                    vec![
+                        (b"k1".to_vec(), Some(b"v1".to_vec())),
+                        (b"k0".to_vec(), None),
+                    ],
+                )
+                .into(),
+            );
+            assert!(res.is_ok());
+            assert_eq!(res.unwrap(), CommandResult::Store(StoreResult::WriteBatch));
             TestEnv.commit_tx(tx).unwrap();
             mstore.write().unwrap().apply(|store| {
-                let v = store.get(b"k0");
-                assert_eq!(v, Some(b"v0".to_vec()));
+                assert_eq!(store.get(b"k0"), None);
+                assert_eq!(store.get(b"k1"), Some(b"v1".to_vec()));
             });
         }
+        {
+            // the values exceed the ocall output buffer in total
+            let pairs: Vec<_> = (0..8u8)
+                .map(|i| (vec![b'l', i], vec![i; 16 * 1024]))
+                .collect();
+            let tx = TestEnv.begin_tx(None).unwrap();
+            let res = execute_command(
+                StoreCommand::WriteBatch(
+                    tx.get_id(),
+                    pairs
+                        .iter()
+                        .map(|(k, v)| (k.clone(), Some(v.clone())))
+                        .collect(),
+                )
+                .into(),
+            );
+            assert!(res.is_ok());
+            let res = execute_command(
+                StoreCommand::Range(
+                    tx.get_id(),
+                    Some(b"l".to_vec()),
+                    None,
+                    Order::Ascending,
+                    None,
+                )
+                .into(),
+            );
+            assert_eq!(
+                res.unwrap(),
+                CommandResult::Store(StoreResult::Range(pairs[..3].to_vec(), true))
+            );
+
+            let store = crate::store::new_enclave_store(tx.get_id());
+            assert_eq!(store.range(Some(b"l"), None, Order::Ascending, None), pairs);
+            assert_eq!(
+                store.range(Some(b"l"), None, Order::Descending, Some(5)),
+                pairs.iter().rev().take(5).cloned().collect::<Vec<_>>()
+            );
+
+            // a pair that does not fit in the output buffer is rejected
+            let res = execute_command(
+                StoreCommand::Set(tx.get_id(), b"m".to_vec(), vec![0; 64 * 1024]).into(),
+            );
+            assert!(res.is_ok());
+            let res = execute_command(
+                StoreCommand::Range(
+                    tx.get_id(),
+                    Some(b"m".to_vec()),
+                    None,
+                    Order::Ascending,
+                    None,
+                )
+                .into(),
+            );
+            assert!(res.is_err());
+            TestEnv.rollback_tx(tx);
+        }
     }
 }
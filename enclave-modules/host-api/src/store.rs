@@ -1,12 +1,16 @@
 use crate::prelude::*;
 use crate::{api::execute_command, Error};
 use ocall_commands::{Command, CommandResult, StoreCommand, StoreResult};
-use store::cache::CacheKVS;
-use store::{KVStore, TxId};
+use store::cache::WriteBackKVS;
+use store::{KVPair, KVStore, Order, TxId, TxKVStore, WriteBatch};
 
-/// The store guarantees that reads a value from the host store only once per key
-pub fn new_enclave_store(tx_id: TxId) -> Box<dyn KVStore> {
-    Box::new(CacheKVS::new(TxStore::new(tx_id)))
+/// The maximum number of key-value pairs fetched from the host's store per ocall
+const RANGE_PAGE_SIZE: usize = 32;
+
+/// The store guarantees that reads a value from the host store only once per key,
+/// and it writes the changes to the host store in a single ocall when it is finalized
+pub fn new_enclave_store(tx_id: TxId) -> Box<dyn TxKVStore> {
+    Box::new(WriteBackKVS::new(TxStore::new(tx_id)))
 }
 
 /// TxStore is a KVStore implementation that uses the ocall_commands to interact with the
@@ -33,6 +37,51 @@ impl KVStore for TxStore {
     fn remove(&mut self, key: &[u8]) {
         remove(self.tx_id, key.to_vec()).unwrap();
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        // the result is fetched by pages because the ocall output buffer size is limited,
+        // and the host also truncates a page that exceeds the buffer size
+        let (mut start, mut end) = (start.map(|s| s.to_vec()), end.map(|e| e.to_vec()));
+        let mut pairs = Vec::new();
+        loop {
+            let page_size = match limit {
+                Some(limit) => core::cmp::min(limit - pairs.len(), RANGE_PAGE_SIZE),
+                None => RANGE_PAGE_SIZE,
+            };
+            if page_size == 0 {
+                break;
+            }
+            let (page, truncated) =
+                range(self.tx_id, start.clone(), end.clone(), order, page_size).unwrap();
+            let is_last_page = !truncated && page.len() < page_size;
+            if let Some((last_key, _)) = page.last() {
+                match order {
+                    Order::Ascending => {
+                        // the smallest key that is greater than `last_key`
+                        let mut next = last_key.clone();
+                        next.push(0);
+                        start = Some(next);
+                    }
+                    Order::Descending => end = Some(last_key.clone()),
+                }
+            }
+            pairs.extend(page);
+            if is_last_page {
+                break;
+            }
+        }
+        pairs
+    }
+
+    fn write_batch(&mut self, batch: WriteBatch) {
+        write_batch(self.tx_id, batch).unwrap();
+    }
 }
 
 fn get(tx_id: TxId, key: Vec<u8>) -> Result<Option<Vec<u8>>, Error> {
@@ -61,3 +110,27 @@ fn remove(tx_id: TxId, key: Vec<u8>) -> Result<(), Error> {
         unreachable!()
     }
 }
+
+fn write_batch(tx_id: TxId, batch: WriteBatch) -> Result<(), Error> {
+    let cmd = Command::Store(StoreCommand::WriteBatch(tx_id, batch));
+    if let CommandResult::Store(StoreResult::WriteBatch) = execute_command(cmd)? {
+        Ok(())
+    } else {
+        unreachable!()
+    }
+}
+
+fn range(
+    tx_id: TxId,
+    start: Option<Vec<u8>>,
+    end: Option<Vec<u8>>,
+    order: Order,
+    limit: usize,
+) -> Result<(Vec<KVPair>, bool), Error> {
+    let cmd = Command::Store(StoreCommand::Range(tx_id, start, end, order, Some(limit)));
+    if let CommandResult::Store(StoreResult::Range(v, truncated)) = execute_command(cmd)? {
+        Ok((v, truncated))
+    } else {
+        unreachable!()
+    }
+}
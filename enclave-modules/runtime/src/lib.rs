@@ -13,7 +13,9 @@ pub use alloc::alloc::*;
 pub use sgx_alloc::System;
 
 pub use ecalls::{ecall_execute_command, set_environment};
-pub use enclave_environment::{Environment, MapLightClientRegistry};
+pub use enclave_environment::{
+    AuthenticationConfig, EncryptionConfig, Environment, MapLightClientRegistry,
+};
 /// re-export
 pub use sgx_trts;
 pub use sgx_types;
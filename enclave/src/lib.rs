@@ -2,7 +2,29 @@
 extern crate alloc;
 use enclave_runtime::{setup_runtime, Environment, MapLightClientRegistry};
 
-setup_runtime!({ Environment::new(build_lc_registry()) });
+setup_runtime!({ build_environment() });
+
+/// `build_environment` configures the environment with the features of the enclave
+///
+/// The configuration is part of MRENCLAVE, so the host cannot change it.
+fn build_environment() -> Environment {
+    #[allow(unused_mut)]
+    let mut env = Environment::new(build_lc_registry());
+    #[cfg(feature = "authenticated-store")]
+    {
+        env = env.with_store_authentication(enclave_runtime::AuthenticationConfig {
+            migrate_existing: cfg!(feature = "authenticated-store-migration"),
+        });
+    }
+    #[cfg(feature = "store-encryption")]
+    {
+        env = env.with_store_encryption(enclave_runtime::EncryptionConfig {
+            mac_keys: cfg!(feature = "store-encryption-mac-keys"),
+            migrate_plaintext: cfg!(feature = "store-encryption-migration"),
+        });
+    }
+    env
+}
 
 fn build_lc_registry() -> MapLightClientRegistry {
     let mut registry = MapLightClientRegistry::new();
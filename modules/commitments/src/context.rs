@@ -1,12 +1,21 @@
+use crate::encoder::{required, EthABIHeight, ProtobufEncoder};
 use crate::prelude::*;
 use crate::{Error, EthABIEncoder};
 use alloy_sol_types::{sol, SolValue};
 use core::{fmt::Display, time::Duration};
-use lcp_types::{nanos_to_duration, Time};
+use lcp_types::proto::google::protobuf::Duration as ProtoDuration;
+use lcp_types::proto::lcp::commitments::v1::{
+    validation_context::Context as ProtoContext, HostHeightContext as ProtoHostHeightContext,
+    TrustingPeriodContext as ProtoTrustingPeriodContext,
+    ValidationContext as ProtoValidationContext,
+};
+use lcp_types::{nanos_to_duration, Height, Time};
+use prost::Message;
 use serde::{Deserialize, Serialize};
 
 pub const VALIDATION_CONTEXT_TYPE_EMPTY_EMPTY: u16 = 0;
 pub const VALIDATION_CONTEXT_TYPE_EMPTY_WITHIN_TRUSTING_PERIOD: u16 = 1;
+pub const VALIDATION_CONTEXT_TYPE_WITHIN_HOST_HEIGHT: u16 = 2;
 pub const VALIDATION_CONTEXT_HEADER_SIZE: usize = 32;
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -14,16 +23,34 @@ pub enum ValidationContext {
     #[default]
     Empty,
     TrustingPeriod(TrustingPeriodContext),
+    HostHeight(HostHeightContext),
 }
 
 impl ValidationContext {
-    pub fn validate(&self, current_timestamp: Time) -> Result<(), Error> {
+    /// validate validates the context with the current timestamp and height of the host
+    ///
+    /// `current_height` is None if the host doesn't know the height of the counterparty chain (e.g. the enclave).
+    /// In that case, the host height bound is not checked, so the verifier on the counterparty chain must provide it.
+    pub fn validate(
+        &self,
+        current_timestamp: Time,
+        current_height: Option<Height>,
+    ) -> Result<(), Error> {
         match self {
             ValidationContext::Empty => Ok(()),
             ValidationContext::TrustingPeriod(ctx) => ctx.validate(current_timestamp),
+            ValidationContext::HostHeight(ctx) => match current_height {
+                Some(current_height) => ctx.validate(current_height),
+                None => Ok(()),
+            },
         }
     }
 
+    /// requires_host_height returns true if the context can only be validated with the host height
+    pub fn requires_host_height(&self) -> bool {
+        matches!(self, ValidationContext::HostHeight(_))
+    }
+
     // MSB first
     // 0-1:  type
     // 2-31: reserved
@@ -39,6 +66,10 @@ impl ValidationContext {
                     &VALIDATION_CONTEXT_TYPE_EMPTY_WITHIN_TRUSTING_PERIOD.to_be_bytes(),
                 );
             }
+            ValidationContext::HostHeight(_) => {
+                header[0..=1]
+                    .copy_from_slice(&VALIDATION_CONTEXT_TYPE_WITHIN_HOST_HEIGHT.to_be_bytes());
+            }
         }
         header
     }
@@ -51,6 +82,15 @@ impl ValidationContext {
             (Self::TrustingPeriod(ctx1), Self::TrustingPeriod(ctx2)) => {
                 Ok(Self::TrustingPeriod(ctx1.aggregate(ctx2)?))
             }
+            (Self::Empty, Self::HostHeight(ctx)) => Ok(Self::HostHeight(ctx)),
+            (Self::HostHeight(ctx), Self::Empty) => Ok(Self::HostHeight(ctx)),
+            (Self::HostHeight(ctx1), Self::HostHeight(ctx2)) => {
+                Ok(Self::HostHeight(ctx1.aggregate(ctx2)))
+            }
+            (ctx1, ctx2) => Err(Error::context_aggregation_failed(format!(
+                "context type mismatch: self={} other={}",
+                ctx1, ctx2
+            ))),
         }
     }
 
@@ -84,6 +124,11 @@ impl EthABIEncoder for ValidationContext {
                 context_bytes: ctx.ethabi_encode().into(),
             }
             .abi_encode(),
+            ValidationContext::HostHeight(ctx) => EthABIValidationContext {
+                header,
+                context_bytes: ctx.ethabi_encode().into(),
+            }
+            .abi_encode(),
         }
     }
     fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
@@ -101,6 +146,10 @@ impl EthABIEncoder for ValidationContext {
                 let ctx = TrustingPeriodContext::ethabi_decode(&context_bytes)?;
                 Ok(ValidationContext::TrustingPeriod(ctx))
             }
+            VALIDATION_CONTEXT_TYPE_WITHIN_HOST_HEIGHT => {
+                let ctx = HostHeightContext::ethabi_decode(&context_bytes)?;
+                Ok(ValidationContext::HostHeight(ctx))
+            }
             type_ => Err(Error::invalid_validation_context_header(format!(
                 "unknown validation context type: {}",
                 type_
@@ -109,6 +158,45 @@ impl EthABIEncoder for ValidationContext {
     }
 }
 
+impl From<ValidationContext> for ProtoValidationContext {
+    fn from(ctx: ValidationContext) -> Self {
+        Self {
+            context: match ctx {
+                ValidationContext::Empty => None,
+                ValidationContext::TrustingPeriod(ctx) => {
+                    Some(ProtoContext::TrustingPeriod(ctx.into()))
+                }
+                ValidationContext::HostHeight(ctx) => Some(ProtoContext::HostHeight(ctx.into())),
+            },
+        }
+    }
+}
+
+impl TryFrom<ProtoValidationContext> for ValidationContext {
+    type Error = Error;
+    fn try_from(ctx: ProtoValidationContext) -> Result<Self, Self::Error> {
+        match ctx.context {
+            None => Ok(ValidationContext::Empty),
+            Some(ProtoContext::TrustingPeriod(ctx)) => {
+                Ok(ValidationContext::TrustingPeriod(ctx.try_into()?))
+            }
+            Some(ProtoContext::HostHeight(ctx)) => {
+                Ok(ValidationContext::HostHeight(ctx.try_into()?))
+            }
+        }
+    }
+}
+
+impl ProtobufEncoder for ValidationContext {
+    fn protobuf_encode(self) -> Vec<u8> {
+        ProtoValidationContext::from(self).encode_to_vec()
+    }
+
+    fn protobuf_decode(bz: &[u8]) -> Result<Self, Error> {
+        ProtoValidationContext::decode(bz)?.try_into()
+    }
+}
+
 sol! {
     struct EthABIValidationContext {
         bytes32 header;
@@ -121,6 +209,7 @@ impl Display for ValidationContext {
         match self {
             ValidationContext::Empty => write!(f, "Empty"),
             ValidationContext::TrustingPeriod(ctx) => write!(f, "TrustingPeriod {{{}}}", ctx),
+            ValidationContext::HostHeight(ctx) => write!(f, "HostHeight {{{}}}", ctx),
         }
     }
 }
@@ -162,6 +251,22 @@ impl TrustingPeriodContext {
         }
     }
 
+    pub fn trusting_period(&self) -> Duration {
+        self.trusting_period
+    }
+
+    pub fn clock_drift(&self) -> Duration {
+        self.clock_drift
+    }
+
+    pub fn untrusted_header_timestamp(&self) -> Time {
+        self.untrusted_header_timestamp
+    }
+
+    pub fn trusted_state_timestamp(&self) -> Time {
+        self.trusted_state_timestamp
+    }
+
     pub fn validate(&self, current_timestamp: Time) -> Result<(), Error> {
         // ensure that trusted consensus state's timestamp hasn't passed the trusting period
         Self::ensure_within_trust_period(
@@ -294,6 +399,51 @@ impl EthABIEncoder for TrustingPeriodContext {
     }
 }
 
+impl From<TrustingPeriodContext> for ProtoTrustingPeriodContext {
+    fn from(ctx: TrustingPeriodContext) -> Self {
+        Self {
+            trusting_period: Some(duration_to_proto(ctx.trusting_period)),
+            clock_drift: Some(duration_to_proto(ctx.clock_drift)),
+            untrusted_header_timestamp: Some(ctx.untrusted_header_timestamp.into()),
+            trusted_state_timestamp: Some(ctx.trusted_state_timestamp.into()),
+        }
+    }
+}
+
+impl TryFrom<ProtoTrustingPeriodContext> for TrustingPeriodContext {
+    type Error = Error;
+    fn try_from(ctx: ProtoTrustingPeriodContext) -> Result<Self, Self::Error> {
+        Ok(Self {
+            trusting_period: duration_from_proto(required(
+                ctx.trusting_period,
+                "trusting_period",
+            )?)?,
+            clock_drift: duration_from_proto(required(ctx.clock_drift, "clock_drift")?)?,
+            untrusted_header_timestamp: required(
+                ctx.untrusted_header_timestamp,
+                "untrusted_header_timestamp",
+            )?
+            .try_into()?,
+            trusted_state_timestamp: required(
+                ctx.trusted_state_timestamp,
+                "trusted_state_timestamp",
+            )?
+            .try_into()?,
+        })
+    }
+}
+
+fn duration_to_proto(d: Duration) -> ProtoDuration {
+    ProtoDuration {
+        seconds: d.as_secs() as i64,
+        nanos: d.subsec_nanos() as i32,
+    }
+}
+
+fn duration_from_proto(d: ProtoDuration) -> Result<Duration, Error> {
+    Ok(Duration::new(d.seconds.try_into()?, d.nanos.try_into()?))
+}
+
 impl From<TrustingPeriodContext> for ValidationContext {
     fn from(ctx: TrustingPeriodContext) -> Self {
         ValidationContext::TrustingPeriod(ctx)
@@ -312,6 +462,92 @@ sol! {
     }
 }
 
+/// HostHeightContext bounds the validity of a message by the height of the host chain
+///
+/// This is useful for a counterparty chain whose block timestamps are unreliable but whose block heights are reliable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostHeightContext {
+    /// The last height of the host chain at which the message is valid
+    valid_until: Height,
+}
+
+impl HostHeightContext {
+    pub fn new(valid_until: Height) -> Self {
+        Self { valid_until }
+    }
+
+    pub fn valid_until(&self) -> Height {
+        self.valid_until
+    }
+
+    pub fn validate(&self, current_height: Height) -> Result<(), Error> {
+        if current_height <= self.valid_until {
+            Ok(())
+        } else {
+            Err(Error::host_height_expired(current_height, self.valid_until))
+        }
+    }
+
+    /// aggregate returns the context that is valid only while both contexts are valid
+    pub fn aggregate(self, other: Self) -> Self {
+        if self.valid_until < other.valid_until {
+            self
+        } else {
+            other
+        }
+    }
+}
+
+impl Display for HostHeightContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "valid_until={}", self.valid_until)
+    }
+}
+
+impl EthABIEncoder for HostHeightContext {
+    fn ethabi_encode(self) -> Vec<u8> {
+        EthABIHostHeightContext {
+            valid_until: self.valid_until.into(),
+        }
+        .abi_encode()
+    }
+    fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
+        let c = EthABIHostHeightContext::abi_decode(bz, true)?;
+        Ok(Self {
+            valid_until: c.valid_until.into(),
+        })
+    }
+}
+
+impl From<HostHeightContext> for ProtoHostHeightContext {
+    fn from(ctx: HostHeightContext) -> Self {
+        Self {
+            valid_until: Some(ctx.valid_until.into()),
+        }
+    }
+}
+
+impl TryFrom<ProtoHostHeightContext> for HostHeightContext {
+    type Error = Error;
+    fn try_from(ctx: ProtoHostHeightContext) -> Result<Self, Self::Error> {
+        Ok(Self {
+            valid_until: required(ctx.valid_until, "valid_until")?.into(),
+        })
+    }
+}
+
+impl From<HostHeightContext> for ValidationContext {
+    fn from(ctx: HostHeightContext) -> Self {
+        ValidationContext::HostHeight(ctx)
+    }
+}
+
+sol! {
+    struct EthABIHostHeightContext {
+        EthABIHeight valid_until;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -337,6 +573,10 @@ mod tests {
             let bz = ctx.clone().ethabi_encode();
             let ctx2 = ValidationContext::ethabi_decode(&bz).unwrap();
             assert_eq!(ctx, ctx2);
+
+            let bz = ctx.clone().protobuf_encode();
+            let ctx2 = ValidationContext::protobuf_decode(&bz).unwrap();
+            assert_eq!(ctx, ctx2);
         }
     }
 
@@ -346,6 +586,10 @@ mod tests {
         let bz = ctx.clone().ethabi_encode();
         let ctx2 = ValidationContext::ethabi_decode(&bz).unwrap();
         assert_eq!(ctx, ctx2);
+
+        let bz = ctx.clone().protobuf_encode();
+        let ctx2 = ValidationContext::protobuf_decode(&bz).unwrap();
+        assert_eq!(ctx, ctx2);
     }
 
     #[test]
@@ -663,4 +907,69 @@ mod tests {
             panic!("{:?}", res);
         }
     }
+
+    #[test]
+    fn test_host_height_context_serialization() {
+        let ctx: ValidationContext = HostHeightContext::new(Height::new(1, 100)).into();
+        let bz = ctx.clone().ethabi_encode();
+        let ctx2 = ValidationContext::ethabi_decode(&bz).unwrap();
+        assert_eq!(ctx, ctx2);
+        assert_eq!(
+            VALIDATION_CONTEXT_TYPE_WITHIN_HOST_HEIGHT,
+            ValidationContext::parse_context_type_from_header(&ctx.header()).unwrap()
+        );
+
+        let bz = ctx.clone().protobuf_encode();
+        let ctx2 = ValidationContext::protobuf_decode(&bz).unwrap();
+        assert_eq!(ctx, ctx2);
+    }
+
+    #[test]
+    fn test_host_height_context() {
+        let ctx: ValidationContext = HostHeightContext::new(Height::new(1, 100)).into();
+        let now = Time::now();
+        assert!(ctx.validate(now, Some(Height::new(1, 99))).is_ok());
+        assert!(ctx.validate(now, Some(Height::new(1, 100))).is_ok());
+        let res = ctx.validate(now, Some(Height::new(1, 101)));
+        assert!(
+            matches!(
+                res.as_ref().err().map(|e| e.detail()),
+                Some(ErrorDetail::HostHeightExpired(_))
+            ),
+            "{:?}",
+            res
+        );
+        // the bound is not checked if the host height is unknown
+        assert!(ctx.requires_host_height());
+        assert!(ctx.validate(now, None).is_ok());
+    }
+
+    #[test]
+    fn test_host_height_context_aggregation() {
+        let ctx0: ValidationContext = HostHeightContext::new(Height::new(1, 100)).into();
+        let ctx1: ValidationContext = HostHeightContext::new(Height::new(1, 50)).into();
+        assert_eq!(ctx0.clone().aggregate(ctx1.clone()).unwrap(), ctx1);
+        assert_eq!(ctx1.clone().aggregate(ctx0.clone()).unwrap(), ctx1);
+        assert_eq!(
+            ValidationContext::Empty.aggregate(ctx0.clone()).unwrap(),
+            ctx0
+        );
+
+        let ctx2: ValidationContext = build_trusting_period_context(
+            1,
+            1,
+            datetime!(2023-08-20 0:00 UTC),
+            datetime!(2023-08-20 0:00 UTC),
+        )
+        .into();
+        let res = ctx0.aggregate(ctx2);
+        assert!(
+            matches!(
+                res.as_ref().err().map(|e| e.detail()),
+                Some(ErrorDetail::ContextAggregationFailed(_))
+            ),
+            "{:?}",
+            res
+        );
+    }
 }
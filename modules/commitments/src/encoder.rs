@@ -2,8 +2,73 @@ use crate::message::EmittedState;
 use crate::prelude::*;
 use crate::Error;
 use alloy_sol_types::sol;
+use core::fmt::Display;
+use lcp_types::proto::lcp::commitments::v1::EmittedState as ProtoEmittedState;
 use lcp_types::{Any, Height};
 use prost::Message;
+use serde::{Deserialize, Serialize};
+
+pub const MESSAGE_ENCODING_ETHABI: u16 = 0;
+pub const MESSAGE_ENCODING_PROTOBUF: u16 = 1;
+
+/// MessageEncoding is the encoding of a proxy message.
+///
+/// The encoding is carried in the message header, so the signature covers the exact bytes that the counterparty decodes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageEncoding {
+    /// Solidity ABI encoding for EVM chains
+    #[default]
+    EthABI,
+    /// Protobuf encoding defined in `lcp/commitments/v1/commitments.proto`
+    Protobuf,
+}
+
+impl MessageEncoding {
+    pub fn as_u16(&self) -> u16 {
+        match self {
+            Self::EthABI => MESSAGE_ENCODING_ETHABI,
+            Self::Protobuf => MESSAGE_ENCODING_PROTOBUF,
+        }
+    }
+
+    pub fn from_u16(value: u16) -> Result<Self, Error> {
+        match value {
+            MESSAGE_ENCODING_ETHABI => Ok(Self::EthABI),
+            MESSAGE_ENCODING_PROTOBUF => Ok(Self::Protobuf),
+            _ => Err(Error::unknown_message_encoding(value)),
+        }
+    }
+}
+
+impl Display for MessageEncoding {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EthABI => write!(f, "EthABI"),
+            Self::Protobuf => write!(f, "Protobuf"),
+        }
+    }
+}
+
+pub trait ProtobufEncoder {
+    fn protobuf_encode(self) -> Vec<u8>;
+    fn protobuf_decode(bz: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized;
+}
+
+/// required returns the value of a required message field of the protobuf encoding
+pub(crate) fn required<T>(value: Option<T>, field: &str) -> Result<T, Error> {
+    value.ok_or_else(|| Error::missing_proto_field(field.to_string()))
+}
+
+/// optional_bytes32 converts a bytes field of the protobuf encoding that is either empty or 32 bytes
+pub(crate) fn optional_bytes32(bz: Vec<u8>) -> Result<Option<[u8; 32]>, Error> {
+    match bz.len() {
+        0 => Ok(None),
+        32 => Ok(Some(bz.as_slice().try_into().unwrap())),
+        actual => Err(Error::invalid_optional_bytes_length(32, actual)),
+    }
+}
 
 pub trait EthABIEncoder {
     fn ethabi_encode(self) -> Vec<u8>;
@@ -79,3 +144,22 @@ impl TryFrom<EthABIEmittedState> for EmittedState {
         ))
     }
 }
+
+impl From<EmittedState> for ProtoEmittedState {
+    fn from(value: EmittedState) -> Self {
+        Self {
+            height: Some(value.0.into()),
+            state: Some(value.1.into()),
+        }
+    }
+}
+
+impl TryFrom<ProtoEmittedState> for EmittedState {
+    type Error = Error;
+    fn try_from(value: ProtoEmittedState) -> Result<Self, Self::Error> {
+        Ok(Self(
+            required(value.height, "height")?.into(),
+            required(value.state, "state")?.into(),
+        ))
+    }
+}
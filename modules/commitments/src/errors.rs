@@ -1,7 +1,8 @@
+use crate::encoder::MessageEncoding;
 use crate::prelude::*;
 use crate::STATE_ID_SIZE;
 use flex_error::*;
-use lcp_types::Time;
+use lcp_types::{Height, Time};
 
 define_error! {
     #[derive(Debug, Clone, PartialEq, Eq)]
@@ -57,6 +58,39 @@ define_error! {
             format_args!("invalid message header: descr={}", e.descr)
         },
 
+        InvalidMessageDomain
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("invalid message domain: descr={}", e.descr)
+        },
+
+        UnknownMessageEncoding
+        {
+            encoding: u16
+        }
+        |e| {
+            format_args!("unknown message encoding: encoding={}", e.encoding)
+        },
+
+        UnexpectedMessageEncoding
+        {
+            expected: MessageEncoding,
+            actual: MessageEncoding
+        }
+        |e| {
+            format_args!("unexpected message encoding: expected={} actual={}", e.expected, e.actual)
+        },
+
+        MissingProtoField
+        {
+            field: String
+        }
+        |e| {
+            format_args!("missing field in protobuf message: field={}", e.field)
+        },
+
         InvalidValidationContextHeader
         {
             descr: String
@@ -83,6 +117,15 @@ define_error! {
             format_args!("header is coming from future: current_timestamp={} header_timestamp={}", e.current_timestamp, e.header_timestamp)
         },
 
+        HostHeightExpired
+        {
+            current_height: Height,
+            valid_until: Height
+        }
+        |e| {
+            format_args!("host height exceeds the valid height: current_height={} valid_until={}", e.current_height, e.valid_until)
+        },
+
         NotTruncatedTimestamp
         {
             timestamp_nanos: u128
@@ -127,6 +170,36 @@ define_error! {
         {}
         |_| {"empty prev_states in misbehaviour message"},
 
+        EmptyMembershipEntries
+        {}
+        |_| {"empty entries in verify membership batch message"},
+
+        EmptyMerkleLeaves
+        {}
+        |_| {"empty leaves in merkle tree"},
+
+        InvalidMerkleHashLength
+        {
+            actual: usize
+        }
+        |e| {
+            format_args!("invalid merkle hash length: expected={} actual={}", crate::merkle::MERKLE_HASH_SIZE, e.actual)
+        },
+
+        EmittedStateNotIncluded
+        {
+            state: String,
+            root: String,
+            computed_root: String
+        }
+        |e| {
+            format_args!("emitted state is not included in the root: state={} root=0x{} computed_root=0x{}", e.state, e.root, e.computed_root)
+        },
+
+        EmittedStatesConflict
+        {}
+        |_| {"emitted_states and emitted_states_root must not be set at the same time"},
+
         ProtoDecodeError
         [TraceError<prost::DecodeError>]
         |_| {"proto decode error"},
@@ -173,6 +246,12 @@ impl From<alloy_sol_types::Error> for Error {
     }
 }
 
+impl From<prost::DecodeError> for Error {
+    fn from(value: prost::DecodeError) -> Self {
+        Error::proto_decode_error(value)
+    }
+}
+
 impl From<crypto::Error> for Error {
     fn from(value: crypto::Error) -> Self {
         Error::crypto(value)
@@ -20,20 +20,28 @@ mod prelude {
     pub use core::iter::FromIterator;
 }
 
-pub use context::{TrustingPeriodContext, ValidationContext};
-pub use encoder::EthABIEncoder;
+pub use context::{HostHeightContext, TrustingPeriodContext, ValidationContext};
+pub use encoder::{EthABIEncoder, MessageEncoding, ProtobufEncoder};
 pub use errors::Error;
+pub use merkle::{
+    compute_emitted_states_root, compute_merkle_root, emitted_state_leaf, verify_emitted_state,
+    MerkleProof, MerkleProofNode,
+};
 pub use message::{
-    aggregate_messages, CommitmentPrefix, EmittedState, MisbehaviourProxyMessage, PrevState,
-    ProxyMessage, UpdateStateProxyMessage, VerifyMembershipProxyMessage,
+    aggregate_messages, aggregate_messages_with_proofs, aggregate_misbehaviour_messages,
+    CommitmentPrefix, EmittedState, MembershipEntry, MessageDomain, MisbehaviourProxyMessage,
+    PrevState, ProxyMessage, UpdateStateProxyMessage, VerifyMembershipBatchProxyMessage,
+    VerifyMembershipProxyMessage, AGGREGATED_CLIENT_MESSAGES_TYPE_URL, MESSAGE_SCHEMA_VERSION,
+    MESSAGE_SCHEMA_VERSION_V2,
 };
 pub use proof::CommitmentProof;
-pub use prover::prove_commitment;
+pub use prover::{prove_commitment, prove_commitment_with_domain, prove_commitment_with_encoding};
 pub use state::{gen_state_id_from_any, gen_state_id_from_bytes, StateID, STATE_ID_SIZE};
 
 mod context;
 mod encoder;
 mod errors;
+mod merkle;
 mod message;
 mod proof;
 mod prover;
@@ -0,0 +1,303 @@
+use crate::encoder::{EthABIEmittedState, EthABIEncoder};
+use crate::message::EmittedState;
+use crate::prelude::*;
+use crate::Error;
+use alloy_sol_types::{private::B256, sol, SolValue};
+use crypto::Keccak256;
+use lcp_types::proto::lcp::commitments::v1::{
+    MerkleProof as ProtoMerkleProof, MerkleProofNode as ProtoMerkleProofNode,
+};
+use serde::{Deserialize, Serialize};
+
+pub const MERKLE_HASH_SIZE: usize = 32;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// MerkleProofNode is a sibling hash on the path from a leaf to the root
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MerkleProofNode {
+    /// The sibling is the left child of the parent
+    Left([u8; MERKLE_HASH_SIZE]),
+    /// The sibling is the right child of the parent
+    Right([u8; MERKLE_HASH_SIZE]),
+}
+
+/// MerkleProof is an inclusion proof of a leaf in the emitted states tree
+///
+/// The tree is a binary keccak256 tree where a leaf is `keccak256(0x00 || leaf)` and an inner node is `keccak256(0x01 || left || right)`.
+/// A node without a sibling at the end of a level is promoted to the next level as is.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    /// The sibling hashes from the leaf to the root
+    pub path: Vec<MerkleProofNode>,
+}
+
+impl MerkleProof {
+    /// compute_root computes the root from the given node hash by following the path
+    pub fn compute_root(&self, node: [u8; MERKLE_HASH_SIZE]) -> [u8; MERKLE_HASH_SIZE] {
+        self.path.iter().fold(node, |acc, n| match n {
+            MerkleProofNode::Left(sibling) => hash_node(sibling, &acc),
+            MerkleProofNode::Right(sibling) => hash_node(&acc, sibling),
+        })
+    }
+
+    /// join extends the proof with a proof of its root in a parent tree
+    ///
+    /// This is used to prove an emitted state against the root of aggregated messages.
+    pub fn join(mut self, parent: MerkleProof) -> Self {
+        self.path.extend(parent.path);
+        self
+    }
+}
+
+/// emitted_state_leaf returns the leaf hash of the emitted state
+///
+/// The leaf is computed from the EthABI encoding regardless of the message encoding, so the root is the same for all encodings.
+pub fn emitted_state_leaf(state: &EmittedState) -> [u8; MERKLE_HASH_SIZE] {
+    let mut bz = vec![LEAF_PREFIX];
+    bz.extend(EthABIEmittedState::from(state.clone()).abi_encode());
+    bz.keccak256()
+}
+
+/// compute_emitted_states_root returns the root of the emitted states and the inclusion proof of each state
+pub fn compute_emitted_states_root(
+    states: &[EmittedState],
+) -> Result<([u8; MERKLE_HASH_SIZE], Vec<MerkleProof>), Error> {
+    compute_merkle_root(states.iter().map(emitted_state_leaf).collect())
+}
+
+/// compute_merkle_root returns the root of the given leaf hashes and the inclusion proof of each leaf
+pub fn compute_merkle_root(
+    leaves: Vec<[u8; MERKLE_HASH_SIZE]>,
+) -> Result<([u8; MERKLE_HASH_SIZE], Vec<MerkleProof>), Error> {
+    if leaves.is_empty() {
+        return Err(Error::empty_merkle_leaves());
+    }
+    let mut proofs = vec![MerkleProof::default(); leaves.len()];
+    // indices of the leaves under each node of the current level
+    let mut members: Vec<Vec<usize>> = (0..leaves.len()).map(|i| vec![i]).collect();
+    let mut level = leaves;
+    while level.len() > 1 {
+        let mut next_level = Vec::with_capacity(level.len().div_ceil(2));
+        let mut next_members = Vec::with_capacity(level.len().div_ceil(2));
+        let mut members_iter = members.into_iter();
+        for pair in level.chunks(2) {
+            let mut left_members = members_iter.next().unwrap();
+            if let [left, right] = pair {
+                let right_members = members_iter.next().unwrap();
+                for &i in left_members.iter() {
+                    proofs[i].path.push(MerkleProofNode::Right(*right));
+                }
+                for &i in right_members.iter() {
+                    proofs[i].path.push(MerkleProofNode::Left(*left));
+                }
+                left_members.extend(right_members);
+                next_level.push(hash_node(left, right));
+            } else {
+                next_level.push(pair[0]);
+            }
+            next_members.push(left_members);
+        }
+        level = next_level;
+        members = next_members;
+    }
+    Ok((level[0], proofs))
+}
+
+/// verify_emitted_state verifies that the emitted state is included in the tree of the given root
+pub fn verify_emitted_state(
+    root: [u8; MERKLE_HASH_SIZE],
+    state: &EmittedState,
+    proof: &MerkleProof,
+) -> Result<(), Error> {
+    let computed = proof.compute_root(emitted_state_leaf(state));
+    if computed != root {
+        return Err(Error::emitted_state_not_included(
+            state.to_string(),
+            hex::encode(root),
+            hex::encode(computed),
+        ));
+    }
+    Ok(())
+}
+
+fn hash_node(
+    left: &[u8; MERKLE_HASH_SIZE],
+    right: &[u8; MERKLE_HASH_SIZE],
+) -> [u8; MERKLE_HASH_SIZE] {
+    let mut bz = Vec::with_capacity(1 + 2 * MERKLE_HASH_SIZE);
+    bz.push(NODE_PREFIX);
+    bz.extend_from_slice(left);
+    bz.extend_from_slice(right);
+    bz.keccak256()
+}
+
+sol! {
+    struct EthABIMerkleProofNode {
+        bool left;
+        bytes32 sibling;
+    }
+
+    struct EthABIMerkleProof {
+        EthABIMerkleProofNode[] path;
+    }
+}
+
+impl From<MerkleProof> for EthABIMerkleProof {
+    fn from(value: MerkleProof) -> Self {
+        Self {
+            path: value
+                .path
+                .into_iter()
+                .map(|n| match n {
+                    MerkleProofNode::Left(sibling) => EthABIMerkleProofNode {
+                        left: true,
+                        sibling: B256::from(sibling),
+                    },
+                    MerkleProofNode::Right(sibling) => EthABIMerkleProofNode {
+                        left: false,
+                        sibling: B256::from(sibling),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<EthABIMerkleProof> for MerkleProof {
+    fn from(value: EthABIMerkleProof) -> Self {
+        Self {
+            path: value
+                .path
+                .into_iter()
+                .map(|n| {
+                    if n.left {
+                        MerkleProofNode::Left(n.sibling.0)
+                    } else {
+                        MerkleProofNode::Right(n.sibling.0)
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl EthABIEncoder for MerkleProof {
+    fn ethabi_encode(self) -> Vec<u8> {
+        EthABIMerkleProof::from(self).abi_encode()
+    }
+
+    fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
+        Ok(EthABIMerkleProof::abi_decode(bz, true)?.into())
+    }
+}
+
+impl From<MerkleProof> for ProtoMerkleProof {
+    fn from(value: MerkleProof) -> Self {
+        Self {
+            path: value
+                .path
+                .into_iter()
+                .map(|n| match n {
+                    MerkleProofNode::Left(sibling) => ProtoMerkleProofNode {
+                        left: true,
+                        sibling: sibling.to_vec(),
+                    },
+                    MerkleProofNode::Right(sibling) => ProtoMerkleProofNode {
+                        left: false,
+                        sibling: sibling.to_vec(),
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<ProtoMerkleProof> for MerkleProof {
+    type Error = Error;
+    fn try_from(value: ProtoMerkleProof) -> Result<Self, Self::Error> {
+        Ok(Self {
+            path: value
+                .path
+                .into_iter()
+                .map(|n| {
+                    let sibling: [u8; MERKLE_HASH_SIZE] = n
+                        .sibling
+                        .as_slice()
+                        .try_into()
+                        .map_err(|_| Error::invalid_merkle_hash_length(n.sibling.len()))?;
+                    Ok(if n.left {
+                        MerkleProofNode::Left(sibling)
+                    } else {
+                        MerkleProofNode::Right(sibling)
+                    })
+                })
+                .collect::<Result<Vec<_>, Error>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lcp_types::{Any, Height};
+
+    fn emitted_states(n: u64) -> Vec<EmittedState> {
+        (1..=n)
+            .map(|i| {
+                EmittedState(
+                    Height::new(0, i),
+                    Any::new("/foo".to_string(), i.to_be_bytes().to_vec()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_emitted_states_root() {
+        for n in 1..=9 {
+            let states = emitted_states(n);
+            let (root, proofs) = compute_emitted_states_root(&states).unwrap();
+            assert_eq!(proofs.len(), states.len());
+            for (state, proof) in states.iter().zip(proofs.iter()) {
+                assert!(verify_emitted_state(root, state, proof).is_ok());
+                let proof = MerkleProof::ethabi_decode(&proof.clone().ethabi_encode()).unwrap();
+                assert!(verify_emitted_state(root, state, &proof).is_ok());
+                let proof = MerkleProof::try_from(ProtoMerkleProof::from(proof)).unwrap();
+                assert!(verify_emitted_state(root, state, &proof).is_ok());
+            }
+            // a state is not included at the position of another state
+            if n > 1 {
+                assert!(verify_emitted_state(root, &states[0], &proofs[1]).is_err());
+            }
+            let other = EmittedState(Height::new(0, 100), Any::new("/foo".to_string(), vec![]));
+            assert!(verify_emitted_state(root, &other, &proofs[0]).is_err());
+        }
+        assert!(compute_emitted_states_root(&[]).is_err());
+    }
+
+    #[test]
+    fn test_single_leaf_root() {
+        let states = emitted_states(1);
+        let (root, proofs) = compute_emitted_states_root(&states).unwrap();
+        assert_eq!(root, emitted_state_leaf(&states[0]));
+        assert!(proofs[0].path.is_empty());
+    }
+
+    #[test]
+    fn test_join_proofs() {
+        let (states0, states1) = (emitted_states(3), emitted_states(5)[3..].to_vec());
+        let (root0, proofs0) = compute_emitted_states_root(&states0).unwrap();
+        let (root1, proofs1) = compute_emitted_states_root(&states1).unwrap();
+        let (root, parent_proofs) = compute_merkle_root(vec![root0, root1]).unwrap();
+        for (state, proof) in states0.iter().zip(proofs0) {
+            let proof = proof.join(parent_proofs[0].clone());
+            assert!(verify_emitted_state(root, state, &proof).is_ok());
+        }
+        for (state, proof) in states1.iter().zip(proofs1) {
+            let proof = proof.join(parent_proofs[1].clone());
+            assert!(verify_emitted_state(root, state, &proof).is_ok());
+        }
+    }
+}
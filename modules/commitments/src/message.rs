@@ -1,22 +1,42 @@
-pub use self::misbehaviour::{MisbehaviourProxyMessage, PrevState};
-pub use self::update_state::{aggregate_messages, EmittedState, UpdateStateProxyMessage};
+pub use self::misbehaviour::{
+    aggregate_misbehaviour_messages, MisbehaviourProxyMessage, PrevState,
+    AGGREGATED_CLIENT_MESSAGES_TYPE_URL,
+};
+pub use self::update_state::{
+    aggregate_messages, aggregate_messages_with_proofs, EmittedState, UpdateStateProxyMessage,
+};
 pub use self::verify_membership::{CommitmentPrefix, VerifyMembershipProxyMessage};
-use crate::encoder::EthABIEncoder;
+pub use self::verify_membership_batch::{MembershipEntry, VerifyMembershipBatchProxyMessage};
+use crate::encoder::{EthABIEncoder, MessageEncoding, ProtobufEncoder};
 use crate::prelude::*;
 use crate::Error;
 use alloy_sol_types::{sol, SolValue};
 use core::fmt::Display;
+use lcp_types::proto::lcp::commitments::v1::{
+    DomainBoundMessage as ProtoDomainBoundMessage,
+    HeaderedProxyMessage as ProtoHeaderedProxyMessage,
+};
+use prost::Message;
 use serde::{Deserialize, Serialize};
 mod misbehaviour;
 mod update_state;
 mod verify_membership;
+mod verify_membership_batch;
 
 pub const MESSAGE_SCHEMA_VERSION: u16 = 1;
+/// The message schema version that binds a message to the chain and client that verifies it
+pub const MESSAGE_SCHEMA_VERSION_V2: u16 = 2;
 pub const MESSAGE_HEADER_SIZE: usize = 32;
 
+/// The key of the `header` field (field number 1, length-delimited) that starts a protobuf-encoded message
+const PROTOBUF_HEADER_KEY: u8 = 0x0a;
+
 pub const MESSAGE_TYPE_UPDATE_STATE: u16 = 1;
 pub const MESSAGE_TYPE_STATE: u16 = 2;
 pub const MESSAGE_TYPE_MISBEHAVIOUR: u16 = 3;
+pub const MESSAGE_TYPE_STATE_BATCH: u16 = 4;
+/// The message type of an update state message that commits the emitted states by the merkle root
+pub const MESSAGE_TYPE_UPDATE_STATE_WITH_ROOT: u16 = 5;
 
 /// ProxyMessage is a message generated by the ELC to be submit to the LCP client on the chain.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -24,6 +44,47 @@ pub enum ProxyMessage {
     UpdateState(UpdateStateProxyMessage),
     VerifyMembership(VerifyMembershipProxyMessage),
     Misbehaviour(MisbehaviourProxyMessage),
+    VerifyMembershipBatch(VerifyMembershipBatchProxyMessage),
+}
+
+/// MessageDomain identifies the LCP client that is expected to verify a message.
+///
+/// A message bound to a domain cannot be replayed to another LCP client that trusts the same enclave key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageDomain {
+    /// The chain id of the counterparty chain on which the LCP client is deployed
+    pub chain_id: String,
+    /// The client id of the LCP client on the counterparty chain
+    pub client_id: String,
+}
+
+impl MessageDomain {
+    pub fn new(chain_id: String, client_id: String) -> Self {
+        Self {
+            chain_id,
+            client_id,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.chain_id.is_empty() || self.client_id.is_empty() {
+            return Err(Error::invalid_message_domain(format!(
+                "chain_id and client_id must be non-empty: chain_id={} client_id={}",
+                self.chain_id, self.client_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Display for MessageDomain {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "MessageDomain(chain_id: {}, client_id: {})",
+            self.chain_id, self.client_id
+        )
+    }
 }
 
 impl ProxyMessage {
@@ -31,26 +92,208 @@ impl ProxyMessage {
         self.ethabi_encode()
     }
 
+    /// from_bytes decodes the message of any supported schema version and encoding
     pub fn from_bytes(bz: &[u8]) -> Result<Self, Error> {
-        Self::ethabi_decode(bz)
+        Ok(Self::from_bytes_with_encoding(bz)?.0)
+    }
+
+    /// to_bytes_with_domain encodes the message with schema version 2 if `domain` is given, otherwise with schema version 1.
+    pub fn to_bytes_with_domain(self, domain: Option<MessageDomain>) -> Vec<u8> {
+        self.to_bytes_with_encoding(domain, MessageEncoding::EthABI)
+    }
+
+    /// to_bytes_with_encoding encodes the message with `encoding`, and binds it to `domain` if given.
+    pub fn to_bytes_with_encoding(
+        self,
+        domain: Option<MessageDomain>,
+        encoding: MessageEncoding,
+    ) -> Vec<u8> {
+        let version = if domain.is_some() {
+            MESSAGE_SCHEMA_VERSION_V2
+        } else {
+            MESSAGE_SCHEMA_VERSION
+        };
+        let header = self.header_with(version, encoding);
+        let body = self.encode_body(encoding);
+        let message = match (domain, encoding) {
+            (None, _) => body,
+            (Some(domain), MessageEncoding::EthABI) => EthABIDomainBoundMessage {
+                chain_id: domain.chain_id,
+                client_id: domain.client_id,
+                message: body.into(),
+            }
+            .abi_encode(),
+            (Some(domain), MessageEncoding::Protobuf) => ProtoDomainBoundMessage {
+                chain_id: domain.chain_id,
+                client_id: domain.client_id,
+                message: body,
+            }
+            .encode_to_vec(),
+        };
+        match encoding {
+            MessageEncoding::EthABI => EthABIHeaderedMessage {
+                header: header.into(),
+                message: message.into(),
+            }
+            .abi_encode(),
+            MessageEncoding::Protobuf => ProtoHeaderedProxyMessage {
+                header: header.to_vec(),
+                message,
+            }
+            .encode_to_vec(),
+        }
+    }
+
+    /// from_bytes_with_domain decodes the message of any supported schema version and returns the domain if the message is bound to it.
+    pub fn from_bytes_with_domain(bz: &[u8]) -> Result<(Self, Option<MessageDomain>), Error> {
+        let (message, domain, _) = Self::from_bytes_with_encoding(bz)?;
+        Ok((message, domain))
+    }
+
+    /// from_bytes_with_encoding decodes the message of any supported schema version and encoding.
+    ///
+    /// It returns the domain if the message is bound to it, and the encoding carried in the header.
+    pub fn from_bytes_with_encoding(
+        bz: &[u8],
+    ) -> Result<(Self, Option<MessageDomain>, MessageEncoding), Error> {
+        // The EthABI encoding starts with the header whose first byte is always zero,
+        // whereas the protobuf encoding starts with the key of the `header` field.
+        let (envelope_encoding, header, message) = if bz.first() == Some(&PROTOBUF_HEADER_KEY) {
+            let m = ProtoHeaderedProxyMessage::decode(bz)?;
+            (MessageEncoding::Protobuf, m.header, m.message)
+        } else {
+            let m = EthABIHeaderedMessage::abi_decode(bz, true)?;
+            (
+                MessageEncoding::EthABI,
+                m.header.to_vec(),
+                m.message.to_vec(),
+            )
+        };
+        let (version, message_type, encoding) = Self::parse_header(&header)?;
+        if encoding != envelope_encoding {
+            return Err(Error::unexpected_message_encoding(
+                envelope_encoding,
+                encoding,
+            ));
+        }
+        let (body, domain) = match version {
+            MESSAGE_SCHEMA_VERSION => (message, None),
+            MESSAGE_SCHEMA_VERSION_V2 => {
+                let (chain_id, client_id, body) = match encoding {
+                    MessageEncoding::EthABI => {
+                        let bound = EthABIDomainBoundMessage::abi_decode(&message, true)?;
+                        (bound.chain_id, bound.client_id, bound.message.to_vec())
+                    }
+                    MessageEncoding::Protobuf => {
+                        let bound = ProtoDomainBoundMessage::decode(message.as_slice())?;
+                        (bound.chain_id, bound.client_id, bound.message)
+                    }
+                };
+                let domain = MessageDomain::new(chain_id, client_id);
+                domain.validate()?;
+                (body, Some(domain))
+            }
+            _ => {
+                return Err(Error::invalid_message_header(format!(
+                    "invalid version: expected={} or {} actual={} header={:?}",
+                    MESSAGE_SCHEMA_VERSION, MESSAGE_SCHEMA_VERSION_V2, version, header
+                )))
+            }
+        };
+        Ok((
+            Self::decode_body(message_type, encoding, &body)?,
+            domain,
+            encoding,
+        ))
     }
 
     // MSB first
     // 0-1:  version
     // 2-3:  message type
-    // 4-31: reserved
+    // 4-5:  message encoding
+    // 6-31: reserved
     pub fn header(&self) -> [u8; MESSAGE_HEADER_SIZE] {
+        self.header_with(MESSAGE_SCHEMA_VERSION, MessageEncoding::EthABI)
+    }
+
+    fn header_with(&self, version: u16, encoding: MessageEncoding) -> [u8; MESSAGE_HEADER_SIZE] {
         let mut header = [0u8; MESSAGE_HEADER_SIZE];
-        header[0..=1].copy_from_slice(&MESSAGE_SCHEMA_VERSION.to_be_bytes());
+        header[0..=1].copy_from_slice(&version.to_be_bytes());
         header[2..=3].copy_from_slice(&self.message_type().to_be_bytes());
+        header[4..=5].copy_from_slice(&encoding.as_u16().to_be_bytes());
         header
     }
 
+    fn parse_header(header: &[u8]) -> Result<(u16, u16, MessageEncoding), Error> {
+        if header.len() != MESSAGE_HEADER_SIZE {
+            return Err(Error::invalid_message_header(format!(
+                "invalid header length: expected={MESSAGE_HEADER_SIZE} actual={} header={:?}",
+                header.len(),
+                header
+            )));
+        }
+        let version = u16::from_be_bytes([header[0], header[1]]);
+        let message_type = u16::from_be_bytes([header[2], header[3]]);
+        let encoding = MessageEncoding::from_u16(u16::from_be_bytes([header[4], header[5]]))?;
+        Ok((version, message_type, encoding))
+    }
+
+    fn encode_body(self, encoding: MessageEncoding) -> Vec<u8> {
+        match self {
+            Self::UpdateState(c) => encode_as(encoding, c),
+            Self::VerifyMembership(c) => encode_as(encoding, c),
+            Self::Misbehaviour(c) => encode_as(encoding, c),
+            Self::VerifyMembershipBatch(c) => encode_as(encoding, c),
+        }
+    }
+
+    fn decode_body(
+        message_type: u16,
+        encoding: MessageEncoding,
+        message: &[u8],
+    ) -> Result<Self, Error> {
+        match message_type {
+            MESSAGE_TYPE_UPDATE_STATE | MESSAGE_TYPE_UPDATE_STATE_WITH_ROOT => {
+                let msg: Self = match (message_type, encoding) {
+                    (MESSAGE_TYPE_UPDATE_STATE_WITH_ROOT, MessageEncoding::EthABI) => {
+                        UpdateStateProxyMessage::ethabi_decode_with_root(message)?.into()
+                    }
+                    _ => decode_as::<UpdateStateProxyMessage>(encoding, message)?.into(),
+                };
+                // the type in the header must be consistent with whether the emitted states root is set
+                if msg.message_type() != message_type {
+                    return Err(Error::unexpected_message_type(
+                        message_type,
+                        msg.message_type(),
+                    ));
+                }
+                Ok(msg)
+            }
+            MESSAGE_TYPE_STATE => {
+                Ok(decode_as::<VerifyMembershipProxyMessage>(encoding, message)?.into())
+            }
+            MESSAGE_TYPE_MISBEHAVIOUR => {
+                Ok(decode_as::<MisbehaviourProxyMessage>(encoding, message)?.into())
+            }
+            MESSAGE_TYPE_STATE_BATCH => {
+                Ok(decode_as::<VerifyMembershipBatchProxyMessage>(encoding, message)?.into())
+            }
+            _ => Err(Error::invalid_abi(format!(
+                "invalid message type: {}",
+                message_type
+            ))),
+        }
+    }
+
     pub fn message_type(&self) -> u16 {
         match self {
+            Self::UpdateState(m) if m.emitted_states_root.is_some() => {
+                MESSAGE_TYPE_UPDATE_STATE_WITH_ROOT
+            }
             Self::UpdateState(_) => MESSAGE_TYPE_UPDATE_STATE,
             Self::VerifyMembership(_) => MESSAGE_TYPE_STATE,
             Self::Misbehaviour(_) => MESSAGE_TYPE_MISBEHAVIOUR,
+            Self::VerifyMembershipBatch(_) => MESSAGE_TYPE_STATE_BATCH,
         }
     }
 
@@ -59,6 +302,7 @@ impl ProxyMessage {
             Self::UpdateState(c) => c.validate(),
             Self::VerifyMembership(c) => c.validate(),
             Self::Misbehaviour(c) => c.validate(),
+            Self::VerifyMembershipBatch(c) => c.validate(),
         }
     }
 }
@@ -69,6 +313,7 @@ impl Display for ProxyMessage {
             Self::UpdateState(c) => write!(f, "{}", c),
             Self::VerifyMembership(c) => write!(f, "{}", c),
             Self::Misbehaviour(c) => write!(f, "{}", c),
+            Self::VerifyMembershipBatch(c) => write!(f, "{}", c),
         }
     }
 }
@@ -112,6 +357,19 @@ impl TryFrom<ProxyMessage> for MisbehaviourProxyMessage {
     }
 }
 
+impl TryFrom<ProxyMessage> for VerifyMembershipBatchProxyMessage {
+    type Error = Error;
+    fn try_from(value: ProxyMessage) -> Result<Self, Self::Error> {
+        match value {
+            ProxyMessage::VerifyMembershipBatch(m) => Ok(m),
+            _ => Err(Error::unexpected_message_type(
+                MESSAGE_TYPE_STATE_BATCH,
+                value.message_type(),
+            )),
+        }
+    }
+}
+
 impl From<UpdateStateProxyMessage> for ProxyMessage {
     fn from(value: UpdateStateProxyMessage) -> Self {
         ProxyMessage::UpdateState(value)
@@ -130,67 +388,77 @@ impl From<MisbehaviourProxyMessage> for ProxyMessage {
     }
 }
 
+impl From<VerifyMembershipBatchProxyMessage> for ProxyMessage {
+    fn from(value: VerifyMembershipBatchProxyMessage) -> Self {
+        ProxyMessage::VerifyMembershipBatch(value)
+    }
+}
+
 sol! {
     struct EthABIHeaderedMessage {
         bytes32 header;
         bytes message;
     }
+
+    struct EthABIDomainBoundMessage {
+        string chain_id;
+        string client_id;
+        bytes message;
+    }
 }
 
 impl EthABIEncoder for ProxyMessage {
     fn ethabi_encode(self) -> Vec<u8> {
-        EthABIHeaderedMessage {
-            header: self.header().into(),
-            message: match self {
-                Self::UpdateState(c) => c.ethabi_encode(),
-                Self::VerifyMembership(c) => c.ethabi_encode(),
-                Self::Misbehaviour(c) => c.ethabi_encode(),
-            }
-            .into(),
-        }
-        .abi_encode()
+        self.to_bytes_with_encoding(None, MessageEncoding::EthABI)
     }
 
+    /// ethabi_decode decodes the EthABI-encoded message of any supported schema version and discards the domain.
+    ///
+    /// Verifiers that need to check the domain should use `ProxyMessage::from_bytes_with_domain` instead.
     fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
-        let eth_abi_message = EthABIHeaderedMessage::abi_decode(bz, true)?;
-        let (version, message_type) = {
-            let header = &eth_abi_message.header;
-            if header.len() != MESSAGE_HEADER_SIZE {
-                return Err(Error::invalid_message_header(format!(
-                    "invalid header length: expected={MESSAGE_HEADER_SIZE} actual={} header={:?}",
-                    header.len(),
-                    eth_abi_message.header
-                )));
-            }
-            let mut version = [0u8; 2];
-            version.copy_from_slice(&header[0..=1]);
-            let mut commitment_type = [0u8; 2];
-            commitment_type.copy_from_slice(&header[2..=3]);
-            (
-                u16::from_be_bytes(version),
-                u16::from_be_bytes(commitment_type),
-            )
-        };
-        if version != MESSAGE_SCHEMA_VERSION {
-            return Err(Error::invalid_message_header(format!(
-                "invalid version: expected={} actual={} header={:?}",
-                MESSAGE_SCHEMA_VERSION, version, eth_abi_message.header
-            )));
+        let (message, _, encoding) = Self::from_bytes_with_encoding(bz)?;
+        if encoding != MessageEncoding::EthABI {
+            return Err(Error::unexpected_message_encoding(
+                MessageEncoding::EthABI,
+                encoding,
+            ));
         }
-        let message = eth_abi_message.message;
-        match message_type {
-            MESSAGE_TYPE_UPDATE_STATE => {
-                Ok(UpdateStateProxyMessage::ethabi_decode(&message)?.into())
-            }
-            MESSAGE_TYPE_STATE => Ok(VerifyMembershipProxyMessage::ethabi_decode(&message)?.into()),
-            MESSAGE_TYPE_MISBEHAVIOUR => {
-                Ok(MisbehaviourProxyMessage::ethabi_decode(&message)?.into())
-            }
-            _ => Err(Error::invalid_abi(format!(
-                "invalid message type: {}",
-                message_type
-            ))),
+        Ok(message)
+    }
+}
+
+impl ProtobufEncoder for ProxyMessage {
+    fn protobuf_encode(self) -> Vec<u8> {
+        self.to_bytes_with_encoding(None, MessageEncoding::Protobuf)
+    }
+
+    /// protobuf_decode decodes the protobuf-encoded message of any supported schema version and discards the domain.
+    fn protobuf_decode(bz: &[u8]) -> Result<Self, Error> {
+        let (message, _, encoding) = Self::from_bytes_with_encoding(bz)?;
+        if encoding != MessageEncoding::Protobuf {
+            return Err(Error::unexpected_message_encoding(
+                MessageEncoding::Protobuf,
+                encoding,
+            ));
         }
+        Ok(message)
+    }
+}
+
+fn encode_as<T: EthABIEncoder + ProtobufEncoder>(encoding: MessageEncoding, value: T) -> Vec<u8> {
+    match encoding {
+        MessageEncoding::EthABI => value.ethabi_encode(),
+        MessageEncoding::Protobuf => value.protobuf_encode(),
+    }
+}
+
+fn decode_as<T: EthABIEncoder + ProtobufEncoder>(
+    encoding: MessageEncoding,
+    bz: &[u8],
+) -> Result<T, Error> {
+    match encoding {
+        MessageEncoding::EthABI => T::ethabi_decode(bz),
+        MessageEncoding::Protobuf => T::protobuf_decode(bz),
     }
 }
 
@@ -198,8 +466,8 @@ impl EthABIEncoder for ProxyMessage {
 mod tests {
     use super::*;
     use crate::{
-        message::verify_membership::CommitmentPrefix, CommitmentProof, StateID,
-        TrustingPeriodContext,
+        message::verify_membership::CommitmentPrefix, verify_emitted_state, CommitmentProof,
+        StateID, TrustingPeriodContext,
     };
     use lcp_types::{nanos_to_duration, Any, Height, Time, MAX_UNIX_TIMESTAMP_NANOS};
     use proptest::prelude::*;
@@ -213,6 +481,35 @@ mod tests {
         let c2 = UpdateStateProxyMessage::ethabi_decode(&v).unwrap();
         assert_eq!(c1, c2);
 
+        let v = c1.clone().protobuf_encode();
+        let c2 = UpdateStateProxyMessage::protobuf_decode(&v).unwrap();
+        assert_eq!(c1, c2);
+        let msg = ProxyMessage::from(c1.clone());
+        let v = msg.clone().protobuf_encode();
+        assert_eq!(msg, ProxyMessage::protobuf_decode(&v).unwrap());
+        assert_eq!(msg, ProxyMessage::from_bytes(&v).unwrap());
+        // the encoding in the header must match the decoder
+        assert!(ProxyMessage::ethabi_decode(&v).is_err());
+        assert!(ProxyMessage::protobuf_decode(&msg.clone().to_bytes()).is_err());
+
+        let mut c3 = c1.clone();
+        let proofs = c3.commit_emitted_states().unwrap();
+        assert_eq!(proofs.len(), c1.emitted_states.len());
+        assert!(c3.emitted_states.is_empty());
+        if let Some(root) = c3.emitted_states_root {
+            for (state, proof) in c1.emitted_states.iter().zip(proofs.iter()) {
+                assert!(verify_emitted_state(root, state, proof).is_ok());
+            }
+        }
+        let msg = ProxyMessage::from(c3);
+        for encoding in [MessageEncoding::EthABI, MessageEncoding::Protobuf] {
+            let (msg2, _, _) = ProxyMessage::from_bytes_with_encoding(
+                &msg.clone().to_bytes_with_encoding(None, encoding),
+            )
+            .unwrap();
+            assert_eq!(msg, msg2);
+        }
+
         let p1 = CommitmentProof {
             message: ProxyMessage::from(c1).to_bytes(),
             signature: proof_signature.to_vec(),
@@ -221,6 +518,8 @@ mod tests {
         // println!("{{\"{}\"}},", hex::encode(p1.clone().ethabi_encode()));
         let p2 = CommitmentProof::ethabi_decode(&p1.clone().ethabi_encode()).unwrap();
         assert_eq!(p1, p2);
+        let p2 = CommitmentProof::protobuf_decode(&p1.clone().protobuf_encode()).unwrap();
+        assert_eq!(p1, p2);
     }
 
     proptest! {
@@ -244,6 +543,7 @@ mod tests {
                 }).collect(),
                 timestamp: Time::from_unix_timestamp_nanos(timestamp).unwrap(),
                 context: Default::default(),
+                emitted_states_root: None,
             };
             test_update_client_message(c1, proof_signature.to_vec());
         }
@@ -277,6 +577,7 @@ mod tests {
                     Time::from_unix_timestamp_nanos(untrusted_header_timestamp).unwrap(),
                     Time::from_unix_timestamp_nanos(trusted_state_timestamp).unwrap(),
                 ).into(),
+                emitted_states_root: None,
             };
             test_update_client_message(c1, proof_signature.to_vec());
         }
@@ -300,6 +601,9 @@ mod tests {
             let v = c1.clone().ethabi_encode();
             let c2 = VerifyMembershipProxyMessage::ethabi_decode(&v).unwrap();
             assert_eq!(c1, c2);
+            let v = c1.clone().protobuf_encode();
+            let c2 = VerifyMembershipProxyMessage::protobuf_decode(&v).unwrap();
+            assert_eq!(c1, c2);
 
             let p1 = CommitmentProof {
                 message: ProxyMessage::from(c1).to_bytes(),
@@ -309,6 +613,72 @@ mod tests {
             assert_eq!(p1, p2);
         }
 
+        #[test]
+        fn pt_verify_membership_batch(
+            prefix in any::<CommitmentPrefix>(),
+            entries in any::<Vec<(String, Option<[u8; 32]>)>>(),
+            height in any::<(u64, u64)>().prop_map(height_from_tuple),
+            state_id in any::<[u8; 32]>().prop_map(StateID::from),
+            proof_signature in any::<[u8; 65]>()
+        ) {
+            let c1 = VerifyMembershipBatchProxyMessage {
+                prefix,
+                entries: entries.into_iter().map(|(path, value)| MembershipEntry::new(path, value)).collect(),
+                height,
+                state_id,
+            };
+            let v = c1.clone().ethabi_encode();
+            let c2 = VerifyMembershipBatchProxyMessage::ethabi_decode(&v).unwrap();
+            assert_eq!(c1, c2);
+            let v = c1.clone().protobuf_encode();
+            let c2 = VerifyMembershipBatchProxyMessage::protobuf_decode(&v).unwrap();
+            assert_eq!(c1, c2);
+
+            let p1 = CommitmentProof {
+                message: ProxyMessage::from(c1).to_bytes(),
+                signature: proof_signature.to_vec(),
+            };
+            let p2 = CommitmentProof::ethabi_decode(&p1.clone().ethabi_encode()).unwrap();
+            assert_eq!(p1, p2);
+        }
+
+        #[test]
+        fn pt_verify_membership_with_domain(
+            prefix in any::<CommitmentPrefix>(),
+            path in any::<String>(),
+            value in any::<Option<[u8; 32]>>(),
+            height in any::<(u64, u64)>().prop_map(height_from_tuple),
+            state_id in any::<[u8; 32]>().prop_map(StateID::from),
+            chain_id in "[a-z0-9-]{1,32}",
+            client_id in "[a-z0-9-]{1,32}",
+        ) {
+            let msg: ProxyMessage = VerifyMembershipProxyMessage {
+                prefix,
+                path,
+                value,
+                height,
+                state_id,
+            }.into();
+            let domain = MessageDomain::new(chain_id, client_id);
+            let bz = msg.clone().to_bytes_with_domain(Some(domain.clone()));
+            let (msg2, domain2) = ProxyMessage::from_bytes_with_domain(&bz).unwrap();
+            assert_eq!(msg, msg2);
+            assert_eq!(Some(domain), domain2);
+            // the domain-bound message differs from the v1 message
+            assert_ne!(bz, msg.clone().to_bytes());
+            assert_eq!(msg, ProxyMessage::from_bytes(&bz).unwrap());
+
+            let (msg3, domain3) = ProxyMessage::from_bytes_with_domain(&msg.clone().to_bytes()).unwrap();
+            assert_eq!(msg, msg3);
+            assert_eq!(None, domain3);
+
+            let bz = msg.clone().to_bytes_with_encoding(Some(domain.clone()), MessageEncoding::Protobuf);
+            let (msg4, domain4, encoding4) = ProxyMessage::from_bytes_with_encoding(&bz).unwrap();
+            assert_eq!(msg, msg4);
+            assert_eq!(Some(domain), domain4);
+            assert_eq!(MessageEncoding::Protobuf, encoding4);
+        }
+
         #[test]
         fn pt_misbehaviour_with_empty_context(
             prev_states in any::<Vec<((u64, u64), [u8; 32])>>().prop_filter("empty prev_states", |v| !v.is_empty()),
@@ -328,6 +698,8 @@ mod tests {
             assert!(res.is_ok(), "validation failed: {}", res.unwrap_err());
             let msg2 = ProxyMessage::from_bytes(&msg.clone().to_bytes()).unwrap();
             assert_eq!(msg, msg2);
+            let msg2 = ProxyMessage::protobuf_decode(&msg.clone().protobuf_encode()).unwrap();
+            assert_eq!(msg, msg2);
         }
 
         #[test]
@@ -358,6 +730,8 @@ mod tests {
             assert!(res.is_ok(), "validation failed: {}", res.unwrap_err());
             let msg2 = ProxyMessage::from_bytes(&msg.clone().to_bytes()).unwrap();
             assert_eq!(msg, msg2);
+            let msg2 = ProxyMessage::protobuf_decode(&msg.clone().protobuf_encode()).unwrap();
+            assert_eq!(msg, msg2);
         }
     }
 }
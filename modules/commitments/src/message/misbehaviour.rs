@@ -1,10 +1,18 @@
-use crate::{encoder::EthABIHeight, prelude::*, Error, EthABIEncoder, StateID, ValidationContext};
+use crate::encoder::{required, EthABIHeight, ProtobufEncoder};
+use crate::{prelude::*, Error, EthABIEncoder, StateID, ValidationContext};
 use alloy_sol_types::{private::B256, sol, SolValue};
 use core::fmt::Display;
+use lcp_types::proto::lcp::commitments::v1::{
+    ClientMessages as ProtoClientMessages,
+    MisbehaviourProxyMessage as ProtoMisbehaviourProxyMessage, PrevState as ProtoPrevState,
+};
 use lcp_types::{Any, Height};
 use prost::Message;
 use serde::{Deserialize, Serialize};
 
+/// The type URL of the client message that aggregates the client messages of multiple misbehaviour messages
+pub const AGGREGATED_CLIENT_MESSAGES_TYPE_URL: &str = "/lcp.commitments.v1.ClientMessages";
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MisbehaviourProxyMessage {
     pub prev_states: Vec<PrevState>,
@@ -25,6 +33,79 @@ impl MisbehaviourProxyMessage {
         }
         Ok(())
     }
+
+    /// aggregate merges the evidence of two misbehaviour messages into one.
+    ///
+    /// The prev_states are merged as a set, and the contexts are aggregated in the same way as the update state messages.
+    /// The client message becomes an `AGGREGATED_CLIENT_MESSAGES_TYPE_URL` message that contains the client messages of both.
+    pub fn aggregate(self, other: Self) -> Result<Self, Error> {
+        let mut prev_states = self.prev_states;
+        for state in other.prev_states {
+            match prev_states.iter().find(|s| s.height == state.height) {
+                Some(s) if s.state_id != state.state_id => {
+                    return Err(Error::message_aggregation_failed(format!(
+                        "conflicting prev_state: height={} state_id={} other_state_id={}",
+                        state.height, s.state_id, state.state_id
+                    )));
+                }
+                Some(_) => {}
+                None => prev_states.push(state),
+            }
+        }
+        Ok(Self {
+            prev_states,
+            context: self.context.aggregate(other.context)?,
+            client_message: aggregate_client_messages(self.client_message, other.client_message)?,
+        })
+    }
+
+    /// client_messages returns the client messages contained in the message
+    pub fn client_messages(&self) -> Result<Vec<Any>, Error> {
+        if self.client_message.type_url == AGGREGATED_CLIENT_MESSAGES_TYPE_URL {
+            Ok(
+                ProtoClientMessages::decode(self.client_message.value.as_slice())?
+                    .client_messages
+                    .into_iter()
+                    .map(Any::from)
+                    .collect(),
+            )
+        } else {
+            Ok(vec![self.client_message.clone()])
+        }
+    }
+}
+
+fn aggregate_client_messages(left: Any, right: Any) -> Result<Any, Error> {
+    let mut client_messages = vec![];
+    for any in [left, right] {
+        if any.type_url == AGGREGATED_CLIENT_MESSAGES_TYPE_URL {
+            client_messages
+                .extend(ProtoClientMessages::decode(any.value.as_slice())?.client_messages);
+        } else {
+            client_messages.push(any.to_proto());
+        }
+    }
+    Ok(Any::new(
+        AGGREGATED_CLIENT_MESSAGES_TYPE_URL.to_string(),
+        ProtoClientMessages { client_messages }.encode_to_vec(),
+    ))
+}
+
+/// Aggregate a list of misbehaviour messages into a single message
+pub fn aggregate_misbehaviour_messages(
+    messages: Vec<MisbehaviourProxyMessage>,
+) -> Result<MisbehaviourProxyMessage, Error> {
+    if messages.is_empty() {
+        return Err(Error::message_aggregation_failed(
+            "cannot aggregate empty messages".to_string(),
+        ));
+    }
+    let mut messages = messages.into_iter();
+    let mut message = messages.next().unwrap();
+    for m in messages {
+        message = message.aggregate(m)?;
+    }
+    Ok(message)
 }
 
 impl Display for MisbehaviourProxyMessage {
@@ -117,3 +198,148 @@ impl EthABIEncoder for MisbehaviourProxyMessage {
         EthABIMisbehaviourProxyMessage::abi_decode(bz, true)?.try_into()
     }
 }
+
+impl From<MisbehaviourProxyMessage> for ProtoMisbehaviourProxyMessage {
+    fn from(msg: MisbehaviourProxyMessage) -> Self {
+        Self {
+            prev_states: msg
+                .prev_states
+                .into_iter()
+                .map(|v| ProtoPrevState {
+                    height: Some(v.height.into()),
+                    state_id: v.state_id.to_vec(),
+                })
+                .collect(),
+            context: Some(msg.context.into()),
+            client_message: Some(msg.client_message.into()),
+        }
+    }
+}
+
+impl TryFrom<ProtoMisbehaviourProxyMessage> for MisbehaviourProxyMessage {
+    type Error = Error;
+
+    fn try_from(msg: ProtoMisbehaviourProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prev_states: msg
+                .prev_states
+                .into_iter()
+                .map(|v| -> Result<_, Error> {
+                    Ok(PrevState {
+                        height: required(v.height, "height")?.into(),
+                        state_id: v.state_id.as_slice().try_into()?,
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            context: msg
+                .context
+                .map(ValidationContext::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+            client_message: required(msg.client_message, "client_message")?.into(),
+        })
+    }
+}
+
+impl ProtobufEncoder for MisbehaviourProxyMessage {
+    fn protobuf_encode(self) -> Vec<u8> {
+        ProtoMisbehaviourProxyMessage::from(self).encode_to_vec()
+    }
+
+    fn protobuf_decode(bz: &[u8]) -> Result<Self, Error> {
+        ProtoMisbehaviourProxyMessage::decode(bz)?.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrustingPeriodContext;
+    use core::time::Duration;
+    use lcp_types::Time;
+
+    fn prev_state(height: u64, state_id: u8) -> PrevState {
+        PrevState {
+            height: Height::new(0, height),
+            state_id: StateID::from([state_id; 32]),
+        }
+    }
+
+    fn context(
+        untrusted_header_timestamp: u128,
+        trusted_state_timestamp: u128,
+    ) -> ValidationContext {
+        TrustingPeriodContext::new(
+            Duration::from_secs(10),
+            Duration::from_secs(1),
+            Time::from_unix_timestamp_nanos(untrusted_header_timestamp).unwrap(),
+            Time::from_unix_timestamp_nanos(trusted_state_timestamp).unwrap(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_misbehaviour_message_aggregation() {
+        let msg0 = MisbehaviourProxyMessage {
+            prev_states: vec![prev_state(1, 1), prev_state(2, 2)],
+            context: context(3, 2),
+            client_message: Any::new("/foo".to_string(), vec![0u8; 32]),
+        };
+        let msg1 = MisbehaviourProxyMessage {
+            prev_states: vec![prev_state(2, 2), prev_state(3, 3)],
+            context: context(4, 1),
+            client_message: Any::new("/bar".to_string(), vec![1u8; 32]),
+        };
+        let msg2 = MisbehaviourProxyMessage {
+            prev_states: vec![prev_state(4, 4)],
+            context: ValidationContext::Empty,
+            client_message: Any::new("/baz".to_string(), vec![2u8; 32]),
+        };
+        let aggregated =
+            aggregate_misbehaviour_messages(vec![msg0.clone(), msg1.clone(), msg2.clone()])
+                .unwrap();
+        assert!(aggregated.validate().is_ok());
+        assert_eq!(
+            aggregated.prev_states,
+            vec![
+                prev_state(1, 1),
+                prev_state(2, 2),
+                prev_state(3, 3),
+                prev_state(4, 4)
+            ]
+        );
+        assert_eq!(aggregated.context, context(4, 1));
+        assert_eq!(
+            aggregated.client_messages().unwrap(),
+            vec![
+                msg0.client_message.clone(),
+                msg1.client_message.clone(),
+                msg2.client_message.clone()
+            ]
+        );
+        assert_eq!(
+            MisbehaviourProxyMessage::ethabi_decode(&aggregated.clone().ethabi_encode()).unwrap(),
+            aggregated
+        );
+
+        // single message
+        assert_eq!(
+            aggregate_misbehaviour_messages(vec![msg0.clone()]).unwrap(),
+            msg0
+        );
+        assert_eq!(
+            msg0.client_messages().unwrap(),
+            vec![msg0.client_message.clone()]
+        );
+
+        // empty messages
+        assert!(aggregate_misbehaviour_messages(vec![]).is_err());
+
+        // conflicting prev_states
+        let msg3 = MisbehaviourProxyMessage {
+            prev_states: vec![prev_state(1, 9)],
+            ..msg2
+        };
+        assert!(msg0.aggregate(msg3).is_err());
+    }
+}
@@ -1,9 +1,13 @@
 use crate::context::ValidationContext;
-use crate::encoder::{EthABIEmittedState, EthABIEncoder, EthABIHeight};
+use crate::encoder::{
+    optional_bytes32, required, EthABIEmittedState, EthABIEncoder, EthABIHeight, ProtobufEncoder,
+};
+use crate::merkle::{compute_emitted_states_root, compute_merkle_root, MerkleProof};
 use crate::prelude::*;
 use crate::{Error, StateID};
 use alloy_sol_types::{private::B256, sol, SolValue};
 use core::fmt::Display;
+use lcp_types::proto::lcp::commitments::v1::UpdateStateProxyMessage as ProtoUpdateStateProxyMessage;
 use lcp_types::{Any, Height, Time};
 use prost::Message;
 use serde::{Deserialize, Serialize};
@@ -17,6 +21,11 @@ pub struct UpdateStateProxyMessage {
     pub timestamp: Time,
     pub context: ValidationContext,
     pub emitted_states: Vec<EmittedState>,
+    /// The merkle root of the emitted states if they are committed by the root instead of inline
+    ///
+    /// See `crate::merkle` for the tree construction.
+    #[serde(default)]
+    pub emitted_states_root: Option<[u8; 32]>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -38,9 +47,31 @@ impl UpdateStateProxyMessage {
         if self.prev_height.is_none() != self.prev_state_id.is_none() {
             return Err(Error::invalid_prev_state_and_height());
         }
+        if !self.emitted_states.is_empty() && self.emitted_states_root.is_some() {
+            return Err(Error::emitted_states_conflict());
+        }
         Ok(())
     }
 
+    /// has_emitted_states returns true if the message emits states either inline or by the root
+    pub fn has_emitted_states(&self) -> bool {
+        !self.emitted_states.is_empty() || self.emitted_states_root.is_some()
+    }
+
+    /// commit_emitted_states replaces the emitted states with their merkle root.
+    ///
+    /// It returns the inclusion proof of each emitted state in the original order.
+    /// If the message has no emitted states, the message is unchanged and the proofs are empty.
+    pub fn commit_emitted_states(&mut self) -> Result<Vec<MerkleProof>, Error> {
+        if self.emitted_states.is_empty() {
+            return Ok(vec![]);
+        }
+        let (root, proofs) = compute_emitted_states_root(&self.emitted_states)?;
+        self.emitted_states = vec![];
+        self.emitted_states_root = Some(root);
+        Ok(proofs)
+    }
+
     pub fn aggregate(self, other: Self) -> Result<Self, Error> {
         if self.post_state_id != other.prev_state_id.unwrap_or_default() {
             return Err(Error::message_aggregation_failed(format!(
@@ -56,6 +87,18 @@ impl UpdateStateProxyMessage {
                 other.prev_height.unwrap_or_default()
             )));
         }
+        let emitted_states_root = match (self.emitted_states_root, other.emitted_states_root) {
+            (None, None) => None,
+            (left, right) => {
+                if !self.emitted_states.is_empty() || !other.emitted_states.is_empty() {
+                    return Err(Error::message_aggregation_failed(
+                        "cannot aggregate inline emitted states and emitted states roots"
+                            .to_string(),
+                    ));
+                }
+                Some(compute_merkle_root(left.into_iter().chain(right).collect())?.0)
+            }
+        };
         Ok(Self {
             prev_height: self.prev_height,
             prev_state_id: self.prev_state_id,
@@ -64,6 +107,7 @@ impl UpdateStateProxyMessage {
             timestamp: other.timestamp,
             context: self.context.aggregate(other.context)?,
             emitted_states: [self.emitted_states, other.emitted_states].concat(),
+            emitted_states_root,
         })
     }
 }
@@ -72,14 +116,15 @@ impl Display for UpdateStateProxyMessage {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "UpdateClient(prev_height: {}, prev_state_id: {}, post_height: {}, post_state_id: {}, timestamp: {}, context: {}, emitted_states: [{}])",
+            "UpdateClient(prev_height: {}, prev_state_id: {}, post_height: {}, post_state_id: {}, timestamp: {}, context: {}, emitted_states: [{}], emitted_states_root: {})",
             self.prev_height.as_ref().map_or("None".to_string(), |h| h.to_string()),
             self.prev_state_id.as_ref().map_or("None".to_string(), |id| id.to_string()),
             self.post_height,
             self.post_state_id,
             self.timestamp.as_unix_timestamp_nanos(),
             self.context,
-            self.emitted_states.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+            self.emitted_states.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "),
+            self.emitted_states_root.map_or("None".to_string(), |r| format!("0x{}", hex::encode(r)))
         )
     }
 }
@@ -88,17 +133,56 @@ impl Display for UpdateStateProxyMessage {
 pub fn aggregate_messages(
     messages: Vec<UpdateStateProxyMessage>,
 ) -> Result<UpdateStateProxyMessage, Error> {
+    Ok(aggregate_messages_with_proofs(messages)?.0)
+}
+
+/// Aggregate a list of messages into a single message, and return the inclusion proof of each message's emitted states root
+///
+/// The emitted states root of the aggregated message is the merkle root of the emitted states roots of the given messages.
+/// A proof of an emitted state against the root of a given message can be joined with the returned proof for that message
+/// to prove the state against the aggregated root. The proof is empty for a message without an emitted states root.
+pub fn aggregate_messages_with_proofs(
+    messages: Vec<UpdateStateProxyMessage>,
+) -> Result<(UpdateStateProxyMessage, Vec<MerkleProof>), Error> {
     if messages.is_empty() {
         return Err(Error::message_aggregation_failed(
             "cannot aggregate empty messages".to_string(),
         ));
     }
-    let mut messages = messages.into_iter();
+    let roots: Vec<_> = messages
+        .iter()
+        .filter_map(|m| m.emitted_states_root)
+        .collect();
+    let (root, proofs) = if roots.is_empty() {
+        (None, vec![MerkleProof::default(); messages.len()])
+    } else {
+        if messages.iter().any(|m| !m.emitted_states.is_empty()) {
+            return Err(Error::message_aggregation_failed(
+                "cannot aggregate inline emitted states and emitted states roots".to_string(),
+            ));
+        }
+        let (root, root_proofs) = compute_merkle_root(roots)?;
+        let mut root_proofs = root_proofs.into_iter();
+        let proofs = messages
+            .iter()
+            .map(|m| match m.emitted_states_root {
+                Some(_) => root_proofs.next().unwrap(),
+                None => MerkleProof::default(),
+            })
+            .collect();
+        (Some(root), proofs)
+    };
+    // the roots are aggregated as a balanced tree above instead of pairwise
+    let mut messages = messages.into_iter().map(|m| UpdateStateProxyMessage {
+        emitted_states_root: None,
+        ..m
+    });
     let mut message = messages.next().unwrap();
     for m in messages {
         message = message.aggregate(m)?;
     }
-    Ok(message)
+    message.emitted_states_root = root;
+    Ok((message, proofs))
 }
 
 sol! {
@@ -111,6 +195,16 @@ sol! {
         bytes context;
         EthABIEmittedState[] emitted_states;
     }
+
+    struct EthABIUpdateStateWithRootProxyMessage {
+        EthABIHeight prev_height;
+        bytes32 prev_state_id;
+        EthABIHeight post_height;
+        bytes32 post_state_id;
+        uint128 timestamp;
+        bytes context;
+        bytes32 emitted_states_root;
+    }
 }
 
 impl From<UpdateStateProxyMessage> for EthABIUpdateStateProxyMessage {
@@ -149,13 +243,59 @@ impl TryFrom<EthABIUpdateStateProxyMessage> for UpdateStateProxyMessage {
                 .into_iter()
                 .map(EmittedState::try_from)
                 .collect::<Result<Vec<_>, _>>()?,
+            emitted_states_root: None,
+        })
+    }
+}
+
+impl From<UpdateStateProxyMessage> for EthABIUpdateStateWithRootProxyMessage {
+    fn from(msg: UpdateStateProxyMessage) -> Self {
+        Self {
+            prev_height: msg.prev_height.into(),
+            prev_state_id: B256::from_slice(
+                msg.prev_state_id.unwrap_or_default().to_vec().as_slice(),
+            ),
+            post_height: msg.post_height.into(),
+            post_state_id: B256::from_slice(msg.post_state_id.to_vec().as_slice()),
+            timestamp: msg.timestamp.as_unix_timestamp_nanos(),
+            context: msg.context.ethabi_encode().into(),
+            emitted_states_root: B256::from(msg.emitted_states_root.unwrap_or_default()),
+        }
+    }
+}
+
+impl TryFrom<EthABIUpdateStateWithRootProxyMessage> for UpdateStateProxyMessage {
+    type Error = Error;
+    fn try_from(msg: EthABIUpdateStateWithRootProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prev_height: msg.prev_height.into(),
+            prev_state_id: (!msg.prev_state_id.is_zero())
+                .then_some(StateID::from(msg.prev_state_id.0)),
+            post_height: msg.post_height.into(),
+            post_state_id: msg.post_state_id.as_slice().try_into()?,
+            timestamp: Time::from_unix_timestamp_nanos(msg.timestamp)?,
+            context: ValidationContext::ethabi_decode(msg.context.as_ref())?,
+            emitted_states: vec![],
+            emitted_states_root: Some(msg.emitted_states_root.0),
         })
     }
 }
 
+impl UpdateStateProxyMessage {
+    /// ethabi_decode_with_root decodes the EthABI-encoded message that commits the emitted states by the root
+    pub fn ethabi_decode_with_root(bz: &[u8]) -> Result<Self, Error> {
+        EthABIUpdateStateWithRootProxyMessage::abi_decode(bz, true)?.try_into()
+    }
+}
+
 impl EthABIEncoder for UpdateStateProxyMessage {
+    /// ethabi_encode encodes the message with the emitted states root instead of the emitted states if the root is set.
     fn ethabi_encode(self) -> Vec<u8> {
-        Into::<EthABIUpdateStateProxyMessage>::into(self).abi_encode()
+        if self.emitted_states_root.is_some() {
+            Into::<EthABIUpdateStateWithRootProxyMessage>::into(self).abi_encode()
+        } else {
+            Into::<EthABIUpdateStateProxyMessage>::into(self).abi_encode()
+        }
     }
 
     fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
@@ -163,6 +303,59 @@ impl EthABIEncoder for UpdateStateProxyMessage {
     }
 }
 
+impl From<UpdateStateProxyMessage> for ProtoUpdateStateProxyMessage {
+    fn from(msg: UpdateStateProxyMessage) -> Self {
+        Self {
+            prev_height: msg.prev_height.map(Into::into),
+            prev_state_id: msg.prev_state_id.map_or(vec![], |id| id.to_vec()),
+            post_height: Some(msg.post_height.into()),
+            post_state_id: msg.post_state_id.to_vec(),
+            timestamp: Some(msg.timestamp.into()),
+            context: Some(msg.context.into()),
+            emitted_states: msg.emitted_states.into_iter().map(Into::into).collect(),
+            emitted_states_root: msg.emitted_states_root.map_or(vec![], |r| r.to_vec()),
+        }
+    }
+}
+
+impl TryFrom<ProtoUpdateStateProxyMessage> for UpdateStateProxyMessage {
+    type Error = Error;
+    fn try_from(msg: ProtoUpdateStateProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prev_height: msg.prev_height.map(Into::into),
+            prev_state_id: if msg.prev_state_id.is_empty() {
+                None
+            } else {
+                Some(msg.prev_state_id.as_slice().try_into()?)
+            },
+            post_height: required(msg.post_height, "post_height")?.into(),
+            post_state_id: msg.post_state_id.as_slice().try_into()?,
+            timestamp: required(msg.timestamp, "timestamp")?.try_into()?,
+            context: msg
+                .context
+                .map(ValidationContext::try_from)
+                .transpose()?
+                .unwrap_or_default(),
+            emitted_states: msg
+                .emitted_states
+                .into_iter()
+                .map(EmittedState::try_from)
+                .collect::<Result<Vec<_>, _>>()?,
+            emitted_states_root: optional_bytes32(msg.emitted_states_root)?,
+        })
+    }
+}
+
+impl ProtobufEncoder for UpdateStateProxyMessage {
+    fn protobuf_encode(self) -> Vec<u8> {
+        ProtoUpdateStateProxyMessage::from(self).encode_to_vec()
+    }
+
+    fn protobuf_decode(bz: &[u8]) -> Result<Self, Error> {
+        ProtoUpdateStateProxyMessage::decode(bz)?.try_into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -180,6 +373,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             let msg1 = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(2, 2)),
@@ -189,6 +383,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             let expected = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(1, 1)),
@@ -198,6 +393,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             assert_eq!(aggregate_messages(vec![msg0, msg1]).unwrap(), expected);
         }
@@ -213,6 +409,7 @@ mod tests {
                     Height::new(1, 1),
                     Any::new("/foo".to_string(), vec![1u8; 32]),
                 )],
+                emitted_states_root: None,
             };
             let msg1 = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(2, 2)),
@@ -225,6 +422,7 @@ mod tests {
                     Height::new(2, 2),
                     Any::new("/bar".to_string(), vec![2u8; 32]),
                 )],
+                emitted_states_root: None,
             };
             let expected = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(1, 1)),
@@ -243,6 +441,7 @@ mod tests {
                         Any::new("/bar".to_string(), vec![2u8; 32]),
                     ),
                 ],
+                emitted_states_root: None,
             };
             assert_eq!(aggregate_messages(vec![msg0, msg1]).unwrap(), expected);
         }
@@ -262,6 +461,7 @@ mod tests {
                 )
                 .into(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             let msg1 = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(2, 2)),
@@ -277,6 +477,7 @@ mod tests {
                 )
                 .into(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             let expected = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(1, 1)),
@@ -292,6 +493,7 @@ mod tests {
                 )
                 .into(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             assert_eq!(aggregate_messages(vec![msg0, msg1]).unwrap(), expected);
         }
@@ -305,6 +507,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             let msg1 = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(2, 2)),
@@ -314,6 +517,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             assert!(msg0.aggregate(msg1).is_err());
         }
@@ -327,6 +531,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             let msg1 = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(3, 3)),
@@ -336,6 +541,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             assert!(msg0.aggregate(msg1).is_err());
         }
@@ -353,6 +559,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             assert_eq!(aggregate_messages(vec![msg0.clone()]).unwrap(), msg0);
         }
@@ -366,6 +573,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(1).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             let msg1 = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(2, 2)),
@@ -375,6 +583,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(2).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             let msg2 = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(3, 3)),
@@ -384,6 +593,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(3).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             let expected = UpdateStateProxyMessage {
                 prev_height: Some(Height::new(1, 1)),
@@ -393,6 +603,7 @@ mod tests {
                 timestamp: Time::from_unix_timestamp_nanos(3).unwrap(),
                 context: ValidationContext::default(),
                 emitted_states: vec![],
+                emitted_states_root: None,
             };
             assert_eq!(
                 aggregate_messages(vec![msg0, msg1, msg2]).unwrap(),
@@ -400,4 +611,69 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_update_client_message_aggregation_with_emitted_states_root() {
+        let message = |i: u64, emitted: bool| {
+            let mut msg = UpdateStateProxyMessage {
+                prev_height: Some(Height::new(0, i)),
+                prev_state_id: Some(StateID::from([i as u8; 32])),
+                post_height: Height::new(0, i + 1),
+                post_state_id: StateID::from([i as u8 + 1; 32]),
+                timestamp: Time::from_unix_timestamp_nanos(i as u128).unwrap(),
+                context: ValidationContext::default(),
+                emitted_states: if emitted {
+                    vec![
+                        EmittedState(
+                            Height::new(0, i + 1),
+                            Any::new("/foo".to_string(), vec![i as u8; 32]),
+                        ),
+                        EmittedState(
+                            Height::new(0, i + 1),
+                            Any::new("/bar".to_string(), vec![i as u8; 32]),
+                        ),
+                    ]
+                } else {
+                    vec![]
+                },
+                emitted_states_root: None,
+            };
+            let states = msg.emitted_states.clone();
+            let proofs = msg.commit_emitted_states().unwrap();
+            assert!(msg.validate().is_ok());
+            (msg, states, proofs)
+        };
+
+        // the emitted states can be proven against the aggregated root by joining the proofs
+        let messages: Vec<_> = (1..=5).map(|i| message(i, i != 3)).collect();
+        let (aggregated, root_proofs) =
+            aggregate_messages_with_proofs(messages.iter().map(|(m, _, _)| m.clone()).collect())
+                .unwrap();
+        assert_eq!(root_proofs.len(), messages.len());
+        assert!(aggregated.emitted_states.is_empty());
+        let root = aggregated.emitted_states_root.unwrap();
+        for ((_, states, proofs), root_proof) in messages.iter().zip(root_proofs.iter()) {
+            for (state, proof) in states.iter().zip(proofs.iter()) {
+                let proof = proof.clone().join(root_proof.clone());
+                assert!(crate::verify_emitted_state(root, state, &proof).is_ok());
+            }
+        }
+        assert_eq!(
+            aggregate_messages(messages.iter().map(|(m, _, _)| m.clone()).collect()).unwrap(),
+            aggregated
+        );
+
+        // inline emitted states and emitted states roots cannot be aggregated
+        let mut inline = message(6, false).0;
+        inline.emitted_states = vec![EmittedState(
+            Height::new(0, 7),
+            Any::new("/foo".to_string(), vec![]),
+        )];
+        assert!(aggregate_messages(vec![messages[4].0.clone(), inline.clone()]).is_err());
+        assert!(messages[4].0.clone().aggregate(inline.clone()).is_err());
+
+        // the emitted states and the root are mutually exclusive
+        inline.emitted_states_root = Some([1u8; 32]);
+        assert!(inline.validate().is_err());
+    }
 }
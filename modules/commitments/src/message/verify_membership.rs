@@ -1,9 +1,11 @@
-use crate::encoder::{EthABIEncoder, EthABIHeight};
+use crate::encoder::{optional_bytes32, required, EthABIEncoder, EthABIHeight, ProtobufEncoder};
 use crate::prelude::*;
 use crate::{Error, StateID};
 use alloy_sol_types::{private::B256, sol, SolValue};
 use core::fmt::Display;
+use lcp_types::proto::lcp::commitments::v1::VerifyMembershipProxyMessage as ProtoVerifyMembershipProxyMessage;
 use lcp_types::Height;
+use prost::Message;
 use serde::{Deserialize, Serialize};
 
 pub type CommitmentPrefix = Vec<u8>;
@@ -106,3 +108,38 @@ impl EthABIEncoder for VerifyMembershipProxyMessage {
         EthABIVerifyMembershipProxyMessage::abi_decode(bz, true)?.try_into()
     }
 }
+
+impl From<VerifyMembershipProxyMessage> for ProtoVerifyMembershipProxyMessage {
+    fn from(msg: VerifyMembershipProxyMessage) -> Self {
+        Self {
+            prefix: msg.prefix,
+            path: msg.path,
+            value: msg.value.map_or(vec![], |v| v.to_vec()),
+            height: Some(msg.height.into()),
+            state_id: msg.state_id.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<ProtoVerifyMembershipProxyMessage> for VerifyMembershipProxyMessage {
+    type Error = Error;
+    fn try_from(msg: ProtoVerifyMembershipProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prefix: msg.prefix,
+            path: msg.path,
+            value: optional_bytes32(msg.value)?,
+            height: required(msg.height, "height")?.into(),
+            state_id: msg.state_id.as_slice().try_into()?,
+        })
+    }
+}
+
+impl ProtobufEncoder for VerifyMembershipProxyMessage {
+    fn protobuf_encode(self) -> Vec<u8> {
+        ProtoVerifyMembershipProxyMessage::from(self).encode_to_vec()
+    }
+
+    fn protobuf_decode(bz: &[u8]) -> Result<Self, Error> {
+        ProtoVerifyMembershipProxyMessage::decode(bz)?.try_into()
+    }
+}
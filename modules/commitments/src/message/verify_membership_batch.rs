@@ -0,0 +1,224 @@
+use crate::encoder::{optional_bytes32, required, EthABIEncoder, EthABIHeight, ProtobufEncoder};
+use crate::message::verify_membership::CommitmentPrefix;
+use crate::prelude::*;
+use crate::{Error, StateID};
+use alloy_sol_types::{private::B256, sol, SolValue};
+use core::fmt::Display;
+use lcp_types::proto::lcp::commitments::v1::{
+    MembershipEntry as ProtoMembershipEntry,
+    VerifyMembershipBatchProxyMessage as ProtoVerifyMembershipBatchProxyMessage,
+};
+use lcp_types::Height;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+
+/// VerifyMembershipBatchProxyMessage commits to multiple `(path, value)` pairs that are verified at the same height and state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerifyMembershipBatchProxyMessage {
+    pub prefix: CommitmentPrefix,
+    pub entries: Vec<MembershipEntry>,
+    pub height: Height,
+    pub state_id: StateID,
+}
+
+/// MembershipEntry is a pair of a path and the hash of the value stored at the path
+///
+/// If `value` is None, the entry represents the absence of the value at the path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MembershipEntry {
+    pub path: String,
+    pub value: Option<[u8; 32]>,
+}
+
+impl MembershipEntry {
+    pub fn new(path: String, value: Option<[u8; 32]>) -> Self {
+        Self { path, value }
+    }
+}
+
+impl Display for MembershipEntry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "(path: {}, value: {})",
+            self.path,
+            self.value.map_or("None".to_string(), hex::encode),
+        )
+    }
+}
+
+impl Display for VerifyMembershipBatchProxyMessage {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VerifyMembershipBatch(prefix: {:?}, entries: [{}], height: {}, state_id: {})",
+            self.prefix,
+            self.entries
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.height,
+            self.state_id,
+        )
+    }
+}
+
+impl VerifyMembershipBatchProxyMessage {
+    pub fn new(
+        prefix: CommitmentPrefix,
+        entries: Vec<MembershipEntry>,
+        height: Height,
+        state_id: StateID,
+    ) -> Self {
+        Self {
+            prefix,
+            entries,
+            height,
+            state_id,
+        }
+    }
+
+    /// contains returns true if the message commits to `value` at `path`
+    pub fn contains(&self, path: &str, value: Option<[u8; 32]>) -> bool {
+        self.entries
+            .iter()
+            .any(|e| e.path == path && e.value == value)
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.entries.is_empty() {
+            return Err(Error::empty_membership_entries());
+        }
+        if self.entries.iter().any(|e| e.path.is_empty()) {
+            return Err(Error::empty_path());
+        }
+        if self.height.is_zero() {
+            return Err(Error::zero_height());
+        }
+        if self.state_id.is_zero() {
+            return Err(Error::zero_state_id());
+        }
+        Ok(())
+    }
+}
+
+sol! {
+    struct EthABIMembershipEntry {
+        bytes path;
+        bytes32 value;
+    }
+
+    struct EthABIVerifyMembershipBatchProxyMessage {
+        bytes prefix;
+        EthABIMembershipEntry[] entries;
+        EthABIHeight height;
+        bytes32 state_id;
+    }
+}
+
+impl From<MembershipEntry> for EthABIMembershipEntry {
+    fn from(entry: MembershipEntry) -> Self {
+        Self {
+            path: entry.path.into_bytes().into(),
+            value: B256::from_slice(entry.value.unwrap_or_default().as_slice()),
+        }
+    }
+}
+
+impl TryFrom<EthABIMembershipEntry> for MembershipEntry {
+    type Error = Error;
+    fn try_from(entry: EthABIMembershipEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            path: String::from_utf8(entry.path.to_vec())?,
+            value: (!entry.value.is_zero()).then_some(entry.value.0),
+        })
+    }
+}
+
+impl From<VerifyMembershipBatchProxyMessage> for EthABIVerifyMembershipBatchProxyMessage {
+    fn from(msg: VerifyMembershipBatchProxyMessage) -> Self {
+        Self {
+            prefix: msg.prefix.into(),
+            entries: msg
+                .entries
+                .into_iter()
+                .map(EthABIMembershipEntry::from)
+                .collect(),
+            height: EthABIHeight::from(msg.height),
+            state_id: B256::from_slice(&msg.state_id.to_vec()),
+        }
+    }
+}
+
+impl TryFrom<EthABIVerifyMembershipBatchProxyMessage> for VerifyMembershipBatchProxyMessage {
+    type Error = Error;
+    fn try_from(msg: EthABIVerifyMembershipBatchProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prefix: msg.prefix.into(),
+            entries: msg
+                .entries
+                .into_iter()
+                .map(MembershipEntry::try_from)
+                .collect::<Result<Vec<_>, _>>()?,
+            height: msg.height.into(),
+            state_id: msg.state_id.as_slice().try_into()?,
+        })
+    }
+}
+
+impl EthABIEncoder for VerifyMembershipBatchProxyMessage {
+    fn ethabi_encode(self) -> Vec<u8> {
+        Into::<EthABIVerifyMembershipBatchProxyMessage>::into(self).abi_encode()
+    }
+
+    fn ethabi_decode(bz: &[u8]) -> Result<Self, Error> {
+        EthABIVerifyMembershipBatchProxyMessage::abi_decode(bz, true)?.try_into()
+    }
+}
+
+impl From<VerifyMembershipBatchProxyMessage> for ProtoVerifyMembershipBatchProxyMessage {
+    fn from(msg: VerifyMembershipBatchProxyMessage) -> Self {
+        Self {
+            prefix: msg.prefix,
+            entries: msg
+                .entries
+                .into_iter()
+                .map(|e| ProtoMembershipEntry {
+                    path: e.path,
+                    value: e.value.map_or(vec![], |v| v.to_vec()),
+                })
+                .collect(),
+            height: Some(msg.height.into()),
+            state_id: msg.state_id.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<ProtoVerifyMembershipBatchProxyMessage> for VerifyMembershipBatchProxyMessage {
+    type Error = Error;
+    fn try_from(msg: ProtoVerifyMembershipBatchProxyMessage) -> Result<Self, Self::Error> {
+        Ok(Self {
+            prefix: msg.prefix,
+            entries: msg
+                .entries
+                .into_iter()
+                .map(|e| -> Result<_, Error> {
+                    Ok(MembershipEntry::new(e.path, optional_bytes32(e.value)?))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            height: required(msg.height, "height")?.into(),
+            state_id: msg.state_id.as_slice().try_into()?,
+        })
+    }
+}
+
+impl ProtobufEncoder for VerifyMembershipBatchProxyMessage {
+    fn protobuf_encode(self) -> Vec<u8> {
+        ProtoVerifyMembershipBatchProxyMessage::from(self).encode_to_vec()
+    }
+
+    fn protobuf_decode(bz: &[u8]) -> Result<Self, Error> {
+        ProtoVerifyMembershipBatchProxyMessage::decode(bz)?.try_into()
+    }
+}
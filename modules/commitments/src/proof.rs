@@ -1,5 +1,8 @@
-use crate::{encoder::EthABIEncoder, prelude::*, Error, ProxyMessage};
+use crate::encoder::{EthABIEncoder, ProtobufEncoder};
+use crate::{prelude::*, Error, MessageDomain, ProxyMessage};
 use alloy_sol_types::{sol, SolValue};
+use lcp_types::proto::lcp::commitments::v1::CommitmentProof as ProtoCommitmentProof;
+use prost::Message;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
@@ -27,6 +30,10 @@ impl CommitmentProof {
     pub fn message(&self) -> Result<ProxyMessage, Error> {
         ProxyMessage::from_bytes(&self.message)
     }
+
+    pub fn message_with_domain(&self) -> Result<(ProxyMessage, Option<MessageDomain>), Error> {
+        ProxyMessage::from_bytes_with_domain(&self.message)
+    }
 }
 
 impl EthABIEncoder for CommitmentProof {
@@ -39,6 +46,24 @@ impl EthABIEncoder for CommitmentProof {
     }
 }
 
+impl ProtobufEncoder for CommitmentProof {
+    fn protobuf_encode(self) -> Vec<u8> {
+        ProtoCommitmentProof {
+            message: self.message,
+            signature: self.signature,
+        }
+        .encode_to_vec()
+    }
+
+    fn protobuf_decode(bz: &[u8]) -> Result<Self, Error> {
+        let proof = ProtoCommitmentProof::decode(bz)?;
+        Ok(Self {
+            message: proof.message,
+            signature: proof.signature,
+        })
+    }
+}
+
 sol! {
     struct EthABICommitmentProof {
         bytes message;
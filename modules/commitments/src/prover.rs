@@ -1,14 +1,40 @@
 use crate::errors::Error;
-use crate::{prelude::*, CommitmentProof, ProxyMessage};
+use crate::{prelude::*, CommitmentProof, MessageDomain, MessageEncoding, ProxyMessage};
 use crypto::Signer;
 
 /// Calculate the commitment of a message and sign it
 pub fn prove_commitment(
     signer: &dyn Signer,
     message: ProxyMessage,
+) -> Result<CommitmentProof, Error> {
+    prove_commitment_with_domain(signer, message, None)
+}
+
+/// Calculate the commitment of a message bound to `domain` and sign it
+///
+/// If `domain` is None, the message is encoded with the schema version 1.
+pub fn prove_commitment_with_domain(
+    signer: &dyn Signer,
+    message: ProxyMessage,
+    domain: Option<MessageDomain>,
+) -> Result<CommitmentProof, Error> {
+    prove_commitment_with_encoding(signer, message, domain, MessageEncoding::EthABI)
+}
+
+/// Calculate the commitment of a message bound to `domain` with `encoding` and sign it
+///
+/// The signature covers the encoded bytes, so the counterparty can verify it without re-encoding the message.
+pub fn prove_commitment_with_encoding(
+    signer: &dyn Signer,
+    message: ProxyMessage,
+    domain: Option<MessageDomain>,
+    encoding: MessageEncoding,
 ) -> Result<CommitmentProof, Error> {
     message.validate()?;
-    let message_bytes = message.to_bytes();
+    if let Some(domain) = domain.as_ref() {
+        domain.validate()?;
+    }
+    let message_bytes = message.to_bytes_with_encoding(domain, encoding);
     let signature = signer.sign(&message_bytes).map_err(Error::crypto)?;
     Ok(CommitmentProof::new(message_bytes, signature))
 }
@@ -5,7 +5,7 @@ use light_client::{
     ClientKeeper, ClientReader, HostClientKeeper, HostClientReader, HostContext,
     LightClientResolver,
 };
-use store::KVStore;
+use store::{KVPair, KVStore, Order};
 
 pub struct Context<'k, R: LightClientResolver, S: KVStore, K: Signer> {
     lc_registry: R,
@@ -27,6 +27,10 @@ impl<'k, R: LightClientResolver, S: KVStore, K: Signer> Context<'k, R, S, K> {
     pub fn get_enclave_key(&self) -> &'k dyn Signer {
         self.ek
     }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
 }
 
 impl<'k, R: LightClientResolver, S: KVStore, K: Signer> KVStore for Context<'k, R, S, K> {
@@ -41,6 +45,16 @@ impl<'k, R: LightClientResolver, S: KVStore, K: Signer> KVStore for Context<'k,
     fn remove(&mut self, key: &[u8]) {
         self.store.remove(key)
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.store.range(start, end, order, limit)
+    }
 }
 
 impl<'k, R: LightClientResolver, S: KVStore, K: Signer> HostContext for Context<'k, R, S, K> {
@@ -31,6 +31,14 @@ define_error! {
             format_args!("failed to unseal: descr={}", e.descr)
         },
 
+        InvalidCiphertext
+        {
+            descr: String,
+        }
+        |e| {
+            format_args!("invalid ciphertext: descr={}", e.descr)
+        },
+
         InvalidSealedEnclaveKey
         {
             descr: String,
@@ -0,0 +1,57 @@
+use crate::errors::Error;
+use crate::prelude::*;
+use crate::sgx::rand::rand_slice;
+use sgx_tcrypto::{
+    rsgx_rijndael128GCM_decrypt, rsgx_rijndael128GCM_encrypt, rsgx_rijndael128_cmac_slice,
+};
+use sgx_types::{SGX_AESGCM_IV_SIZE, SGX_AESGCM_MAC_SIZE};
+use zeroize::Zeroizing;
+
+/// `Aes128Key` is an AES-128 key for the authenticated encryption and the MAC
+pub struct Aes128Key(Zeroizing<[u8; 16]>);
+
+impl Aes128Key {
+    pub fn new(key: Zeroizing<[u8; 16]>) -> Self {
+        Self(key)
+    }
+
+    /// `encrypt` encrypts `plaintext` with AES-128-GCM and returns `iv || tag || ciphertext`
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut iv = [0u8; SGX_AESGCM_IV_SIZE];
+        rand_slice(&mut iv)?;
+        let mut tag = [0u8; SGX_AESGCM_MAC_SIZE];
+        let mut ciphertext = vec![0u8; plaintext.len()];
+        rsgx_rijndael128GCM_encrypt(&self.0, plaintext, &iv, aad, &mut ciphertext, &mut tag)
+            .map_err(|e| Error::sgx_error(e, "failed to encrypt".to_string()))?;
+        Ok([iv.as_slice(), tag.as_slice(), ciphertext.as_slice()].concat())
+    }
+
+    /// `decrypt` decrypts the output of `encrypt` after verifying it with `aad`
+    pub fn decrypt(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>, Error> {
+        if ciphertext.len() < SGX_AESGCM_IV_SIZE + SGX_AESGCM_MAC_SIZE {
+            return Err(Error::invalid_ciphertext(format!(
+                "too short: length={}",
+                ciphertext.len()
+            )));
+        }
+        let (iv, rest) = ciphertext.split_at(SGX_AESGCM_IV_SIZE);
+        let (tag, ciphertext) = rest.split_at(SGX_AESGCM_MAC_SIZE);
+        let mut plaintext = vec![0u8; ciphertext.len()];
+        rsgx_rijndael128GCM_decrypt(
+            &self.0,
+            ciphertext,
+            iv,
+            aad,
+            tag.try_into().unwrap(),
+            &mut plaintext,
+        )
+        .map_err(|e| Error::sgx_error(e, "failed to decrypt".to_string()))?;
+        Ok(plaintext)
+    }
+
+    /// `cmac` returns the AES-128-CMAC of `msg`
+    pub fn cmac(&self, msg: &[u8]) -> Result<[u8; 16], Error> {
+        rsgx_rijndael128_cmac_slice(&self.0, msg)
+            .map_err(|e| Error::sgx_error(e, "failed to compute CMAC".to_string()))
+    }
+}
@@ -1,2 +1,3 @@
+pub mod aead;
 pub mod rand;
 pub mod sealing;
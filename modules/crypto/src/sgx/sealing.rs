@@ -12,6 +12,11 @@ use sgx_types::{
 };
 use zeroize::Zeroizing;
 
+const SEAL_ATTRIBUTE_MASK: sgx_attributes_t = sgx_attributes_t {
+    flags: 0xffff_ffff_ffff_fff3,
+    xfrm: 0,
+};
+
 #[derive(Clone)]
 struct UnsealedEnclaveKey(Zeroizing<[u8; SECRET_KEY_SIZE]>);
 
@@ -28,47 +33,86 @@ impl SealingKey for EnclaveKey {
 }
 
 fn seal_enclave_key(data: &UnsealedEnclaveKey) -> Result<SealedEnclaveKey, Error> {
-    let attribute_mask = sgx_attributes_t {
-        flags: 0xffff_ffff_ffff_fff3,
-        xfrm: 0,
-    };
-    let sealed_data = SgxSealedData::<[u8; SECRET_KEY_SIZE]>::seal_data_ex(
+    Ok(SealedEnclaveKey(seal_data_32(
+        data.0.deref(),
+        "failed to seal enclave key",
+    )?))
+}
+
+fn unseal_enclave_key(sek: &SealedEnclaveKey) -> Result<UnsealedEnclaveKey, Error> {
+    Ok(UnsealedEnclaveKey(Zeroizing::new(unseal_data_32(
+        &sek.0,
+        "failed to unseal enclave key",
+    )?)))
+}
+
+/// `seal_digest` seals a 32-byte digest with a key bound to the enclave's MRENCLAVE
+pub fn seal_digest(digest: &[u8; 32]) -> Result<Vec<u8>, Error> {
+    Ok(seal_data_32(digest, "failed to seal digest")?.to_vec())
+}
+
+/// `unseal_digest` unseals a digest sealed by `seal_digest`
+pub fn unseal_digest(sealed: &[u8]) -> Result<[u8; 32], Error> {
+    let sealed: &[u8; SEALED_DATA_32_USIZE] = sealed.try_into().map_err(|_| {
+        Error::failed_unseal(format!("invalid sealed data length: {}", sealed.len()))
+    })?;
+    unseal_data_32(sealed, "failed to unseal digest")
+}
+
+/// `seal_secret` seals a 32-byte secret with a key bound to the enclave's MRENCLAVE
+///
+/// The sealed data keeps the SVNs from which the sealing key is derived, so it can be unsealed after a TCB update.
+pub fn seal_secret(secret: &Zeroizing<[u8; 32]>) -> Result<Vec<u8>, Error> {
+    Ok(seal_data_32(secret.deref(), "failed to seal secret")?.to_vec())
+}
+
+/// `unseal_secret` unseals a secret sealed by `seal_secret`
+pub fn unseal_secret(sealed: &[u8]) -> Result<Zeroizing<[u8; 32]>, Error> {
+    let sealed: &[u8; SEALED_DATA_32_USIZE] = sealed.try_into().map_err(|_| {
+        Error::failed_unseal(format!("invalid sealed data length: {}", sealed.len()))
+    })?;
+    Ok(Zeroizing::new(unseal_data_32(
+        sealed,
+        "failed to unseal secret",
+    )?))
+}
+
+fn seal_data_32(data: &[u8; 32], descr: &str) -> Result<[u8; SEALED_DATA_32_USIZE], Error> {
+    let sealed_data = SgxSealedData::<[u8; 32]>::seal_data_ex(
         SGX_KEYPOLICY_MRENCLAVE,
-        attribute_mask,
+        SEAL_ATTRIBUTE_MASK,
         TSEAL_DEFAULT_MISCMASK,
         Default::default(),
-        data.0.deref(),
+        data,
     )
-    .map_err(|e| Error::sgx_error(e, "failed to seal enclave key".to_string()))?;
-    let mut sek = SealedEnclaveKey([0; SEALED_DATA_32_USIZE]);
+    .map_err(|e| Error::sgx_error(e, descr.to_string()))?;
+    let mut sealed = [0; SEALED_DATA_32_USIZE];
     match unsafe {
         sealed_data.to_raw_sealed_data_t(
-            sek.0.as_mut_ptr() as *mut sgx_sealed_data_t,
+            sealed.as_mut_ptr() as *mut sgx_sealed_data_t,
             SEALED_DATA_32_SIZE,
         )
     } {
-        Some(_) => Ok(sek),
+        Some(_) => Ok(sealed),
         None => Err(Error::failed_seal(
             "failed to convert to raw sealed data".to_owned(),
         )),
     }
 }
 
-fn unseal_enclave_key(sek: &SealedEnclaveKey) -> Result<UnsealedEnclaveKey, Error> {
-    let mut sek = sek.clone();
+fn unseal_data_32(sealed: &[u8; SEALED_DATA_32_USIZE], descr: &str) -> Result<[u8; 32], Error> {
+    let mut sealed = *sealed;
     let sealed = unsafe {
-        SgxSealedData::<[u8; SECRET_KEY_SIZE]>::from_raw_sealed_data_t(
-            sek.0.as_mut_ptr() as *mut sgx_sealed_data_t,
+        SgxSealedData::<[u8; 32]>::from_raw_sealed_data_t(
+            sealed.as_mut_ptr() as *mut sgx_sealed_data_t,
             SEALED_DATA_32_SIZE,
         )
     }
     .ok_or_else(|| Error::failed_unseal("failed to convert from raw sealed data".to_owned()))?;
-    Ok(UnsealedEnclaveKey(Zeroizing::new(
-        *sealed
-            .unseal_data()
-            .map_err(|e| Error::sgx_error(e, "failed to unseal enclave key".to_string()))?
-            .get_decrypt_txt(),
-    )))
+    Ok(*sealed
+        .unseal_data()
+        .map_err(|e| Error::sgx_error(e, descr.to_string()))?
+        .get_decrypt_txt())
 }
 
 impl Signer for SealedEnclaveKey {
@@ -31,10 +31,12 @@ pub use enclave_manage::{
 };
 pub use errors::InputValidationError;
 pub use light_client::{
-    AggregateMessagesInput, AggregateMessagesResponse, CommitmentProofPair, InitClientInput,
+    AggregateMessagesInput, AggregateMessagesResponse, CommitmentProofPair,
+    CountersignUpdateClientInput, CountersignUpdateClientResponse, InitClientInput,
     InitClientResponse, LightClientCommand, LightClientExecuteCommand, LightClientQueryCommand,
     LightClientResponse, QueryClientInput, QueryClientResponse, UpdateClientInput,
-    UpdateClientResponse, VerifyMembershipInput, VerifyMembershipResponse,
+    UpdateClientResponse, VerifyMembershipBatchInput, VerifyMembershipBatchItem,
+    VerifyMembershipBatchResponse, VerifyMembershipInput, VerifyMembershipResponse,
     VerifyNonMembershipInput, VerifyNonMembershipResponse,
 };
 
@@ -1,5 +1,5 @@
 use crate::{prelude::*, EnclaveKeySelector};
-use commitments::CommitmentProof;
+use commitments::{CommitmentProof, MerkleProof, MessageDomain, MessageEncoding};
 use crypto::Address;
 use lcp_types::{Any, ClientId, Height};
 use serde::{Deserialize, Serialize};
@@ -17,6 +17,8 @@ pub enum LightClientExecuteCommand {
     AggregateMessages(AggregateMessagesInput),
     VerifyMembership(VerifyMembershipInput),
     VerifyNonMembership(VerifyNonMembershipInput),
+    VerifyMembershipBatch(VerifyMembershipBatchInput),
+    CountersignUpdateClient(CountersignUpdateClientInput),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -33,6 +35,10 @@ impl EnclaveKeySelector for LightClientCommand {
                 LightClientExecuteCommand::AggregateMessages(input) => Some(input.signer),
                 LightClientExecuteCommand::VerifyMembership(input) => Some(input.signer),
                 LightClientExecuteCommand::VerifyNonMembership(input) => Some(input.signer),
+                LightClientExecuteCommand::VerifyMembershipBatch(input) => Some(input.signer),
+                LightClientExecuteCommand::CountersignUpdateClient(input) => {
+                    Some(input.update.signer)
+                }
             },
             Self::Query(_) => None,
         }
@@ -53,6 +59,20 @@ pub struct UpdateClientInput {
     pub any_header: Any,
     pub include_state: bool,
     pub signer: Address,
+    pub domain: Option<MessageDomain>,
+    pub encoding: MessageEncoding,
+    /// If true, the emitted states are committed by their merkle root instead of inline
+    pub commit_emitted_states_root: bool,
+}
+
+/// CountersignUpdateClientInput requests to re-run the update processed by another LCP node and countersign its proxy message
+///
+/// The enclave signs the message only if the result of the update is identical to `message`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CountersignUpdateClientInput {
+    pub update: UpdateClientInput,
+    /// The proxy message produced by the other LCP node
+    pub message: Vec<u8>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -70,6 +90,8 @@ pub struct VerifyMembershipInput {
     pub value: Vec<u8>,
     pub proof: CommitmentProofPair,
     pub signer: Address,
+    pub domain: Option<MessageDomain>,
+    pub encoding: MessageEncoding,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -79,6 +101,26 @@ pub struct VerifyNonMembershipInput {
     pub path: String,
     pub proof: CommitmentProofPair,
     pub signer: Address,
+    pub domain: Option<MessageDomain>,
+    pub encoding: MessageEncoding,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyMembershipBatchInput {
+    pub client_id: ClientId,
+    pub prefix: Vec<u8>,
+    pub proof_height: Height,
+    pub items: Vec<VerifyMembershipBatchItem>,
+    pub signer: Address,
+    pub domain: Option<MessageDomain>,
+    pub encoding: MessageEncoding,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyMembershipBatchItem {
+    pub path: String,
+    pub value: Vec<u8>,
+    pub proof: Vec<u8>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -97,6 +139,8 @@ pub enum LightClientResponse {
 
     VerifyMembership(VerifyMembershipResponse),
     VerifyNonMembership(VerifyNonMembershipResponse),
+    VerifyMembershipBatch(VerifyMembershipBatchResponse),
+    CountersignUpdateClient(CountersignUpdateClientResponse),
 
     QueryClient(QueryClientResponse),
 }
@@ -106,11 +150,18 @@ pub struct InitClientResponse {
     pub proof: CommitmentProof,
 }
 
+/// UpdateClientResponse is the proof of the update and the inclusion proofs of the emitted states in the emitted states root
+///
+/// The inclusion proofs are empty if the emitted states are not committed by the root.
 #[derive(Serialize, Deserialize, Debug)]
-pub struct UpdateClientResponse(pub CommitmentProof);
+pub struct UpdateClientResponse(pub CommitmentProof, pub Vec<MerkleProof>);
 
+/// AggregateMessagesResponse is the proof of the aggregated message and the inclusion proof of each message's emitted states root in the aggregated root
 #[derive(Serialize, Deserialize, Debug)]
-pub struct AggregateMessagesResponse(pub CommitmentProof);
+pub struct AggregateMessagesResponse(pub CommitmentProof, pub Vec<MerkleProof>);
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CountersignUpdateClientResponse(pub CommitmentProof);
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VerifyMembershipResponse(pub CommitmentProof);
@@ -118,6 +169,9 @@ pub struct VerifyMembershipResponse(pub CommitmentProof);
 #[derive(Serialize, Deserialize, Debug)]
 pub struct VerifyNonMembershipResponse(pub CommitmentProof);
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyMembershipBatchResponse(pub CommitmentProof);
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct QueryClientResponse {
     pub found: bool,
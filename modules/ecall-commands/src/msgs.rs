@@ -1,12 +1,15 @@
 use crate::errors::InputValidationError as Error;
 use crate::light_client::*;
 use crate::prelude::*;
+use commitments::{MessageDomain, MessageEncoding};
 use core::str::FromStr;
 use crypto::Address;
 use lcp_types::proto::lcp::service::elc::v1::{
-    MsgAggregateMessages, MsgAggregateMessagesResponse, MsgCreateClient, MsgCreateClientResponse,
-    MsgUpdateClient, MsgUpdateClientResponse, MsgVerifyMembership, MsgVerifyMembershipResponse,
-    MsgVerifyNonMembership, MsgVerifyNonMembershipResponse,
+    MessageDomain as RawMessageDomain, MessageEncoding as RawMessageEncoding, MsgAggregateMessages,
+    MsgAggregateMessagesResponse, MsgCountersignUpdateClient, MsgCountersignUpdateClientResponse,
+    MsgCreateClient, MsgCreateClientResponse, MsgUpdateClient, MsgUpdateClientResponse,
+    MsgVerifyMembership, MsgVerifyMembershipBatch, MsgVerifyMembershipBatchResponse,
+    MsgVerifyMembershipResponse, MsgVerifyNonMembership, MsgVerifyNonMembershipResponse,
     QueryClientRequest as MsgQueryClientRequest, QueryClientResponse as MsgQueryClientResponse,
 };
 use lcp_types::ClientId;
@@ -44,6 +47,26 @@ impl TryFrom<MsgUpdateClient> for UpdateClientInput {
             any_header,
             include_state: msg.include_state,
             signer: Address::try_from(msg.signer.as_slice())?,
+            domain: msg.domain.map(message_domain),
+            encoding: message_encoding(msg.encoding)?,
+            commit_emitted_states_root: msg.commit_emitted_states_root,
+        })
+    }
+}
+
+impl TryFrom<MsgCountersignUpdateClient> for CountersignUpdateClientInput {
+    type Error = Error;
+    fn try_from(msg: MsgCountersignUpdateClient) -> Result<Self, Error> {
+        let update = msg
+            .update
+            .ok_or_else(|| Error::invalid_argument("update must be non-nil".into()))?
+            .try_into()?;
+        if msg.message.is_empty() {
+            return Err(Error::invalid_argument("message must be non-empty".into()));
+        }
+        Ok(Self {
+            update,
+            message: msg.message,
         })
     }
 }
@@ -78,6 +101,8 @@ impl TryFrom<MsgVerifyMembership> for VerifyMembershipInput {
             path: msg.path,
             value: msg.value,
             signer: Address::try_from(msg.signer.as_slice())?,
+            domain: msg.domain.map(message_domain),
+            encoding: message_encoding(msg.encoding)?,
         })
     }
 }
@@ -99,10 +124,58 @@ impl TryFrom<MsgVerifyNonMembership> for VerifyNonMembershipInput {
             proof,
             path: msg.path,
             signer: Address::try_from(msg.signer.as_slice())?,
+            domain: msg.domain.map(message_domain),
+            encoding: message_encoding(msg.encoding)?,
+        })
+    }
+}
+
+impl TryFrom<MsgVerifyMembershipBatch> for VerifyMembershipBatchInput {
+    type Error = Error;
+
+    fn try_from(msg: MsgVerifyMembershipBatch) -> Result<Self, Self::Error> {
+        let client_id = ClientId::from_str(&msg.client_id)?;
+        if msg.items.is_empty() {
+            return Err(Error::invalid_argument("items must be non-empty".into()));
+        }
+        Ok(Self {
+            client_id,
+            prefix: msg.prefix,
+            proof_height: msg
+                .proof_height
+                .ok_or_else(|| Error::invalid_argument("proof_height must be non-nil".into()))?
+                .into(),
+            items: msg
+                .items
+                .into_iter()
+                .map(|item| VerifyMembershipBatchItem {
+                    path: item.path,
+                    value: item.value,
+                    proof: item.proof,
+                })
+                .collect(),
+            signer: Address::try_from(msg.signer.as_slice())?,
+            domain: msg.domain.map(message_domain),
+            encoding: message_encoding(msg.encoding)?,
         })
     }
 }
 
+fn message_domain(domain: RawMessageDomain) -> MessageDomain {
+    MessageDomain::new(domain.chain_id, domain.client_id)
+}
+
+fn message_encoding(encoding: i32) -> Result<MessageEncoding, Error> {
+    match RawMessageEncoding::from_i32(encoding) {
+        Some(RawMessageEncoding::Ethabi) => Ok(MessageEncoding::EthABI),
+        Some(RawMessageEncoding::Protobuf) => Ok(MessageEncoding::Protobuf),
+        None => Err(Error::invalid_argument(format!(
+            "unknown message encoding: {}",
+            encoding
+        ))),
+    }
+}
+
 impl TryFrom<MsgQueryClientRequest> for QueryClientInput {
     type Error = Error;
     fn try_from(query: MsgQueryClientRequest) -> Result<Self, Error> {
@@ -125,12 +198,23 @@ impl From<UpdateClientResponse> for MsgUpdateClientResponse {
         Self {
             message: res.0.message,
             signature: res.0.signature,
+            emitted_state_proofs: res.1.into_iter().map(Into::into).collect(),
         }
     }
 }
 
 impl From<AggregateMessagesResponse> for MsgAggregateMessagesResponse {
     fn from(res: AggregateMessagesResponse) -> Self {
+        Self {
+            message: res.0.message,
+            signature: res.0.signature,
+            emitted_states_root_proofs: res.1.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<CountersignUpdateClientResponse> for MsgCountersignUpdateClientResponse {
+    fn from(res: CountersignUpdateClientResponse) -> Self {
         Self {
             message: res.0.message,
             signature: res.0.signature,
@@ -156,6 +240,15 @@ impl From<VerifyNonMembershipResponse> for MsgVerifyNonMembershipResponse {
     }
 }
 
+impl From<VerifyMembershipBatchResponse> for MsgVerifyMembershipBatchResponse {
+    fn from(res: VerifyMembershipBatchResponse) -> Self {
+        Self {
+            message: res.0.message,
+            signature: res.0.signature,
+        }
+    }
+}
+
 impl From<QueryClientResponse> for MsgQueryClientResponse {
     fn from(res: QueryClientResponse) -> Self {
         Self {
@@ -2,11 +2,13 @@ use crate::{EnclavePrimitiveAPI, Result};
 use attestation_report::QEType;
 use ecall_commands::{
     AggregateMessagesInput, AggregateMessagesResponse, Command, CommandResponse,
-    EnclaveManageCommand, EnclaveManageResponse, GenerateEnclaveKeyInput,
-    GenerateEnclaveKeyResponse, InitClientInput, InitClientResponse, LightClientCommand,
-    LightClientExecuteCommand, LightClientQueryCommand, LightClientResponse, QueryClientInput,
-    QueryClientResponse, UpdateClientInput, UpdateClientResponse, VerifyMembershipInput,
-    VerifyMembershipResponse, VerifyNonMembershipInput, VerifyNonMembershipResponse,
+    CountersignUpdateClientInput, CountersignUpdateClientResponse, EnclaveManageCommand,
+    EnclaveManageResponse, GenerateEnclaveKeyInput, GenerateEnclaveKeyResponse, InitClientInput,
+    InitClientResponse, LightClientCommand, LightClientExecuteCommand, LightClientQueryCommand,
+    LightClientResponse, QueryClientInput, QueryClientResponse, UpdateClientInput,
+    UpdateClientResponse, VerifyMembershipBatchInput, VerifyMembershipBatchResponse,
+    VerifyMembershipInput, VerifyMembershipResponse, VerifyNonMembershipInput,
+    VerifyNonMembershipResponse,
 };
 use store::transaction::CommitStore;
 
@@ -57,6 +59,25 @@ pub trait EnclaveCommandAPI<S: CommitStore>: EnclavePrimitiveAPI<S> {
         }
     }
 
+    /// countersign_update_client re-runs the update processed by another LCP node and countersigns its proxy message
+    fn countersign_update_client(
+        &self,
+        input: CountersignUpdateClientInput,
+    ) -> Result<CountersignUpdateClientResponse> {
+        let update_key = Some(input.update.client_id.to_string());
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::CountersignUpdateClient(input),
+            )),
+            update_key,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::CountersignUpdateClient(res)) => {
+                Ok(res)
+            }
+            _ => unreachable!(),
+        }
+    }
+
     /// aggregate_messages aggregates the messages and proofs into a single message and proof
     fn aggregate_messages(
         &self,
@@ -102,6 +123,24 @@ pub trait EnclaveCommandAPI<S: CommitStore>: EnclavePrimitiveAPI<S> {
         }
     }
 
+    /// verify_membership_batch verifies the existence of multiple states in the upstream chain and generates a message that represents membership of all values at the same height
+    fn verify_membership_batch(
+        &self,
+        input: VerifyMembershipBatchInput,
+    ) -> Result<VerifyMembershipBatchResponse> {
+        match self.execute_command(
+            Command::LightClient(LightClientCommand::Execute(
+                LightClientExecuteCommand::VerifyMembershipBatch(input),
+            )),
+            None,
+        )? {
+            CommandResponse::LightClient(LightClientResponse::VerifyMembershipBatch(res)) => {
+                Ok(res)
+            }
+            _ => unreachable!(),
+        }
+    }
+
     /// query_client queries the client state and consensus state
     fn query_client(&self, input: QueryClientInput) -> Result<QueryClientResponse> {
         match self.execute_command(
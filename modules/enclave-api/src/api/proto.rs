@@ -1,10 +1,11 @@
 use super::command::EnclaveCommandAPI;
 use crate::Result;
 use lcp_proto::lcp::service::elc::v1::{
-    MsgAggregateMessages, MsgAggregateMessagesResponse, MsgCreateClient, MsgCreateClientResponse,
-    MsgUpdateClient, MsgUpdateClientResponse, MsgVerifyMembership, MsgVerifyMembershipResponse,
-    MsgVerifyNonMembership, MsgVerifyNonMembershipResponse, QueryClientRequest,
-    QueryClientResponse,
+    MsgAggregateMessages, MsgAggregateMessagesResponse, MsgCountersignUpdateClient,
+    MsgCountersignUpdateClientResponse, MsgCreateClient, MsgCreateClientResponse, MsgUpdateClient,
+    MsgUpdateClientResponse, MsgVerifyMembership, MsgVerifyMembershipBatch,
+    MsgVerifyMembershipBatchResponse, MsgVerifyMembershipResponse, MsgVerifyNonMembership,
+    MsgVerifyNonMembershipResponse, QueryClientRequest, QueryClientResponse,
 };
 use log::*;
 use store::transaction::CommitStore;
@@ -32,6 +33,24 @@ pub trait EnclaveProtoAPI<S: CommitStore>: EnclaveCommandAPI<S> {
         Ok(res.into())
     }
 
+    fn proto_countersign_update_client(
+        &self,
+        msg: MsgCountersignUpdateClient,
+    ) -> Result<MsgCountersignUpdateClientResponse> {
+        let client_id = msg
+            .update
+            .as_ref()
+            .map(|update| update.client_id.clone())
+            .unwrap_or_default();
+        let res = self.countersign_update_client(msg.try_into()?)?;
+        info!(
+            "countersign_update_client: client_id={} message={{{}}}",
+            client_id,
+            res.0.message()?
+        );
+        Ok(res.into())
+    }
+
     fn proto_aggregate_messages(
         &self,
         msg: MsgAggregateMessages,
@@ -69,6 +88,20 @@ pub trait EnclaveProtoAPI<S: CommitStore>: EnclaveCommandAPI<S> {
         Ok(res.into())
     }
 
+    fn proto_verify_membership_batch(
+        &self,
+        msg: MsgVerifyMembershipBatch,
+    ) -> Result<MsgVerifyMembershipBatchResponse> {
+        let client_id = msg.client_id.clone();
+        let res = self.verify_membership_batch(msg.try_into()?)?;
+        info!(
+            "verify_membership_batch: client_id={} message={{{}}}",
+            client_id,
+            res.0.message()?
+        );
+        Ok(res.into())
+    }
+
     fn proto_query_client(&self, query: QueryClientRequest) -> Result<QueryClientResponse> {
         Ok(self.query_client(query.try_into()?)?.into())
     }
@@ -10,3 +10,5 @@ mod ffi;
 mod memory;
 #[cfg(feature = "rocksdb")]
 mod rocksdb;
+#[cfg(feature = "sqlite")]
+mod sqlite;
@@ -0,0 +1,9 @@
+use crate::{
+    enclave::HostStoreTxManager, Enclave, EnclaveCommandAPI, EnclavePrimitiveAPI, EnclaveProtoAPI,
+};
+use store::sqlite::SQLiteStore;
+
+impl HostStoreTxManager<SQLiteStore> for Enclave<SQLiteStore> {}
+impl EnclavePrimitiveAPI<SQLiteStore> for Enclave<SQLiteStore> {}
+impl EnclaveCommandAPI<SQLiteStore> for Enclave<SQLiteStore> {}
+impl EnclaveProtoAPI<SQLiteStore> for Enclave<SQLiteStore> {}
@@ -433,6 +433,21 @@ impl EnclaveKeyManager {
         };
         Ok(stmt.execute(params)?)
     }
+
+    /// Writes a consistent copy of the database to `path`
+    ///
+    /// The copy is taken in a read transaction, so other connections can keep writing to the database.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the copy, which must not exist
+    pub fn backup(&self, path: &Path) -> Result<(), Error> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::mutex_lock(e.to_string()))?;
+        conn.execute("VACUUM INTO ?1", params![path.to_string_lossy()])?;
+        Ok(())
+    }
 }
 
 /// Prune policy for the Key Manager
@@ -586,6 +601,26 @@ mod tests {
         assert_eq!(keys[2].address, address3);
     }
 
+    #[test]
+    fn test_backup() {
+        let km = EnclaveKeyManager::new_in_memory().unwrap();
+        let address = create_address();
+        let report = create_report(create_mrenclave(), address, false);
+        km.save(create_sealed_sk(), report, QEType::QE3).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(KEY_MANAGER_DB);
+        km.backup(&path).unwrap();
+        let keys = EnclaveKeyManager::new(dir.path())
+            .unwrap()
+            .all_keys()
+            .unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].address, address);
+        // the existing file is not overwritten
+        assert!(km.backup(&path).is_err());
+    }
+
     #[test]
     fn test_available_keys() {
         let km = EnclaveKeyManager::new_in_memory().unwrap();
@@ -1,23 +1,29 @@
-use crate::client_state::{ClientState, ZKDCAPVerifierInfo, ZKVMType};
+use crate::client_state::{ClientState, MrEnclavePolicy, ZKDCAPVerifierInfo, ZKVMType};
 use crate::consensus_state::ConsensusState;
 use crate::errors::Error;
 use crate::message::{
-    ClientMessage, CommitmentProofs, RegisterEnclaveKeyMessage, UpdateOperatorsMessage,
+    ClientMessage, CommitmentProofs, DCAPRegisterEnclaveKeyMessage, RegisterEnclaveKeyMessage,
+    RevokeEnclaveKeyMessage, UpdateClientMessage, UpdateMrenclaveMessage, UpdateOperatorsMessage,
     ZKDCAPRegisterEnclaveKeyMessage,
 };
+use crate::pruning::{PruningIndex, MAX_PRUNING_ENTRIES_PER_UPDATE};
 use alloy_sol_types::{sol, SolValue};
 use attestation_report::{IASSignedReport, ReportData};
 use crypto::{verify_signature_address, Address, Keccak256};
+use dcap_quote_verifier::quotes::version_3::verify_quote_v3;
 use dcap_quote_verifier::types::quotes::body::QuoteBody;
+use dcap_quote_verifier::types::quotes::version_3::QuoteV3;
 use dcap_quote_verifier::types::SGX_TEE_TYPE;
-use dcap_quote_verifier::verifier::Status;
+use dcap_quote_verifier::verifier::{QuoteVerificationOutput, Status};
 use hex_literal::hex;
 use light_client::commitments::{
-    CommitmentPrefix, EthABIEncoder, MisbehaviourProxyMessage, ProxyMessage,
-    UpdateStateProxyMessage, VerifyMembershipProxyMessage,
+    verify_emitted_state, CommitmentPrefix, CommitmentProof, EmittedState, EthABIEncoder,
+    MembershipEntry, MerkleProof, MessageDomain, MessageEncoding, MisbehaviourProxyMessage,
+    ProxyMessage, StateID, UpdateStateProxyMessage, ValidationContext,
+    VerifyMembershipBatchProxyMessage, VerifyMembershipProxyMessage,
 };
 use light_client::types::{ClientId, Height, Time};
-use light_client::{HostClientKeeper, HostClientReader};
+use light_client::{ErrorDetail as LightClientErrorDetail, HostClientKeeper, HostClientReader};
 use tiny_keccak::{Hasher, Keccak};
 
 pub const LCP_CLIENT_TYPE: &str = "0000-lcp";
@@ -43,18 +49,55 @@ pub const LCP_CLIENT_DOMAIN_SEPARATOR: [u8; 32] =
 pub struct LCPClient;
 
 #[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
-struct EKOperatorInfo {
+pub(crate) struct EKOperatorInfo {
     expired_at: u64,
     operator: Address,
+    #[serde(default)]
+    revoked: bool,
+    /// The MRENCLAVE of the enclave that the key was registered with
+    ///
+    /// This is empty if the MRENCLAVE is unknown, e.g. the key was stored before this field was introduced.
+    #[serde(default)]
+    mr_enclave: Vec<u8>,
 }
 
 impl EKOperatorInfo {
-    fn new(expired_at: u64, operator: Address) -> Self {
+    pub(crate) fn new(expired_at: u64, operator: Address, mr_enclave: Vec<u8>) -> Self {
         Self {
             expired_at,
             operator,
+            revoked: false,
+            mr_enclave,
         }
     }
+
+    pub(crate) fn expired_at(&self) -> u64 {
+        self.expired_at
+    }
+
+    pub(crate) fn revoked(&self) -> bool {
+        self.revoked
+    }
+
+    fn with_revoked(mut self) -> Self {
+        self.revoked = true;
+        self
+    }
+}
+
+/// ConflictingStateEvidence is a record of a validly signed update that conflicts with the consensus state stored at the same height
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct ConflictingStateEvidence {
+    /// The height at which the conflict was detected
+    pub height: Height,
+    /// The state ID of the consensus state already stored at `height`
+    pub stored_state_id: StateID,
+    /// The state ID committed by the conflicting update
+    pub conflicting_state_id: StateID,
+    /// The serialized `ProxyMessage` of the conflicting update
+    pub proxy_message: Vec<u8>,
+    /// The signatures of the conflicting update
+    pub signatures: Vec<Vec<u8>>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -73,62 +116,109 @@ impl LCPClient {
         consensus_state: ConsensusState,
     ) -> Result<(), Error> {
         // An initial client state's latest height must be empty
-        assert!(client_state.latest_height.is_zero());
+        if !client_state.latest_height.is_zero() {
+            return Err(Error::non_zero_initial_latest_height(
+                client_state.latest_height,
+            ));
+        }
         // mr_enclave length must be 32
-        assert!(client_state.mr_enclave.len() == 32);
+        if client_state.mr_enclave.len() != 32 {
+            return Err(Error::invalid_mrenclave_length(
+                client_state.mr_enclave.len(),
+            ));
+        }
+        // additional mr_enclaves must be 32 bytes and unique
+        for (i, allowed) in client_state.additional_mr_enclaves.iter().enumerate() {
+            if allowed.mr_enclave.len() != 32 {
+                return Err(Error::invalid_mrenclave_length(allowed.mr_enclave.len()));
+            }
+            if allowed.mr_enclave == client_state.mr_enclave
+                || client_state.additional_mr_enclaves[..i]
+                    .iter()
+                    .any(|prev| prev.mr_enclave == allowed.mr_enclave)
+            {
+                return Err(Error::duplicate_mrenclave(allowed.mr_enclave.clone()));
+            }
+        }
+        // previous_mr_enclave is set only by the MRENCLAVE update
+        if !client_state.previous_mr_enclave.is_empty()
+            || client_state.previous_mr_enclave_expiration_time != 0
+        {
+            return Err(Error::invalid_previous_mrenclave(
+                client_state.previous_mr_enclave.clone(),
+                client_state.previous_mr_enclave_expiration_time,
+            ));
+        }
         // operators_threshold_denominator and operators_threshold_numerator must not be 0
-        assert!(
-            client_state.operators.is_empty()
-                || client_state.operators_threshold_denominator != 0
-                    && client_state.operators_threshold_numerator != 0
-        );
-        // check if the operators order is sorted
-        client_state.operators.windows(2).for_each(|pair| {
-            assert!(pair[0].0 < pair[1].0);
-        });
         // operators_threshold_numerator must be less than or equal to operators_threshold_denominator
-        assert!(
-            client_state.operators_threshold_numerator
-                <= client_state.operators_threshold_denominator
-        );
+        if (!client_state.operators.is_empty()
+            && (client_state.operators_threshold_denominator == 0
+                || client_state.operators_threshold_numerator == 0))
+            || client_state.operators_threshold_numerator
+                > client_state.operators_threshold_denominator
+        {
+            return Err(Error::invalid_operators_threshold(
+                client_state.operators_threshold_numerator,
+                client_state.operators_threshold_denominator,
+            ));
+        }
+        // check if the operators order is sorted
+        for pair in client_state.operators.windows(2) {
+            if pair[0].0 >= pair[1].0 {
+                return Err(Error::operators_not_sorted(pair[0], pair[1]));
+            }
+        }
         // operators_nonce must be 0
-        assert!(client_state.operators_nonce == 0);
+        if client_state.operators_nonce != 0 {
+            return Err(Error::unexpected_operators_nonce(
+                0,
+                client_state.operators_nonce,
+            ));
+        }
 
         if !client_state.zkdcap_verifier_infos.is_empty() {
-            assert!(
-                client_state.zkdcap_verifier_infos.len() == 1,
-                "only one verifier is supported"
-            );
-            assert!(
-                client_state.zkdcap_verifier_infos[0].as_type() == ZKVMType::Risc0,
-                "only Risc0 is supported"
-            );
-            assert!(
-                client_state.current_tcb_evaluation_data_number != 0,
-                "current_tcb_evaluation_data_number must not be 0"
-            );
-            assert_eq!(
-                client_state.next_tcb_evaluation_data_number == 0,
-                client_state.next_tcb_evaluation_data_number_update_time == 0,
-                "next_tcb_evaluation_data_number and next_tcb_evaluation_data_number_update_time must be both 0 or both non-zero"
-            );
-            assert!(client_state.next_tcb_evaluation_data_number == 0 || client_state.next_tcb_evaluation_data_number > client_state.current_tcb_evaluation_data_number, "next_tcb_evaluation_data_number must be greater than current_tcb_evaluation_data_number");
-        } else {
-            assert!(
-                !client_state.key_expiration.is_zero(),
-                "key_expiration must not be 0 if DCAP/zkDCAP is not supported"
-            );
+            // each verifier must specify a supported zkVM and must not be duplicated
+            for (i, info) in client_state.zkdcap_verifier_infos.iter().enumerate() {
+                if info.as_type() == ZKVMType::Unspecified {
+                    return Err(Error::unsupported_zkvm_type(info.as_type()));
+                }
+                if client_state.zkdcap_verifier_infos[..i].contains(info) {
+                    return Err(Error::duplicate_zkdcap_verifier_info(*info));
+                }
+            }
+            if client_state.current_tcb_evaluation_data_number == 0 {
+                return Err(Error::zero_current_tcb_evaluation_data_number());
+            }
+            // next_tcb_evaluation_data_number and next_tcb_evaluation_data_number_update_time must be both 0 or both non-zero
+            // next_tcb_evaluation_data_number must be greater than current_tcb_evaluation_data_number
+            if (client_state.next_tcb_evaluation_data_number == 0)
+                != (client_state.next_tcb_evaluation_data_number_update_time == 0)
+                || (client_state.next_tcb_evaluation_data_number != 0
+                    && client_state.next_tcb_evaluation_data_number
+                        <= client_state.current_tcb_evaluation_data_number)
+            {
+                return Err(Error::invalid_next_tcb_evaluation_data_number(
+                    client_state.current_tcb_evaluation_data_number,
+                    client_state.next_tcb_evaluation_data_number,
+                    client_state.next_tcb_evaluation_data_number_update_time,
+                ));
+            }
+        } else if client_state.key_expiration.is_zero() {
+            return Err(Error::zero_key_expiration());
         }
 
         // An initial consensus state must be empty
-        assert!(consensus_state.is_empty());
+        if !consensus_state.is_empty() {
+            return Err(Error::non_empty_initial_consensus_state());
+        }
 
         ctx.store_any_client_state(client_id.clone(), client_state.clone().into())?;
         ctx.store_any_consensus_state(
-            client_id,
+            client_id.clone(),
             client_state.latest_height,
             consensus_state.into(),
         )?;
+        PruningIndex::new(&client_id).set_consensus_states_count(ctx, 1);
         Ok(())
     }
 
@@ -142,12 +232,24 @@ impl LCPClient {
         let client_state = ctx.client_state(&client_id)?.try_into()?;
         match message {
             ClientMessage::UpdateClient(msg) => match msg.proxy_message {
-                ProxyMessage::UpdateState(pmsg) => {
-                    self.update_state(ctx, client_id, client_state, pmsg, msg.signatures)
-                }
-                ProxyMessage::Misbehaviour(pmsg) => {
-                    self.submit_misbehaviour(ctx, client_id, client_state, pmsg, msg.signatures)
-                }
+                ProxyMessage::UpdateState(pmsg) => self.update_state(
+                    ctx,
+                    client_id,
+                    client_state,
+                    pmsg,
+                    msg.domain,
+                    msg.encoding,
+                    msg.signatures,
+                ),
+                ProxyMessage::Misbehaviour(pmsg) => self.submit_misbehaviour(
+                    ctx,
+                    client_id,
+                    client_state,
+                    pmsg,
+                    msg.domain,
+                    msg.encoding,
+                    msg.signatures,
+                ),
                 _ => Err(Error::unexpected_header_type(format!("{:?}", msg))),
             },
             ClientMessage::RegisterEnclaveKey(msg) => {
@@ -156,9 +258,18 @@ impl LCPClient {
             ClientMessage::ZKDCAPRegisterEnclaveKey(msg) => {
                 self.zkdcap_register_enclave_key(ctx, client_id, client_state, msg)
             }
+            ClientMessage::DCAPRegisterEnclaveKey(msg) => {
+                self.dcap_register_enclave_key(ctx, client_id, client_state, msg)
+            }
             ClientMessage::UpdateOperators(msg) => {
                 self.update_operators(ctx, client_id, client_state, msg)
             }
+            ClientMessage::RevokeEnclaveKey(msg) => {
+                self.revoke_enclave_key(ctx, client_id, client_state, msg)
+            }
+            ClientMessage::UpdateMrenclave(msg) => {
+                self.update_mrenclave(ctx, client_id, client_state, msg)
+            }
         }
     }
 
@@ -168,46 +279,178 @@ impl LCPClient {
         client_id: ClientId,
         client_state: ClientState,
         message: UpdateStateProxyMessage,
+        domain: Option<MessageDomain>,
+        encoding: MessageEncoding,
         signatures: Vec<Vec<u8>>,
     ) -> Result<(), Error> {
         message.validate()?;
-        // TODO return an error instead of assertion
+        verify_message_domain(&client_state, &client_id, domain.as_ref())?;
 
-        assert!(!client_state.frozen);
+        if client_state.frozen {
+            return Err(Error::client_frozen(client_id));
+        }
 
         if client_state.latest_height.is_zero() {
             // if the client state's latest height is zero, the commitment's new_state must be non-nil
-            assert!(!message.emitted_states.is_empty());
+            if !message.has_emitted_states() {
+                return Err(Error::empty_emitted_states());
+            }
         } else {
             // if the client state's latest height is non-zero, the commitment's prev_* must be non-nil
-            assert!(message.prev_height.is_some() && message.prev_state_id.is_some());
+            let (prev_height, prev_state_id) = match (message.prev_height, message.prev_state_id) {
+                (Some(prev_height), Some(prev_state_id)) => (prev_height, prev_state_id),
+                _ => return Err(Error::missing_prev_state()),
+            };
             // check if the previous consensus state exists in the store
-            let prev_consensus_state: ConsensusState = ctx
-                .consensus_state(&client_id, &message.prev_height.unwrap())?
-                .try_into()?;
-            assert!(prev_consensus_state.state_id == message.prev_state_id.unwrap());
+            let prev_consensus_state: ConsensusState =
+                ctx.consensus_state(&client_id, &prev_height)?.try_into()?;
+            if prev_consensus_state.state_id != prev_state_id {
+                return Err(Error::prev_state_id_mismatch(
+                    prev_height,
+                    prev_consensus_state.state_id,
+                    prev_state_id,
+                ));
+            }
         }
 
+        let proxy_message =
+            ProxyMessage::from(message.clone()).to_bytes_with_encoding(domain, encoding);
         self.verify_ek_signatures(
             ctx,
             &client_id,
             &client_state,
-            ProxyMessage::from(message.clone()).to_bytes().as_slice(),
-            signatures,
+            proxy_message.as_slice(),
+            signatures.clone(),
         )?;
 
         // check if proxy's validation context matches our's context
-        message.context.validate(ctx.host_timestamp())?;
+        self.validate_context(ctx, &message.context)?;
+
+        // check if a different state has already been committed at the same height
+        //
+        // Two validly signed updates that conflict with each other indicate that the enclave is compromised,
+        // so the client is frozen instead of overwriting the existing consensus state.
+        let existing_consensus_state = match ctx.consensus_state(&client_id, &message.post_height) {
+            Ok(consensus_state) => Some(ConsensusState::try_from(consensus_state)?),
+            Err(e) => match e.detail() {
+                LightClientErrorDetail::ConsensusStateNotFound(_) => None,
+                _ => return Err(e.into()),
+            },
+        };
+        let is_new_height = existing_consensus_state.is_none();
+        if let Some(consensus_state) = existing_consensus_state.as_ref() {
+            if consensus_state.state_id != message.post_state_id {
+                self.set_conflicting_state_evidence(
+                    ctx,
+                    &client_id,
+                    ConflictingStateEvidence {
+                        height: message.post_height,
+                        stored_state_id: consensus_state.state_id,
+                        conflicting_state_id: message.post_state_id,
+                        proxy_message,
+                        signatures,
+                    },
+                );
+                ctx.store_any_client_state(client_id, client_state.with_frozen().into())?;
+                return Ok(());
+            }
+        }
 
         // create a new state
         let new_client_state = client_state.with_header(&message);
+        // NOTE: the root stored by the first update at the height is kept, so the proofs against it remain valid
         let new_consensus_state = ConsensusState {
             state_id: message.post_state_id,
             timestamp: message.timestamp,
+            emitted_states_root: existing_consensus_state
+                .and_then(|consensus_state| consensus_state.emitted_states_root)
+                .or(message.emitted_states_root),
         };
 
-        ctx.store_any_client_state(client_id.clone(), new_client_state.into())?;
-        ctx.store_any_consensus_state(client_id, message.post_height, new_consensus_state.into())?;
+        // NOTE: the count must be loaded before storing the new consensus state because it counts the existing states at the first load
+        let pruning_index = PruningIndex::new(&client_id);
+        let consensus_states_count = pruning_index.consensus_states_count(ctx)?;
+        ctx.store_any_client_state(client_id.clone(), new_client_state.clone().into())?;
+        ctx.store_any_consensus_state(
+            client_id.clone(),
+            message.post_height,
+            new_consensus_state.into(),
+        )?;
+        if is_new_height {
+            pruning_index.set_consensus_states_count(ctx, consensus_states_count + 1);
+        }
+
+        self.prune_consensus_states(ctx, &client_id, &new_client_state)?;
+        self.prune_enclave_keys(ctx, &client_id)?;
+        Ok(())
+    }
+
+    /// prune_consensus_states removes the consensus states with the lowest heights that exceed the client's retention.
+    ///
+    /// The consensus states are examined in ascending order of height, so the states stored by out-of-order updates are also pruned by their heights.
+    /// The consensus state at the latest height is never removed, and at most `MAX_PRUNING_ENTRIES_PER_UPDATE` states are removed per call.
+    fn prune_consensus_states(
+        &self,
+        ctx: &mut dyn HostClientKeeper,
+        client_id: &ClientId,
+        client_state: &ClientState,
+    ) -> Result<(), Error> {
+        if client_state.max_consensus_states == 0
+            && client_state.consensus_state_retention_period.is_zero()
+        {
+            return Ok(());
+        }
+        let pruning_index = PruningIndex::new(client_id);
+        let mut count = pruning_index.consensus_states_count(ctx)?;
+        let host_timestamp = ctx.host_timestamp();
+        let heights =
+            ctx.consensus_heights(client_id, Some(MAX_PRUNING_ENTRIES_PER_UPDATE as usize))?;
+        for height in heights {
+            if height >= client_state.latest_height {
+                break;
+            }
+            let exceeded =
+                client_state.max_consensus_states != 0 && count > client_state.max_consensus_states;
+            let expired = !client_state.consensus_state_retention_period.is_zero() && {
+                let consensus_state =
+                    ConsensusState::try_from(ctx.consensus_state(client_id, &height)?)?;
+                (consensus_state.timestamp + client_state.consensus_state_retention_period)?
+                    < host_timestamp
+            };
+            if !exceeded && !expired {
+                break;
+            }
+            ctx.remove_consensus_state(client_id, &height)?;
+            count = count.saturating_sub(1);
+        }
+        pruning_index.set_consensus_states_count(ctx, count);
+        Ok(())
+    }
+
+    /// prune_enclave_keys removes the operator info of the expired enclave keys.
+    ///
+    /// The keys are examined in ascending order of expiration, and at most `MAX_PRUNING_ENTRIES_PER_UPDATE` keys are removed per call.
+    /// The info of the revoked keys is retained to reject their re-registration.
+    fn prune_enclave_keys(
+        &self,
+        ctx: &mut dyn HostClientKeeper,
+        client_id: &ClientId,
+    ) -> Result<(), Error> {
+        let pruning_index = PruningIndex::new(client_id);
+        let host_timestamp = ctx.host_timestamp().as_unix_timestamp_secs();
+        for (expired_at, ek) in pruning_index.expired_enclave_keys(
+            ctx,
+            host_timestamp,
+            MAX_PRUNING_ENTRIES_PER_UPDATE as usize,
+        )? {
+            pruning_index.remove_enclave_key(ctx, expired_at, ek);
+            match self.get_enclave_operator_info(ctx, client_id, ek) {
+                Some(info) if !info.revoked && info.expired_at == expired_at => {
+                    ctx.remove(enclave_key_path(client_id, ek).as_slice());
+                }
+                _ => {}
+            }
+        }
         Ok(())
     }
 
@@ -218,16 +461,20 @@ impl LCPClient {
         client_state: ClientState,
         message: RegisterEnclaveKeyMessage,
     ) -> Result<(), Error> {
-        // TODO return an error instead of assertion
-
-        assert!(!client_state.frozen);
+        if client_state.frozen {
+            return Err(Error::client_frozen(client_id));
+        }
 
-        let (report_data, attestation_time) =
+        let (report_data, attestation_time, mr_enclave, mr_enclave_policy) =
             verify_ias_report(ctx.host_timestamp(), &client_state, &message.report)?;
 
         let operator = if let Some(operator_signature) = message.operator_signature {
             verify_signature_address(
-                compute_eip712_register_enclave_key(&message.report.avr).as_ref(),
+                compute_eip712_register_enclave_key(
+                    client_state.domain_separator(),
+                    &message.report.avr,
+                )
+                .as_ref(),
                 operator_signature.as_ref(),
             )?
         } else {
@@ -235,17 +482,22 @@ impl LCPClient {
         };
         let expected_operator = report_data.operator();
         // check if the operator matches the expected operator in the report data
-        assert!(expected_operator.is_zero() || operator == expected_operator);
+        if !expected_operator.is_zero() && operator != expected_operator {
+            return Err(Error::operator_mismatch(expected_operator, operator));
+        }
         self.set_enclave_operator_info(
             ctx,
             &client_id,
             report_data.enclave_key(),
             EKOperatorInfo::new(
-                (attestation_time + client_state.key_expiration)?.as_unix_timestamp_secs(),
+                core::cmp::min(
+                    (attestation_time + mr_enclave_policy.key_expiration)?.as_unix_timestamp_secs(),
+                    mr_enclave_policy.expiration_time,
+                ),
                 operator,
+                mr_enclave,
             ),
-        );
-        Ok(())
+        )
     }
 
     fn zkdcap_register_enclave_key(
@@ -255,18 +507,70 @@ impl LCPClient {
         client_state: ClientState,
         message: ZKDCAPRegisterEnclaveKeyMessage,
     ) -> Result<(), Error> {
-        assert!(!client_state.frozen);
-        assert!(client_state.zkdcap_verifier_infos.len() == 1);
-        let risc0_program_id = match client_state.zkdcap_verifier_infos[0] {
-            ZKDCAPVerifierInfo::Risc0(info) => info,
-            vi => return Err(Error::unexpected_zkvm_type(ZKVMType::Risc0, vi.as_type())),
-        };
+        if client_state.frozen {
+            return Err(Error::client_frozen(client_id));
+        }
 
-        let (selector, seal) = message.risc0_seal_selector()?;
+        let verifier_info = verify_zkdcap_proof(&client_state, &message)?;
         let output = message.quote_verification_output;
+        let operator_sign_bytes = compute_eip712_zkdcap_register_enclave_key(
+            client_state.domain_separator(),
+            &verifier_info,
+            keccak256(&output.to_bytes()),
+        );
+        self.register_enclave_key_with_qv_output(
+            ctx,
+            client_id,
+            client_state,
+            output,
+            &operator_sign_bytes,
+            message.operator_signature,
+        )
+    }
+
+    fn dcap_register_enclave_key(
+        &self,
+        ctx: &mut dyn HostClientKeeper,
+        client_id: ClientId,
+        client_state: ClientState,
+        message: DCAPRegisterEnclaveKeyMessage,
+    ) -> Result<(), Error> {
+        if client_state.frozen {
+            return Err(Error::client_frozen(client_id));
+        }
 
-        zkvm::verifier::verify_groth16_proof(selector, seal, risc0_program_id, output.to_bytes())?;
+        let (quote, _) = QuoteV3::from_bytes(&message.quote).map_err(Error::dcap_quote_verifier)?;
+        let output = verify_quote_v3(
+            &quote,
+            &message.collateral.into(),
+            ctx.host_timestamp().as_unix_timestamp_secs(),
+        )
+        .map_err(Error::dcap_quote_verifier)?;
+        let operator_sign_bytes = compute_eip712_dcap_register_enclave_key(
+            client_state.domain_separator(),
+            keccak256(&output.to_bytes()),
+        );
+        self.register_enclave_key_with_qv_output(
+            ctx,
+            client_id,
+            client_state,
+            output,
+            &operator_sign_bytes,
+            message.operator_signature,
+        )
+    }
 
+    /// register_enclave_key_with_qv_output registers the enclave key in the verified quote verification output
+    /// if the output satisfies the client's requirements
+    fn register_enclave_key_with_qv_output(
+        &self,
+        ctx: &mut dyn HostClientKeeper,
+        client_id: ClientId,
+        client_state: ClientState,
+        output: QuoteVerificationOutput,
+        operator_sign_bytes: &[u8],
+        operator_signature: Option<Vec<u8>>,
+    ) -> Result<(), Error> {
         let report = if let QuoteBody::SGXQuoteBody(report) = output.quote_body {
             report
         } else {
@@ -274,17 +578,32 @@ impl LCPClient {
         };
         let report_data = ReportData(report.report_data);
 
-        assert_eq!(
-            report.mrenclave.as_slice(),
-            client_state.mr_enclave.as_slice(),
-            "mrenclave mismatch"
-        );
-        assert_eq!(output.quote_version, 3, "unexpected quote version");
-        assert_eq!(output.tee_type, SGX_TEE_TYPE, "unexpected tee type");
-        assert_eq!(
-            output.sgx_intel_root_ca_hash,
-            remote_attestation::dcap::INTEL_ROOT_CA_HASH,
-        );
+        let mr_enclave_policy = client_state
+            .mr_enclave_policy(
+                report.mrenclave.as_slice(),
+                ctx.host_timestamp().as_unix_timestamp_secs(),
+            )
+            .ok_or_else(|| {
+                Error::mrenclave_mismatch(
+                    client_state.mr_enclave.clone(),
+                    report.mrenclave.to_vec(),
+                )
+            })?;
+        if output.quote_version != 3 {
+            return Err(Error::unexpected_quote_version(
+                3,
+                output.quote_version.into(),
+            ));
+        }
+        if output.tee_type != SGX_TEE_TYPE {
+            return Err(Error::unexpected_tee_type(SGX_TEE_TYPE, output.tee_type));
+        }
+        if output.sgx_intel_root_ca_hash != remote_attestation::dcap::INTEL_ROOT_CA_HASH {
+            return Err(Error::unexpected_intel_root_ca_hash(
+                remote_attestation::dcap::INTEL_ROOT_CA_HASH,
+                output.sgx_intel_root_ca_hash,
+            ));
+        }
 
         let (client_state, current_updated, next_updated) = self
             .check_and_update_tcb_evaluation_data_number(
@@ -297,57 +616,55 @@ impl LCPClient {
         }
 
         let host_timestamp = ctx.host_timestamp().as_unix_timestamp_secs();
-        assert!(
-            output.validity.not_before <= host_timestamp
-                && host_timestamp <= output.validity.not_after,
-            "output validity check failed"
-        );
+        if host_timestamp < output.validity.not_before || output.validity.not_after < host_timestamp
+        {
+            return Err(Error::out_of_quote_validity(
+                host_timestamp,
+                output.validity.not_before,
+                output.validity.not_after,
+            ));
+        }
 
-        assert!(
-            output.status == Status::Ok
-                || client_state
-                    .allowed_quote_statuses
-                    .contains(&output.status.to_string()),
-            "unexpected tcb status"
-        );
+        if output.status != Status::Ok
+            && !client_state
+                .allowed_quote_statuses
+                .contains(&output.status.to_string())
+        {
+            return Err(Error::disallowed_quote_status(output.status.to_string()));
+        }
         for advisory_id in output.advisory_ids.iter() {
-            assert!(
-                client_state.allowed_advisory_ids.contains(advisory_id),
-                "unexpected advisory id"
-            );
+            if !client_state.allowed_advisory_ids.contains(advisory_id) {
+                return Err(Error::disallowed_advisory_id(advisory_id.clone()));
+            }
         }
 
-        let operator = if let Some(operator_signature) = message.operator_signature {
-            verify_signature_address(
-                compute_eip712_zkdcap_register_enclave_key(
-                    &client_state.zkdcap_verifier_infos[0],
-                    keccak256(&output.to_bytes()),
-                )
-                .as_ref(),
-                operator_signature.as_ref(),
-            )?
+        let operator = if let Some(operator_signature) = operator_signature {
+            verify_signature_address(operator_sign_bytes, operator_signature.as_ref())?
         } else {
             Default::default()
         };
         let expected_operator = report_data.operator();
         // check if the operator matches the expected operator in the report data
-        assert!(expected_operator.is_zero() || operator == expected_operator);
+        if !expected_operator.is_zero() && operator != expected_operator {
+            return Err(Error::operator_mismatch(expected_operator, operator));
+        }
 
-        let expired_at = if client_state.key_expiration.is_zero() {
+        let expired_at = if mr_enclave_policy.key_expiration.is_zero() {
             output.validity.not_after
         } else {
             core::cmp::min(
-                output.validity.not_before + client_state.key_expiration.as_secs(),
+                output.validity.not_before + mr_enclave_policy.key_expiration.as_secs(),
                 output.validity.not_after,
             )
         };
+        // the key registered with the previous MRENCLAVE expires at the end of the transition period at the latest
+        let expired_at = core::cmp::min(expired_at, mr_enclave_policy.expiration_time);
         self.set_enclave_operator_info(
             ctx,
             &client_id,
             report_data.enclave_key(),
-            EKOperatorInfo::new(expired_at, operator),
-        );
-        Ok(())
+            EKOperatorInfo::new(expired_at, operator, report.mrenclave.to_vec()),
+        )
     }
 
     /// check_and_update_tcb_evaluation_data_number checks if the current or next TCB evaluation data number update is required.
@@ -391,10 +708,15 @@ impl LCPClient {
                 // Immediate update due to zero grace period.
                 client_state.current_tcb_evaluation_data_number = output_tcb_evaluation_data_number;
                 // Sanity check: No next number should be reserved if grace period is zero.
-                assert!(
-                    client_state.next_tcb_evaluation_data_number == 0
-                        && client_state.next_tcb_evaluation_data_number_update_time == 0
-                );
+                if client_state.next_tcb_evaluation_data_number != 0
+                    || client_state.next_tcb_evaluation_data_number_update_time != 0
+                {
+                    return Err(Error::invalid_next_tcb_evaluation_data_number(
+                        client_state.current_tcb_evaluation_data_number,
+                        client_state.next_tcb_evaluation_data_number,
+                        client_state.next_tcb_evaluation_data_number_update_time,
+                    ));
+                }
                 Ok((client_state, true, false))
             } else {
                 let next_update_time = ctx.host_timestamp().as_unix_timestamp_secs()
@@ -449,13 +771,30 @@ impl LCPClient {
         client_state: ClientState,
         message: UpdateOperatorsMessage,
     ) -> Result<(), Error> {
-        // TODO return an error instead of assertion
-
-        assert!(!client_state.frozen);
+        if client_state.frozen {
+            return Err(Error::client_frozen(client_id));
+        }
 
-        assert_eq!(message.nonce, client_state.operators_nonce + 1);
+        if message.nonce != client_state.operators_nonce + 1 {
+            return Err(Error::unexpected_operators_nonce(
+                client_state.operators_nonce + 1,
+                message.nonce,
+            ));
+        }
+        // the new threshold must satisfy the same conditions as the one in `initialise`
+        if message.new_operators_threshold_denominator == 0
+            || message.new_operators_threshold_numerator == 0
+            || message.new_operators_threshold_numerator
+                > message.new_operators_threshold_denominator
+        {
+            return Err(Error::invalid_operators_threshold(
+                message.new_operators_threshold_numerator,
+                message.new_operators_threshold_denominator,
+            ));
+        }
 
         let sign_bytes = compute_eip712_update_operators(
+            client_state.domain_separator(),
             client_id.clone(),
             message.nonce,
             message.new_operators.clone(),
@@ -463,23 +802,14 @@ impl LCPClient {
             message.new_operators_threshold_denominator,
         );
 
-        let mut success = 0u64;
-        for (op, sig) in client_state
-            .operators
-            .clone()
-            .into_iter()
-            .zip(message.signatures.iter())
-            .filter(|(_, sig)| !sig.is_empty())
-        {
-            // check if the operator's signature is valid
-            let operator = verify_signature_address(sign_bytes.as_ref(), sig.as_ref())?;
-            assert_eq!(op, operator);
-            success += 1;
-        }
-        assert!(
-            success * client_state.operators_threshold_denominator
-                >= message.new_operators_threshold_numerator * client_state.operators.len() as u64
-        );
+        // NOTE: the number of signatures is checked against the numerator of the new threshold and the denominator of the current threshold
+        let success = count_operator_signatures(&client_state, &sign_bytes, &message.signatures)?;
+        check_threshold(
+            client_state.operators.len() as u64,
+            success,
+            message.new_operators_threshold_numerator,
+            client_state.operators_threshold_denominator,
+        )?;
 
         let new_client_state = client_state.with_operators(
             message.new_operators,
@@ -492,28 +822,124 @@ impl LCPClient {
         Ok(())
     }
 
+    fn update_mrenclave(
+        &self,
+        ctx: &mut dyn HostClientKeeper,
+        client_id: ClientId,
+        client_state: ClientState,
+        message: UpdateMrenclaveMessage,
+    ) -> Result<(), Error> {
+        if client_state.frozen {
+            return Err(Error::client_frozen(client_id));
+        }
+        // an MRENCLAVE update must be approved by the operators
+        if client_state.operators.is_empty() {
+            return Err(Error::empty_operators());
+        }
+        let new_mr_enclave: [u8; 32] = message
+            .new_mr_enclave
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::invalid_mrenclave_length(message.new_mr_enclave.len()))?;
+        // the nonce is shared with the operators update
+        if message.nonce != client_state.operators_nonce + 1 {
+            return Err(Error::unexpected_operators_nonce(
+                client_state.operators_nonce + 1,
+                message.nonce,
+            ));
+        }
+
+        let sign_bytes = compute_eip712_update_mrenclave(
+            client_state.domain_separator(),
+            client_id.clone(),
+            message.nonce,
+            new_mr_enclave,
+            message.transition_period.as_secs(),
+        );
+        verify_operator_signatures(&client_state, &sign_bytes, &message.signatures)?;
+
+        let previous_mr_enclave_expiration_time = if message.transition_period.is_zero() {
+            0
+        } else {
+            (ctx.host_timestamp() + message.transition_period)?.as_unix_timestamp_secs()
+        };
+        let new_client_state = client_state.with_mr_enclave(
+            message.new_mr_enclave,
+            message.nonce,
+            previous_mr_enclave_expiration_time,
+        );
+        ctx.store_any_client_state(client_id, new_client_state.into())?;
+
+        Ok(())
+    }
+
+    fn revoke_enclave_key(
+        &self,
+        ctx: &mut dyn HostClientKeeper,
+        client_id: ClientId,
+        client_state: ClientState,
+        message: RevokeEnclaveKeyMessage,
+    ) -> Result<(), Error> {
+        if client_state.frozen {
+            return Err(Error::client_frozen(client_id));
+        }
+        // a revocation must be approved by the operators
+        if client_state.operators.is_empty() {
+            return Err(Error::empty_operators());
+        }
+
+        let sign_bytes = compute_eip712_revoke_enclave_key(
+            client_state.domain_separator(),
+            client_id.clone(),
+            message.enclave_key,
+        );
+        verify_operator_signatures(&client_state, &sign_bytes, &message.signatures)?;
+
+        // NOTE: a key that has not been registered yet is also revoked to prevent its future registration
+        let info = self
+            .get_enclave_operator_info(ctx, &client_id, message.enclave_key)
+            .unwrap_or_else(|| EKOperatorInfo::new(0, Default::default(), vec![]))
+            .with_revoked();
+        ctx.set(
+            enclave_key_path(&client_id, message.enclave_key),
+            serde_json::to_string(&info).unwrap().into_bytes(),
+        );
+        Ok(())
+    }
+
     fn submit_misbehaviour(
         &self,
         ctx: &mut dyn HostClientKeeper,
         client_id: ClientId,
         client_state: ClientState,
         message: MisbehaviourProxyMessage,
+        domain: Option<MessageDomain>,
+        encoding: MessageEncoding,
         signatures: Vec<Vec<u8>>,
     ) -> Result<(), Error> {
         message.validate()?;
+        verify_message_domain(&client_state, &client_id, domain.as_ref())?;
 
-        assert!(!client_state.frozen);
+        if client_state.frozen {
+            return Err(Error::client_frozen(client_id));
+        }
 
         for state in message.prev_states.iter() {
             // check if the previous consensus state exists in the store
             let prev_consensus_state: ConsensusState =
                 ctx.consensus_state(&client_id, &state.height)?.try_into()?;
-            assert!(prev_consensus_state.state_id == state.state_id);
+            if prev_consensus_state.state_id != state.state_id {
+                return Err(Error::prev_state_id_mismatch(
+                    state.height,
+                    prev_consensus_state.state_id,
+                    state.state_id,
+                ));
+            }
         }
 
         // check if proxy's validation context matches our's context
-        message.context.validate(ctx.host_timestamp())?;
-        let sign_bytes = ProxyMessage::from(message).to_bytes();
+        self.validate_context(ctx, &message.context)?;
+        let sign_bytes = ProxyMessage::from(message).to_bytes_with_encoding(domain, encoding);
         self.verify_ek_signatures(ctx, &client_id, &client_state, &sign_bytes, signatures)?;
 
         let new_client_state = client_state.with_frozen();
@@ -533,54 +959,219 @@ impl LCPClient {
         proof_height: Height,
         proof: Vec<u8>,
     ) -> Result<(), Error> {
-        // TODO return an error instead of assertion
-
-        // convert `proof` to CommitmentProof
-        let commitment_proofs = CommitmentProofs::ethabi_decode(proof.as_slice()).unwrap();
-        let msg: VerifyMembershipProxyMessage = commitment_proofs.message()?.try_into()?;
-
-        // check if `.prefix` matches the counterparty connection's prefix
-        assert!(msg.prefix == prefix);
-        // check if `.path` matches expected the commitment path
-        assert!(msg.path == path);
-        // check if `.height` matches proof height
-        assert!(msg.height == proof_height);
-
-        // check if `.value` matches expected state
-        assert!(msg.value == Some(value.keccak256()));
-
-        // check if `.state_id` matches the corresponding stored consensus state's state_id
-        let consensus_state =
-            ConsensusState::try_from(ctx.consensus_state(&client_id, &proof_height)?)?;
-        assert!(consensus_state.state_id == msg.state_id);
-
-        let client_state = ClientState::try_from(ctx.client_state(&client_id)?)?;
-
-        self.verify_ek_signatures(
+        self.verify_commitment(
             ctx,
-            &client_id,
-            &client_state,
-            &commitment_proofs.message,
-            commitment_proofs.signatures,
-        )?;
+            client_id,
+            prefix,
+            path,
+            Some(value.keccak256()),
+            proof_height,
+            proof,
+        )
+    }
 
-        Ok(())
+    /// verify_non_membership is a generic proof verification method which verifies the absence of a given path at a specified height.
+    pub fn verify_non_membership(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        prefix: CommitmentPrefix,
+        path: String,
+        proof_height: Height,
+        proof: Vec<u8>,
+    ) -> Result<(), Error> {
+        self.verify_commitment(ctx, client_id, prefix, path, None, proof_height, proof)
     }
 
-    fn verify_ek_signatures<T: HostClientReader + ?Sized>(
+    /// verify_emitted_state verifies that `state` is one of the states emitted by the update at `height`
+    ///
+    /// The state is checked with `proof` against the emitted states root stored with the consensus state,
+    /// so a contract can verify only the states it needs instead of receiving all of them in the update.
+    pub fn verify_emitted_state(
         &self,
-        ctx: &T,
-        client_id: &ClientId,
-        client_state: &ClientState,
-        sign_bytes: &[u8],
-        signatures: Vec<Vec<u8>>,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        height: Height,
+        state: EmittedState,
+        proof: MerkleProof,
     ) -> Result<(), Error> {
-        if client_state.operators.is_empty() {
-            assert!(signatures.len() == 1);
-            let ek = verify_signature_address(sign_bytes, &signatures[0])?;
-            assert!(self.is_active_enclave_key(ctx, client_id, ek));
-        } else {
-            let mut success = 0u64;
+        let consensus_state = ConsensusState::try_from(ctx.consensus_state(&client_id, &height)?)?;
+        let root = consensus_state
+            .emitted_states_root
+            .ok_or_else(|| Error::emitted_states_root_not_found(client_id, height))?;
+        verify_emitted_state(root, &state, &proof)?;
+        Ok(())
+    }
+
+    /// verify_membership_batch verifies a proof of the commitments that each path of `entries` has the corresponding value at the specified height.
+    ///
+    /// `proof` must contain a `VerifyMembershipBatchProxyMessage` that includes all of `entries`.
+    pub fn verify_membership_batch(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        prefix: CommitmentPrefix,
+        entries: Vec<MembershipEntry>,
+        proof_height: Height,
+        proof: Vec<u8>,
+    ) -> Result<(), Error> {
+        let commitment_proofs = CommitmentProofs::ethabi_decode(proof.as_slice())?;
+        let (msg, domain) = commitment_proofs.message_with_domain()?;
+        let msg: VerifyMembershipBatchProxyMessage = msg.try_into()?;
+
+        if msg.prefix != prefix {
+            return Err(Error::commitment_prefix_mismatch(prefix, msg.prefix));
+        }
+        if msg.height != proof_height {
+            return Err(Error::proof_height_mismatch(proof_height, msg.height));
+        }
+        for entry in entries {
+            if !msg.contains(&entry.path, entry.value) {
+                return Err(Error::commitment_not_found_in_batch(
+                    entry.path,
+                    entry.value,
+                ));
+            }
+        }
+        self.verify_state_id_and_signatures(
+            ctx,
+            client_id,
+            proof_height,
+            msg.state_id,
+            domain,
+            commitment_proofs,
+        )
+    }
+
+    /// verify_commitment verifies a proof of the commitment that `path` has the value whose hash is `value_hash` at the specified height.
+    /// If `value_hash` is None, it verifies the absence of the value at the path.
+    ///
+    /// The proof can be either a `VerifyMembershipProxyMessage` or a `VerifyMembershipBatchProxyMessage` that includes the commitment.
+    fn verify_commitment(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        prefix: CommitmentPrefix,
+        path: String,
+        value_hash: Option<[u8; 32]>,
+        proof_height: Height,
+        proof: Vec<u8>,
+    ) -> Result<(), Error> {
+        // convert `proof` to CommitmentProof
+        let commitment_proofs = CommitmentProofs::ethabi_decode(proof.as_slice())?;
+        let (msg, domain) = commitment_proofs.message_with_domain()?;
+        let state_id = match msg {
+            ProxyMessage::VerifyMembershipBatch(msg) => {
+                if msg.prefix != prefix {
+                    return Err(Error::commitment_prefix_mismatch(prefix, msg.prefix));
+                }
+                if msg.height != proof_height {
+                    return Err(Error::proof_height_mismatch(proof_height, msg.height));
+                }
+                // check if the batch includes the expected commitment
+                if !msg.contains(&path, value_hash) {
+                    return Err(Error::commitment_not_found_in_batch(path, value_hash));
+                }
+                msg.state_id
+            }
+            msg => {
+                let msg: VerifyMembershipProxyMessage = msg.try_into()?;
+
+                // check if `.prefix` matches the counterparty connection's prefix
+                if msg.prefix != prefix {
+                    return Err(Error::commitment_prefix_mismatch(prefix, msg.prefix));
+                }
+                // check if `.path` matches expected the commitment path
+                if msg.path != path {
+                    return Err(Error::commitment_path_mismatch(path, msg.path));
+                }
+                // check if `.height` matches proof height
+                if msg.height != proof_height {
+                    return Err(Error::proof_height_mismatch(proof_height, msg.height));
+                }
+
+                // check if `.value` matches expected state
+                if msg.value != value_hash {
+                    return Err(Error::commitment_value_mismatch(value_hash, msg.value));
+                }
+                msg.state_id
+            }
+        };
+        self.verify_state_id_and_signatures(
+            ctx,
+            client_id,
+            proof_height,
+            state_id,
+            domain,
+            commitment_proofs,
+        )
+    }
+
+    /// verify_state_id_and_signatures verifies that `state_id` matches the consensus state at `proof_height` and the proof is signed by the active enclave keys for the client's domain.
+    fn verify_state_id_and_signatures(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: ClientId,
+        proof_height: Height,
+        state_id: StateID,
+        domain: Option<MessageDomain>,
+        commitment_proofs: CommitmentProofs,
+    ) -> Result<(), Error> {
+        // check if `.state_id` matches the corresponding stored consensus state's state_id
+        let consensus_state =
+            ConsensusState::try_from(ctx.consensus_state(&client_id, &proof_height)?)?;
+        if consensus_state.state_id != state_id {
+            return Err(Error::state_id_mismatch(
+                proof_height,
+                consensus_state.state_id,
+                state_id,
+            ));
+        }
+
+        let client_state = ClientState::try_from(ctx.client_state(&client_id)?)?;
+        verify_message_domain(&client_state, &client_id, domain.as_ref())?;
+
+        self.verify_ek_signatures(
+            ctx,
+            &client_id,
+            &client_state,
+            &commitment_proofs.message,
+            commitment_proofs.signatures,
+        )?;
+
+        Ok(())
+    }
+
+    /// validate_context checks the proxy's validation context against the host's timestamp and height
+    fn validate_context<T: HostClientReader + ?Sized>(
+        &self,
+        ctx: &T,
+        context: &ValidationContext,
+    ) -> Result<(), Error> {
+        let host_height = ctx.host_height();
+        if host_height.is_none() && context.requires_host_height() {
+            return Err(Error::missing_host_height());
+        }
+        context.validate(ctx.host_timestamp(), host_height)?;
+        Ok(())
+    }
+
+    fn verify_ek_signatures<T: HostClientReader + ?Sized>(
+        &self,
+        ctx: &T,
+        client_id: &ClientId,
+        client_state: &ClientState,
+        sign_bytes: &[u8],
+        signatures: Vec<Vec<u8>>,
+    ) -> Result<(), Error> {
+        if client_state.operators.is_empty() {
+            if signatures.len() != 1 {
+                return Err(Error::invalid_signatures_length(1, signatures.len()));
+            }
+            let ek = verify_signature_address(sign_bytes, &signatures[0])?;
+            self.ensure_active_enclave_key(ctx, client_id, client_state, ek)?;
+        } else {
+            let mut success = 0u64;
             for (signature, operator) in signatures
                 .into_iter()
                 .zip(client_state.operators.clone().into_iter())
@@ -589,44 +1180,57 @@ impl LCPClient {
                 // check if the `header.signer` matches the commitment prover
                 let ek = verify_signature_address(sign_bytes, &signature)?;
                 // check if the specified signer exists in the client state
-                assert!(self.is_active_enclave_key_and_check_operator(ctx, client_id, ek, operator));
+                let info = self.ensure_active_enclave_key(ctx, client_id, client_state, ek)?;
+                if info.operator != operator {
+                    return Err(Error::enclave_key_operator_mismatch(
+                        ek,
+                        operator,
+                        info.operator,
+                    ));
+                }
                 success += 1;
             }
-            assert!(
-                success * client_state.operators_threshold_denominator
-                    >= client_state.operators_threshold_numerator
-                        * client_state.operators.len() as u64
-            );
+            check_operators_threshold(client_state, success)?;
         }
         Ok(())
     }
 
-    fn is_active_enclave_key_and_check_operator<T: HostClientReader + ?Sized>(
-        &self,
-        ctx: &T,
-        client_id: &ClientId,
-        ek: Address,
-        operator: Address,
-    ) -> bool {
-        let info = match self.get_enclave_operator_info(ctx, client_id, ek) {
-            Some(info) => info,
-            None => return false,
-        };
-        assert!(info.operator == operator);
-        ctx.host_timestamp().as_unix_timestamp_secs() < info.expired_at
-    }
-
-    fn is_active_enclave_key<T: HostClientReader + ?Sized>(
+    /// ensure_active_enclave_key returns the operator info of the enclave key if it is registered, not revoked and not expired
+    ///
+    /// It also checks that the MRENCLAVE of the key is still accepted by the client, so the keys registered with the previous MRENCLAVE are rejected after `previous_mr_enclave_expiration_time`.
+    fn ensure_active_enclave_key<T: HostClientReader + ?Sized>(
         &self,
         ctx: &T,
         client_id: &ClientId,
+        client_state: &ClientState,
         ek: Address,
-    ) -> bool {
-        let info = match self.get_enclave_operator_info(ctx, client_id, ek) {
-            Some(info) => info,
-            None => return false,
-        };
-        ctx.host_timestamp().as_unix_timestamp_secs() < info.expired_at
+    ) -> Result<EKOperatorInfo, Error> {
+        let info = self
+            .get_enclave_operator_info(ctx, client_id, ek)
+            .ok_or_else(|| Error::enclave_key_not_found(ek))?;
+        if info.revoked {
+            return Err(Error::enclave_key_revoked(ek));
+        }
+        let current_timestamp = ctx.host_timestamp().as_unix_timestamp_secs();
+        if current_timestamp >= info.expired_at {
+            return Err(Error::enclave_key_expired(
+                ek,
+                info.expired_at,
+                current_timestamp,
+            ));
+        }
+        // NOTE: the keys stored without MRENCLAVE are only limited by their expiration time
+        if !info.mr_enclave.is_empty()
+            && client_state
+                .mr_enclave_policy(&info.mr_enclave, current_timestamp)
+                .is_none()
+        {
+            return Err(Error::enclave_key_mrenclave_not_allowed(
+                ek,
+                info.mr_enclave,
+            ));
+        }
+        Ok(info)
     }
 
     fn set_enclave_operator_info<T: HostClientKeeper + ?Sized>(
@@ -635,18 +1239,109 @@ impl LCPClient {
         client_id: &ClientId,
         ek: Address,
         info: EKOperatorInfo,
-    ) {
+    ) -> Result<(), Error> {
         match self.get_enclave_operator_info(ctx, client_id, ek) {
             Some(v) => {
-                assert!(v.expired_at == info.expired_at && v.operator == info.operator);
+                if v.revoked {
+                    return Err(Error::enclave_key_revoked(ek));
+                }
+                if v != info {
+                    return Err(Error::enclave_key_already_registered(
+                        ek,
+                        v.expired_at,
+                        v.operator,
+                    ));
+                }
             }
             None => {
                 ctx.set(
                     enclave_key_path(client_id, ek),
                     serde_json::to_string(&info).unwrap().into_bytes(),
                 );
+                PruningIndex::new(client_id).add_enclave_key(ctx, info.expired_at, ek);
             }
         }
+        Ok(())
+    }
+
+    /// build_cosigned_update_client_message combines the proofs of the same proxy message signed by multiple enclaves into an `UpdateClientMessage`
+    ///
+    /// Each signature is placed at the position of the operator of its enclave key, and the position of an operator without a signature is left empty.
+    /// It fails if the combined signatures don't meet the operators threshold of the client.
+    pub fn build_cosigned_update_client_message(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: &ClientId,
+        proofs: Vec<CommitmentProof>,
+    ) -> Result<UpdateClientMessage, Error> {
+        let message = match proofs.first() {
+            Some(proof) => proof.message.clone(),
+            None => return Err(Error::empty_commitment_proofs()),
+        };
+        let client_state = ClientState::try_from(ctx.client_state(client_id)?)?;
+        let signatures = if client_state.operators.is_empty() {
+            if proofs.len() != 1 {
+                return Err(Error::invalid_signatures_length(1, proofs.len()));
+            }
+            vec![proofs[0].signature.clone()]
+        } else {
+            let mut signatures = vec![vec![]; client_state.operators.len()];
+            for (i, proof) in proofs.into_iter().enumerate() {
+                if proof.message != message {
+                    return Err(Error::commitment_proof_message_mismatch(i));
+                }
+                if !proof.is_proven() {
+                    return Err(Error::unsigned_commitment_proof(i));
+                }
+                let ek = verify_signature_address(&message, &proof.signature)?;
+                let info = self.ensure_active_enclave_key(ctx, client_id, &client_state, ek)?;
+                let pos = client_state
+                    .operators
+                    .iter()
+                    .position(|op| *op == info.operator)
+                    .ok_or_else(|| Error::unknown_enclave_key_operator(ek, info.operator))?;
+                if !signatures[pos].is_empty() {
+                    return Err(Error::duplicate_operator_signature(info.operator));
+                }
+                signatures[pos] = proof.signature;
+            }
+            check_operators_threshold(
+                &client_state,
+                signatures.iter().filter(|sig| !sig.is_empty()).count() as u64,
+            )?;
+            signatures
+        };
+        let (proxy_message, domain, encoding) = ProxyMessage::from_bytes_with_encoding(&message)?;
+        Ok(UpdateClientMessage {
+            signatures,
+            proxy_message,
+            domain,
+            encoding,
+        })
+    }
+
+    /// get_conflicting_state_evidence returns the evidence recorded when the client was frozen due to conflicting states at `height`
+    pub fn get_conflicting_state_evidence(
+        &self,
+        ctx: &dyn HostClientReader,
+        client_id: &ClientId,
+        height: Height,
+    ) -> Result<Option<ConflictingStateEvidence>, Error> {
+        ctx.get(conflicting_state_evidence_path(client_id, height).as_slice())
+            .map(|evidence| serde_json::from_slice(evidence.as_slice()).map_err(Error::serde_json))
+            .transpose()
+    }
+
+    fn set_conflicting_state_evidence(
+        &self,
+        ctx: &mut dyn HostClientKeeper,
+        client_id: &ClientId,
+        evidence: ConflictingStateEvidence,
+    ) {
+        ctx.set(
+            conflicting_state_evidence_path(client_id, evidence.height),
+            serde_json::to_string(&evidence).unwrap().into_bytes(),
+        );
     }
 
     fn get_enclave_operator_info<T: HostClientReader + ?Sized>(
@@ -660,7 +1355,28 @@ impl LCPClient {
     }
 }
 
-pub fn compute_eip712_register_enclave_key(avr: &str) -> Vec<u8> {
+/// compute_eip712_domain_separator computes the EIP-712 domain separator of the LCP client
+///
+/// `LCP_CLIENT_DOMAIN_SEPARATOR` corresponds to the case where both `chain_id` and `verifying_contract` are zero.
+pub fn compute_eip712_domain_separator(chain_id: u64, verifying_contract: Address) -> [u8; 32] {
+    // keccak256(keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract,bytes32 salt)") | keccak256("LCPClient") | keccak256("1") | chainId | verifyingContract | salt)
+    let mut h = Keccak::v256();
+    h.update(&keccak256(
+        b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract,bytes32 salt)",
+    ));
+    h.update(&keccak256(b"LCPClient"));
+    h.update(&keccak256(b"1"));
+    h.update(&[0u8; 24]);
+    h.update(&chain_id.to_be_bytes());
+    h.update(&[0u8; 12]);
+    h.update(verifying_contract.0.as_slice());
+    h.update(&[0u8; 32]);
+    let mut result = [0u8; 32];
+    h.finalize(result.as_mut());
+    result
+}
+
+pub fn compute_eip712_register_enclave_key(domain_separator: [u8; 32], avr: &str) -> Vec<u8> {
     // 0x1901 | DOMAIN_SEPARATOR_REGISTER_ENCLAVE_KEY | keccak256(keccak256("RegisterEnclaveKey(string avr)") | keccak256(avr))
     let type_hash = {
         let mut h = Keccak::v256();
@@ -672,16 +1388,17 @@ pub fn compute_eip712_register_enclave_key(avr: &str) -> Vec<u8> {
     };
     [0x19, 0x01]
         .into_iter()
-        .chain(LCP_CLIENT_DOMAIN_SEPARATOR)
+        .chain(domain_separator)
         .chain(type_hash)
         .collect()
 }
 
-pub fn compute_eip712_register_enclave_key_hash(avr: &str) -> [u8; 32] {
-    keccak256(&compute_eip712_register_enclave_key(avr))
+pub fn compute_eip712_register_enclave_key_hash(domain_separator: [u8; 32], avr: &str) -> [u8; 32] {
+    keccak256(&compute_eip712_register_enclave_key(domain_separator, avr))
 }
 
 pub fn compute_eip712_zkdcap_register_enclave_key(
+    domain_separator: [u8; 32],
     zkdcap_verifier_info: &ZKDCAPVerifierInfo,
     output_hash: [u8; 32],
 ) -> Vec<u8> {
@@ -699,12 +1416,33 @@ pub fn compute_eip712_zkdcap_register_enclave_key(
     };
     [0x19, 0x01]
         .into_iter()
-        .chain(LCP_CLIENT_DOMAIN_SEPARATOR)
+        .chain(domain_separator)
+        .chain(type_hash)
+        .collect()
+}
+
+pub fn compute_eip712_dcap_register_enclave_key(
+    domain_separator: [u8; 32],
+    output_hash: [u8; 32],
+) -> Vec<u8> {
+    // 0x1901 | DOMAIN_SEPARATOR_DCAP_REGISTER_ENCLAVE_KEY | keccak256(keccak256("DCAPRegisterEnclaveKey(bytes32 outputHash)") | output_hash)
+    let type_hash = {
+        let mut h = Keccak::v256();
+        h.update(&keccak256(b"DCAPRegisterEnclaveKey(bytes32 outputHash)"));
+        h.update(&output_hash);
+        let mut result = [0u8; 32];
+        h.finalize(result.as_mut());
+        result
+    };
+    [0x19, 0x01]
+        .into_iter()
+        .chain(domain_separator)
         .chain(type_hash)
         .collect()
 }
 
 pub fn compute_eip712_update_operators(
+    domain_separator: [u8; 32],
     client_id: ClientId,
     nonce: u64,
     new_operators: Vec<Address>,
@@ -748,12 +1486,13 @@ pub fn compute_eip712_update_operators(
     };
     [0x19, 0x01]
         .into_iter()
-        .chain(LCP_CLIENT_DOMAIN_SEPARATOR)
+        .chain(domain_separator)
         .chain(type_hash)
         .collect()
 }
 
 pub fn compute_eip712_update_operators_hash(
+    domain_separator: [u8; 32],
     client_id: ClientId,
     nonce: u64,
     new_operators: Vec<Address>,
@@ -761,6 +1500,7 @@ pub fn compute_eip712_update_operators_hash(
     threshold_denominator: u64,
 ) -> [u8; 32] {
     keccak256(&compute_eip712_update_operators(
+        domain_separator,
         client_id,
         nonce,
         new_operators,
@@ -769,14 +1509,209 @@ pub fn compute_eip712_update_operators_hash(
     ))
 }
 
+pub fn compute_eip712_update_mrenclave(
+    domain_separator: [u8; 32],
+    client_id: ClientId,
+    nonce: u64,
+    new_mr_enclave: [u8; 32],
+    transition_period: u64,
+) -> Vec<u8> {
+    sol! {
+        struct EIP712UpdateMrenclave {
+            bytes32 typeHash;
+            bytes32 clientIdHash;
+            uint64 nonce;
+            bytes32 newMrenclave;
+            uint64 transitionPeriod;
+        }
+    }
+    // 0x1901 | DOMAIN_SEPARATOR_UPDATE_MRENCLAVE | keccak256(keccak256("UpdateMrenclave(string clientId,uint64 nonce,bytes32 newMrenclave,uint64 transitionPeriod)") | keccak256(client_id) | nonce | new_mr_enclave | transition_period)
+    let type_hash = {
+        let eip712_update_mrenclave = EIP712UpdateMrenclave {
+            typeHash: keccak256(b"UpdateMrenclave(string clientId,uint64 nonce,bytes32 newMrenclave,uint64 transitionPeriod)").into(),
+            clientIdHash: keccak256(client_id.as_bytes()).into(),
+            nonce,
+            newMrenclave: new_mr_enclave.into(),
+            transitionPeriod: transition_period,
+        };
+        keccak256(&eip712_update_mrenclave.abi_encode())
+    };
+    [0x19, 0x01]
+        .into_iter()
+        .chain(domain_separator)
+        .chain(type_hash)
+        .collect()
+}
+
+pub fn compute_eip712_update_mrenclave_hash(
+    domain_separator: [u8; 32],
+    client_id: ClientId,
+    nonce: u64,
+    new_mr_enclave: [u8; 32],
+    transition_period: u64,
+) -> [u8; 32] {
+    keccak256(&compute_eip712_update_mrenclave(
+        domain_separator,
+        client_id,
+        nonce,
+        new_mr_enclave,
+        transition_period,
+    ))
+}
+
+/// verify_operator_signatures verifies that the signatures of `sign_bytes` are produced by the client's operators
+/// and that the number of valid signatures satisfies the client's threshold
+fn verify_operator_signatures(
+    client_state: &ClientState,
+    sign_bytes: &[u8],
+    signatures: &[Vec<u8>],
+) -> Result<(), Error> {
+    let success = count_operator_signatures(client_state, sign_bytes, signatures)?;
+    check_operators_threshold(client_state, success)
+}
+
+/// count_operator_signatures returns the number of the signatures of `sign_bytes` produced by the client's operators
+///
+/// It returns an error if a non-empty signature is not produced by the operator at the same position.
+fn count_operator_signatures(
+    client_state: &ClientState,
+    sign_bytes: &[u8],
+    signatures: &[Vec<u8>],
+) -> Result<u64, Error> {
+    let mut success = 0u64;
+    for (op, sig) in client_state
+        .operators
+        .iter()
+        .zip(signatures.iter())
+        .filter(|(_, sig)| !sig.is_empty())
+    {
+        // check if the operator's signature is valid
+        let operator = verify_signature_address(sign_bytes, sig.as_ref())?;
+        if *op != operator {
+            return Err(Error::operator_mismatch(*op, operator));
+        }
+        success += 1;
+    }
+    Ok(success)
+}
+
+/// check_operators_threshold checks if the number of valid operator signatures satisfies the client's threshold
+fn check_operators_threshold(client_state: &ClientState, success: u64) -> Result<(), Error> {
+    check_threshold(
+        client_state.operators.len() as u64,
+        success,
+        client_state.operators_threshold_numerator,
+        client_state.operators_threshold_denominator,
+    )
+}
+
+/// check_threshold checks if `success` out of `total` signatures satisfies the threshold `numerator/denominator`
+fn check_threshold(
+    total: u64,
+    success: u64,
+    threshold_numerator: u64,
+    threshold_denominator: u64,
+) -> Result<(), Error> {
+    // NOTE: the products are computed in u128 because the numerator may come from a message
+    if (success as u128) * (threshold_denominator as u128)
+        < (threshold_numerator as u128) * (total as u128)
+    {
+        return Err(Error::insufficient_operator_signatures(
+            success,
+            total,
+            threshold_numerator,
+            threshold_denominator,
+        ));
+    }
+    Ok(())
+}
+
+pub fn compute_eip712_revoke_enclave_key(
+    domain_separator: [u8; 32],
+    client_id: ClientId,
+    enclave_key: Address,
+) -> Vec<u8> {
+    sol! {
+        struct EIP712RevokeEnclaveKey {
+            bytes32 typeHash;
+            bytes32 clientIdHash;
+            bytes32 enclaveKey;
+        }
+    }
+    // 0x1901 | DOMAIN_SEPARATOR_REVOKE_ENCLAVE_KEY | keccak256(keccak256("RevokeEnclaveKey(string clientId,address enclaveKey)") | keccak256(client_id) | enclave_key)
+    let type_hash = {
+        // an address is encoded as a left-padded 32 bytes value in EIP-712
+        let mut padded_enclave_key = [0u8; 32];
+        padded_enclave_key[12..].copy_from_slice(enclave_key.0.as_ref());
+        let eip712_revoke_enclave_key = EIP712RevokeEnclaveKey {
+            typeHash: keccak256(b"RevokeEnclaveKey(string clientId,address enclaveKey)").into(),
+            clientIdHash: keccak256(client_id.as_bytes()).into(),
+            enclaveKey: padded_enclave_key.into(),
+        };
+        keccak256(&eip712_revoke_enclave_key.abi_encode())
+    };
+    [0x19, 0x01]
+        .into_iter()
+        .chain(domain_separator)
+        .chain(type_hash)
+        .collect()
+}
+
+pub fn compute_eip712_revoke_enclave_key_hash(
+    domain_separator: [u8; 32],
+    client_id: ClientId,
+    enclave_key: Address,
+) -> [u8; 32] {
+    keccak256(&compute_eip712_revoke_enclave_key(
+        domain_separator,
+        client_id,
+        enclave_key,
+    ))
+}
+
+/// verify_zkdcap_proof verifies the proof of the quote verification output with the client's verifiers
+/// that match the message's zkVM type, and returns the verifier info that accepted the proof
+fn verify_zkdcap_proof(
+    client_state: &ClientState,
+    message: &ZKDCAPRegisterEnclaveKeyMessage,
+) -> Result<ZKDCAPVerifierInfo, Error> {
+    let output = message.quote_verification_output.to_bytes();
+    let mut last_err = None;
+    for info in client_state
+        .zkdcap_verifier_infos
+        .iter()
+        .filter(|info| info.as_type() == message.zkvm_type)
+    {
+        let res = match info {
+            ZKDCAPVerifierInfo::Risc0(image_id) => {
+                let (selector, seal) = message.risc0_seal_selector()?;
+                zkvm::verifier::verify_groth16_proof(selector, seal, *image_id, output.clone())
+            }
+            ZKDCAPVerifierInfo::SP1(vkey_hash) => {
+                zkvm::verifier::verify_sp1_groth16_proof(&message.proof, *vkey_hash, &output)
+            }
+            ZKDCAPVerifierInfo::Unspecified => continue,
+        };
+        match res {
+            Ok(()) => return Ok(*info),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    match last_err {
+        Some(e) => Err(e.into()),
+        None => Err(Error::missing_zkdcap_verifier_info(message.zkvm_type)),
+    }
+}
+
 // verify_ias_report
 // - verifies the Attestation Verification Report
 // - calculate a key expiration with client_state and report's timestamp
+// - returns the report's MRENCLAVE and its registration policy
 fn verify_ias_report(
     current_timestamp: Time,
     client_state: &ClientState,
     signed_avr: &IASSignedReport,
-) -> Result<(ReportData, Time), Error> {
+) -> Result<(ReportData, Time, Vec<u8>, MrEnclavePolicy), Error> {
     // verify AVR with Intel SGX Attestation Report Signing CA
     // NOTE: This verification is skipped in tests because the CA is not available in the test environment
     // #[cfg(not(test))]
@@ -784,33 +1719,83 @@ fn verify_ias_report(
 
     let quote = signed_avr.get_avr()?.parse_quote()?;
 
+    // check if `mr_enclave` that is included in the quote matches the expected value
+    let mr_enclave_policy = client_state
+        .mr_enclave_policy(
+            quote.raw.report_body.mr_enclave.m.as_slice(),
+            current_timestamp.as_unix_timestamp_secs(),
+        )
+        .ok_or_else(|| {
+            Error::mrenclave_mismatch(
+                client_state.mr_enclave.clone(),
+                quote.raw.report_body.mr_enclave.m.to_vec(),
+            )
+        })?;
+
     // check if attestation report's timestamp is not expired
-    let key_expiration = (quote.attestation_time + client_state.key_expiration)?;
+    let key_expiration = (quote.attestation_time + mr_enclave_policy.key_expiration)?;
     if current_timestamp > key_expiration {
         return Err(Error::expired_avr(
             current_timestamp,
             quote.attestation_time,
-            client_state.key_expiration,
-        ));
-    }
-
-    // check if `mr_enclave` that is included in the quote matches the expected value
-    if quote.raw.report_body.mr_enclave.m != client_state.mr_enclave.as_slice() {
-        return Err(Error::mrenclave_mismatch(
-            quote.raw.report_body.mr_enclave.m.to_vec(),
-            client_state.mr_enclave.clone(),
+            mr_enclave_policy.key_expiration,
         ));
     }
 
     let report_data = quote.report_data();
     report_data.validate()?;
-    Ok((report_data, quote.attestation_time))
+    Ok((
+        report_data,
+        quote.attestation_time,
+        quote.raw.report_body.mr_enclave.m.to_vec(),
+        mr_enclave_policy,
+    ))
 }
 
-fn enclave_key_path(client_id: &ClientId, ek: Address) -> Vec<u8> {
-    format!("clients/{}/aux/enclave_keys/{}", client_id, ek)
-        .as_bytes()
-        .to_vec()
+/// verify_message_domain verifies that the domain of a proxy message is bound to the client
+///
+/// If the client state has a non-empty `chain_id`, the message must be bound to the domain (i.e. v1 messages are rejected).
+fn verify_message_domain(
+    client_state: &ClientState,
+    client_id: &ClientId,
+    domain: Option<&MessageDomain>,
+) -> Result<(), Error> {
+    match domain {
+        Some(domain) => {
+            if domain.client_id != client_id.as_str()
+                || (!client_state.chain_id.is_empty() && domain.chain_id != client_state.chain_id)
+            {
+                return Err(Error::message_domain_mismatch(
+                    client_state.chain_id.clone(),
+                    client_id.clone(),
+                    domain.clone(),
+                ));
+            }
+        }
+        None => {
+            if !client_state.chain_id.is_empty() {
+                return Err(Error::missing_message_domain(client_id.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn enclave_key_path(client_id: &ClientId, ek: Address) -> Vec<u8> {
+    let mut path = enclave_keys_prefix(client_id);
+    path.extend(ek.to_string().into_bytes());
+    path
+}
+
+/// enclave_keys_prefix returns the common prefix of `enclave_key_path` of the client
+pub(crate) fn enclave_keys_prefix(client_id: &ClientId) -> Vec<u8> {
+    format!("clients/{}/aux/enclave_keys/", client_id).into_bytes()
+}
+
+fn conflicting_state_evidence_path(client_id: &ClientId, height: Height) -> Vec<u8> {
+    format!("clients/{}/aux/conflicting_states/{}", client_id, height)
+        .as_bytes()
+        .to_vec()
 }
 
 fn keccak256(bz: &[u8]) -> [u8; 32] {
@@ -824,7 +1809,9 @@ fn keccak256(bz: &[u8]) -> [u8; 32] {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::message::UpdateClientMessage;
+    use crate::client_state::AllowedMrEnclave;
+    use crate::errors::ErrorDetail;
+    use crate::message::{UpdateClientMessage, LCP_ZKDCAP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL};
     use alloc::rc::Rc;
     use alloc::sync::Arc;
     use attestation_report::{IASAttestationVerificationReport, ReportData};
@@ -841,17 +1828,30 @@ mod tests {
         },
         Height as ICS02Height,
     };
-    use light_client::{commitments::prove_commitment, UpdateClientResult};
-    use light_client::{ClientKeeper, LightClient, LightClientResolver, MapLightClientRegistry};
+    use light_client::commitments::{
+        prove_commitment, prove_commitment_with_domain, prove_commitment_with_encoding,
+        verify_emitted_state, EmittedState, ValidationContext,
+    };
+    use light_client::types::proto::ibc::lightclients::lcp::v1::UpdateClientMessage as RawUpdateClientMessage;
+    use light_client::types::Any;
+    use light_client::UpdateClientResult;
+    use light_client::{
+        ClientKeeper, ClientReader, LightClient, LightClientResolver, MapLightClientRegistry,
+    };
     use mock_lc::MockLightClient;
-    use sgx_types::{sgx_quote_t, sgx_report_body_t};
+    use remote_attestation::dcap_simulation::{
+        simulate_gen_quote_and_collaterals, DCAPRASimulationOpts, DCAP_SIM_ROOT_CA_PEM,
+        DCAP_SIM_ROOT_KEY_PKCS8,
+    };
+    use sgx_types::{sgx_measurement_t, sgx_quote_t, sgx_report_body_t, sgx_report_t};
     use store::memory::MemStore;
+    use store::KVStore;
 
     #[test]
     fn test_compute_eip712_register_enclave_key() {
         let avr = "{}";
         let expected = hex!("2ab70eb55dea90c4d477a7e668812653ca37c079036e92e31d4d092bcacf61cb");
-        let got = compute_eip712_register_enclave_key_hash(avr);
+        let got = compute_eip712_register_enclave_key_hash(LCP_CLIENT_DOMAIN_SEPARATOR, avr);
         assert_eq!(got, expected);
     }
 
@@ -865,6 +1865,7 @@ mod tests {
         let threshold_denominator = 1;
         let expected = hex!("19017fd21c2453e80741907e7ff11fd62ae1daa34c6fc0c2eced821f1c1d3fe88a4cd9ff770a728b1198fc341496b4aca7383efe5836cf011691da8008d5232e3a24");
         let got = compute_eip712_update_operators(
+            LCP_CLIENT_DOMAIN_SEPARATOR,
             client_id,
             nonce,
             new_operators,
@@ -874,6 +1875,22 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn test_compute_eip712_domain_separator() {
+        assert_eq!(
+            compute_eip712_domain_separator(0, Address::default()),
+            LCP_CLIENT_DOMAIN_SEPARATOR
+        );
+        assert_ne!(
+            compute_eip712_domain_separator(1, Address::default()),
+            LCP_CLIENT_DOMAIN_SEPARATOR
+        );
+        assert_ne!(
+            compute_eip712_domain_separator(0, Address([1u8; 20])),
+            LCP_CLIENT_DOMAIN_SEPARATOR
+        );
+    }
+
     #[test]
     fn test_client() {
         // ek is a signing key to prove LCP's commitments
@@ -902,6 +1919,7 @@ mod tests {
             let initial_consensus_state = ConsensusState {
                 state_id: Default::default(),
                 timestamp: Time::unix_epoch(),
+                emitted_states_root: None,
             };
 
             let mut ctx = Context::new(registry.clone(), ibc_store.clone(), &ek, Time::now());
@@ -922,7 +1940,13 @@ mod tests {
             let mut ctx = Context::new(registry.clone(), ibc_store.clone(), &ek, Time::now());
             let report = generate_dummy_signed_avr(&ek.get_pubkey());
             let operator_signature = op_key
-                .sign(compute_eip712_register_enclave_key(report.avr.as_str()).as_slice())
+                .sign(
+                    compute_eip712_register_enclave_key(
+                        LCP_CLIENT_DOMAIN_SEPARATOR,
+                        report.avr.as_str(),
+                    )
+                    .as_slice(),
+                )
                 .unwrap();
             let header = ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
                 report,
@@ -1000,6 +2024,8 @@ mod tests {
             let header = ClientMessage::UpdateClient(UpdateClientMessage {
                 proxy_message: proof1.message().unwrap(),
                 signatures: vec![proof1.signature],
+                domain: None,
+                encoding: MessageEncoding::EthABI,
             });
             let mut ctx = Context::new(
                 registry.clone(),
@@ -1041,6 +2067,8 @@ mod tests {
             let header = ClientMessage::UpdateClient(UpdateClientMessage {
                 proxy_message: misbehaviour_proof.message().unwrap(),
                 signatures: vec![misbehaviour_proof.signature],
+                domain: None,
+                encoding: MessageEncoding::EthABI,
             });
             let mut ctx = Context::new(
                 registry,
@@ -1053,17 +2081,2237 @@ mod tests {
         }
     }
 
-    #[allow(clippy::arc_with_non_send_sync)]
-    fn build_lc_registry() -> Arc<dyn LightClientResolver> {
-        let registry = MapLightClientRegistry::new();
-        Arc::new(registry)
+    #[test]
+    fn test_initialise_errors() {
+        let ek = EnclaveKey::new().unwrap();
+        let registry = build_lc_registry();
+        let zkdcap_client_state = ClientState {
+            zkdcap_verifier_infos: vec![ZKDCAPVerifierInfo::Risc0([1u8; 32])],
+            current_tcb_evaluation_data_number: 1,
+            ..default_client_state()
+        };
+        let operators = vec![
+            Address::from_hex_string("0x0000000000000000000000000000000000000001").unwrap(),
+            Address::from_hex_string("0x0000000000000000000000000000000000000002").unwrap(),
+        ];
+
+        let initialise = |client_state: ClientState, consensus_state: ConsensusState| {
+            let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+            let mut ctx = Context::new(registry.clone(), ibc_store, &ek, Time::now());
+            LCPClient.initialise(&mut ctx, lcp_client_id(), client_state, consensus_state)
+        };
+
+        assert_err(
+            initialise(
+                ClientState {
+                    latest_height: Height::new(0, 1),
+                    ..default_client_state()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::NonZeroInitialLatestHeight(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    mr_enclave: [0u8; 31].to_vec(),
+                    ..default_client_state()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::InvalidMrenclaveLength(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    operators: operators.clone(),
+                    operators_threshold_numerator: 0,
+                    operators_threshold_denominator: 1,
+                    ..default_client_state()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::InvalidOperatorsThreshold(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    operators: operators.clone(),
+                    operators_threshold_numerator: 2,
+                    operators_threshold_denominator: 1,
+                    ..default_client_state()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::InvalidOperatorsThreshold(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    operators: operators.iter().rev().cloned().collect(),
+                    operators_threshold_numerator: 1,
+                    operators_threshold_denominator: 2,
+                    ..default_client_state()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::OperatorsNotSorted(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    operators_nonce: 1,
+                    ..default_client_state()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::UnexpectedOperatorsNonce(_)),
+        );
+        // a client can accept proofs from multiple zkVMs
+        let res = initialise(
+            ClientState {
+                zkdcap_verifier_infos: vec![
+                    ZKDCAPVerifierInfo::Risc0([1u8; 32]),
+                    ZKDCAPVerifierInfo::Risc0([2u8; 32]),
+                    ZKDCAPVerifierInfo::SP1([3u8; 32]),
+                ],
+                ..zkdcap_client_state.clone()
+            },
+            empty_consensus_state(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        assert_err(
+            initialise(
+                ClientState {
+                    zkdcap_verifier_infos: vec![
+                        ZKDCAPVerifierInfo::Risc0([1u8; 32]),
+                        ZKDCAPVerifierInfo::SP1([2u8; 32]),
+                        ZKDCAPVerifierInfo::Risc0([1u8; 32]),
+                    ],
+                    ..zkdcap_client_state.clone()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::DuplicateZkdcapVerifierInfo(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    zkdcap_verifier_infos: vec![
+                        ZKDCAPVerifierInfo::Risc0([1u8; 32]),
+                        ZKDCAPVerifierInfo::Unspecified,
+                    ],
+                    ..zkdcap_client_state.clone()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::UnsupportedZkvmType(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    current_tcb_evaluation_data_number: 0,
+                    ..zkdcap_client_state.clone()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::ZeroCurrentTcbEvaluationDataNumber(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    next_tcb_evaluation_data_number: 2,
+                    ..zkdcap_client_state.clone()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::InvalidNextTcbEvaluationDataNumber(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    next_tcb_evaluation_data_number: 1,
+                    next_tcb_evaluation_data_number_update_time: 1,
+                    ..zkdcap_client_state.clone()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::InvalidNextTcbEvaluationDataNumber(_)),
+        );
+        assert_err(
+            initialise(
+                ClientState {
+                    key_expiration: Duration::from_secs(0),
+                    ..default_client_state()
+                },
+                empty_consensus_state(),
+            ),
+            |e| matches!(e, ErrorDetail::ZeroKeyExpiration(_)),
+        );
+        assert_err(
+            initialise(
+                default_client_state(),
+                ConsensusState {
+                    state_id: [1u8; 32].into(),
+                    timestamp: Time::unix_epoch(),
+                    emitted_states_root: None,
+                },
+            ),
+            |e| matches!(e, ErrorDetail::NonEmptyInitialConsensusState(_)),
+        );
+        let res = initialise(zkdcap_client_state, empty_consensus_state());
+        assert!(res.is_ok(), "res={:?}", res);
     }
 
-    fn generate_dummy_signed_avr(key: &EnclavePublicKey) -> IASSignedReport {
+    #[test]
+    fn test_update_state_errors() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let client_id = {
+            let mut ctx = Context::new(registry.clone(), ibc_store.clone(), &ek, Time::now());
+            setup_client(&mut ctx, default_client_state(), &ek, None)
+        };
+        let mut ctx = Context::new(registry.clone(), ibc_store.clone(), &ek, Time::now());
+
+        // the first update must emit states
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(&ek, update_state_message(None, Height::new(0, 1), vec![])),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::EmptyEmittedStates(_)));
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(None, Height::new(0, 1), dummy_emitted_states()),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // the subsequent updates must refer to the previous state
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(&ek, update_state_message(None, Height::new(0, 2), vec![])),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::MissingPrevState(_)));
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                UpdateStateProxyMessage {
+                    prev_state_id: Some([9u8; 32].into()),
+                    ..update_state_message(Some(Height::new(0, 1)), Height::new(0, 2), vec![])
+                },
+            ),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::PrevStateIdMismatch(_)));
+
+        // the number of signatures must be 1 if the client has no operators
+        let res = LCPClient.update_client(&mut ctx, client_id.clone(), {
+            let mut msg = update_client_message(
+                &ek,
+                update_state_message(Some(Height::new(0, 1)), Height::new(0, 2), vec![]),
+            );
+            if let ClientMessage::UpdateClient(ref mut msg) = msg {
+                msg.signatures.push(msg.signatures[0].clone());
+            }
+            msg
+        });
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::InvalidSignaturesLength(_))
+        });
+
+        // the signer must be a registered enclave key
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &EnclaveKey::new().unwrap(),
+                update_state_message(Some(Height::new(0, 1)), Height::new(0, 2), vec![]),
+            ),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::EnclaveKeyNotFound(_)));
+
+        // the enclave key must not be expired
+        {
+            let mut ctx = Context::new(
+                registry.clone(),
+                ibc_store.clone(),
+                &ek,
+                (Time::now() + Duration::from_secs(60 * 60 * 24 * 8)).unwrap(),
+            );
+            let res = LCPClient.update_client(
+                &mut ctx,
+                client_id.clone(),
+                update_client_message(
+                    &ek,
+                    update_state_message(Some(Height::new(0, 1)), Height::new(0, 2), vec![]),
+                ),
+            );
+            assert_err(res, |e| matches!(e, ErrorDetail::EnclaveKeyExpired(_)));
+        }
+
+        // a frozen client rejects any updates
+        freeze_client(&mut ctx, &client_id);
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(Some(Height::new(0, 1)), Height::new(0, 2), vec![]),
+            ),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::ClientFrozen(_)));
+    }
+
+    #[test]
+    fn test_register_enclave_key_errors() {
+        let ek = EnclaveKey::new().unwrap();
+        let op_key = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = lcp_client_id();
+        let res = LCPClient.initialise(
+            &mut ctx,
+            client_id.clone(),
+            default_client_state(),
+            empty_consensus_state(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // the operator signature must match the operator in the report data
+        let report = generate_dummy_signed_avr_with_operator(
+            &ek.get_pubkey(),
+            Some(op_key.get_pubkey().as_address()),
+        );
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                operator_signature: Some(
+                    EnclaveKey::new()
+                        .unwrap()
+                        .sign(
+                            compute_eip712_register_enclave_key(
+                                LCP_CLIENT_DOMAIN_SEPARATOR,
+                                report.avr.as_str(),
+                            )
+                            .as_slice(),
+                        )
+                        .unwrap(),
+                ),
+                report: report.clone(),
+            }),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::OperatorMismatch(_)));
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                operator_signature: Some(
+                    op_key
+                        .sign(
+                            compute_eip712_register_enclave_key(
+                                LCP_CLIENT_DOMAIN_SEPARATOR,
+                                report.avr.as_str(),
+                            )
+                            .as_slice(),
+                        )
+                        .unwrap(),
+                ),
+                report: report.clone(),
+            }),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // the same enclave key cannot be re-registered with a different operator
+        let report = generate_dummy_signed_avr(&ek.get_pubkey());
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                report,
+                operator_signature: None,
+            }),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::EnclaveKeyAlreadyRegistered(_))
+        });
+
+        // a frozen client rejects any registrations
+        freeze_client(&mut ctx, &client_id);
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id,
+            ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                report: generate_dummy_signed_avr(&ek.get_pubkey()),
+                operator_signature: None,
+            }),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::ClientFrozen(_)));
+    }
+
+    #[test]
+    fn test_dcap_register_enclave_key_errors() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(build_lc_registry(), ibc_store, &ek, Time::now());
+        let client_id = setup_client(&mut ctx, default_client_state(), &ek, None);
+        let message = ClientMessage::DCAPRegisterEnclaveKey(DCAPRegisterEnclaveKeyMessage {
+            quote: vec![0u8; 16],
+            collateral: Default::default(),
+            operator_signature: None,
+        });
+
+        // the message is decodable from `Any`
+        assert_eq!(
+            ClientMessage::try_from(Any::from(message.clone())).unwrap(),
+            message
+        );
+
+        let res = LCPClient.update_client(&mut ctx, client_id.clone(), message.clone());
+        assert_err(res, |e| matches!(e, ErrorDetail::DcapQuoteVerifier(_)));
+
+        freeze_client(&mut ctx, &client_id);
+        let res = LCPClient.update_client(&mut ctx, client_id, message);
+        assert_err(res, |e| matches!(e, ErrorDetail::ClientFrozen(_)));
+    }
+
+    #[test]
+    fn test_zkdcap_register_enclave_key_message_decoding() {
+        let opts = DCAPRASimulationOpts::new(
+            DCAP_SIM_ROOT_CA_PEM.as_bytes(),
+            DCAP_SIM_ROOT_KEY_PKCS8.as_bytes(),
+        )
+        .unwrap();
+        let (quote, collateral) =
+            simulate_gen_quote_and_collaterals(&sgx_report_t::default(), opts).unwrap();
+        let output =
+            verify_quote_v3(&quote, &collateral, Time::now().as_unix_timestamp_secs()).unwrap();
+
+        for (zkvm_type, operator_signature) in [
+            (ZKVMType::Risc0, None),
+            (ZKVMType::SP1, Some(vec![1u8; 65])),
+        ] {
+            let message =
+                ClientMessage::ZKDCAPRegisterEnclaveKey(ZKDCAPRegisterEnclaveKeyMessage {
+                    zkvm_type,
+                    quote_verification_output: output.clone(),
+                    proof: vec![2u8; 32],
+                    operator_signature,
+                });
+            let any = Any::from(message.clone());
+            assert_eq!(
+                any.type_url,
+                LCP_ZKDCAP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL
+            );
+            assert_eq!(ClientMessage::try_from(any).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn test_operators_errors() {
+        let ek = EnclaveKey::new().unwrap();
+        let op_key = EnclaveKey::new().unwrap();
+        let other_op_key = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                operators: vec![op_key.get_pubkey().as_address()],
+                operators_threshold_numerator: 1,
+                operators_threshold_denominator: 1,
+                ..default_client_state()
+            },
+            &ek,
+            Some(&other_op_key),
+        );
+
+        // the enclave key is registered with an operator that is not the expected one
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(None, Height::new(0, 1), dummy_emitted_states()),
+            ),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::EnclaveKeyOperatorMismatch(_))
+        });
+
+        // the threshold is not satisfied if no operator signs
+        let res = LCPClient.update_client(&mut ctx, client_id.clone(), {
+            let mut msg = update_client_message(
+                &ek,
+                update_state_message(None, Height::new(0, 1), dummy_emitted_states()),
+            );
+            if let ClientMessage::UpdateClient(ref mut msg) = msg {
+                msg.signatures = vec![vec![]];
+            }
+            msg
+        });
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::InsufficientOperatorSignatures(_))
+        });
+
+        let update_operators = |nonce: u64, signer: Option<&EnclaveKey>| {
+            let new_operators = vec![other_op_key.get_pubkey().as_address()];
+            let signature = signer
+                .map(|signer| {
+                    signer
+                        .sign(&compute_eip712_update_operators(
+                            LCP_CLIENT_DOMAIN_SEPARATOR,
+                            client_id.clone(),
+                            nonce,
+                            new_operators.clone(),
+                            1,
+                            1,
+                        ))
+                        .unwrap()
+                })
+                .unwrap_or_default();
+            ClientMessage::UpdateOperators(UpdateOperatorsMessage {
+                nonce,
+                new_operators,
+                new_operators_threshold_numerator: 1,
+                new_operators_threshold_denominator: 1,
+                signatures: vec![signature],
+            })
+        };
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_operators(2, Some(&op_key)),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::UnexpectedOperatorsNonce(_))
+        });
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_operators(1, Some(&other_op_key)),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::OperatorMismatch(_)));
+
+        let res = LCPClient.update_client(&mut ctx, client_id.clone(), update_operators(1, None));
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::InsufficientOperatorSignatures(_))
+        });
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_operators(1, Some(&op_key)),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // the enclave key's operator is now the expected one
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id,
+            update_client_message(
+                &ek,
+                update_state_message(None, Height::new(0, 1), dummy_emitted_states()),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+    }
+
+    #[test]
+    fn test_update_operators_threshold() {
+        let ek = EnclaveKey::new().unwrap();
+        let op_keys = [EnclaveKey::new().unwrap(), EnclaveKey::new().unwrap()];
+        let operators: Vec<Address> = op_keys
+            .iter()
+            .map(|k| k.get_pubkey().as_address())
+            .collect();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                operators: operators.clone(),
+                operators_threshold_numerator: 1,
+                operators_threshold_denominator: 2,
+                ..default_client_state()
+            },
+            &ek,
+            Some(&op_keys[0]),
+        );
+
+        let update_operators = |numerator: u64, denominator: u64, signed: bool| {
+            let sign_bytes = compute_eip712_update_operators(
+                LCP_CLIENT_DOMAIN_SEPARATOR,
+                client_id.clone(),
+                1,
+                operators.clone(),
+                numerator,
+                denominator,
+            );
+            let signature = if signed {
+                op_keys[0].sign(&sign_bytes).unwrap()
+            } else {
+                vec![]
+            };
+            ClientMessage::UpdateOperators(UpdateOperatorsMessage {
+                nonce: 1,
+                new_operators: operators.clone(),
+                new_operators_threshold_numerator: numerator,
+                new_operators_threshold_denominator: denominator,
+                signatures: vec![signature, vec![]],
+            })
+        };
+
+        // a single signature satisfies the current threshold 1/2, but not the numerator of the new threshold 2/2
+        let res =
+            LCPClient.update_client(&mut ctx, client_id.clone(), update_operators(2, 2, true));
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::InsufficientOperatorSignatures(_))
+        });
+
+        // the required signatures must not overflow: 2^63 * 2 operators wraps to 0 in u64
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_operators(1 << 63, 1 << 63, false),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::InsufficientOperatorSignatures(_))
+        });
+
+        // the new threshold must be valid
+        for (numerator, denominator) in [(0, 2), (1, 0), (0, 0), (3, 2), (u64::MAX, 2)] {
+            let res = LCPClient.update_client(
+                &mut ctx,
+                client_id.clone(),
+                update_operators(numerator, denominator, true),
+            );
+            assert_err(res, |e| {
+                matches!(e, ErrorDetail::InvalidOperatorsThreshold(_))
+            });
+        }
+
+        let res = LCPClient.update_client(&mut ctx, client_id, update_operators(1, 2, true));
+        assert!(res.is_ok(), "res={:?}", res);
+    }
+
+    #[test]
+    fn test_verify_membership_errors() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(&mut ctx, default_client_state(), &ek, None);
+        let height = Height::new(0, 1);
+        let state_id = update_state_message(None, height, vec![]).post_state_id;
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(None, height, dummy_emitted_states()),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let prefix = b"ibc".to_vec();
+        let path = "path".to_string();
+        let value = b"value".to_vec();
+        let proof = |msg: VerifyMembershipProxyMessage| {
+            let proof = prove_commitment(&ek, msg.into()).unwrap();
+            CommitmentProofs {
+                message: proof.message,
+                signatures: vec![proof.signature],
+            }
+            .ethabi_encode()
+        };
+        let valid_message = VerifyMembershipProxyMessage::new(
+            prefix.clone(),
+            path.clone(),
+            Some(value.keccak256()),
+            height,
+            state_id,
+        );
+        let verify = |prefix: &[u8], path: &str, value: &[u8], proof_height, proof| {
+            LCPClient.verify_membership(
+                &ctx,
+                client_id.clone(),
+                prefix.to_vec(),
+                path.to_string(),
+                value.to_vec(),
+                proof_height,
+                proof,
+            )
+        };
+
+        let res = verify(&prefix, &path, &value, height, proof(valid_message.clone()));
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let res = verify(
+            b"other",
+            &path,
+            &value,
+            height,
+            proof(valid_message.clone()),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::CommitmentPrefixMismatch(_))
+        });
+
+        let res = verify(
+            &prefix,
+            "other",
+            &value,
+            height,
+            proof(valid_message.clone()),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::CommitmentPathMismatch(_)));
+
+        let res = verify(
+            &prefix,
+            &path,
+            &value,
+            Height::new(0, 2),
+            proof(valid_message.clone()),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::ProofHeightMismatch(_)));
+
+        let res = verify(
+            &prefix,
+            &path,
+            b"other",
+            height,
+            proof(valid_message.clone()),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::CommitmentValueMismatch(_))
+        });
+
+        let res = verify(
+            &prefix,
+            &path,
+            &value,
+            height,
+            proof(VerifyMembershipProxyMessage {
+                state_id: [9u8; 32].into(),
+                ..valid_message
+            }),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::StateIdMismatch(_)));
+    }
+
+    #[test]
+    fn test_verify_non_membership() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(&mut ctx, default_client_state(), &ek, None);
+        let height = Height::new(0, 1);
+        let state_id = update_state_message(None, height, vec![]).post_state_id;
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(None, height, dummy_emitted_states()),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let prefix = b"ibc".to_vec();
+        let path = "receipts/ports/transfer/channels/channel-0/sequences/1".to_string();
+        let proof = |msg: VerifyMembershipProxyMessage| {
+            let proof = prove_commitment(&ek, msg.into()).unwrap();
+            CommitmentProofs {
+                message: proof.message,
+                signatures: vec![proof.signature],
+            }
+            .ethabi_encode()
+        };
+        let absence_message =
+            VerifyMembershipProxyMessage::new(prefix.clone(), path.clone(), None, height, state_id);
+
+        let res = LCPClient.verify_non_membership(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            path.clone(),
+            height,
+            proof(absence_message.clone()),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // a proof of non-membership cannot be used as a proof of membership
+        let res = LCPClient.verify_membership(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            path.clone(),
+            vec![],
+            height,
+            proof(absence_message.clone()),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::CommitmentValueMismatch(_))
+        });
+
+        // a proof of membership cannot be used as a proof of non-membership
+        let res = LCPClient.verify_non_membership(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            path.clone(),
+            height,
+            proof(VerifyMembershipProxyMessage {
+                value: Some(b"value".keccak256()),
+                ..absence_message.clone()
+            }),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::CommitmentValueMismatch(_))
+        });
+
+        let res = LCPClient.verify_non_membership(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            "other".to_string(),
+            height,
+            proof(absence_message.clone()),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::CommitmentPathMismatch(_)));
+
+        let res = LCPClient.verify_non_membership(
+            &ctx,
+            client_id.clone(),
+            b"other".to_vec(),
+            path.clone(),
+            height,
+            proof(absence_message.clone()),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::CommitmentPrefixMismatch(_))
+        });
+
+        let res = LCPClient.verify_non_membership(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            path.clone(),
+            height,
+            proof(VerifyMembershipProxyMessage {
+                state_id: [9u8; 32].into(),
+                ..absence_message.clone()
+            }),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::StateIdMismatch(_)));
+
+        // the proof must be signed by a registered enclave key
+        let res = LCPClient.verify_non_membership(&ctx, client_id, prefix, path, height, {
+            let proof =
+                prove_commitment(&EnclaveKey::new().unwrap(), absence_message.into()).unwrap();
+            CommitmentProofs {
+                message: proof.message,
+                signatures: vec![proof.signature],
+            }
+            .ethabi_encode()
+        });
+        assert_err(res, |e| matches!(e, ErrorDetail::EnclaveKeyNotFound(_)));
+    }
+
+    #[test]
+    fn test_verify_membership_batch() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(&mut ctx, default_client_state(), &ek, None);
+        let height = Height::new(0, 1);
+        let state_id = update_state_message(None, height, vec![]).post_state_id;
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(&ek, update_state_message(None, height, vec![])),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let prefix = b"ibc".to_vec();
+        let entries = vec![
+            MembershipEntry::new("path0".to_string(), Some(b"value0".keccak256())),
+            MembershipEntry::new("path1".to_string(), Some(b"value1".keccak256())),
+            MembershipEntry::new("path2".to_string(), None),
+        ];
+        let proof = |msg: VerifyMembershipBatchProxyMessage| {
+            let proof = prove_commitment(&ek, msg.into()).unwrap();
+            CommitmentProofs {
+                message: proof.message,
+                signatures: vec![proof.signature],
+            }
+            .ethabi_encode()
+        };
+        let valid_proof = proof(VerifyMembershipBatchProxyMessage::new(
+            prefix.clone(),
+            entries.clone(),
+            height,
+            state_id,
+        ));
+
+        let res = LCPClient.verify_membership_batch(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            entries.clone(),
+            height,
+            valid_proof.clone(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // each commitment in the batch can be verified individually
+        let res = LCPClient.verify_membership(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            "path1".to_string(),
+            b"value1".to_vec(),
+            height,
+            valid_proof.clone(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let res = LCPClient.verify_non_membership(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            "path2".to_string(),
+            height,
+            valid_proof.clone(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let res = LCPClient.verify_membership(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            "path1".to_string(),
+            b"other".to_vec(),
+            height,
+            valid_proof.clone(),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::CommitmentNotFoundInBatch(_))
+        });
+
+        let res = LCPClient.verify_membership_batch(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            vec![MembershipEntry::new("path3".to_string(), None)],
+            height,
+            valid_proof.clone(),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::CommitmentNotFoundInBatch(_))
+        });
+
+        let res = LCPClient.verify_membership_batch(
+            &ctx,
+            client_id.clone(),
+            b"other".to_vec(),
+            entries.clone(),
+            height,
+            valid_proof.clone(),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::CommitmentPrefixMismatch(_))
+        });
+
+        let res = LCPClient.verify_membership_batch(
+            &ctx,
+            client_id.clone(),
+            prefix.clone(),
+            entries.clone(),
+            height,
+            proof(VerifyMembershipBatchProxyMessage::new(
+                prefix.clone(),
+                entries,
+                height,
+                [9u8; 32].into(),
+            )),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::StateIdMismatch(_)));
+    }
+
+    #[test]
+    fn test_message_domain() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                chain_id: "ibc-0".to_string(),
+                ..default_client_state()
+            },
+            &ek,
+            None,
+        );
+        let height = Height::new(0, 1);
+        let domain = MessageDomain::new("ibc-0".to_string(), client_id.to_string());
+        let update = |domain: Option<MessageDomain>| {
+            let proof = prove_commitment_with_domain(
+                &ek,
+                update_state_message(None, height, vec![]).into(),
+                domain,
+            )
+            .unwrap();
+            let (proxy_message, domain) = proof.message_with_domain().unwrap();
+            ClientMessage::UpdateClient(UpdateClientMessage {
+                proxy_message,
+                signatures: vec![proof.signature],
+                domain,
+                encoding: MessageEncoding::EthABI,
+            })
+        };
+
+        // v1 messages are rejected if the client state has a chain id
+        let res = LCPClient.update_client(&mut ctx, client_id.clone(), update(None));
+        assert_err(res, |e| matches!(e, ErrorDetail::MissingMessageDomain(_)));
+
+        // messages bound to another chain or client are rejected
+        for other in [
+            MessageDomain::new("ibc-1".to_string(), client_id.to_string()),
+            MessageDomain::new("ibc-0".to_string(), "lcp-client-1".to_string()),
+        ] {
+            let res = LCPClient.update_client(&mut ctx, client_id.clone(), update(Some(other)));
+            assert_err(res, |e| matches!(e, ErrorDetail::MessageDomainMismatch(_)));
+        }
+
+        // the domain is covered by the signature
+        let tampered = match update(Some(MessageDomain::new(
+            "ibc-1".to_string(),
+            client_id.to_string(),
+        ))) {
+            ClientMessage::UpdateClient(msg) => ClientMessage::UpdateClient(UpdateClientMessage {
+                domain: Some(domain.clone()),
+                ..msg
+            }),
+            _ => unreachable!(),
+        };
+        let res = LCPClient.update_client(&mut ctx, client_id.clone(), tampered);
+        assert!(res.is_err(), "res={:?}", res);
+
+        let res =
+            LCPClient.update_client(&mut ctx, client_id.clone(), update(Some(domain.clone())));
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let value = b"value".to_vec();
+        let proof = |domain: Option<MessageDomain>| {
+            let proof = prove_commitment_with_domain(
+                &ek,
+                VerifyMembershipProxyMessage::new(
+                    b"ibc".to_vec(),
+                    "path".to_string(),
+                    Some(value.keccak256()),
+                    height,
+                    update_state_message(None, height, vec![]).post_state_id,
+                )
+                .into(),
+                domain,
+            )
+            .unwrap();
+            CommitmentProofs {
+                message: proof.message,
+                signatures: vec![proof.signature],
+            }
+            .ethabi_encode()
+        };
+        let res = LCPClient.verify_membership(
+            &ctx,
+            client_id.clone(),
+            b"ibc".to_vec(),
+            "path".to_string(),
+            value.clone(),
+            height,
+            proof(None),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::MissingMessageDomain(_)));
+        let res = LCPClient.verify_membership(
+            &ctx,
+            client_id.clone(),
+            b"ibc".to_vec(),
+            "path".to_string(),
+            value.clone(),
+            height,
+            proof(Some(domain)),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+    }
+
+    #[test]
+    fn test_emitted_states_root() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(build_lc_registry(), ibc_store, &ek, Time::now());
+        let client_id = setup_client(&mut ctx, default_client_state(), &ek, None);
+
+        let mut message = update_state_message(None, Height::new(0, 1), dummy_emitted_states());
+        let proofs = message.commit_emitted_states().unwrap();
+        let root = message.emitted_states_root.unwrap();
+        for (state, proof) in dummy_emitted_states().iter().zip(proofs.iter()) {
+            assert!(verify_emitted_state(root, state, proof).is_ok());
+        }
+
+        // the root counts as emitted states for the first update
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(&ek, message),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // the emitted states are verified against the root stored with the consensus state
+        let height = Height::new(0, 1);
+        let state = dummy_emitted_states()[0].clone();
+        let res = LCPClient.verify_emitted_state(
+            &ctx,
+            client_id.clone(),
+            height,
+            state.clone(),
+            proofs[0].clone(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let wrong_state = EmittedState(state.0, Any::new("/foo".to_string(), vec![2u8; 32]));
+        let res = LCPClient.verify_emitted_state(
+            &ctx,
+            client_id.clone(),
+            height,
+            wrong_state,
+            proofs[0].clone(),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::CommitmentProof(_)));
+
+        // an update that carries the emitted states inline stores no root
+        let next_height = Height::new(0, 2);
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(Some(height), next_height, dummy_emitted_states()),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let res = LCPClient.verify_emitted_state(
+            &ctx,
+            client_id.clone(),
+            next_height,
+            state.clone(),
+            proofs[0].clone(),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::EmittedStatesRootNotFound(_))
+        });
+
+        // the consensus state must exist at the height
+        let res = LCPClient.verify_emitted_state(
+            &ctx,
+            client_id.clone(),
+            Height::new(0, 3),
+            state.clone(),
+            proofs[0].clone(),
+        );
+        assert!(res.is_err());
+
+        // the root is kept when the same state is updated again without a root
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(&ek, update_state_message(Some(height), height, vec![])),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let res = LCPClient.verify_emitted_state(&ctx, client_id, height, state, proofs[0].clone());
+        assert!(res.is_ok(), "res={:?}", res);
+    }
+
+    #[test]
+    fn test_protobuf_encoded_message() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(build_lc_registry(), ibc_store, &ek, Time::now());
+        let client_id = setup_client(&mut ctx, default_client_state(), &ek, None);
+        let height = Height::new(0, 1);
+        let proof = prove_commitment_with_encoding(
+            &ek,
+            update_state_message(None, height, dummy_emitted_states()).into(),
+            None,
+            MessageEncoding::Protobuf,
+        )
+        .unwrap();
+        let raw = RawUpdateClientMessage {
+            proxy_message: proof.message.clone(),
+            signatures: vec![proof.signature.clone()],
+        };
+        let msg = UpdateClientMessage::try_from(raw).unwrap();
+        assert_eq!(msg.encoding, MessageEncoding::Protobuf);
+
+        // the encoding is covered by the signature
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::UpdateClient(UpdateClientMessage {
+                encoding: MessageEncoding::EthABI,
+                ..msg.clone()
+            }),
+        );
+        assert!(res.is_err(), "res={:?}", res);
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::UpdateClient(msg),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let value = b"value".to_vec();
+        let proof = prove_commitment_with_encoding(
+            &ek,
+            VerifyMembershipProxyMessage::new(
+                b"ibc".to_vec(),
+                "path".to_string(),
+                Some(value.keccak256()),
+                height,
+                update_state_message(None, height, vec![]).post_state_id,
+            )
+            .into(),
+            None,
+            MessageEncoding::Protobuf,
+        )
+        .unwrap();
+        let res = LCPClient.verify_membership(
+            &ctx,
+            client_id,
+            b"ibc".to_vec(),
+            "path".to_string(),
+            value,
+            height,
+            CommitmentProofs {
+                message: proof.message,
+                signatures: vec![proof.signature],
+            }
+            .ethabi_encode(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+    }
+
+    #[test]
+    fn test_conflicting_state() {
+        let ek = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(build_lc_registry(), ibc_store, &ek, Time::now());
+        let client_id = setup_client(&mut ctx, default_client_state(), &ek, None);
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(None, Height::new(0, 1), dummy_emitted_states()),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(Some(Height::new(0, 1)), Height::new(0, 2), vec![]),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // an update that commits the same state again is accepted
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(Some(Height::new(0, 1)), Height::new(0, 2), vec![]),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // an existing consensus state that cannot be decoded is an error instead of a new height
+        ctx.store_any_consensus_state(
+            client_id.clone(),
+            Height::new(0, 3),
+            Any::new("/foo".to_string(), vec![]),
+        )
+        .unwrap();
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(Some(Height::new(0, 2)), Height::new(0, 3), vec![]),
+            ),
+        );
+        assert!(res.is_err());
+        let client_state = ClientState::try_from(ctx.client_state(&client_id).unwrap()).unwrap();
+        assert!(!client_state.frozen);
+        assert_eq!(client_state.latest_height, Height::new(0, 2));
+
+        assert!(LCPClient
+            .get_conflicting_state_evidence(&ctx, &client_id, Height::new(0, 2))
+            .unwrap()
+            .is_none());
+
+        // an update that commits a different state at the same height freezes the client
+        let conflicting_message = UpdateStateProxyMessage {
+            post_state_id: [9u8; 32].into(),
+            ..update_state_message(Some(Height::new(0, 1)), Height::new(0, 2), vec![])
+        };
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(&ek, conflicting_message),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let client_state = ClientState::try_from(ctx.client_state(&client_id).unwrap()).unwrap();
+        assert!(client_state.frozen);
+        assert_eq!(client_state.latest_height, Height::new(0, 2));
+
+        let evidence = LCPClient
+            .get_conflicting_state_evidence(&ctx, &client_id, Height::new(0, 2))
+            .unwrap()
+            .unwrap();
+        assert_eq!(evidence.stored_state_id, [2u8; 32].into());
+        assert_eq!(evidence.conflicting_state_id, [9u8; 32].into());
+        let consensus_state =
+            ConsensusState::try_from(ctx.consensus_state(&client_id, &Height::new(0, 2)).unwrap())
+                .unwrap();
+        assert_eq!(consensus_state.state_id, evidence.stored_state_id);
+
+        // a corrupted evidence is reported as an error
+        ctx.set(
+            conflicting_state_evidence_path(&client_id, Height::new(0, 3)),
+            b"corrupted".to_vec(),
+        );
+        assert_err(
+            LCPClient.get_conflicting_state_evidence(&ctx, &client_id, Height::new(0, 3)),
+            |e| matches!(e, ErrorDetail::SerdeJson(_)),
+        );
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id,
+            update_client_message(
+                &ek,
+                update_state_message(Some(Height::new(0, 2)), Height::new(0, 3), vec![]),
+            ),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::ClientFrozen(_)));
+    }
+
+    #[test]
+    fn test_prune_consensus_states() {
+        let ek = EnclaveKey::new().unwrap();
+        let registry = build_lc_registry();
+        let consensus_state_exists = |ctx: &dyn HostClientKeeper, client_id: &ClientId, h: u64| {
+            ctx.consensus_state(client_id, &Height::new(0, h)).is_ok()
+        };
+
+        // prune by count
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(registry.clone(), ibc_store, &ek, Time::now());
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                max_consensus_states: 2,
+                ..default_client_state()
+            },
+            &ek,
+            None,
+        );
+        for h in 1..=4 {
+            let res = LCPClient.update_client(
+                &mut ctx,
+                client_id.clone(),
+                update_client_message(
+                    &ek,
+                    update_state_message(
+                        (h > 1).then_some(Height::new(0, h - 1)),
+                        Height::new(0, h),
+                        dummy_emitted_states(),
+                    ),
+                ),
+            );
+            assert!(res.is_ok(), "res={:?}", res);
+        }
+        for h in 0..=2 {
+            assert!(!consensus_state_exists(&ctx, &client_id, h), "h={}", h);
+        }
+        for h in 3..=4 {
+            assert!(consensus_state_exists(&ctx, &client_id, h), "h={}", h);
+        }
+
+        // prune by age: the consensus states have the unix epoch as their timestamp
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                consensus_state_retention_period: Duration::from_secs(60),
+                ..default_client_state()
+            },
+            &ek,
+            None,
+        );
+        for h in 1..=2 {
+            let res = LCPClient.update_client(
+                &mut ctx,
+                client_id.clone(),
+                update_client_message(
+                    &ek,
+                    update_state_message(
+                        (h > 1).then_some(Height::new(0, h - 1)),
+                        Height::new(0, h),
+                        dummy_emitted_states(),
+                    ),
+                ),
+            );
+            assert!(res.is_ok(), "res={:?}", res);
+        }
+        // the consensus state at the latest height is retained
+        for h in 0..=1 {
+            assert!(!consensus_state_exists(&ctx, &client_id, h), "h={}", h);
+        }
+        assert!(consensus_state_exists(&ctx, &client_id, 2));
+    }
+
+    #[test]
+    fn test_prune_consensus_states_by_height() {
+        let ek = EnclaveKey::new().unwrap();
+        let registry = build_lc_registry();
+        let consensus_state_exists = |ctx: &dyn HostClientKeeper, client_id: &ClientId, h: u64| {
+            ctx.consensus_state(client_id, &Height::new(0, h)).is_ok()
+        };
+        let update =
+            |ctx: &mut dyn HostClientKeeper, client_id: &ClientId, prev: Option<u64>, h: u64| {
+                let res = LCPClient.update_client(
+                    ctx,
+                    client_id.clone(),
+                    update_client_message(
+                        &ek,
+                        update_state_message(
+                            prev.map(|prev| Height::new(0, prev)),
+                            Height::new(0, h),
+                            dummy_emitted_states(),
+                        ),
+                    ),
+                );
+                assert!(res.is_ok(), "res={:?}", res);
+            };
+
+        // the consensus states stored by out-of-order updates are pruned in ascending order of height
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(registry.clone(), ibc_store, &ek, Time::now());
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                max_consensus_states: 2,
+                ..default_client_state()
+            },
+            &ek,
+            None,
+        );
+        update(&mut ctx, &client_id, None, 10);
+        update(&mut ctx, &client_id, Some(10), 5);
+        assert!(!consensus_state_exists(&ctx, &client_id, 0));
+        update(&mut ctx, &client_id, Some(10), 7);
+        assert!(!consensus_state_exists(&ctx, &client_id, 5));
+        for h in [7, 10] {
+            assert!(consensus_state_exists(&ctx, &client_id, h), "h={}", h);
+        }
+
+        // the consensus states stored before the pruning index was introduced are also pruned
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                max_consensus_states: 2,
+                ..default_client_state()
+            },
+            &ek,
+            None,
+        );
+        for h in 1..=3 {
+            ctx.store_any_consensus_state(
+                client_id.clone(),
+                Height::new(0, h),
+                ConsensusState {
+                    state_id: [h as u8; 32].into(),
+                    timestamp: Time::unix_epoch(),
+                    emitted_states_root: None,
+                }
+                .into(),
+            )
+            .unwrap();
+        }
+        for h in 0..=3u64 {
+            let mut key = format!("clients/{}/consensusHeights/", client_id).into_bytes();
+            key.extend(0u64.to_be_bytes());
+            key.extend(h.to_be_bytes());
+            ctx.remove(&key);
+        }
+        ctx.remove(format!("clients/{}/aux/consensus_states_count", client_id).as_bytes());
+        update(&mut ctx, &client_id, None, 4);
+        for h in 0..=2 {
+            assert!(!consensus_state_exists(&ctx, &client_id, h), "h={}", h);
+        }
+        for h in 3..=4 {
+            assert!(consensus_state_exists(&ctx, &client_id, h), "h={}", h);
+        }
+    }
+
+    #[test]
+    fn test_prune_enclave_keys() {
+        let ek = EnclaveKey::new().unwrap();
+        let op_key = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let now = Time::now();
+        let mut ctx = Context::new(registry.clone(), ibc_store.clone(), &ek, now);
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                key_expiration: Duration::from_secs(60),
+                operators: vec![op_key.get_pubkey().as_address()],
+                operators_threshold_numerator: 1,
+                operators_threshold_denominator: 1,
+                ..default_client_state()
+            },
+            &ek,
+            Some(&op_key),
+        );
+        let revoked_ek = EnclaveKey::new().unwrap();
+        let report = generate_dummy_signed_avr(&revoked_ek.get_pubkey());
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                operator_signature: Some(
+                    op_key
+                        .sign(
+                            compute_eip712_register_enclave_key(
+                                LCP_CLIENT_DOMAIN_SEPARATOR,
+                                report.avr.as_str(),
+                            )
+                            .as_slice(),
+                        )
+                        .unwrap(),
+                ),
+                report,
+            }),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let revoked_ek = revoked_ek.get_pubkey().as_address();
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::RevokeEnclaveKey(RevokeEnclaveKeyMessage {
+                enclave_key: revoked_ek,
+                signatures: vec![op_key
+                    .sign(&compute_eip712_revoke_enclave_key(
+                        LCP_CLIENT_DOMAIN_SEPARATOR,
+                        client_id.clone(),
+                        revoked_ek,
+                    ))
+                    .unwrap()],
+            }),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // the keys are still active
+        let res = LCPClient.prune_enclave_keys(&mut ctx, &client_id);
+        assert!(res.is_ok(), "res={:?}", res);
+        let ek = ek.get_pubkey().as_address();
+        assert!(LCPClient
+            .get_enclave_operator_info(&ctx, &client_id, ek)
+            .is_some());
+
+        // the expired key is removed, but the revoked key is retained
+        let mut ctx = Context::new(
+            registry,
+            ibc_store,
+            &op_key,
+            (now + Duration::from_secs(120)).unwrap(),
+        );
+        let res = LCPClient.prune_enclave_keys(&mut ctx, &client_id);
+        assert!(res.is_ok(), "res={:?}", res);
+        assert!(LCPClient
+            .get_enclave_operator_info(&ctx, &client_id, ek)
+            .is_none());
+        assert!(
+            LCPClient
+                .get_enclave_operator_info(&ctx, &client_id, revoked_ek)
+                .unwrap()
+                .revoked
+        );
+    }
+
+    #[test]
+    fn test_additional_mrenclaves() {
+        let ek = EnclaveKey::new().unwrap();
+        let registry = build_lc_registry();
+        let additional_mr_enclave = [2u8; 32];
+        let client_state = ClientState {
+            additional_mr_enclaves: vec![AllowedMrEnclave {
+                mr_enclave: additional_mr_enclave.to_vec(),
+                key_expiration: Duration::from_secs(30),
+            }],
+            ..default_client_state()
+        };
+
+        let initialise = |client_state: ClientState| {
+            let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+            let mut ctx = Context::new(registry.clone(), ibc_store, &ek, Time::now());
+            LCPClient.initialise(
+                &mut ctx,
+                lcp_client_id(),
+                client_state,
+                empty_consensus_state(),
+            )
+        };
+        assert_err(
+            initialise(ClientState {
+                additional_mr_enclaves: vec![AllowedMrEnclave {
+                    mr_enclave: [2u8; 31].to_vec(),
+                    key_expiration: Duration::ZERO,
+                }],
+                ..default_client_state()
+            }),
+            |e| matches!(e, ErrorDetail::InvalidMrenclaveLength(_)),
+        );
+        assert_err(
+            initialise(ClientState {
+                additional_mr_enclaves: vec![AllowedMrEnclave {
+                    mr_enclave: default_client_state().mr_enclave,
+                    key_expiration: Duration::ZERO,
+                }],
+                ..default_client_state()
+            }),
+            |e| matches!(e, ErrorDetail::DuplicateMrenclave(_)),
+        );
+
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(&mut ctx, client_state, &ek, None);
+        let register_enclave_key = |ctx: &mut dyn HostClientKeeper, ek: &EnclaveKey, mr_enclave| {
+            LCPClient.update_client(
+                ctx,
+                client_id.clone(),
+                ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                    report: generate_dummy_signed_avr_with_mr_enclave(&ek.get_pubkey(), mr_enclave),
+                    operator_signature: None,
+                }),
+            )
+        };
+
+        // the key registered with the additional MRENCLAVE has its own expiration
+        let ek2 = EnclaveKey::new().unwrap();
+        let res = register_enclave_key(&mut ctx, &ek2, additional_mr_enclave);
+        assert!(res.is_ok(), "res={:?}", res);
+        let ek_info = LCPClient
+            .get_enclave_operator_info(&ctx, &client_id, ek.get_pubkey().as_address())
+            .unwrap();
+        let ek2_info = LCPClient
+            .get_enclave_operator_info(&ctx, &client_id, ek2.get_pubkey().as_address())
+            .unwrap();
+        assert!(ek2_info.expired_at < ek_info.expired_at);
+
+        let res = register_enclave_key(&mut ctx, &EnclaveKey::new().unwrap(), [3u8; 32]);
+        assert_err(res, |e| matches!(e, ErrorDetail::MrenclaveMismatch(_)));
+    }
+
+    #[test]
+    fn test_update_mrenclave() {
+        let ek = EnclaveKey::new().unwrap();
+        let op_key = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let now = Time::now();
+        let mut ctx = Context::new(registry.clone(), ibc_store.clone(), &ek, now);
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                operators: vec![op_key.get_pubkey().as_address()],
+                operators_threshold_numerator: 1,
+                operators_threshold_denominator: 1,
+                ..default_client_state()
+            },
+            &ek,
+            Some(&op_key),
+        );
+        let (old_mr_enclave, new_mr_enclave) = ([0u8; 32], [1u8; 32]);
+        let update_mrenclave =
+            |signer: &EnclaveKey, nonce: u64, mr_enclave: Vec<u8>, transition_period: u64| {
+                let signature = signer
+                    .sign(&compute_eip712_update_mrenclave(
+                        LCP_CLIENT_DOMAIN_SEPARATOR,
+                        client_id.clone(),
+                        nonce,
+                        mr_enclave.as_slice().try_into().unwrap_or_default(),
+                        transition_period,
+                    ))
+                    .unwrap();
+                ClientMessage::UpdateMrenclave(UpdateMrenclaveMessage {
+                    nonce,
+                    new_mr_enclave: mr_enclave,
+                    transition_period: Duration::from_secs(transition_period),
+                    signatures: vec![signature],
+                })
+            };
+        let register_enclave_key = |ctx: &mut dyn HostClientKeeper, ek: &EnclaveKey, mr_enclave| {
+            let report = generate_dummy_signed_avr_with_mr_enclave(&ek.get_pubkey(), mr_enclave);
+            let operator_signature = op_key
+                .sign(
+                    compute_eip712_register_enclave_key(
+                        LCP_CLIENT_DOMAIN_SEPARATOR,
+                        report.avr.as_str(),
+                    )
+                    .as_slice(),
+                )
+                .unwrap();
+            LCPClient.update_client(
+                ctx,
+                client_id.clone(),
+                ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                    report,
+                    operator_signature: Some(operator_signature),
+                }),
+            )
+        };
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_mrenclave(&op_key, 2, new_mr_enclave.to_vec(), 60),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::UnexpectedOperatorsNonce(_))
+        });
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_mrenclave(&op_key, 1, [1u8; 31].to_vec(), 60),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::InvalidMrenclaveLength(_)));
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_mrenclave(&EnclaveKey::new().unwrap(), 1, new_mr_enclave.to_vec(), 60),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::OperatorMismatch(_)));
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_mrenclave(&op_key, 1, new_mr_enclave.to_vec(), 60),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let client_state = ClientState::try_from(ctx.client_state(&client_id).unwrap()).unwrap();
+        assert_eq!(client_state.mr_enclave, new_mr_enclave.to_vec());
+        assert_eq!(client_state.previous_mr_enclave, old_mr_enclave.to_vec());
+        assert_eq!(client_state.operators_nonce, 1);
+
+        // both MRENCLAVEs are accepted during the transition period
+        let old_ek = EnclaveKey::new().unwrap();
+        let res = register_enclave_key(&mut ctx, &old_ek, old_mr_enclave);
+        assert!(res.is_ok(), "res={:?}", res);
+        let new_ek = EnclaveKey::new().unwrap();
+        let res = register_enclave_key(&mut ctx, &new_ek, new_mr_enclave);
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // the previous MRENCLAVE and its keys are no longer accepted after the transition period
+        let mut ctx = Context::new(
+            registry.clone(),
+            ibc_store.clone(),
+            &ek,
+            (now + Duration::from_secs(120)).unwrap(),
+        );
+        let res = register_enclave_key(&mut ctx, &EnclaveKey::new().unwrap(), old_mr_enclave);
+        assert_err(res, |e| matches!(e, ErrorDetail::MrenclaveMismatch(_)));
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &old_ek,
+                update_state_message(None, Height::new(0, 1), dummy_emitted_states()),
+            ),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::EnclaveKeyExpired(_)));
+        // the key registered by `setup_client` has not expired yet, but its MRENCLAVE is no longer allowed
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(None, Height::new(0, 1), dummy_emitted_states()),
+            ),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::EnclaveKeyMrenclaveNotAllowed(_))
+        });
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &new_ek,
+                update_state_message(None, Height::new(0, 1), dummy_emitted_states()),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // without a transition period, the previous MRENCLAVE is rejected immediately
+        let mut ctx = Context::new(registry, ibc_store, &ek, now);
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_mrenclave(&op_key, 2, old_mr_enclave.to_vec(), 0),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let res = register_enclave_key(&mut ctx, &EnclaveKey::new().unwrap(), new_mr_enclave);
+        assert_err(res, |e| matches!(e, ErrorDetail::MrenclaveMismatch(_)));
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &new_ek,
+                update_state_message(
+                    Some(Height::new(0, 1)),
+                    Height::new(0, 2),
+                    dummy_emitted_states(),
+                ),
+            ),
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::EnclaveKeyMrenclaveNotAllowed(_))
+        });
+        // the keys registered with the restored MRENCLAVE are accepted again
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(
+                    Some(Height::new(0, 1)),
+                    Height::new(0, 2),
+                    dummy_emitted_states(),
+                ),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+    }
+
+    #[test]
+    fn test_revoke_enclave_key() {
+        let ek = EnclaveKey::new().unwrap();
+        let op_key = EnclaveKey::new().unwrap();
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let registry = build_lc_registry();
+        let mut ctx = Context::new(registry.clone(), ibc_store, &ek, Time::now());
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                operators: vec![op_key.get_pubkey().as_address()],
+                operators_threshold_numerator: 1,
+                operators_threshold_denominator: 1,
+                ..default_client_state()
+            },
+            &ek,
+            Some(&op_key),
+        );
+        let ek_addr = ek.get_pubkey().as_address();
+        let revoke_enclave_key = |signer: &EnclaveKey, enclave_key: Address| {
+            ClientMessage::RevokeEnclaveKey(RevokeEnclaveKeyMessage {
+                enclave_key,
+                signatures: vec![signer
+                    .sign(&compute_eip712_revoke_enclave_key(
+                        LCP_CLIENT_DOMAIN_SEPARATOR,
+                        client_id.clone(),
+                        enclave_key,
+                    ))
+                    .unwrap()],
+            })
+        };
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(None, Height::new(0, 1), dummy_emitted_states()),
+            ),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // only the operators can revoke the enclave key
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            revoke_enclave_key(&EnclaveKey::new().unwrap(), ek_addr),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::OperatorMismatch(_)));
+
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            revoke_enclave_key(&op_key, ek_addr),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // the revoked key is no longer accepted
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            update_client_message(
+                &ek,
+                update_state_message(Some(Height::new(0, 1)), Height::new(0, 2), vec![]),
+            ),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::EnclaveKeyRevoked(_)));
+
+        // the revoked key cannot be registered again
+        let report = generate_dummy_signed_avr(&ek.get_pubkey());
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                operator_signature: Some(
+                    op_key
+                        .sign(
+                            compute_eip712_register_enclave_key(
+                                LCP_CLIENT_DOMAIN_SEPARATOR,
+                                report.avr.as_str(),
+                            )
+                            .as_slice(),
+                        )
+                        .unwrap(),
+                ),
+                report,
+            }),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::EnclaveKeyRevoked(_)));
+
+        // a key that has not been registered yet can also be revoked
+        let ek2 = EnclaveKey::new().unwrap();
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            revoke_enclave_key(&op_key, ek2.get_pubkey().as_address()),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let report = generate_dummy_signed_avr(&ek2.get_pubkey());
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id,
+            ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                operator_signature: Some(
+                    op_key
+                        .sign(
+                            compute_eip712_register_enclave_key(
+                                LCP_CLIENT_DOMAIN_SEPARATOR,
+                                report.avr.as_str(),
+                            )
+                            .as_slice(),
+                        )
+                        .unwrap(),
+                ),
+                report,
+            }),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::EnclaveKeyRevoked(_)));
+
+        // a client without operators cannot revoke keys
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(registry, ibc_store, &ek, Time::now());
+        let client_id = setup_client(&mut ctx, default_client_state(), &ek, None);
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::RevokeEnclaveKey(RevokeEnclaveKeyMessage {
+                enclave_key: ek_addr,
+                signatures: vec![],
+            }),
+        );
+        assert_err(res, |e| matches!(e, ErrorDetail::EmptyOperators(_)));
+    }
+
+    #[test]
+    fn test_cosigned_update_client_message() {
+        let ek0 = EnclaveKey::new().unwrap();
+        let ek1 = EnclaveKey::new().unwrap();
+        let mut op_keys = vec![EnclaveKey::new().unwrap(), EnclaveKey::new().unwrap()];
+        op_keys.sort_by_key(|k| k.get_pubkey().as_address().0);
+        let ibc_store = Rc::new(RefCell::new(MemStore::default()));
+        let mut ctx = Context::new(build_lc_registry(), ibc_store, &ek0, Time::now());
+        let client_id = setup_client(
+            &mut ctx,
+            ClientState {
+                operators: op_keys
+                    .iter()
+                    .map(|k| k.get_pubkey().as_address())
+                    .collect(),
+                operators_threshold_numerator: 2,
+                operators_threshold_denominator: 2,
+                ..default_client_state()
+            },
+            &ek0,
+            Some(&op_keys[0]),
+        );
+        // the key of the second node is registered by the second operator
+        let report = generate_dummy_signed_avr(&ek1.get_pubkey());
+        let res = LCPClient.update_client(
+            &mut ctx,
+            client_id.clone(),
+            ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                operator_signature: Some(
+                    op_keys[1]
+                        .sign(
+                            compute_eip712_register_enclave_key(
+                                LCP_CLIENT_DOMAIN_SEPARATOR,
+                                report.avr.as_str(),
+                            )
+                            .as_slice(),
+                        )
+                        .unwrap(),
+                ),
+                report,
+            }),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let message: ProxyMessage =
+            update_state_message(None, Height::new(0, 1), dummy_emitted_states()).into();
+        let proof0 = prove_commitment(&ek0, message.clone()).unwrap();
+        let proof1 = prove_commitment(&ek1, message).unwrap();
+
+        let res = LCPClient.build_cosigned_update_client_message(&ctx, &client_id, vec![]);
+        assert_err(res, |e| matches!(e, ErrorDetail::EmptyCommitmentProofs(_)));
+
+        // a single node doesn't meet the threshold
+        let res =
+            LCPClient.build_cosigned_update_client_message(&ctx, &client_id, vec![proof0.clone()]);
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::InsufficientOperatorSignatures(_))
+        });
+
+        let res = LCPClient.build_cosigned_update_client_message(
+            &ctx,
+            &client_id,
+            vec![proof0.clone(), proof0.clone()],
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::DuplicateOperatorSignature(_))
+        });
+
+        let other = prove_commitment(
+            &ek1,
+            update_state_message(None, Height::new(0, 2), dummy_emitted_states()).into(),
+        )
+        .unwrap();
+        let res = LCPClient.build_cosigned_update_client_message(
+            &ctx,
+            &client_id,
+            vec![proof0.clone(), other],
+        );
+        assert_err(res, |e| {
+            matches!(e, ErrorDetail::CommitmentProofMessageMismatch(_))
+        });
+
+        // the signatures are ordered by the operators regardless of the order of the proofs
+        let message = LCPClient
+            .build_cosigned_update_client_message(
+                &ctx,
+                &client_id,
+                vec![proof1.clone(), proof0.clone()],
+            )
+            .unwrap();
+        assert_eq!(message.signatures, vec![proof0.signature, proof1.signature]);
+        let res =
+            LCPClient.update_client(&mut ctx, client_id, ClientMessage::UpdateClient(message));
+        assert!(res.is_ok(), "res={:?}", res);
+    }
+
+    fn assert_err<T: core::fmt::Debug>(
+        res: Result<T, Error>,
+        f: impl FnOnce(&ErrorDetail) -> bool,
+    ) {
+        match res {
+            Ok(v) => panic!("unexpected success: {:?}", v),
+            Err(e) => assert!(f(e.detail()), "unexpected error: {:?}", e),
+        }
+    }
+
+    fn lcp_client_id() -> ClientId {
+        ClientId::from_str(&format!("{}-0", LCPClient.client_type())).unwrap()
+    }
+
+    fn default_client_state() -> ClientState {
+        ClientState {
+            mr_enclave: [0u8; 32].to_vec(),
+            key_expiration: Duration::from_secs(60 * 60 * 24 * 7),
+            frozen: false,
+            latest_height: Height::zero(),
+            ..Default::default()
+        }
+    }
+
+    fn empty_consensus_state() -> ConsensusState {
+        ConsensusState {
+            state_id: Default::default(),
+            timestamp: Time::unix_epoch(),
+            emitted_states_root: None,
+        }
+    }
+
+    /// setup_client initialises the LCP client and registers `ek` with an optional operator
+    fn setup_client(
+        ctx: &mut dyn HostClientKeeper,
+        client_state: ClientState,
+        ek: &EnclaveKey,
+        op_key: Option<&EnclaveKey>,
+    ) -> ClientId {
+        let client_id = lcp_client_id();
+        let res = LCPClient.initialise(
+            ctx,
+            client_id.clone(),
+            client_state,
+            empty_consensus_state(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let report = generate_dummy_signed_avr(&ek.get_pubkey());
+        let operator_signature = op_key.map(|op_key| {
+            op_key
+                .sign(
+                    compute_eip712_register_enclave_key(
+                        LCP_CLIENT_DOMAIN_SEPARATOR,
+                        report.avr.as_str(),
+                    )
+                    .as_slice(),
+                )
+                .unwrap()
+        });
+        let res = LCPClient.update_client(
+            ctx,
+            client_id.clone(),
+            ClientMessage::RegisterEnclaveKey(RegisterEnclaveKeyMessage {
+                report,
+                operator_signature,
+            }),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        client_id
+    }
+
+    fn freeze_client(ctx: &mut dyn HostClientKeeper, client_id: &ClientId) {
+        let client_state = ClientState::try_from(ctx.client_state(client_id).unwrap()).unwrap();
+        ctx.store_any_client_state(client_id.clone(), client_state.with_frozen().into())
+            .unwrap();
+    }
+
+    fn update_state_message(
+        prev_height: Option<Height>,
+        post_height: Height,
+        emitted_states: Vec<EmittedState>,
+    ) -> UpdateStateProxyMessage {
+        UpdateStateProxyMessage {
+            prev_height,
+            prev_state_id: prev_height.map(|h| [h.revision_height() as u8; 32].into()),
+            post_height,
+            post_state_id: [post_height.revision_height() as u8; 32].into(),
+            timestamp: Time::unix_epoch(),
+            context: ValidationContext::Empty,
+            emitted_states,
+            emitted_states_root: None,
+        }
+    }
+
+    fn dummy_emitted_states() -> Vec<EmittedState> {
+        vec![EmittedState(
+            Height::new(0, 1),
+            Any::new("/foo".to_string(), vec![1u8; 32]),
+        )]
+    }
+
+    fn update_client_message(
+        signer: &dyn Signer,
+        message: UpdateStateProxyMessage,
+    ) -> ClientMessage {
+        let proof = prove_commitment(signer, message.into()).unwrap();
+        ClientMessage::UpdateClient(UpdateClientMessage {
+            proxy_message: proof.message().unwrap(),
+            signatures: vec![proof.signature],
+            domain: None,
+            encoding: MessageEncoding::EthABI,
+        })
+    }
+
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn build_lc_registry() -> Arc<dyn LightClientResolver> {
+        let registry = MapLightClientRegistry::new();
+        Arc::new(registry)
+    }
+
+    fn generate_dummy_signed_avr(key: &EnclavePublicKey) -> IASSignedReport {
+        generate_dummy_signed_avr_with_operator(key, None)
+    }
+
+    fn generate_dummy_signed_avr_with_operator(
+        key: &EnclavePublicKey,
+        operator: Option<Address>,
+    ) -> IASSignedReport {
+        build_dummy_signed_avr(key, operator, [0u8; 32])
+    }
+
+    fn generate_dummy_signed_avr_with_mr_enclave(
+        key: &EnclavePublicKey,
+        mr_enclave: [u8; 32],
+    ) -> IASSignedReport {
+        build_dummy_signed_avr(key, None, mr_enclave)
+    }
+
+    fn build_dummy_signed_avr(
+        key: &EnclavePublicKey,
+        operator: Option<Address>,
+        mr_enclave: [u8; 32],
+    ) -> IASSignedReport {
         let quote = sgx_quote_t {
             version: 4,
             report_body: sgx_report_body_t {
-                report_data: ReportData::new(key.as_address(), None).into(),
+                mr_enclave: sgx_measurement_t { m: mr_enclave },
+                report_data: ReportData::new(key.as_address(), operator).into(),
                 ..Default::default()
             },
             ..Default::default()
@@ -1,3 +1,4 @@
+use crate::client_def::{compute_eip712_domain_separator, LCP_CLIENT_DOMAIN_SEPARATOR};
 use crate::errors::Error;
 use crate::prelude::*;
 use core::time::Duration;
@@ -6,7 +7,9 @@ use light_client::commitments::UpdateStateProxyMessage;
 use light_client::types::proto::{
     ibc::{
         core::client::v1::Height as ProtoHeight,
-        lightclients::lcp::v1::ClientState as RawClientState,
+        lightclients::lcp::v1::{
+            AllowedMrenclave as RawAllowedMrenclave, ClientState as RawClientState,
+        },
     },
     protobuf::Protobuf,
 };
@@ -34,6 +37,49 @@ pub struct ClientState {
     pub next_tcb_evaluation_data_number: u32,
     pub next_tcb_evaluation_data_number_update_time: u64,
     pub zkdcap_verifier_infos: Vec<ZKDCAPVerifierInfo>,
+    pub previous_mr_enclave: Vec<u8>,
+    pub previous_mr_enclave_expiration_time: u64,
+    pub max_consensus_states: u64,
+    pub consensus_state_retention_period: Duration,
+    pub additional_mr_enclaves: Vec<AllowedMrEnclave>,
+    pub chain_id: String,
+    pub eip712_chain_id: u64,
+    pub eip712_verifying_contract: Address,
+}
+
+/// An MRENCLAVE value allowed in addition to `ClientState::mr_enclave`
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct AllowedMrEnclave {
+    pub mr_enclave: Vec<u8>,
+    /// The key expiration for the EKs registered with this MRENCLAVE. If zero, the client's `key_expiration` is used.
+    pub key_expiration: Duration,
+}
+
+impl From<AllowedMrEnclave> for RawAllowedMrenclave {
+    fn from(value: AllowedMrEnclave) -> Self {
+        RawAllowedMrenclave {
+            mrenclave: value.mr_enclave,
+            key_expiration: value.key_expiration.as_secs(),
+        }
+    }
+}
+
+impl From<RawAllowedMrenclave> for AllowedMrEnclave {
+    fn from(value: RawAllowedMrenclave) -> Self {
+        AllowedMrEnclave {
+            mr_enclave: value.mrenclave,
+            key_expiration: Duration::from_secs(value.key_expiration),
+        }
+    }
+}
+
+/// The registration policy for an MRENCLAVE accepted by the client
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MrEnclavePolicy {
+    /// The key expiration for the EKs registered with the MRENCLAVE
+    pub key_expiration: Duration,
+    /// The time (UNIX time seconds) until which the MRENCLAVE is accepted
+    pub expiration_time: u64,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -41,6 +87,7 @@ pub enum ZKVMType {
     #[default]
     Unspecified,
     Risc0,
+    SP1,
 }
 
 impl ZKVMType {
@@ -48,6 +95,7 @@ impl ZKVMType {
         match value {
             0 => Ok(Self::Unspecified),
             1 => Ok(Self::Risc0),
+            2 => Ok(Self::SP1),
             _ => Err(Error::invalid_zkdcap_verifier_info(vec![value])),
         }
     }
@@ -55,6 +103,7 @@ impl ZKVMType {
         match self {
             Self::Unspecified => 0,
             Self::Risc0 => 1,
+            Self::SP1 => 2,
         }
     }
 }
@@ -63,7 +112,10 @@ impl ZKVMType {
 pub enum ZKDCAPVerifierInfo {
     #[default]
     Unspecified,
+    /// RISC Zero zkVM with the image ID of the zkDCAP program
     Risc0([u8; 32]),
+    /// SP1 zkVM with the verification key hash of the zkDCAP program
+    SP1([u8; 32]),
 }
 
 impl ZKDCAPVerifierInfo {
@@ -71,6 +123,7 @@ impl ZKDCAPVerifierInfo {
         match self {
             Self::Unspecified => ZKVMType::Unspecified,
             Self::Risc0(_) => ZKVMType::Risc0,
+            Self::SP1(_) => ZKVMType::SP1,
         }
     }
 
@@ -79,26 +132,29 @@ impl ZKDCAPVerifierInfo {
             return Ok(Self::Unspecified);
         }
         let zkvm_type = ZKVMType::from_u8(bytes[0])?;
+        if zkvm_type == ZKVMType::Unspecified {
+            return Ok(Self::Unspecified);
+        }
+        // RISC Zero and SP1 share the same layout: | zkvm type (1) | reserved (31) | program identifier (32) |
+        if bytes.len() != 64 {
+            return Err(Error::invalid_zkdcap_verifier_info(bytes.to_vec()));
+        }
+        let mut program_id = [0u8; 32];
+        program_id.copy_from_slice(&bytes[32..]);
         match zkvm_type {
-            ZKVMType::Unspecified => Ok(Self::Unspecified),
-            ZKVMType::Risc0 => {
-                if bytes.len() != 64 {
-                    return Err(Error::invalid_zkdcap_verifier_info(bytes.to_vec()));
-                }
-                let mut image_id = [0u8; 32];
-                image_id.copy_from_slice(&bytes[32..]);
-                Ok(Self::Risc0(image_id))
-            }
+            ZKVMType::Risc0 => Ok(Self::Risc0(program_id)),
+            ZKVMType::SP1 => Ok(Self::SP1(program_id)),
+            ZKVMType::Unspecified => unreachable!(),
         }
     }
 
     pub fn to_bytes(&self) -> Vec<u8> {
         match self {
             Self::Unspecified => vec![0],
-            Self::Risc0(image_id) => {
-                let mut bytes = vec![ZKVMType::Risc0.to_u8()];
+            Self::Risc0(program_id) | Self::SP1(program_id) => {
+                let mut bytes = vec![self.as_type().to_u8()];
                 bytes.extend_from_slice([0u8; 31].as_ref());
-                bytes.extend_from_slice(image_id);
+                bytes.extend_from_slice(program_id);
                 bytes
             }
         }
@@ -131,6 +187,80 @@ impl ClientState {
         self.operators_threshold_denominator = threshold_denominator;
         self
     }
+
+    /// with_mr_enclave replaces the current MRENCLAVE with `mr_enclave`.
+    ///
+    /// If `previous_mr_enclave_expiration_time` is non-zero, the current MRENCLAVE is still accepted until that time.
+    pub fn with_mr_enclave(
+        mut self,
+        mr_enclave: Vec<u8>,
+        nonce: u64,
+        previous_mr_enclave_expiration_time: u64,
+    ) -> Self {
+        let previous_mr_enclave = core::mem::replace(&mut self.mr_enclave, mr_enclave);
+        if previous_mr_enclave_expiration_time == 0 {
+            self.previous_mr_enclave = Default::default();
+            self.previous_mr_enclave_expiration_time = 0;
+        } else {
+            self.previous_mr_enclave = previous_mr_enclave;
+            self.previous_mr_enclave_expiration_time = previous_mr_enclave_expiration_time;
+        }
+        // the new MRENCLAVE no longer needs to be listed as an additional one
+        let mr_enclave = self.mr_enclave.clone();
+        self.additional_mr_enclaves
+            .retain(|allowed| allowed.mr_enclave != mr_enclave);
+        self.operators_nonce = nonce;
+        self
+    }
+
+    /// mr_enclave_policy returns the registration policy for `mr_enclave`.
+    ///
+    /// Returns `None` if `mr_enclave` is not accepted at `current_timestamp`.
+    /// The current and additional MRENCLAVEs are accepted without a time limit, i.e. their `expiration_time` is `u64::MAX`.
+    pub fn mr_enclave_policy(
+        &self,
+        mr_enclave: &[u8],
+        current_timestamp: u64,
+    ) -> Option<MrEnclavePolicy> {
+        if self.mr_enclave.as_slice() == mr_enclave {
+            Some(MrEnclavePolicy {
+                key_expiration: self.key_expiration,
+                expiration_time: u64::MAX,
+            })
+        } else if let Some(allowed) = self
+            .additional_mr_enclaves
+            .iter()
+            .find(|allowed| allowed.mr_enclave.as_slice() == mr_enclave)
+        {
+            Some(MrEnclavePolicy {
+                key_expiration: if allowed.key_expiration.is_zero() {
+                    self.key_expiration
+                } else {
+                    allowed.key_expiration
+                },
+                expiration_time: u64::MAX,
+            })
+        } else if !self.previous_mr_enclave.is_empty()
+            && self.previous_mr_enclave.as_slice() == mr_enclave
+            && current_timestamp < self.previous_mr_enclave_expiration_time
+        {
+            Some(MrEnclavePolicy {
+                key_expiration: self.key_expiration,
+                expiration_time: self.previous_mr_enclave_expiration_time,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// domain_separator returns the EIP-712 domain separator for the operator signatures
+    pub fn domain_separator(&self) -> [u8; 32] {
+        if self.eip712_chain_id == 0 && self.eip712_verifying_contract.is_zero() {
+            LCP_CLIENT_DOMAIN_SEPARATOR
+        } else {
+            compute_eip712_domain_separator(self.eip712_chain_id, self.eip712_verifying_contract)
+        }
+    }
 }
 
 impl From<ClientState> for RawClientState {
@@ -143,12 +273,16 @@ impl From<ClientState> for RawClientState {
                 revision_number: value.latest_height.revision_number(),
                 revision_height: value.latest_height.revision_height(),
             }),
-            allowed_quote_statuses: Default::default(),
-            allowed_advisory_ids: Default::default(),
-            operators: Default::default(),
-            operators_nonce: 0,
-            operators_threshold_numerator: 0,
-            operators_threshold_denominator: 0,
+            allowed_quote_statuses: value.allowed_quote_statuses,
+            allowed_advisory_ids: value.allowed_advisory_ids,
+            operators: value
+                .operators
+                .into_iter()
+                .map(|addr| addr.0.to_vec())
+                .collect(),
+            operators_nonce: value.operators_nonce,
+            operators_threshold_numerator: value.operators_threshold_numerator,
+            operators_threshold_denominator: value.operators_threshold_denominator,
             current_tcb_evaluation_data_number: value.current_tcb_evaluation_data_number,
             tcb_evaluation_data_number_update_grace_period: value
                 .tcb_evaluation_data_number_update_grace_period,
@@ -160,6 +294,22 @@ impl From<ClientState> for RawClientState {
                 .iter()
                 .map(|info| info.to_bytes())
                 .collect(),
+            previous_mrenclave: value.previous_mr_enclave,
+            previous_mrenclave_expiration_time: value.previous_mr_enclave_expiration_time,
+            max_consensus_states: value.max_consensus_states,
+            consensus_state_retention_period: value.consensus_state_retention_period.as_secs(),
+            additional_mrenclaves: value
+                .additional_mr_enclaves
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            chain_id: value.chain_id,
+            eip712_chain_id: value.eip712_chain_id,
+            eip712_verifying_contract: if value.eip712_verifying_contract.is_zero() {
+                vec![]
+            } else {
+                value.eip712_verifying_contract.0.to_vec()
+            },
         }
     }
 }
@@ -168,7 +318,7 @@ impl TryFrom<RawClientState> for ClientState {
     type Error = Error;
 
     fn try_from(raw: RawClientState) -> Result<Self, Self::Error> {
-        let height = raw.latest_height.unwrap();
+        let height = raw.latest_height.ok_or_else(Error::missing_latest_height)?;
         Ok(ClientState {
             mr_enclave: raw.mrenclave,
             key_expiration: Duration::from_secs(raw.key_expiration),
@@ -195,6 +345,24 @@ impl TryFrom<RawClientState> for ClientState {
                 .into_iter()
                 .map(|bytes| ZKDCAPVerifierInfo::from_bytes(&bytes))
                 .collect::<Result<_, _>>()?,
+            previous_mr_enclave: raw.previous_mrenclave,
+            previous_mr_enclave_expiration_time: raw.previous_mrenclave_expiration_time,
+            max_consensus_states: raw.max_consensus_states,
+            consensus_state_retention_period: Duration::from_secs(
+                raw.consensus_state_retention_period,
+            ),
+            additional_mr_enclaves: raw
+                .additional_mrenclaves
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            chain_id: raw.chain_id,
+            eip712_chain_id: raw.eip712_chain_id,
+            eip712_verifying_contract: if raw.eip712_verifying_contract.is_empty() {
+                Address::default()
+            } else {
+                Address::try_from(raw.eip712_verifying_contract.as_slice())?
+            },
         })
     }
 }
@@ -213,9 +381,9 @@ impl TryFrom<Any> for ClientState {
 
     fn try_from(raw: Any) -> Result<Self, Self::Error> {
         match raw.type_url.as_str() {
-            LCP_CLIENT_STATE_TYPE_URL => Ok(ClientState::try_from(
-                RawClientState::decode(&*raw.value).unwrap(),
-            )?),
+            LCP_CLIENT_STATE_TYPE_URL => {
+                ClientState::try_from(RawClientState::decode(&*raw.value)?)
+            }
             type_url => Err(Error::unexpected_client_type(type_url.to_owned())),
         }
     }
@@ -223,3 +391,26 @@ impl TryFrom<Any> for ClientState {
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct UpgradeOptions {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_state_conversion() {
+        let client_state = ClientState {
+            mr_enclave: vec![1; 32],
+            key_expiration: Duration::from_secs(60),
+            latest_height: Height::new(0, 1),
+            allowed_quote_statuses: vec!["SWHardeningNeeded".to_string()],
+            allowed_advisory_ids: vec!["INTEL-SA-00219".to_string()],
+            operators: vec![Address::try_from([2u8; 20].as_slice()).unwrap()],
+            operators_nonce: 1,
+            operators_threshold_numerator: 1,
+            operators_threshold_denominator: 2,
+            ..Default::default()
+        };
+        let any: Any = client_state.clone().into();
+        assert_eq!(ClientState::try_from(any).unwrap(), client_state);
+    }
+}
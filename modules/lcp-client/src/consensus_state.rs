@@ -13,6 +13,8 @@ pub const LCP_CONSENSUS_STATE_TYPE_URL: &str = "/ibc.lightclients.lcp.v1.Consens
 pub struct ConsensusState {
     pub state_id: StateID,
     pub timestamp: Time, // means upstream's timestamp
+    /// The merkle root of the emitted states committed by the update at the height
+    pub emitted_states_root: Option<[u8; 32]>,
 }
 
 impl ConsensusState {
@@ -26,6 +28,10 @@ impl From<ConsensusState> for RawConsensusState {
         RawConsensusState {
             state_id: value.state_id.to_vec(),
             timestamp: value.timestamp.as_unix_timestamp_secs(),
+            emitted_states_root: value
+                .emitted_states_root
+                .map(|root| root.to_vec())
+                .unwrap_or_default(),
         }
     }
 }
@@ -34,11 +40,21 @@ impl TryFrom<RawConsensusState> for ConsensusState {
     type Error = Error;
 
     fn try_from(raw: RawConsensusState) -> Result<Self, Self::Error> {
+        let emitted_states_root = if raw.emitted_states_root.is_empty() {
+            None
+        } else {
+            Some(raw.emitted_states_root.as_slice().try_into().map_err(|_| {
+                Error::invalid_emitted_states_root_length(raw.emitted_states_root.len())
+            })?)
+        };
         Ok(ConsensusState {
-            state_id: raw.state_id.as_slice().try_into().unwrap(),
+            state_id: raw.state_id.as_slice().try_into()?,
             timestamp: Time::from_unix_timestamp_nanos(
-                (raw.timestamp as u128).checked_mul(1_000_000_000).unwrap(),
+                (raw.timestamp as u128)
+                    .checked_mul(1_000_000_000)
+                    .ok_or_else(|| Error::invalid_consensus_state_timestamp(raw.timestamp))?,
             )?,
+            emitted_states_root,
         })
     }
 }
@@ -61,7 +77,7 @@ impl TryFrom<Any> for ConsensusState {
     fn try_from(raw: Any) -> Result<Self, Self::Error> {
         match raw.type_url.as_str() {
             LCP_CONSENSUS_STATE_TYPE_URL => {
-                ConsensusState::try_from(RawConsensusState::decode(&*raw.value).unwrap())
+                ConsensusState::try_from(RawConsensusState::decode(&*raw.value)?)
             }
             type_url => Err(Error::unexpected_client_type(type_url.to_owned())),
         }
@@ -1,6 +1,12 @@
-use crate::{client_state::ZKVMType, prelude::*};
+use crate::{
+    client_state::{ZKDCAPVerifierInfo, ZKVMType},
+    prelude::*,
+};
 use core::time::Duration;
+use crypto::Address;
 use flex_error::*;
+use light_client::commitments::{MessageDomain, StateID};
+use light_client::types::{ClientId, Height};
 
 define_error! {
     #[derive(Debug, PartialEq, Eq)]
@@ -61,6 +67,11 @@ define_error! {
             "Invalid Risc0 proof format"
         },
 
+        MissingDcapCollateral
+        |e| {
+            "DCAP collateral is missing"
+        },
+
         UnexpectedTcbEvaluationDataNumber {
             number: u32
         }
@@ -68,6 +79,393 @@ define_error! {
             format_args!("Unexpected TCB evaluation data number: number={}", e.number)
         },
 
+        ClientFrozen {
+            client_id: ClientId
+        }
+        |e| {
+            format_args!("client is frozen: client_id={}", e.client_id)
+        },
+
+        NonZeroInitialLatestHeight {
+            latest_height: Height
+        }
+        |e| {
+            format_args!("initial client state's latest_height must be zero: latest_height={}", e.latest_height)
+        },
+
+        NonEmptyInitialConsensusState
+        |e| {
+            "initial consensus state must be empty"
+        },
+
+        InvalidMrenclaveLength {
+            length: usize
+        }
+        |e| {
+            format_args!("invalid mrenclave length: expected=32 actual={}", e.length)
+        },
+
+        InvalidOperatorsThreshold {
+            numerator: u64,
+            denominator: u64
+        }
+        |e| {
+            format_args!("invalid operators threshold: numerator={} denominator={}", e.numerator, e.denominator)
+        },
+
+        OperatorsNotSorted {
+            prev: Address,
+            next: Address
+        }
+        |e| {
+            format_args!("operators must be sorted in ascending order: prev={} next={}", e.prev, e.next)
+        },
+
+        UnexpectedOperatorsNonce {
+            expected: u64,
+            actual: u64
+        }
+        |e| {
+            format_args!("unexpected operators nonce: expected={} actual={}", e.expected, e.actual)
+        },
+
+        UnsupportedZkvmType {
+            zkvm_type: ZKVMType
+        }
+        |e| {
+            format_args!("unsupported zkvm type: zkvm_type={:?}", e.zkvm_type)
+        },
+
+        DuplicateZkdcapVerifierInfo {
+            info: ZKDCAPVerifierInfo
+        }
+        |e| {
+            format_args!("duplicate zkdcap_verifier_info: info={:?}", e.info)
+        },
+
+        MissingZkdcapVerifierInfo {
+            zkvm_type: ZKVMType
+        }
+        |e| {
+            format_args!("no zkdcap_verifier_info for the zkvm type: zkvm_type={:?}", e.zkvm_type)
+        },
+
+        ZeroCurrentTcbEvaluationDataNumber
+        |e| {
+            "current_tcb_evaluation_data_number must not be 0"
+        },
+
+        InvalidNextTcbEvaluationDataNumber {
+            current: u32,
+            next: u32,
+            next_update_time: u64
+        }
+        |e| {
+            format_args!("invalid next_tcb_evaluation_data_number: current={} next={} next_update_time={}", e.current, e.next, e.next_update_time)
+        },
+
+        ZeroKeyExpiration
+        |e| {
+            "key_expiration must not be 0 if DCAP/zkDCAP is not supported"
+        },
+
+        EmptyEmittedStates
+        |e| {
+            "emitted_states must not be empty if the client's latest height is zero"
+        },
+
+        EmittedStatesRootNotFound {
+            client_id: ClientId,
+            height: Height
+        }
+        |e| {
+            format_args!("no emitted states root is stored: client_id={} height={}", e.client_id, e.height)
+        },
+
+        InvalidEmittedStatesRootLength {
+            length: usize
+        }
+        |e| {
+            format_args!("invalid emitted states root length: expected=32 actual={}", e.length)
+        },
+
+        MissingPrevState
+        |e| {
+            "prev_height and prev_state_id must be set if the client's latest height is non-zero"
+        },
+
+        PrevStateIdMismatch {
+            height: Height,
+            expected: StateID,
+            actual: StateID
+        }
+        |e| {
+            format_args!("prev_state_id mismatch: height={} expected={} actual={}", e.height, e.expected, e.actual)
+        },
+
+        OperatorMismatch {
+            expected: Address,
+            actual: Address
+        }
+        |e| {
+            format_args!("operator signature mismatch: expected={} actual={}", e.expected, e.actual)
+        },
+
+        EnclaveKeyOperatorMismatch {
+            enclave_key: Address,
+            expected: Address,
+            actual: Address
+        }
+        |e| {
+            format_args!("enclave key operator mismatch: enclave_key={} expected={} actual={}", e.enclave_key, e.expected, e.actual)
+        },
+
+        EnclaveKeyNotFound {
+            enclave_key: Address
+        }
+        |e| {
+            format_args!("enclave key not found: enclave_key={}", e.enclave_key)
+        },
+
+        EnclaveKeyExpired {
+            enclave_key: Address,
+            expired_at: u64,
+            current_timestamp: u64
+        }
+        |e| {
+            format_args!("enclave key expired: enclave_key={} expired_at={} current_timestamp={}", e.enclave_key, e.expired_at, e.current_timestamp)
+        },
+
+        EnclaveKeyRevoked {
+            enclave_key: Address
+        }
+        |e| {
+            format_args!("enclave key is revoked: enclave_key={}", e.enclave_key)
+        },
+
+        InvalidPruningIndexEntry {
+            key: Vec<u8>
+        }
+        |e| {
+            format_args!("invalid pruning index entry: key={:?}", e.key)
+        },
+
+        MissingLatestHeight
+        |_| { "latest_height must be set" },
+
+        InvalidConsensusStateTimestamp {
+            timestamp: u64
+        }
+        |e| {
+            format_args!("invalid consensus state timestamp: timestamp={}", e.timestamp)
+        },
+
+        EnclaveKeyMrenclaveNotAllowed {
+            enclave_key: Address,
+            mr_enclave: Vec<u8>
+        }
+        |e| {
+            format_args!("the MRENCLAVE of the enclave key is no longer allowed: enclave_key={} mr_enclave={:?}", e.enclave_key, e.mr_enclave)
+        },
+
+        EmptyOperators
+        |e| {
+            "operators must not be empty"
+        },
+
+        DuplicateMrenclave {
+            mr_enclave: Vec<u8>
+        }
+        |e| {
+            format_args!("duplicate mrenclave: mr_enclave={:?}", e.mr_enclave)
+        },
+
+        InvalidPreviousMrenclave {
+            previous_mr_enclave: Vec<u8>,
+            expiration_time: u64
+        }
+        |e| {
+            format_args!("Invalid previous mrenclave: previous_mr_enclave={:?} expiration_time={}", e.previous_mr_enclave, e.expiration_time)
+        },
+
+        EnclaveKeyAlreadyRegistered {
+            enclave_key: Address,
+            expired_at: u64,
+            operator: Address
+        }
+        |e| {
+            format_args!("enclave key is already registered with different info: enclave_key={} expired_at={} operator={}", e.enclave_key, e.expired_at, e.operator)
+        },
+
+        InvalidSignaturesLength {
+            expected: usize,
+            actual: usize
+        }
+        |e| {
+            format_args!("invalid signatures length: expected={} actual={}", e.expected, e.actual)
+        },
+
+        InsufficientOperatorSignatures {
+            success: u64,
+            total: u64,
+            threshold_numerator: u64,
+            threshold_denominator: u64
+        }
+        |e| {
+            format_args!("insufficient operator signatures: success={} total={} threshold={}/{}", e.success, e.total, e.threshold_numerator, e.threshold_denominator)
+        },
+
+        UnexpectedQuoteVersion {
+            expected: u32,
+            actual: u32
+        }
+        |e| {
+            format_args!("unexpected quote version: expected={} actual={}", e.expected, e.actual)
+        },
+
+        UnexpectedTeeType {
+            expected: u32,
+            actual: u32
+        }
+        |e| {
+            format_args!("unexpected tee type: expected={} actual={}", e.expected, e.actual)
+        },
+
+        UnexpectedIntelRootCaHash {
+            expected: [u8; 32],
+            actual: [u8; 32]
+        }
+        |e| {
+            format_args!("unexpected intel root ca hash: expected={:?} actual={:?}", e.expected, e.actual)
+        },
+
+        OutOfQuoteValidity {
+            current_timestamp: u64,
+            not_before: u64,
+            not_after: u64
+        }
+        |e| {
+            format_args!("out of quote validity: current_timestamp={} not_before={} not_after={}", e.current_timestamp, e.not_before, e.not_after)
+        },
+
+        DisallowedQuoteStatus {
+            status: String
+        }
+        |e| {
+            format_args!("disallowed quote status: status={}", e.status)
+        },
+
+        DisallowedAdvisoryId {
+            advisory_id: String
+        }
+        |e| {
+            format_args!("disallowed advisory id: advisory_id={}", e.advisory_id)
+        },
+
+        CommitmentPrefixMismatch {
+            expected: Vec<u8>,
+            actual: Vec<u8>
+        }
+        |e| {
+            format_args!("commitment prefix mismatch: expected={:?} actual={:?}", e.expected, e.actual)
+        },
+
+        CommitmentPathMismatch {
+            expected: String,
+            actual: String
+        }
+        |e| {
+            format_args!("commitment path mismatch: expected={} actual={}", e.expected, e.actual)
+        },
+
+        ProofHeightMismatch {
+            expected: Height,
+            actual: Height
+        }
+        |e| {
+            format_args!("proof height mismatch: expected={} actual={}", e.expected, e.actual)
+        },
+
+        CommitmentValueMismatch {
+            expected: Option<[u8; 32]>,
+            actual: Option<[u8; 32]>
+        }
+        |e| {
+            format_args!("commitment value mismatch: expected={:?} actual={:?}", e.expected, e.actual)
+        },
+
+        MissingMessageDomain {
+            client_id: ClientId
+        }
+        |e| {
+            format_args!("proxy message must be bound to the client's domain: client_id={}", e.client_id)
+        },
+
+        MessageDomainMismatch {
+            chain_id: String,
+            client_id: ClientId,
+            domain: MessageDomain
+        }
+        |e| {
+            format_args!("message domain mismatch: expected_chain_id={} expected_client_id={} actual={}", e.chain_id, e.client_id, e.domain)
+        },
+
+        CommitmentNotFoundInBatch {
+            path: String,
+            value: Option<[u8; 32]>
+        }
+        |e| {
+            format_args!("commitment not found in batch: path={} value={:?}", e.path, e.value)
+        },
+
+        StateIdMismatch {
+            height: Height,
+            expected: StateID,
+            actual: StateID
+        }
+        |e| {
+            format_args!("state_id mismatch: height={} expected={} actual={}", e.height, e.expected, e.actual)
+        },
+
+        EmptyCommitmentProofs
+        |_| {
+            "no commitment proofs to combine"
+        },
+
+        CommitmentProofMessageMismatch {
+            index: usize
+        }
+        |e| {
+            format_args!("commitment proof signs a different message from the first proof: index={}", e.index)
+        },
+
+        UnsignedCommitmentProof {
+            index: usize
+        }
+        |e| {
+            format_args!("commitment proof has no signature: index={}", e.index)
+        },
+
+        UnknownEnclaveKeyOperator {
+            enclave_key: Address,
+            operator: Address
+        }
+        |e| {
+            format_args!("operator of the enclave key is not in the client's operators: enclave_key={} operator={}", e.enclave_key, e.operator)
+        },
+
+        DuplicateOperatorSignature {
+            operator: Address
+        }
+        |e| {
+            format_args!("multiple signatures for the same operator: operator={}", e.operator)
+        },
+
+        MissingHostHeight
+        |_| {
+            "the validation context requires the host height, but the host doesn't provide it"
+        },
+
         AttestationReport
         [attestation_report::Error]
         |_| { "Attestation report error" },
@@ -107,6 +505,14 @@ define_error! {
         ZkVmTypeConversion
         [TraceError<core::num::TryFromIntError>]
         |_| { "ZkVmType conversion error" },
+
+        ProtoDecodeError
+        [TraceError<prost::DecodeError>]
+        |_| { "proto decode error" },
+
+        SerdeJson
+        [TraceError<serde_json::Error>]
+        |_| { "serde_json error" },
     }
 }
 
@@ -146,6 +552,12 @@ impl From<zkvm::Error> for Error {
     }
 }
 
+impl From<prost::DecodeError> for Error {
+    fn from(value: prost::DecodeError) -> Self {
+        Self::proto_decode_error(value)
+    }
+}
+
 impl From<alloc::string::FromUtf8Error> for Error {
     fn from(value: alloc::string::FromUtf8Error) -> Self {
         Self::string_from_utf8_error(value)
@@ -26,3 +26,5 @@ pub mod client_state;
 pub mod consensus_state;
 pub mod errors;
 pub mod message;
+pub mod pruning;
+pub mod simulator;
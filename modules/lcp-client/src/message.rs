@@ -3,12 +3,20 @@ use crate::errors::Error;
 use crate::prelude::*;
 use alloy_sol_types::{sol, SolValue};
 use attestation_report::IASSignedReport;
+use core::time::Duration;
 use crypto::Address;
+use dcap_quote_verifier::collateral::QvCollateral;
 use dcap_quote_verifier::verifier::QuoteVerificationOutput;
-use light_client::commitments::{Error as CommitmentError, EthABIEncoder, ProxyMessage};
+use light_client::commitments::{
+    Error as CommitmentError, EthABIEncoder, MessageDomain, MessageEncoding, ProxyMessage,
+};
 use light_client::types::proto::ibc::lightclients::lcp::v1::{
+    DcapCollateral as RawDCAPCollateral,
+    DcapRegisterEnclaveKeyMessage as RawDCAPRegisterEnclaveKeyMessage,
     RegisterEnclaveKeyMessage as RawRegisterEnclaveKeyMessage,
+    RevokeEnclaveKeyMessage as RawRevokeEnclaveKeyMessage,
     UpdateClientMessage as RawUpdateClientMessage,
+    UpdateMrenclaveMessage as RawUpdateMrenclaveMessage,
     UpdateOperatorsMessage as RawUpdateOperatorsMessage,
     ZkdcapRegisterEnclaveKeyMessage as RawZKDCAPRegisterEnclaveKeyMessage,
 };
@@ -19,17 +27,26 @@ pub const LCP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL: &str =
     "/ibc.lightclients.lcp.v1.RegisterEnclaveKeyMessage";
 pub const LCP_ZKDCAP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL: &str =
     "/ibc.lightclients.lcp.v1.ZKDCAPRegisterEnclaveKeyMessage";
+pub const LCP_DCAP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL: &str =
+    "/ibc.lightclients.lcp.v1.DCAPRegisterEnclaveKeyMessage";
 pub const LCP_UPDATE_CLIENT_MESSAGE_TYPE_URL: &str = "/ibc.lightclients.lcp.v1.UpdateClientMessage";
 pub const LCP_UPDATE_OPERATORS_MESSAGE_TYPE_URL: &str =
     "/ibc.lightclients.lcp.v1.UpdateOperatorsMessage";
+pub const LCP_REVOKE_ENCLAVE_KEY_MESSAGE_TYPE_URL: &str =
+    "/ibc.lightclients.lcp.v1.RevokeEnclaveKeyMessage";
+pub const LCP_UPDATE_MRENCLAVE_MESSAGE_TYPE_URL: &str =
+    "/ibc.lightclients.lcp.v1.UpdateMrenclaveMessage";
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum ClientMessage {
     RegisterEnclaveKey(RegisterEnclaveKeyMessage),
     ZKDCAPRegisterEnclaveKey(ZKDCAPRegisterEnclaveKeyMessage),
+    DCAPRegisterEnclaveKey(DCAPRegisterEnclaveKeyMessage),
     UpdateClient(UpdateClientMessage),
     UpdateOperators(UpdateOperatorsMessage),
+    RevokeEnclaveKey(RevokeEnclaveKeyMessage),
+    UpdateMrenclave(UpdateMrenclaveMessage),
 }
 
 impl Protobuf<Any> for ClientMessage {}
@@ -42,12 +59,30 @@ impl TryFrom<Any> for ClientMessage {
             LCP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL => Ok(ClientMessage::RegisterEnclaveKey(
                 RegisterEnclaveKeyMessage::decode_vec(&raw.value).map_err(Error::ibc_proto)?,
             )),
+            LCP_ZKDCAP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL => {
+                Ok(ClientMessage::ZKDCAPRegisterEnclaveKey(
+                    ZKDCAPRegisterEnclaveKeyMessage::decode_vec(&raw.value)
+                        .map_err(Error::ibc_proto)?,
+                ))
+            }
+            LCP_DCAP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL => {
+                Ok(ClientMessage::DCAPRegisterEnclaveKey(
+                    DCAPRegisterEnclaveKeyMessage::decode_vec(&raw.value)
+                        .map_err(Error::ibc_proto)?,
+                ))
+            }
             LCP_UPDATE_CLIENT_MESSAGE_TYPE_URL => Ok(ClientMessage::UpdateClient(
                 UpdateClientMessage::decode_vec(&raw.value).map_err(Error::ibc_proto)?,
             )),
             LCP_UPDATE_OPERATORS_MESSAGE_TYPE_URL => Ok(ClientMessage::UpdateOperators(
                 UpdateOperatorsMessage::decode_vec(&raw.value).map_err(Error::ibc_proto)?,
             )),
+            LCP_REVOKE_ENCLAVE_KEY_MESSAGE_TYPE_URL => Ok(ClientMessage::RevokeEnclaveKey(
+                RevokeEnclaveKeyMessage::decode_vec(&raw.value).map_err(Error::ibc_proto)?,
+            )),
+            LCP_UPDATE_MRENCLAVE_MESSAGE_TYPE_URL => Ok(ClientMessage::UpdateMrenclave(
+                UpdateMrenclaveMessage::decode_vec(&raw.value).map_err(Error::ibc_proto)?,
+            )),
             type_url => Err(Error::unexpected_header_type(type_url.to_owned())),
         }
     }
@@ -64,6 +99,10 @@ impl From<ClientMessage> for Any {
                 LCP_ZKDCAP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL.to_string(),
                 h.encode_vec().unwrap(),
             ),
+            ClientMessage::DCAPRegisterEnclaveKey(h) => Any::new(
+                LCP_DCAP_REGISTER_ENCLAVE_KEY_MESSAGE_TYPE_URL.to_string(),
+                h.encode_vec().unwrap(),
+            ),
             ClientMessage::UpdateClient(h) => Any::new(
                 LCP_UPDATE_CLIENT_MESSAGE_TYPE_URL.to_string(),
                 h.encode_vec().unwrap(),
@@ -72,6 +111,14 @@ impl From<ClientMessage> for Any {
                 LCP_UPDATE_OPERATORS_MESSAGE_TYPE_URL.to_string(),
                 h.encode_vec().unwrap(),
             ),
+            ClientMessage::RevokeEnclaveKey(h) => Any::new(
+                LCP_REVOKE_ENCLAVE_KEY_MESSAGE_TYPE_URL.to_string(),
+                h.encode_vec().unwrap(),
+            ),
+            ClientMessage::UpdateMrenclave(h) => Any::new(
+                LCP_UPDATE_MRENCLAVE_MESSAGE_TYPE_URL.to_string(),
+                h.encode_vec().unwrap(),
+            ),
         }
     }
 }
@@ -165,10 +212,99 @@ impl From<ZKDCAPRegisterEnclaveKeyMessage> for RawZKDCAPRegisterEnclaveKeyMessag
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DCAPRegisterEnclaveKeyMessage {
+    pub quote: Vec<u8>,
+    pub collateral: DCAPCollateral,
+    pub operator_signature: Option<Vec<u8>>,
+}
+
+impl Protobuf<RawDCAPRegisterEnclaveKeyMessage> for DCAPRegisterEnclaveKeyMessage {}
+
+impl TryFrom<RawDCAPRegisterEnclaveKeyMessage> for DCAPRegisterEnclaveKeyMessage {
+    type Error = Error;
+    fn try_from(value: RawDCAPRegisterEnclaveKeyMessage) -> Result<Self, Self::Error> {
+        Ok(DCAPRegisterEnclaveKeyMessage {
+            quote: value.quote,
+            collateral: value
+                .collateral
+                .ok_or_else(Error::missing_dcap_collateral)?
+                .into(),
+            operator_signature: (!value.operator_signature.is_empty())
+                .then_some(value.operator_signature),
+        })
+    }
+}
+
+impl From<DCAPRegisterEnclaveKeyMessage> for RawDCAPRegisterEnclaveKeyMessage {
+    fn from(value: DCAPRegisterEnclaveKeyMessage) -> Self {
+        RawDCAPRegisterEnclaveKeyMessage {
+            quote: value.quote,
+            collateral: Some(value.collateral.into()),
+            operator_signature: value.operator_signature.unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct DCAPCollateral {
+    pub tcb_info_json: String,
+    pub qe_identity_json: String,
+    pub sgx_intel_root_ca_der: Vec<u8>,
+    pub sgx_tcb_signing_der: Vec<u8>,
+    pub sgx_intel_root_ca_crl_der: Vec<u8>,
+    pub sgx_pck_crl_der: Vec<u8>,
+}
+
+impl From<RawDCAPCollateral> for DCAPCollateral {
+    fn from(value: RawDCAPCollateral) -> Self {
+        DCAPCollateral {
+            tcb_info_json: value.tcb_info_json,
+            qe_identity_json: value.qe_identity_json,
+            sgx_intel_root_ca_der: value.sgx_intel_root_ca_der,
+            sgx_tcb_signing_der: value.sgx_tcb_signing_der,
+            sgx_intel_root_ca_crl_der: value.sgx_intel_root_ca_crl_der,
+            sgx_pck_crl_der: value.sgx_pck_crl_der,
+        }
+    }
+}
+
+impl From<DCAPCollateral> for RawDCAPCollateral {
+    fn from(value: DCAPCollateral) -> Self {
+        RawDCAPCollateral {
+            tcb_info_json: value.tcb_info_json,
+            qe_identity_json: value.qe_identity_json,
+            sgx_intel_root_ca_der: value.sgx_intel_root_ca_der,
+            sgx_tcb_signing_der: value.sgx_tcb_signing_der,
+            sgx_intel_root_ca_crl_der: value.sgx_intel_root_ca_crl_der,
+            sgx_pck_crl_der: value.sgx_pck_crl_der,
+        }
+    }
+}
+
+impl From<DCAPCollateral> for QvCollateral {
+    fn from(value: DCAPCollateral) -> Self {
+        QvCollateral {
+            tcb_info_json: value.tcb_info_json,
+            qe_identity_json: value.qe_identity_json,
+            sgx_intel_root_ca_der: value.sgx_intel_root_ca_der,
+            sgx_tcb_signing_der: value.sgx_tcb_signing_der,
+            sgx_intel_root_ca_crl_der: value.sgx_intel_root_ca_crl_der,
+            sgx_pck_crl_der: value.sgx_pck_crl_der,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct UpdateClientMessage {
     pub signatures: Vec<Vec<u8>>,
     pub proxy_message: ProxyMessage,
+    /// The domain that the proxy message is bound to. If None, the message is encoded in the v1 schema.
+    #[serde(default)]
+    pub domain: Option<MessageDomain>,
+    /// The encoding of the proxy message body.
+    #[serde(default)]
+    pub encoding: MessageEncoding,
 }
 
 impl Protobuf<RawUpdateClientMessage> for UpdateClientMessage {}
@@ -176,9 +312,13 @@ impl Protobuf<RawUpdateClientMessage> for UpdateClientMessage {}
 impl TryFrom<RawUpdateClientMessage> for UpdateClientMessage {
     type Error = Error;
     fn try_from(value: RawUpdateClientMessage) -> Result<Self, Self::Error> {
+        let (proxy_message, domain, encoding) =
+            ProxyMessage::from_bytes_with_encoding(&value.proxy_message)?;
         Ok(UpdateClientMessage {
             signatures: value.signatures,
-            proxy_message: ProxyMessage::from_bytes(&value.proxy_message)?,
+            proxy_message,
+            domain,
+            encoding,
         })
     }
 }
@@ -186,7 +326,9 @@ impl TryFrom<RawUpdateClientMessage> for UpdateClientMessage {
 impl From<UpdateClientMessage> for RawUpdateClientMessage {
     fn from(value: UpdateClientMessage) -> Self {
         RawUpdateClientMessage {
-            proxy_message: Into::<ProxyMessage>::into(value.proxy_message).to_bytes(),
+            proxy_message: value
+                .proxy_message
+                .to_bytes_with_encoding(value.domain, value.encoding),
             signatures: value.signatures,
         }
     }
@@ -236,6 +378,66 @@ impl From<UpdateOperatorsMessage> for RawUpdateOperatorsMessage {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct UpdateMrenclaveMessage {
+    pub nonce: u64,
+    pub new_mr_enclave: Vec<u8>,
+    pub transition_period: Duration,
+    pub signatures: Vec<Vec<u8>>,
+}
+
+impl Protobuf<RawUpdateMrenclaveMessage> for UpdateMrenclaveMessage {}
+
+impl TryFrom<RawUpdateMrenclaveMessage> for UpdateMrenclaveMessage {
+    type Error = Error;
+    fn try_from(value: RawUpdateMrenclaveMessage) -> Result<Self, Self::Error> {
+        Ok(UpdateMrenclaveMessage {
+            nonce: value.nonce,
+            new_mr_enclave: value.new_mrenclave,
+            transition_period: Duration::from_secs(value.transition_period),
+            signatures: value.signatures,
+        })
+    }
+}
+
+impl From<UpdateMrenclaveMessage> for RawUpdateMrenclaveMessage {
+    fn from(value: UpdateMrenclaveMessage) -> Self {
+        RawUpdateMrenclaveMessage {
+            nonce: value.nonce,
+            new_mrenclave: value.new_mr_enclave,
+            transition_period: value.transition_period.as_secs(),
+            signatures: value.signatures,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RevokeEnclaveKeyMessage {
+    pub enclave_key: Address,
+    pub signatures: Vec<Vec<u8>>,
+}
+
+impl Protobuf<RawRevokeEnclaveKeyMessage> for RevokeEnclaveKeyMessage {}
+
+impl TryFrom<RawRevokeEnclaveKeyMessage> for RevokeEnclaveKeyMessage {
+    type Error = Error;
+    fn try_from(value: RawRevokeEnclaveKeyMessage) -> Result<Self, Self::Error> {
+        Ok(RevokeEnclaveKeyMessage {
+            enclave_key: Address::try_from(value.enclave_key.as_slice())?,
+            signatures: value.signatures,
+        })
+    }
+}
+
+impl From<RevokeEnclaveKeyMessage> for RawRevokeEnclaveKeyMessage {
+    fn from(value: RevokeEnclaveKeyMessage) -> Self {
+        RawRevokeEnclaveKeyMessage {
+            enclave_key: value.enclave_key.to_vec(),
+            signatures: value.signatures,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct CommitmentProofs {
     pub message: Vec<u8>,
@@ -246,6 +448,12 @@ impl CommitmentProofs {
     pub fn message(&self) -> Result<ProxyMessage, CommitmentError> {
         ProxyMessage::from_bytes(&self.message)
     }
+
+    pub fn message_with_domain(
+        &self,
+    ) -> Result<(ProxyMessage, Option<MessageDomain>), CommitmentError> {
+        ProxyMessage::from_bytes_with_domain(&self.message)
+    }
 }
 
 sol! {
@@ -261,7 +469,7 @@ impl EthABIEncoder for CommitmentProofs {
     }
 
     fn ethabi_decode(bz: &[u8]) -> Result<Self, CommitmentError> {
-        Ok(EthABICommitmentProofs::abi_decode(bz, true).unwrap().into())
+        Ok(EthABICommitmentProofs::abi_decode(bz, true)?.into())
     }
 }
 
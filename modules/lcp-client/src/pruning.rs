@@ -0,0 +1,154 @@
+use crate::client_def::{enclave_keys_prefix, EKOperatorInfo};
+use crate::errors::Error;
+use crate::prelude::*;
+use crypto::Address;
+use light_client::types::ClientId;
+use light_client::{HostClientKeeper, HostClientReader};
+use store::{prefix_end, Order};
+
+/// The maximum number of entries that the client removes for pruning per update
+pub const MAX_PRUNING_ENTRIES_PER_UPDATE: u64 = 16;
+
+/// PruningIndex maintains the auxiliary entries that the client needs to prune its states.
+///
+/// The consensus states are pruned in ascending order of height via the height index of the context,
+/// and the client keeps their number to enforce `max_consensus_states` without counting the index.
+/// The enclave keys are indexed by their expiration time, so the expired keys are found by a range query.
+///
+/// The entries are created for the states stored before this index was introduced when the count is first loaded.
+pub(crate) struct PruningIndex<'a> {
+    client_id: &'a ClientId,
+}
+
+impl<'a> PruningIndex<'a> {
+    pub fn new(client_id: &'a ClientId) -> Self {
+        Self { client_id }
+    }
+
+    /// consensus_states_count returns the number of the consensus states of the client
+    ///
+    /// If the count has not been recorded yet, it indexes the existing consensus states and enclave keys and records the count.
+    pub fn consensus_states_count<T: HostClientKeeper + ?Sized>(
+        &self,
+        ctx: &mut T,
+    ) -> Result<u64, Error> {
+        let path = self.consensus_states_count_path();
+        match ctx.get(&path) {
+            Some(bz) => Ok(u64::from_be_bytes(
+                bz.as_slice()
+                    .try_into()
+                    .map_err(|_| Error::invalid_pruning_index_entry(path))?,
+            )),
+            None => {
+                let count = self.migrate(ctx)?;
+                self.set_consensus_states_count(ctx, count);
+                Ok(count)
+            }
+        }
+    }
+
+    pub fn set_consensus_states_count<T: HostClientKeeper + ?Sized>(
+        &self,
+        ctx: &mut T,
+        count: u64,
+    ) {
+        ctx.set(
+            self.consensus_states_count_path(),
+            count.to_be_bytes().to_vec(),
+        );
+    }
+
+    /// invalidate removes the recorded count, so the index is rebuilt from the stored states when the count is loaded next time
+    ///
+    /// This must be called after the states are stored without updating the index.
+    pub fn invalidate<T: HostClientKeeper + ?Sized>(&self, ctx: &mut T) {
+        ctx.remove(&self.consensus_states_count_path());
+    }
+
+    /// add_enclave_key adds the enclave key to the expiration index
+    pub fn add_enclave_key<T: HostClientKeeper + ?Sized>(
+        &self,
+        ctx: &mut T,
+        expired_at: u64,
+        ek: Address,
+    ) {
+        ctx.set(self.enclave_key_expiration_key(expired_at, ek), vec![]);
+    }
+
+    /// remove_enclave_key removes the enclave key from the expiration index
+    pub fn remove_enclave_key<T: HostClientKeeper + ?Sized>(
+        &self,
+        ctx: &mut T,
+        expired_at: u64,
+        ek: Address,
+    ) {
+        ctx.remove(&self.enclave_key_expiration_key(expired_at, ek));
+    }
+
+    /// expired_enclave_keys returns at most `limit` enclave keys that expire at or before `timestamp` in ascending order of expiration
+    pub fn expired_enclave_keys<T: HostClientReader + ?Sized>(
+        &self,
+        ctx: &T,
+        timestamp: u64,
+        limit: usize,
+    ) -> Result<Vec<(u64, Address)>, Error> {
+        let prefix = self.enclave_key_expirations_prefix();
+        let end = match timestamp.checked_add(1) {
+            Some(end) => {
+                let mut key = prefix.clone();
+                key.extend(end.to_be_bytes());
+                Some(key)
+            }
+            None => prefix_end(&prefix),
+        };
+        ctx.range(Some(&prefix), end.as_deref(), Order::Ascending, Some(limit))
+            .into_iter()
+            .map(|(key, _)| {
+                let entry = &key[prefix.len()..];
+                if entry.len() != 28 {
+                    return Err(Error::invalid_pruning_index_entry(key.clone()));
+                }
+                let expired_at = u64::from_be_bytes(entry[..8].try_into().unwrap());
+                Ok((expired_at, Address::try_from(&entry[8..])?))
+            })
+            .collect()
+    }
+
+    /// migrate indexes the consensus states and the enclave keys stored before the index was introduced
+    ///
+    /// It returns the number of the consensus states of the client.
+    fn migrate<T: HostClientKeeper + ?Sized>(&self, ctx: &mut T) -> Result<u64, Error> {
+        ctx.index_consensus_heights(self.client_id)?;
+        let count = ctx.consensus_heights(self.client_id, None)?.len() as u64;
+
+        let prefix = enclave_keys_prefix(self.client_id);
+        for (key, value) in ctx.prefix_range(&prefix, Order::Ascending, None) {
+            let ek = core::str::from_utf8(&key[prefix.len()..])
+                .ok()
+                .and_then(|ek| Address::from_hex_string(ek).ok())
+                .ok_or_else(|| Error::invalid_pruning_index_entry(key.clone()))?;
+            let info: EKOperatorInfo = serde_json::from_slice(&value)
+                .map_err(|_| Error::invalid_pruning_index_entry(key.clone()))?;
+            // the revoked keys are retained to reject their re-registration
+            if !info.revoked() {
+                self.add_enclave_key(ctx, info.expired_at(), ek);
+            }
+        }
+        Ok(count)
+    }
+
+    fn consensus_states_count_path(&self) -> Vec<u8> {
+        format!("clients/{}/aux/consensus_states_count", self.client_id).into_bytes()
+    }
+
+    fn enclave_key_expirations_prefix(&self) -> Vec<u8> {
+        format!("clients/{}/aux/enclave_key_expirations/", self.client_id).into_bytes()
+    }
+
+    fn enclave_key_expiration_key(&self, expired_at: u64, ek: Address) -> Vec<u8> {
+        let mut key = self.enclave_key_expirations_prefix();
+        key.extend(expired_at.to_be_bytes());
+        key.extend(ek.0);
+        key
+    }
+}
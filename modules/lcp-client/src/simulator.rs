@@ -0,0 +1,479 @@
+use crate::client_def::{enclave_key_path, EKOperatorInfo, LCPClient, LCP_CLIENT_TYPE};
+use crate::client_state::ClientState;
+use crate::consensus_state::ConsensusState;
+use crate::errors::Error;
+use crate::message::ClientMessage;
+use crate::prelude::*;
+use crate::pruning::PruningIndex;
+use alloc::collections::BTreeMap;
+use crypto::Address;
+use light_client::commitments::{CommitmentPrefix, CommitmentProof};
+use light_client::types::{Any, ClientId, Height, Time};
+use light_client::{ClientKeeper, ClientReader, HostClientKeeper, HostClientReader, HostContext};
+use store::{btree_range, KVPair, KVStore, Order};
+
+/// LCPClientSimulator mirrors the state of an LCP client deployed on a counterparty chain.
+///
+/// A relayer can sync the mirror from the client state, consensus states and enclave keys queried from the chain,
+/// and then run `update_client` or `verify_membership` locally before submitting the transaction.
+#[derive(Clone, Debug)]
+pub struct LCPClientSimulator {
+    client_id: ClientId,
+    host_timestamp: Time,
+    host_height: Option<Height>,
+    store: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl LCPClientSimulator {
+    /// new creates an empty simulator for the client `client_id`
+    ///
+    /// `host_timestamp` should be the latest block timestamp of the counterparty chain.
+    pub fn new(client_id: ClientId, host_timestamp: Time) -> Self {
+        Self {
+            client_id,
+            host_timestamp,
+            host_height: None,
+            store: Default::default(),
+        }
+    }
+
+    /// client_id returns the identifier of the simulated client
+    pub fn client_id(&self) -> &ClientId {
+        &self.client_id
+    }
+
+    /// set_host_timestamp updates the timestamp used as the current time of the counterparty chain
+    pub fn set_host_timestamp(&mut self, host_timestamp: Time) {
+        self.host_timestamp = host_timestamp;
+    }
+
+    /// set_host_height updates the height used as the current height of the counterparty chain
+    ///
+    /// A message bounded by the host height is rejected until the height is set.
+    pub fn set_host_height(&mut self, host_height: Height) {
+        self.host_height = Some(host_height);
+    }
+
+    /// sync_client_state stores the client state queried from the counterparty chain
+    pub fn sync_client_state(&mut self, client_state: Any) -> Result<(), Error> {
+        ClientState::try_from(client_state.clone())?;
+        let client_id = self.client_id.clone();
+        self.store_client_type(client_id.clone(), LCP_CLIENT_TYPE.to_owned())?;
+        self.store_any_client_state(client_id, client_state)?;
+        Ok(())
+    }
+
+    /// sync_consensus_state stores the consensus state at `height` queried from the counterparty chain
+    pub fn sync_consensus_state(
+        &mut self,
+        height: Height,
+        consensus_state: Any,
+    ) -> Result<(), Error> {
+        ConsensusState::try_from(consensus_state.clone())?;
+        let client_id = self.client_id.clone();
+        self.store_any_consensus_state(client_id.clone(), height, consensus_state)?;
+        PruningIndex::new(&client_id).invalidate(self);
+        Ok(())
+    }
+
+    /// sync_enclave_key stores the enclave key registered in the counterparty chain
+    ///
+    /// `expired_at` is the expiration time of the key in unix seconds.
+    /// The MRENCLAVE of the key is not recorded, so the key is only limited by `expired_at`.
+    pub fn sync_enclave_key(
+        &mut self,
+        ek: Address,
+        expired_at: u64,
+        operator: Address,
+    ) -> Result<(), Error> {
+        let info = serde_json::to_vec(&EKOperatorInfo::new(expired_at, operator, vec![]))
+            .map_err(Error::serde_json)?;
+        self.set(enclave_key_path(&self.client_id, ek), info);
+        let client_id = self.client_id.clone();
+        PruningIndex::new(&client_id).add_enclave_key(self, expired_at, ek);
+        Ok(())
+    }
+
+    /// client_state returns the client state of the mirror
+    pub fn client_state(&self) -> Result<ClientState, Error> {
+        Ok(ClientReader::client_state(self, &self.client_id)?.try_into()?)
+    }
+
+    /// consensus_state returns the consensus state at `height` of the mirror
+    pub fn consensus_state(&self, height: &Height) -> Result<ConsensusState, Error> {
+        Ok(ClientReader::consensus_state(self, &self.client_id, height)?.try_into()?)
+    }
+
+    /// update_client verifies the client message and applies the resulting updates to the mirror
+    pub fn update_client(&mut self, message: ClientMessage) -> Result<(), Error> {
+        let client_id = self.client_id.clone();
+        LCPClient.update_client(self, client_id, message)
+    }
+
+    /// dry_run_update_client verifies the client message without modifying the mirror
+    pub fn dry_run_update_client(&self, message: ClientMessage) -> Result<(), Error> {
+        self.clone().update_client(message)
+    }
+
+    /// build_cosigned_update_client_message combines the proofs of the same proxy message countersigned by multiple LCP nodes
+    ///
+    /// The resulting message carries the signatures in the order of the operators of the mirrored client.
+    pub fn build_cosigned_update_client_message(
+        &self,
+        proofs: Vec<CommitmentProof>,
+    ) -> Result<ClientMessage, Error> {
+        Ok(ClientMessage::UpdateClient(
+            LCPClient.build_cosigned_update_client_message(self, &self.client_id, proofs)?,
+        ))
+    }
+
+    /// verify_membership verifies a membership proof against the mirror
+    pub fn verify_membership(
+        &self,
+        prefix: CommitmentPrefix,
+        path: String,
+        value: Vec<u8>,
+        proof_height: Height,
+        proof: Vec<u8>,
+    ) -> Result<(), Error> {
+        LCPClient.verify_membership(
+            self,
+            self.client_id.clone(),
+            prefix,
+            path,
+            value,
+            proof_height,
+            proof,
+        )
+    }
+
+    /// verify_non_membership verifies a non-membership proof against the mirror
+    pub fn verify_non_membership(
+        &self,
+        prefix: CommitmentPrefix,
+        path: String,
+        proof_height: Height,
+        proof: Vec<u8>,
+    ) -> Result<(), Error> {
+        LCPClient.verify_non_membership(
+            self,
+            self.client_id.clone(),
+            prefix,
+            path,
+            proof_height,
+            proof,
+        )
+    }
+}
+
+impl KVStore for LCPClientSimulator {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.store.insert(key, value);
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.store.get(key).cloned()
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.store.remove(key);
+    }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        btree_range(&self.store, start, end, order, limit)
+    }
+}
+
+impl HostContext for LCPClientSimulator {
+    fn host_timestamp(&self) -> Time {
+        self.host_timestamp
+    }
+
+    fn host_height(&self) -> Option<Height> {
+        self.host_height
+    }
+}
+
+impl ClientReader for LCPClientSimulator {}
+
+impl ClientKeeper for LCPClientSimulator {}
+
+impl HostClientReader for LCPClientSimulator {}
+
+impl HostClientKeeper for LCPClientSimulator {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client_state::LCP_CLIENT_STATE_TYPE_URL;
+    use crate::consensus_state::LCP_CONSENSUS_STATE_TYPE_URL;
+    use crate::errors::ErrorDetail;
+    use crate::message::{CommitmentProofs, UpdateClientMessage};
+    use core::str::FromStr;
+    use core::time::Duration;
+    use crypto::{EnclaveKey, Keccak256};
+    use light_client::commitments::{
+        prove_commitment, EthABIEncoder, HostHeightContext, UpdateStateProxyMessage,
+        ValidationContext, VerifyMembershipProxyMessage,
+    };
+    use light_client::types::proto::ibc::lightclients::lcp::v1::{
+        ClientState as RawClientState, ConsensusState as RawConsensusState,
+    };
+    use prost::Message;
+
+    #[test]
+    fn test_simulator() {
+        let ek = EnclaveKey::new().unwrap();
+        let now = Time::now();
+        let height = Height::new(0, 1);
+        let client_id = ClientId::from_str(&format!("{}-0", LCP_CLIENT_TYPE)).unwrap();
+
+        let mut sim = LCPClientSimulator::new(client_id, now);
+        let res = sim.sync_client_state(
+            ClientState {
+                mr_enclave: [0u8; 32].to_vec(),
+                key_expiration: Duration::from_secs(60 * 60 * 24 * 7),
+                latest_height: height,
+                ..Default::default()
+            }
+            .into(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let res = sim.sync_consensus_state(
+            height,
+            ConsensusState {
+                state_id: [1u8; 32].into(),
+                timestamp: Time::unix_epoch(),
+                emitted_states_root: None,
+            }
+            .into(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+        let res = sim.sync_enclave_key(
+            ek.get_pubkey().as_address(),
+            (now + Duration::from_secs(60))
+                .unwrap()
+                .as_unix_timestamp_secs(),
+            Default::default(),
+        );
+        assert!(res.is_ok(), "res={:?}", res);
+
+        // the client state must be a valid LCP client state
+        assert!(sim
+            .sync_client_state(Any::new("/foo".to_string(), vec![]))
+            .is_err());
+        // the malformed bytes are rejected without panicking
+        let client_state: Any = sim.client_state().unwrap().into();
+        let consensus_state: Any = sim.consensus_state(&height).unwrap().into();
+        for any in [client_state, consensus_state] {
+            let mut truncated = any.clone();
+            truncated.value.truncate(any.value.len() - 1);
+            let res = if any.type_url == LCP_CLIENT_STATE_TYPE_URL {
+                sim.clone().sync_client_state(truncated)
+            } else {
+                sim.clone().sync_consensus_state(height, truncated)
+            };
+            assert!(res.is_err(), "type_url={}", any.type_url);
+        }
+        // the client state without the latest height is rejected
+        let mut raw = RawClientState::from(sim.client_state().unwrap());
+        raw.latest_height = None;
+        let res = sim.clone().sync_client_state(Any::new(
+            LCP_CLIENT_STATE_TYPE_URL.to_string(),
+            raw.encode_to_vec(),
+        ));
+        assert!(
+            matches!(
+                res.as_ref().err().map(|e| e.detail()),
+                Some(ErrorDetail::MissingLatestHeight(_))
+            ),
+            "res={:?}",
+            res
+        );
+        // the consensus state with an invalid state id is rejected
+        let res = sim.clone().sync_consensus_state(
+            height,
+            Any::new(
+                LCP_CONSENSUS_STATE_TYPE_URL.to_string(),
+                RawConsensusState {
+                    state_id: vec![1u8; 31],
+                    timestamp: 0,
+                    ..Default::default()
+                }
+                .encode_to_vec(),
+            ),
+        );
+        assert!(res.is_err());
+        // the timestamp out of the range of `Time` is rejected
+        let res = sim.clone().sync_consensus_state(
+            height,
+            Any::new(
+                LCP_CONSENSUS_STATE_TYPE_URL.to_string(),
+                RawConsensusState {
+                    state_id: vec![1u8; 32],
+                    timestamp: u64::MAX,
+                    ..Default::default()
+                }
+                .encode_to_vec(),
+            ),
+        );
+        assert!(res.is_err());
+        // the emitted states root with an invalid length is rejected
+        let res = sim.clone().sync_consensus_state(
+            height,
+            Any::new(
+                LCP_CONSENSUS_STATE_TYPE_URL.to_string(),
+                RawConsensusState {
+                    state_id: vec![1u8; 32],
+                    timestamp: 0,
+                    emitted_states_root: vec![1u8; 31],
+                }
+                .encode_to_vec(),
+            ),
+        );
+        assert!(res.is_err());
+
+        let value = b"value".to_vec();
+        let proof = prove_commitment(
+            &ek,
+            VerifyMembershipProxyMessage::new(
+                b"ibc".to_vec(),
+                "path".to_string(),
+                Some(value.keccak256()),
+                height,
+                [1u8; 32].into(),
+            )
+            .into(),
+        )
+        .unwrap();
+        let proof = CommitmentProofs {
+            message: proof.message,
+            signatures: vec![proof.signature],
+        }
+        .ethabi_encode();
+        let res = sim.verify_membership(b"ibc".to_vec(), "path".to_string(), value, height, proof);
+        assert!(res.is_ok(), "res={:?}", res);
+
+        let post_height = Height::new(0, 2);
+        let proof = prove_commitment(
+            &ek,
+            UpdateStateProxyMessage {
+                prev_height: Some(height),
+                prev_state_id: Some([1u8; 32].into()),
+                post_height,
+                post_state_id: [2u8; 32].into(),
+                timestamp: Time::unix_epoch(),
+                context: ValidationContext::Empty,
+                emitted_states: vec![],
+                emitted_states_root: None,
+            }
+            .into(),
+        )
+        .unwrap();
+        let message = ClientMessage::UpdateClient(UpdateClientMessage {
+            proxy_message: proof.message().unwrap(),
+            signatures: vec![proof.signature],
+            domain: None,
+            encoding: Default::default(),
+        });
+
+        // dry run does not modify the mirror
+        let res = sim.dry_run_update_client(message.clone());
+        assert!(res.is_ok(), "res={:?}", res);
+        assert_eq!(sim.client_state().unwrap().latest_height, height);
+        assert!(sim.consensus_state(&post_height).is_err());
+
+        let res = sim.update_client(message);
+        assert!(res.is_ok(), "res={:?}", res);
+        assert_eq!(sim.client_state().unwrap().latest_height, post_height);
+        assert!(sim.consensus_state(&post_height).is_ok());
+
+        // a message bounded by the host height requires the height of the counterparty chain
+        let proof = prove_commitment(
+            &ek,
+            UpdateStateProxyMessage {
+                prev_height: Some(post_height),
+                prev_state_id: Some([2u8; 32].into()),
+                post_height: Height::new(0, 3),
+                post_state_id: [3u8; 32].into(),
+                timestamp: Time::unix_epoch(),
+                context: HostHeightContext::new(Height::new(0, 100)).into(),
+                emitted_states: vec![],
+                emitted_states_root: None,
+            }
+            .into(),
+        )
+        .unwrap();
+        let message = ClientMessage::UpdateClient(UpdateClientMessage {
+            proxy_message: proof.message().unwrap(),
+            signatures: vec![proof.signature],
+            domain: None,
+            encoding: Default::default(),
+        });
+        let res = sim.dry_run_update_client(message.clone());
+        assert!(
+            matches!(
+                res.as_ref().err().map(|e| e.detail()),
+                Some(ErrorDetail::MissingHostHeight(_))
+            ),
+            "res={:?}",
+            res
+        );
+        sim.set_host_height(Height::new(0, 101));
+        assert!(sim.dry_run_update_client(message.clone()).is_err());
+        sim.set_host_height(Height::new(0, 100));
+        let res = sim.dry_run_update_client(message);
+        assert!(res.is_ok(), "res={:?}", res);
+    }
+
+    #[test]
+    fn test_prev_and_next_consensus_state() {
+        let client_id = ClientId::from_str(&format!("{}-0", LCP_CLIENT_TYPE)).unwrap();
+        let mut sim = LCPClientSimulator::new(client_id.clone(), Time::now());
+        for (i, h) in [3, 5, 7].into_iter().enumerate() {
+            let res = sim.sync_consensus_state(
+                Height::new(0, h),
+                ConsensusState {
+                    state_id: [i as u8; 32].into(),
+                    timestamp: Time::unix_epoch(),
+                    emitted_states_root: None,
+                }
+                .into(),
+            );
+            assert!(res.is_ok(), "res={:?}", res);
+        }
+        let next_height = |h| {
+            sim.next_consensus_state(&client_id, &Height::new(0, h))
+                .unwrap()
+                .map(|(h, _)| h.revision_height())
+        };
+        assert_eq!(next_height(1), Some(3));
+        assert_eq!(next_height(3), Some(5));
+        assert_eq!(next_height(6), Some(7));
+        assert_eq!(next_height(7), None);
+        let prev_height = |h| {
+            sim.prev_consensus_state(&client_id, &Height::new(0, h))
+                .unwrap()
+                .map(|(h, _)| h.revision_height())
+        };
+        assert_eq!(prev_height(3), None);
+        assert_eq!(prev_height(5), Some(3));
+        assert_eq!(prev_height(100), Some(7));
+
+        // the removed consensus state is skipped
+        sim.remove_consensus_state(&client_id, &Height::new(0, 5))
+            .unwrap();
+        assert_eq!(
+            sim.next_consensus_state(&client_id, &Height::new(0, 3))
+                .unwrap()
+                .map(|(h, _)| h),
+            Some(Height::new(0, 7))
+        );
+    }
+}
@@ -1,14 +1,24 @@
 use crate::types::{Any, ClientId, Height, Time};
 use crate::{
     errors::Error,
-    path::{ClientConsensusStatePath, ClientStatePath, ClientTypePath},
+    path::{
+        ClientConsensusHeightsIndexedPath, ClientConsensusHeightsPath, ClientConsensusStatePath,
+        ClientConsensusStatesPath, ClientStatePath, ClientTypePath,
+    },
     prelude::*,
 };
-use store::KVStore;
+use store::{prefix_end, KVPair, KVStore, Order};
 
 pub trait HostContext {
     /// Returns the current timestamp of the local.
     fn host_timestamp(&self) -> Time;
+
+    /// Returns the current height of the host chain if it is known.
+    ///
+    /// The enclave doesn't know the height of the counterparty chain, so it returns None by default.
+    fn host_height(&self) -> Option<Height> {
+        None
+    }
 }
 
 pub trait ClientReader: KVStore {
@@ -60,6 +70,75 @@ pub trait ClientReader: KVStore {
                 .0,
         )
     }
+
+    /// Returns at most `limit` heights of the consensus states of the client in ascending order.
+    ///
+    /// Like `next_consensus_state`, the consensus states that are not in the height index are not returned.
+    fn consensus_heights(
+        &self,
+        client_id: &ClientId,
+        limit: Option<usize>,
+    ) -> Result<Vec<Height>, Error> {
+        let path = ClientConsensusHeightsPath::new(client_id);
+        self.prefix_range(format!("{}", path).as_bytes(), Order::Ascending, limit)
+            .into_iter()
+            .map(|(key, _)| {
+                path.parse_key(&key)
+                    .ok_or_else(|| Error::invalid_consensus_height_key(key))
+            })
+            .collect()
+    }
+
+    /// Returns the consensus state with the lowest height greater than `height`
+    /// for the given client ID.
+    ///
+    /// The consensus states are looked up via the height index, so the ones stored before the index was introduced
+    /// are not found until `ClientKeeper::index_consensus_heights` is called for the client.
+    fn next_consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: &Height,
+    ) -> Result<Option<(Height, Any)>, Error> {
+        let path = ClientConsensusHeightsPath::new(client_id);
+        // the smallest key that is greater than the key of `height`
+        let mut start = path.key(height);
+        start.push(0);
+        let end = prefix_end(format!("{}", path).as_bytes());
+        let keys = self.range(Some(&start), end.as_deref(), Order::Ascending, Some(1));
+        indexed_consensus_state(self, client_id, &path, keys.first())
+    }
+
+    /// Returns the consensus state with the highest height less than `height`
+    /// for the given client ID.
+    fn prev_consensus_state(
+        &self,
+        client_id: &ClientId,
+        height: &Height,
+    ) -> Result<Option<(Height, Any)>, Error> {
+        let path = ClientConsensusHeightsPath::new(client_id);
+        let start = format!("{}", path).into_bytes();
+        let end = path.key(height);
+        let keys = self.range(Some(&start), Some(&end), Order::Descending, Some(1));
+        indexed_consensus_state(self, client_id, &path, keys.first())
+    }
+}
+
+/// Returns the consensus state corresponding to the given height index entry
+fn indexed_consensus_state<R: ClientReader + ?Sized>(
+    reader: &R,
+    client_id: &ClientId,
+    path: &ClientConsensusHeightsPath,
+    index: Option<&KVPair>,
+) -> Result<Option<(Height, Any)>, Error> {
+    match index {
+        Some((key, _)) => {
+            let height = path
+                .parse_key(key)
+                .ok_or_else(|| Error::invalid_consensus_height_key(key.clone()))?;
+            Ok(Some((height, reader.consensus_state(client_id, &height)?)))
+        }
+        None => Ok(None),
+    }
 }
 
 pub trait ClientKeeper: ClientReader {
@@ -97,6 +176,49 @@ pub trait ClientKeeper: ClientReader {
             bincode::serde::encode_to_vec(&consensus_state, bincode::config::standard()).unwrap();
         let path = ClientConsensusStatePath::new(&client_id, &height);
         self.set(format!("{}", path).into_bytes(), bz);
+        self.set(
+            ClientConsensusHeightsPath::new(&client_id).key(&height),
+            vec![],
+        );
+        Ok(())
+    }
+
+    /// Adds the consensus states stored before the height index was introduced to the index
+    ///
+    /// This scans all the consensus states of the client at the first call and records that the client has been indexed,
+    /// so the subsequent calls only read the marker. It returns the number of the heights added to the index.
+    fn index_consensus_heights(&mut self, client_id: &ClientId) -> Result<usize, Error> {
+        let marker = format!("{}", ClientConsensusHeightsIndexedPath::new(client_id)).into_bytes();
+        if self.get(&marker).is_some() {
+            return Ok(0);
+        }
+        let states = ClientConsensusStatesPath::new(client_id);
+        let index = ClientConsensusHeightsPath::new(client_id);
+        let mut n = 0;
+        for (key, _) in self.prefix_range(format!("{}", states).as_bytes(), Order::Ascending, None)
+        {
+            let height = states
+                .parse_key(&key)
+                .ok_or_else(|| Error::invalid_consensus_state_key(key.clone()))?;
+            let key = index.key(&height);
+            if self.get(&key).is_none() {
+                self.set(key, vec![]);
+                n += 1;
+            }
+        }
+        self.set(marker, vec![]);
+        Ok(n)
+    }
+
+    /// Called upon pruning of the consensus state
+    fn remove_consensus_state(
+        &mut self,
+        client_id: &ClientId,
+        height: &Height,
+    ) -> Result<(), Error> {
+        let path = ClientConsensusStatePath::new(client_id, height);
+        self.remove(format!("{}", path).as_bytes());
+        self.remove(&ClientConsensusHeightsPath::new(client_id).key(height));
         Ok(())
     }
 }
@@ -104,3 +226,150 @@ pub trait ClientKeeper: ClientReader {
 pub trait HostClientReader: HostContext + ClientReader {}
 
 pub trait HostClientKeeper: HostClientReader + HostContext + ClientKeeper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::collections::BTreeMap;
+    use core::str::FromStr;
+    use store::btree_range;
+
+    #[derive(Default)]
+    struct TestContext(BTreeMap<Vec<u8>, Vec<u8>>);
+
+    impl KVStore for TestContext {
+        fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+            self.0.insert(key, value);
+        }
+
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.0.get(key).cloned()
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.0.remove(key);
+        }
+
+        fn range(
+            &self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+            limit: Option<usize>,
+        ) -> Vec<KVPair> {
+            btree_range(&self.0, start, end, order, limit)
+        }
+    }
+
+    impl ClientReader for TestContext {}
+    impl ClientKeeper for TestContext {}
+
+    fn consensus_state(h: u64) -> Any {
+        Any::new("/test.ConsensusState".into(), h.to_be_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_next_and_prev_consensus_state() {
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let mut ctx = TestContext::default();
+        let heights = [
+            Height::new(0, 5),
+            Height::new(0, 10),
+            Height::new(1, 1),
+            Height::new(1, 2),
+            Height::new(2, 0),
+        ];
+        for (i, height) in heights.iter().enumerate() {
+            ctx.store_any_consensus_state(client_id.clone(), *height, consensus_state(i as u64))
+                .unwrap();
+        }
+        // a consensus state of another client must not be returned
+        let other = ClientId::from_str("07-tendermint-1").unwrap();
+        ctx.store_any_consensus_state(other, Height::new(1, 0), consensus_state(100))
+            .unwrap();
+
+        let next = |ctx: &TestContext, h: Height| {
+            ctx.next_consensus_state(&client_id, &h)
+                .unwrap()
+                .map(|(h, _)| h)
+        };
+        let prev = |ctx: &TestContext, h: Height| {
+            ctx.prev_consensus_state(&client_id, &h)
+                .unwrap()
+                .map(|(h, _)| h)
+        };
+        assert_eq!(next(&ctx, Height::new(0, 1)), Some(Height::new(0, 5)));
+        assert_eq!(next(&ctx, Height::new(0, 5)), Some(Height::new(0, 10)));
+        assert_eq!(next(&ctx, Height::new(0, 10)), Some(Height::new(1, 1)));
+        assert_eq!(next(&ctx, Height::new(0, 100)), Some(Height::new(1, 1)));
+        assert_eq!(next(&ctx, Height::new(1, 2)), Some(Height::new(2, 0)));
+        assert_eq!(next(&ctx, Height::new(2, 0)), None);
+        assert_eq!(prev(&ctx, Height::new(0, 5)), None);
+        assert_eq!(prev(&ctx, Height::new(1, 1)), Some(Height::new(0, 10)));
+        assert_eq!(prev(&ctx, Height::new(1, 0)), Some(Height::new(0, 10)));
+        assert_eq!(prev(&ctx, Height::new(2, 0)), Some(Height::new(1, 2)));
+        assert_eq!(prev(&ctx, Height::new(3, 0)), Some(Height::new(2, 0)));
+        let (_, cs) = ctx
+            .next_consensus_state(&client_id, &Height::new(0, 10))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cs, consensus_state(2));
+
+        ctx.remove_consensus_state(&client_id, &Height::new(1, 1))
+            .unwrap();
+        assert_eq!(next(&ctx, Height::new(0, 10)), Some(Height::new(1, 2)));
+        assert_eq!(prev(&ctx, Height::new(1, 2)), Some(Height::new(0, 10)));
+        assert_eq!(
+            ctx.consensus_heights(&client_id, None).unwrap(),
+            vec![
+                Height::new(0, 5),
+                Height::new(0, 10),
+                Height::new(1, 2),
+                Height::new(2, 0)
+            ]
+        );
+        assert_eq!(
+            ctx.consensus_heights(&client_id, Some(1)).unwrap(),
+            vec![Height::new(0, 5)]
+        );
+    }
+
+    #[test]
+    fn test_index_consensus_heights() {
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let mut ctx = TestContext::default();
+        // the consensus states stored before the height index was introduced
+        for h in [1, 2, 10] {
+            let path = ClientConsensusStatePath::new(&client_id, &Height::new(0, h));
+            ctx.set(
+                format!("{}", path).into_bytes(),
+                bincode::serde::encode_to_vec(&consensus_state(h), bincode::config::standard())
+                    .unwrap(),
+            );
+        }
+        ctx.store_any_consensus_state(client_id.clone(), Height::new(0, 11), consensus_state(11))
+            .unwrap();
+        assert_eq!(
+            ctx.next_consensus_state(&client_id, &Height::new(0, 0))
+                .unwrap()
+                .map(|(h, _)| h),
+            Some(Height::new(0, 11))
+        );
+
+        assert_eq!(ctx.index_consensus_heights(&client_id).unwrap(), 3);
+        assert_eq!(
+            ctx.next_consensus_state(&client_id, &Height::new(0, 0))
+                .unwrap()
+                .map(|(h, _)| h),
+            Some(Height::new(0, 1))
+        );
+        assert_eq!(
+            ctx.next_consensus_state(&client_id, &Height::new(0, 2))
+                .unwrap()
+                .map(|(h, _)| h),
+            Some(Height::new(0, 10))
+        );
+        // the client is indexed only once
+        assert_eq!(ctx.index_consensus_heights(&client_id).unwrap(), 0);
+    }
+}
@@ -34,6 +34,22 @@ define_error! {
             format_args!("consensus_state not found: client_id={} height={}", e.client_id, e.height)
         },
 
+        InvalidConsensusStateKey
+        {
+            key: Vec<u8>
+        }
+        |e| {
+            format_args!("invalid consensus state key: key={:?}", e.key)
+        },
+
+        InvalidConsensusHeightKey
+        {
+            key: Vec<u8>
+        }
+        |e| {
+            format_args!("invalid consensus height index key: key={:?}", e.key)
+        },
+
         LightClientSpecific
         [TraceError<Box<dyn LightClientSpecificError>>]
         |_| { "Light Client specific error" }
@@ -81,7 +81,12 @@ impl<'a, ClientState: Ics02ClientState, ConsensusState: Ics02ConsensusState> Val
         {
             Ok(any_consensus_state) => {
                 let any_consensus_state = ProtoAny::from(any_consensus_state);
-                let consensus_state = ConsensusState::try_from(any_consensus_state).unwrap();
+                let consensus_state =
+                    ConsensusState::try_from(any_consensus_state).map_err(|e| {
+                        ContextError::ClientError(ClientError::ClientSpecific {
+                            description: e.to_string(),
+                        })
+                    })?;
                 Ok(consensus_state.into_box())
             }
             Err(e) => match e.detail() {
@@ -106,7 +111,27 @@ impl<'a, ClientState: Ics02ClientState, ConsensusState: Ics02ConsensusState> Val
         Option<alloc::boxed::Box<dyn ibc::core::ics02_client::consensus_state::ConsensusState>>,
         ibc::core::ContextError,
     > {
-        Ok(None)
+        let height = Height::new(height.revision_number(), height.revision_height());
+        match self
+            .parent
+            .next_consensus_state(&client_id.clone().into(), &height)
+            .map_err(|e| {
+                ContextError::ClientError(ClientError::ClientSpecific {
+                    description: e.to_string(),
+                })
+            })? {
+            Some((_, any_consensus_state)) => {
+                let any_consensus_state = ProtoAny::from(any_consensus_state);
+                let consensus_state =
+                    ConsensusState::try_from(any_consensus_state).map_err(|e| {
+                        ContextError::ClientError(ClientError::ClientSpecific {
+                            description: e.to_string(),
+                        })
+                    })?;
+                Ok(Some(consensus_state.into_box()))
+            }
+            None => Ok(None),
+        }
     }
 
     fn prev_consensus_state(
@@ -117,7 +142,27 @@ impl<'a, ClientState: Ics02ClientState, ConsensusState: Ics02ConsensusState> Val
         Option<alloc::boxed::Box<dyn ibc::core::ics02_client::consensus_state::ConsensusState>>,
         ibc::core::ContextError,
     > {
-        Ok(None)
+        let height = Height::new(height.revision_number(), height.revision_height());
+        match self
+            .parent
+            .prev_consensus_state(&client_id.clone().into(), &height)
+            .map_err(|e| {
+                ContextError::ClientError(ClientError::ClientSpecific {
+                    description: e.to_string(),
+                })
+            })? {
+            Some((_, any_consensus_state)) => {
+                let any_consensus_state = ProtoAny::from(any_consensus_state);
+                let consensus_state =
+                    ConsensusState::try_from(any_consensus_state).map_err(|e| {
+                        ContextError::ClientError(ClientError::ClientSpecific {
+                            description: e.to_string(),
+                        })
+                    })?;
+                Ok(Some(consensus_state.into_box()))
+            }
+            None => Ok(None),
+        }
     }
 
     fn host_height(&self) -> Result<ibc::Height, ibc::core::ContextError> {
@@ -1,3 +1,4 @@
+use crate::prelude::*;
 use crate::types::{ClientId, Height};
 use derive_more::Display;
 
@@ -38,3 +39,110 @@ impl ClientConsensusStatePath {
         }
     }
 }
+
+/// `ClientConsensusStatesPath` is the prefix of the consensus states of a client
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display("clients/{_0}/consensusStates/")]
+pub struct ClientConsensusStatesPath(pub ClientId);
+
+impl ClientConsensusStatesPath {
+    pub fn new(client_id: &ClientId) -> ClientConsensusStatesPath {
+        ClientConsensusStatesPath(client_id.clone())
+    }
+
+    /// Returns the height of the given consensus state key
+    pub fn parse_key(&self, key: &[u8]) -> Option<Height> {
+        let height = key.strip_prefix(format!("{}", self).as_bytes())?;
+        let (epoch, height) = core::str::from_utf8(height).ok()?.split_once('-')?;
+        Some(Height::new(epoch.parse().ok()?, height.parse().ok()?))
+    }
+}
+
+/// `ClientConsensusHeightsPath` is the prefix of the height index of the consensus states of a client
+///
+/// Each height is encoded in big-endian after the prefix, so the keys are ordered by height.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display("clients/{_0}/consensusHeights/")]
+pub struct ClientConsensusHeightsPath(pub ClientId);
+
+impl ClientConsensusHeightsPath {
+    pub fn new(client_id: &ClientId) -> ClientConsensusHeightsPath {
+        ClientConsensusHeightsPath(client_id.clone())
+    }
+
+    /// Returns the index key of the given height
+    pub fn key(&self, height: &Height) -> Vec<u8> {
+        let mut key = format!("{}", self).into_bytes();
+        key.extend(height.revision_number().to_be_bytes());
+        key.extend(height.revision_height().to_be_bytes());
+        key
+    }
+
+    /// Returns the height of the given index key
+    pub fn parse_key(&self, key: &[u8]) -> Option<Height> {
+        let height = key.strip_prefix(format!("{}", self).as_bytes())?;
+        if height.len() != 16 {
+            return None;
+        }
+        Some(Height::new(
+            u64::from_be_bytes(height[..8].try_into().unwrap()),
+            u64::from_be_bytes(height[8..].try_into().unwrap()),
+        ))
+    }
+}
+
+/// `ClientConsensusHeightsIndexedPath` is the path of the marker that indicates the consensus states stored before the height index have been indexed
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[display("clients/{_0}/consensusHeightsIndexed")]
+pub struct ClientConsensusHeightsIndexedPath(pub ClientId);
+
+impl ClientConsensusHeightsIndexedPath {
+    pub fn new(client_id: &ClientId) -> ClientConsensusHeightsIndexedPath {
+        ClientConsensusHeightsIndexedPath(client_id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::str::FromStr;
+
+    #[test]
+    fn test_consensus_heights_path() {
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let path = ClientConsensusHeightsPath::new(&client_id);
+        let heights = [
+            Height::new(0, 1),
+            Height::new(0, 2),
+            Height::new(0, 10),
+            Height::new(0, 256),
+            Height::new(1, 0),
+            Height::new(1, 3),
+        ];
+        for h in heights.iter() {
+            assert_eq!(path.parse_key(&path.key(h)), Some(*h));
+        }
+        // the keys are ordered by height across revisions
+        for w in heights.windows(2) {
+            assert!(path.key(&w[0]) < path.key(&w[1]), "{} {}", w[0], w[1]);
+        }
+
+        let mut key = path.key(&Height::new(0, 1));
+        key.push(0);
+        assert_eq!(path.parse_key(&key), None);
+        let other =
+            ClientConsensusHeightsPath::new(&ClientId::from_str("07-tendermint-1").unwrap());
+        assert_eq!(other.parse_key(&path.key(&Height::new(0, 1))), None);
+    }
+
+    #[test]
+    fn test_consensus_states_path() {
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let path = ClientConsensusStatesPath::new(&client_id);
+        let height = Height::new(1, 20);
+        let key = format!("{}", ClientConsensusStatePath::new(&client_id, &height));
+        assert_eq!(path.parse_key(key.as_bytes()), Some(height));
+        assert_eq!(path.parse_key(format!("{}1", path).as_bytes()), None);
+        assert_eq!(path.parse_key(format!("{}1-x", path).as_bytes()), None);
+    }
+}
@@ -64,6 +64,7 @@ impl LightClient for MockLightClient {
                 timestamp,
                 context: ValidationContext::Empty,
                 emitted_states: vec![EmittedState(height, any_client_state)],
+                emitted_states_root: None,
             }
             .into(),
             prove: false,
@@ -189,6 +190,7 @@ impl MockLightClient {
                 timestamp: header_timestamp,
                 context: ValidationContext::Empty,
                 emitted_states: vec![EmittedState(height, new_any_client_state)],
+                emitted_states_root: None,
             },
             prove: true,
         })
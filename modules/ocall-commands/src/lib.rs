@@ -2,7 +2,7 @@
 #![allow(clippy::large_enum_variant)]
 extern crate alloc;
 pub use crate::log::LogCommand;
-pub use crate::store::{StoreCommand, StoreResult};
+pub use crate::store::{StoreCommand, StoreResult, RANGE_RESULT_MAX_SIZE};
 use serde::{Deserialize, Serialize};
 
 mod log;
@@ -1,12 +1,15 @@
 use alloc::vec::Vec;
 use serde::{Deserialize, Serialize};
-use store::TxId;
+use store::{KVPair, Order, TxId, WriteBatch};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum StoreCommand {
     Get(TxId, Vec<u8>),
     Set(TxId, Vec<u8>, Vec<u8>),
     Remove(TxId, Vec<u8>),
+    /// Range(tx_id, start, end, order, limit)
+    Range(TxId, Option<Vec<u8>>, Option<Vec<u8>>, Order, Option<usize>),
+    WriteBatch(TxId, WriteBatch),
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -14,4 +17,40 @@ pub enum StoreResult {
     Get(Option<Vec<u8>>),
     Set,
     Remove,
+    /// Range(pairs, truncated)
+    ///
+    /// `truncated` is true if the pairs are truncated to fit in `RANGE_RESULT_MAX_SIZE`,
+    /// so the caller needs to fetch the rest by another command.
+    Range(Vec<KVPair>, bool),
+    WriteBatch,
+}
+
+/// The maximum size in bytes of the key-value pairs in a `StoreResult::Range`
+///
+/// The enclave receives the result of an ocall in a 64 KiB buffer, so the rest is left for the encoding.
+pub const RANGE_RESULT_MAX_SIZE: usize = 60 * 1024;
+
+/// The upper bound of the encoded length prefixes of a key-value pair
+const RANGE_PAIR_OVERHEAD: usize = 2 * 9;
+
+impl StoreResult {
+    /// new_range returns `StoreResult::Range` with the longest prefix of `pairs` that fits in `RANGE_RESULT_MAX_SIZE`
+    ///
+    /// It returns None if the first pair does not fit.
+    pub fn new_range(mut pairs: Vec<KVPair>) -> Option<Self> {
+        let mut size = 0;
+        let len = pairs
+            .iter()
+            .take_while(|(key, value)| {
+                size += key.len() + value.len() + RANGE_PAIR_OVERHEAD;
+                size <= RANGE_RESULT_MAX_SIZE
+            })
+            .count();
+        if len == 0 && !pairs.is_empty() {
+            return None;
+        }
+        let truncated = len < pairs.len();
+        pairs.truncate(len);
+        Some(StoreResult::Range(pairs, truncated))
+    }
 }
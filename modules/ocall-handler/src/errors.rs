@@ -1,4 +1,5 @@
 use flex_error::*;
+use host_environment::store::TxId;
 use sgx_types::sgx_status_t;
 
 pub type Result<T> = core::result::Result<T, Error>;
@@ -18,6 +19,14 @@ define_error! {
         [host_environment::store::Error]
         |_| { "Store error" },
 
+        RangeEntryTooLarge {
+            tx_id: TxId,
+            key: Vec<u8>
+        }
+        |e| {
+            format_args!("the key-value pair does not fit in the range result: tx_id={} key={:?}", e.tx_id, e.key)
+        },
+
         Io
         [TraceError<std::io::Error>]
         |_| { "I/O error" },
@@ -1,4 +1,4 @@
-use crate::errors::Result;
+use crate::errors::{Error, Result};
 use host_environment::store::transaction::TxAccessor;
 use host_environment::Environment;
 use log::*;
@@ -36,6 +36,26 @@ pub fn dispatch(env: &Environment, command: StoreCommand) -> Result<StoreResult>
             env.get_mut_store().tx_remove(tx_id, &key)?;
             StoreResult::Remove
         }
+        StoreCommand::Range(tx_id, start, end, order, limit) => {
+            debug!(
+                "Range: tx_id={} start={:?} end={:?} order={:?} limit={:?}",
+                tx_id, start, end, order, limit
+            );
+            let pairs =
+                env.get_store()
+                    .tx_range(tx_id, start.as_deref(), end.as_deref(), order, limit)?;
+            let first_key = pairs
+                .first()
+                .map(|(key, _)| key.clone())
+                .unwrap_or_default();
+            StoreResult::new_range(pairs)
+                .ok_or_else(|| Error::range_entry_too_large(tx_id, first_key))?
+        }
+        StoreCommand::WriteBatch(tx_id, batch) => {
+            debug!("WriteBatch: tx_id={} len={}", tx_id, batch.len());
+            env.get_mut_store().tx_write_batch(tx_id, batch)?;
+            StoreResult::WriteBatch
+        }
     };
     Ok(res)
 }
@@ -198,7 +198,7 @@ pub(crate) fn dcap_ra_simulation(
     })
 }
 
-pub(crate) fn simulate_gen_quote_and_collaterals(
+pub fn simulate_gen_quote_and_collaterals(
     isv_enclave_report: &sgx_report_t,
     opts: DCAPRASimulationOpts,
 ) -> Result<(QuoteV3, QvCollateral), Error> {
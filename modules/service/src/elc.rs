@@ -4,10 +4,11 @@ use lcp_proto::google::protobuf::Any;
 use lcp_proto::lcp::service::elc::v1::msg_update_client_stream_chunk::Chunk;
 use lcp_proto::lcp::service::elc::v1::{
     msg_server::Msg, query_server::Query, MsgAggregateMessages, MsgAggregateMessagesResponse,
-    MsgCreateClient, MsgCreateClientResponse, MsgUpdateClient, MsgUpdateClientResponse,
-    MsgUpdateClientStreamChunk, MsgVerifyMembership, MsgVerifyMembershipResponse,
-    MsgVerifyNonMembership, MsgVerifyNonMembershipResponse, QueryClientRequest,
-    QueryClientResponse,
+    MsgCountersignUpdateClient, MsgCountersignUpdateClientResponse, MsgCreateClient,
+    MsgCreateClientResponse, MsgUpdateClient, MsgUpdateClientResponse, MsgUpdateClientStreamChunk,
+    MsgVerifyMembership, MsgVerifyMembershipBatch, MsgVerifyMembershipBatchResponse,
+    MsgVerifyMembershipResponse, MsgVerifyNonMembership, MsgVerifyNonMembershipResponse,
+    QueryClientRequest, QueryClientResponse,
 };
 use store::transaction::CommitStore;
 use tonic::{Request, Response, Status, Streaming};
@@ -93,6 +94,9 @@ where
                 type_url: init.type_url,
                 value: header_bytes,
             }),
+            domain: init.domain,
+            encoding: init.encoding,
+            commit_emitted_states_root: init.commit_emitted_states_root,
         };
 
         match self.enclave.proto_update_client(msg) {
@@ -133,6 +137,32 @@ where
             Err(e) => Err(Status::aborted(e.to_string())),
         }
     }
+
+    async fn verify_membership_batch(
+        &self,
+        request: Request<MsgVerifyMembershipBatch>,
+    ) -> Result<Response<MsgVerifyMembershipBatchResponse>, Status> {
+        match self
+            .enclave
+            .proto_verify_membership_batch(request.into_inner())
+        {
+            Ok(res) => Ok(Response::new(res)),
+            Err(e) => Err(Status::aborted(e.to_string())),
+        }
+    }
+
+    async fn countersign_update_client(
+        &self,
+        request: Request<MsgCountersignUpdateClient>,
+    ) -> Result<Response<MsgCountersignUpdateClientResponse>, Status> {
+        match self
+            .enclave
+            .proto_countersign_update_client(request.into_inner())
+        {
+            Ok(res) => Ok(Response::new(res)),
+            Err(e) => Err(Status::aborted(e.to_string())),
+        }
+    }
 }
 
 #[tonic::async_trait]
@@ -0,0 +1,198 @@
+//! Compares the number of ocalls issued by the enclave-side stores during light client updates
+//!
+//! Each call to the parent store below corresponds to one `StoreCommand` ocall in the enclave.
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use store::authenticated::{AuthenticatedKVS, Hash, RootSealer, RootTable};
+use store::cache::{CacheKVS, WriteBackKVS};
+use store::{btree_range, KVPair, KVStore, Order, Result, TxKVStore, WriteBatch};
+
+const CLIENT_PREFIX: &str = "clients/07-tendermint-0/";
+const UPDATES: u64 = 100;
+
+#[derive(Default)]
+struct OcallCounts {
+    get: usize,
+    set: usize,
+    remove: usize,
+    range: usize,
+    write_batch: usize,
+}
+
+impl OcallCounts {
+    fn total(&self) -> usize {
+        self.get + self.set + self.remove + self.range + self.write_batch
+    }
+}
+
+/// `HostStore` is the host's store that counts the ocalls to it
+#[derive(Default)]
+struct HostStore {
+    db: BTreeMap<Vec<u8>, Vec<u8>>,
+    counts: OcallCounts,
+}
+
+impl KVStore for HostStore {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.counts.set += 1;
+        self.db.insert(key, value);
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.db.get(key).cloned()
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.counts.remove += 1;
+        self.db.remove(key);
+    }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        btree_range(&self.db, start, end, order, limit)
+    }
+
+    fn write_batch(&mut self, batch: WriteBatch) {
+        self.counts.write_batch += 1;
+        for (key, value) in batch {
+            match value {
+                Some(value) => self.db.insert(key, value),
+                None => self.db.remove(&key),
+            };
+        }
+    }
+}
+
+/// `Ocalls` counts the reads because `KVStore::get` and `KVStore::range` take `&self`
+struct Ocalls(Rc<RefCell<HostStore>>);
+
+impl KVStore for Ocalls {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.0.set(key, value)
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.0.borrow_mut().counts.get += 1;
+        self.0.get(key)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.0.remove(key)
+    }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.0.borrow_mut().counts.range += 1;
+        self.0.range(start, end, order, limit)
+    }
+
+    fn write_batch(&mut self, batch: WriteBatch) {
+        self.0.write_batch(batch)
+    }
+}
+
+struct NopSealer;
+
+impl RootSealer for NopSealer {
+    fn seal(&self, digest: &Hash) -> Result<Vec<u8>> {
+        Ok(digest.to_vec())
+    }
+
+    fn unseal(&self, sealed: &[u8]) -> Result<Hash> {
+        Ok(sealed.try_into().unwrap())
+    }
+}
+
+fn key(path: &str) -> Vec<u8> {
+    format!("{}{}", CLIENT_PREFIX, path).into_bytes()
+}
+
+fn height_key(height: u64) -> Vec<u8> {
+    let mut key = key("consensusHeights/");
+    key.extend(0u64.to_be_bytes());
+    key.extend(height.to_be_bytes());
+    key
+}
+
+/// `update_client` performs the store operations of a light client update at `height`
+fn update_client(store: &mut dyn KVStore, height: u64) {
+    let client_state = store.get(&key("clientState")).unwrap_or_default();
+    store.get(&key(&format!("consensusStates/0-{}", height - 1)));
+    // the previous and next consensus states for the monotonicity check
+    store.range(
+        Some(&key("consensusHeights/")),
+        Some(&height_key(height)),
+        Order::Descending,
+        Some(1),
+    );
+    store.range(
+        Some(&height_key(height + 1)),
+        store::prefix_end(&key("consensusHeights/")).as_deref(),
+        Order::Ascending,
+        Some(1),
+    );
+    store.set(key("clientState"), [client_state, vec![0; 8]].concat());
+    store.set(key(&format!("consensusStates/0-{}", height)), vec![0; 128]);
+    store.set(height_key(height), vec![]);
+}
+
+fn run(name: &str, new_store: impl Fn(Ocalls) -> Box<dyn TxKVStore>) {
+    let host = Rc::new(RefCell::new(HostStore::default()));
+    for height in 1..=UPDATES {
+        let mut store = new_store(Ocalls(host.clone()));
+        update_client(store.as_mut(), height);
+        store.finalize(true).unwrap();
+    }
+    let counts = &host.borrow().counts;
+    println!(
+        "{:<32} {:>13.2} {:>6} {:>6} {:>6} {:>6} {:>11}",
+        name,
+        counts.total() as f64 / UPDATES as f64,
+        counts.get,
+        counts.set,
+        counts.remove,
+        counts.range,
+        counts.write_batch,
+    );
+}
+
+fn main() {
+    println!(
+        "{:<32} {:>13} {:>6} {:>6} {:>6} {:>6} {:>11}",
+        "store", "ocalls/update", "get", "set", "remove", "range", "write_batch"
+    );
+    run("write-through", |ocalls| {
+        Box::new(Box::new(CacheKVS::new(ocalls)) as Box<dyn KVStore>)
+    });
+    run("write-back", |ocalls| Box::new(WriteBackKVS::new(ocalls)));
+
+    let registry: &'static RefCell<RootTable> = Box::leak(Default::default());
+    run("authenticated + write-through", |ocalls| {
+        Box::new(AuthenticatedKVS::new(
+            Box::new(CacheKVS::new(ocalls)) as Box<dyn KVStore>,
+            registry,
+            &NopSealer,
+            Default::default(),
+        ))
+    });
+    let registry: &'static RefCell<RootTable> = Box::leak(Default::default());
+    run("authenticated + write-back", |ocalls| {
+        Box::new(AuthenticatedKVS::new(
+            WriteBackKVS::new(ocalls),
+            registry,
+            &NopSealer,
+            Default::default(),
+        ))
+    });
+}
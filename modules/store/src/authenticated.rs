@@ -0,0 +1,1233 @@
+use crate::prelude::*;
+use crate::{Error, KVPair, KVStore, Order, Result, TxKVStore};
+use alloc::collections::BTreeMap;
+use core::cell::RefCell;
+use sha2::{Digest, Sha256};
+
+/// `Hash` is a SHA-256 digest
+pub type Hash = [u8; 32];
+
+/// The prefix of the keys reserved by the authenticated store
+pub const AUTHENTICATED_KEY_PREFIX: &[u8] = b"authenticated/";
+const NODE_KEY_PREFIX: &[u8] = b"authenticated/nodes/";
+const ROOT_KEY_PREFIX: &[u8] = b"authenticated/roots/";
+const CLIENT_KEY_PREFIX: &[u8] = b"clients/";
+
+/// `RootRecord` is a root of a tree with a version that is incremented on every update
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootRecord {
+    pub tree_id: Vec<u8>,
+    pub version: u64,
+    /// None means that the tree is empty
+    pub root: Option<Hash>,
+}
+
+impl RootRecord {
+    pub fn empty(tree_id: Vec<u8>) -> Self {
+        Self {
+            tree_id,
+            version: 0,
+            root: None,
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bz = Vec::new();
+        put_bytes(&mut bz, &self.tree_id);
+        bz.extend(self.version.to_be_bytes());
+        match self.root {
+            Some(root) => {
+                bz.push(1);
+                bz.extend(root);
+            }
+            None => bz.push(0),
+        }
+        bz
+    }
+
+    fn from_bytes(bz: &[u8]) -> Result<Self> {
+        let mut r = Reader(bz);
+        let tree_id = r.bytes()?.to_vec();
+        let version = r.u64()?;
+        let root = match r.take(1)?[0] {
+            0 => None,
+            1 => Some(r.hash()?),
+            flag => {
+                return Err(Error::invalid_authenticated_data(format!(
+                    "unknown root flag: {}",
+                    flag
+                )))
+            }
+        };
+        r.finish()?;
+        Ok(Self {
+            tree_id,
+            version,
+            root,
+        })
+    }
+}
+
+/// `RootSealer` seals a digest with a key that only the enclave can derive
+pub trait RootSealer {
+    fn seal(&self, digest: &Hash) -> Result<Vec<u8>>;
+    fn unseal(&self, sealed: &[u8]) -> Result<Hash>;
+}
+
+/// `RootRegistry` keeps the latest root of each tree in a storage that the host cannot modify
+///
+/// NOTE: The registry of the enclave lives only in the enclave memory, so it is lost when the enclave restarts.
+///
+/// A new root is kept as pending until it is found in the host's store,
+/// because the enclave is not notified whether the host commits the transaction that writes it.
+pub trait RootRegistry {
+    /// `load` returns the latest root of the tree after checking that `stored`, the root read from the host's store, is consistent with it
+    ///
+    /// If `stored` is a pending root, it becomes the latest one. If the tree is unknown, `stored` is trusted as the latest one
+    /// whatever its version is, so a rollback before the tree is loaded for the first time is not detected.
+    fn load(&self, stored: RootRecord) -> Result<RootRecord>;
+
+    /// `prepare` registers each new record as a pending successor of the corresponding base record
+    ///
+    /// It fails if any of the base records is not the latest one.
+    fn prepare(&self, updates: Vec<(RootRecord, RootRecord)>) -> Result<()>;
+}
+
+/// The maximum number of the pending roots kept per tree
+///
+/// A root remains pending if the host fails to commit the transaction, so the oldest ones are discarded.
+pub const MAX_PENDING_ROOTS: usize = 8;
+
+/// `RootTable` is an in-memory table of the latest and pending roots
+#[derive(Debug, Default)]
+pub struct RootTable(BTreeMap<Vec<u8>, TreeRoots>);
+
+/// `TreeRoots` is the latest root of a tree and the pending roots that may replace it
+#[derive(Debug)]
+pub struct TreeRoots {
+    pub latest: RootRecord,
+    pub pending: Vec<RootRecord>,
+}
+
+impl RootTable {
+    pub fn get(&self, tree_id: &[u8]) -> Option<&TreeRoots> {
+        self.0.get(tree_id)
+    }
+
+    pub fn load(&mut self, stored: RootRecord) -> Result<RootRecord> {
+        let roots = self
+            .0
+            .entry(stored.tree_id.clone())
+            .or_insert_with(|| TreeRoots {
+                latest: stored.clone(),
+                pending: vec![],
+            });
+        if stored == roots.latest {
+            return Ok(stored);
+        } else if roots.pending.contains(&stored) {
+            roots.latest = stored.clone();
+            roots.pending.clear();
+            return Ok(stored);
+        }
+        if stored.version != roots.latest.version {
+            Err(Error::root_version_mismatch(
+                stored.tree_id,
+                roots.latest.version,
+                stored.version,
+            ))
+        } else {
+            Err(Error::invalid_authenticated_data(format!(
+                "the root stored in the host is different from the latest one: tree_id={}",
+                String::from_utf8_lossy(&stored.tree_id)
+            )))
+        }
+    }
+
+    pub fn prepare(&mut self, updates: Vec<(RootRecord, RootRecord)>) -> Result<()> {
+        for (base, new) in updates.iter() {
+            if self.0.get(&base.tree_id).map(|roots| &roots.latest) != Some(base)
+                || new.tree_id != base.tree_id
+                || new.version != base.version + 1
+            {
+                return Err(Error::root_conflict(base.tree_id.clone(), base.version));
+            }
+        }
+        for (_, new) in updates {
+            let roots = self.0.get_mut(&new.tree_id).unwrap();
+            if roots.pending.len() >= MAX_PENDING_ROOTS {
+                roots.pending.remove(0);
+            }
+            roots.pending.push(new);
+        }
+        Ok(())
+    }
+}
+
+impl RootRegistry for RefCell<RootTable> {
+    fn load(&self, stored: RootRecord) -> Result<RootRecord> {
+        self.borrow_mut().load(stored)
+    }
+
+    fn prepare(&self, updates: Vec<(RootRecord, RootRecord)>) -> Result<()> {
+        self.borrow_mut().prepare(updates)
+    }
+}
+
+/// `AuthenticationConfig` is the configuration of `AuthenticatedKVS`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AuthenticationConfig {
+    /// If true, the values written before enabling the authentication are added to the tree
+    /// when the tree is accessed for the first time.
+    ///
+    /// NOTE: This trusts the values stored in the host as they are, so it should be disabled after the migration.
+    pub migrate_existing: bool,
+}
+
+/// `AuthenticatedKVS` is a key-value store that authenticates the values read from the untrusted `parent` store
+///
+/// The keys under `clients/{client_id}/` form a tree per client, and the other keys form a global tree.
+/// Each tree is a crit-bit Merkle trie whose nodes are stored in `parent` by their hashes,
+/// and the latest root of each tree is kept in `registry` with a version.
+/// Every read is verified against the root, so a value that is tampered with by the host is detected.
+/// The root sealed by `sealer` is also stored in `parent` to restore the registry after the enclave restarts.
+/// The nodes replaced by an update are removed from `parent` in the same transaction.
+///
+/// # Rollback
+///
+/// A rollback of a tree to an older root is detected only while the enclave keeps the latest root in `registry`,
+/// i.e. until the enclave restarts. It is not detected across an enclave restart:
+/// the sealed root is not bound to a version that survives a restart, because SGX provides no trusted monotonic counter
+/// on the platforms LCP supports (the counters of the platform services are deprecated and unavailable on DCAP platforms)
+/// and everything else that survives a restart is stored by the host.
+/// Therefore, after a restart, the first root loaded for each tree is trusted as long as it is sealed by the enclave,
+/// and the host can replay an older sealed root together with the nodes and values of that version.
+///
+/// Since `KVStore` methods cannot return an error, the first error is recorded and returned by `finalize`.
+pub struct AuthenticatedKVS<'a, S: KVStore> {
+    parent: RefCell<S>,
+    registry: &'a dyn RootRegistry,
+    sealer: &'a dyn RootSealer,
+    config: AuthenticationConfig,
+    trees: RefCell<BTreeMap<Vec<u8>, TreeState>>,
+    error: RefCell<Option<Error>>,
+}
+
+struct TreeState {
+    base: RootRecord,
+    root: Option<Hash>,
+}
+
+impl<'a, S: KVStore> AuthenticatedKVS<'a, S> {
+    pub fn new(
+        parent: S,
+        registry: &'a dyn RootRegistry,
+        sealer: &'a dyn RootSealer,
+        config: AuthenticationConfig,
+    ) -> Self {
+        Self {
+            parent: RefCell::new(parent),
+            registry,
+            sealer,
+            config,
+            trees: Default::default(),
+            error: Default::default(),
+        }
+    }
+
+    fn record_error(&self, err: Error) {
+        self.error.borrow_mut().get_or_insert(err);
+    }
+
+    fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let root = self.tree_root(tree_id(key)?)?;
+        let value_hash = match root {
+            Some(root) => self.find_leaf(root, key)?,
+            None => None,
+        };
+        match (value_hash, self.parent.borrow().get(key)) {
+            (Some(value_hash), Some(value)) if sha256(&value) == value_hash => Ok(Some(value)),
+            (None, None) => Ok(None),
+            _ => Err(Error::unauthenticated_value(key.to_vec())),
+        }
+    }
+
+    fn try_set(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
+        let tree_id = tree_id(&key)?.to_vec();
+        let root = self.tree_root(&tree_id)?;
+        let root = self.insert(root, &key, sha256(&value))?;
+        self.parent.get_mut().set(key, value);
+        self.set_tree_root(&tree_id, Some(root));
+        Ok(())
+    }
+
+    fn try_remove(&mut self, key: &[u8]) -> Result<()> {
+        let tree_id = tree_id(key)?.to_vec();
+        let root = self.tree_root(&tree_id)?;
+        let root = self.remove_key(root, key)?;
+        self.parent.get_mut().remove(key);
+        self.set_tree_root(&tree_id, root);
+        Ok(())
+    }
+
+    fn try_range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Result<Vec<KVPair>> {
+        if matches!((start, end), (Some(start), Some(end)) if start >= end) {
+            return Ok(vec![]);
+        }
+        let Some(root) = self.tree_root(range_tree_id(start, end)?)? else {
+            return Ok(vec![]);
+        };
+        let mut leaves = Vec::new();
+        self.collect_leaves(
+            root,
+            start,
+            end,
+            order,
+            limit.unwrap_or(usize::MAX),
+            &mut leaves,
+        )?;
+        leaves
+            .into_iter()
+            .map(|(key, value_hash)| match self.parent.borrow().get(&key) {
+                Some(value) if sha256(&value) == value_hash => Ok((key, value)),
+                _ => Err(Error::unauthenticated_value(key)),
+            })
+            .collect()
+    }
+
+    /// `tree_root` returns the current root of the tree, loading it from the registry on the first access
+    fn tree_root(&self, tree_id: &[u8]) -> Result<Option<Hash>> {
+        if let Some(state) = self.trees.borrow().get(tree_id) {
+            return Ok(state.root);
+        }
+        let base = self.load_root(tree_id)?;
+        let root = if base.version == 0 && self.config.migrate_existing {
+            self.migrate_existing(tree_id)?
+        } else {
+            base.root
+        };
+        self.trees
+            .borrow_mut()
+            .insert(tree_id.to_vec(), TreeState { base, root });
+        Ok(root)
+    }
+
+    fn set_tree_root(&self, tree_id: &[u8], root: Option<Hash>) {
+        self.trees
+            .borrow_mut()
+            .get_mut(tree_id)
+            .expect("the tree must be loaded before updating")
+            .root = root;
+    }
+
+    /// `load_root` returns the latest root in the registry after checking that the root stored in the host is consistent with it
+    fn load_root(&self, tree_id: &[u8]) -> Result<RootRecord> {
+        let stored = self.parent.borrow().get(&root_key(tree_id));
+        let stored = match stored {
+            Some(bz) => self.open_root(tree_id, &bz)?,
+            None => RootRecord::empty(tree_id.to_vec()),
+        };
+        self.registry.load(stored)
+    }
+
+    /// `migrate_existing` builds the tree from the values written to `parent` before enabling the authentication
+    fn migrate_existing(&self, tree_id: &[u8]) -> Result<Option<Hash>> {
+        let pairs = {
+            let parent = self.parent.borrow();
+            if tree_id.is_empty() {
+                // the global tree consists of the keys that are neither reserved nor under `clients/`
+                let reserved_end = crate::prefix_end(AUTHENTICATED_KEY_PREFIX);
+                let clients_end = crate::prefix_end(CLIENT_KEY_PREFIX);
+                let mut pairs =
+                    parent.range(None, Some(AUTHENTICATED_KEY_PREFIX), Order::Ascending, None);
+                pairs.extend(parent.range(
+                    reserved_end.as_deref(),
+                    Some(CLIENT_KEY_PREFIX),
+                    Order::Ascending,
+                    None,
+                ));
+                pairs.extend(parent.range(clients_end.as_deref(), None, Order::Ascending, None));
+                pairs
+            } else {
+                parent.prefix_range(tree_id, Order::Ascending, None)
+            }
+        };
+        let mut root = None;
+        for (key, value) in pairs {
+            root = Some(self.insert(root, &key, sha256(&value))?);
+        }
+        Ok(root)
+    }
+
+    /// `seal_root` encodes the record with the sealed digest of it
+    fn seal_root(&self, record: &RootRecord) -> Result<Vec<u8>> {
+        let record_bz = record.to_bytes();
+        let mut bz = Vec::new();
+        put_bytes(&mut bz, &self.sealer.seal(&sha256(&record_bz))?);
+        bz.extend(record_bz);
+        Ok(bz)
+    }
+
+    fn open_root(&self, tree_id: &[u8], bz: &[u8]) -> Result<RootRecord> {
+        let mut r = Reader(bz);
+        let digest = self.sealer.unseal(r.bytes()?)?;
+        let record_bz = r.0;
+        if sha256(record_bz) != digest {
+            return Err(Error::invalid_authenticated_data(format!(
+                "the sealed digest doesn't match the root: tree_id={}",
+                String::from_utf8_lossy(tree_id)
+            )));
+        }
+        let record = RootRecord::from_bytes(record_bz)?;
+        if record.tree_id != tree_id {
+            return Err(Error::invalid_authenticated_data(format!(
+                "the root belongs to another tree: expected={} actual={}",
+                String::from_utf8_lossy(tree_id),
+                String::from_utf8_lossy(&record.tree_id)
+            )));
+        }
+        Ok(record)
+    }
+
+    /// `write_roots` writes the new roots of the changed trees to `parent` and returns the updates for the registry
+    fn write_roots(&mut self) -> Result<Vec<(RootRecord, RootRecord)>> {
+        let mut updates = Vec::new();
+        for (tree_id, state) in self.trees.take() {
+            if state.root == state.base.root {
+                continue;
+            }
+            let record = RootRecord {
+                tree_id,
+                version: state.base.version + 1,
+                root: state.root,
+            };
+            let sealed = self.seal_root(&record)?;
+            self.parent.get_mut().set(root_key(&record.tree_id), sealed);
+            updates.push((state.base, record));
+        }
+        Ok(updates)
+    }
+
+    fn get_node(&self, hash: &Hash) -> Result<Node> {
+        let bz = self
+            .parent
+            .borrow()
+            .get(&node_key(hash))
+            .ok_or_else(|| Error::authenticated_node_not_found(hash.to_vec()))?;
+        if sha256(&bz) != *hash {
+            return Err(Error::invalid_authenticated_data(format!(
+                "node hash mismatch: hash={:?}",
+                hash
+            )));
+        }
+        Node::decode(&bz)
+    }
+
+    fn put_node(&self, node: &Node) -> Hash {
+        let bz = node.encode();
+        let hash = sha256(&bz);
+        self.parent.borrow_mut().set(node_key(&hash), bz);
+        hash
+    }
+
+    /// `remove_node` removes a node replaced by an update
+    ///
+    /// Every node is referenced by only one parent in the latest version of the tree since the keys of the trees are disjoint,
+    /// and a node that is created again with the same hash is written back by `put_node` after the removal.
+    fn remove_node(&self, hash: &Hash) {
+        self.parent.borrow_mut().remove(&node_key(hash));
+    }
+
+    /// `walk` returns the leaf that has the longest common prefix with `key`
+    fn walk(&self, root: Hash, key: &[u8]) -> Result<(Vec<u8>, Hash)> {
+        let mut hash = root;
+        loop {
+            match self.get_node(&hash)? {
+                Node::Leaf { key, value_hash } => return Ok((key, value_hash)),
+                Node::Inner {
+                    bit, left, right, ..
+                } => hash = if key_bit(key, bit) { right } else { left },
+            }
+        }
+    }
+
+    fn find_leaf(&self, root: Hash, key: &[u8]) -> Result<Option<Hash>> {
+        let (leaf_key, value_hash) = self.walk(root, key)?;
+        Ok((leaf_key == key).then_some(value_hash))
+    }
+
+    fn insert(&self, root: Option<Hash>, key: &[u8], value_hash: Hash) -> Result<Hash> {
+        let Some(root) = root else {
+            return Ok(self.put_node(&Node::Leaf {
+                key: key.to_vec(),
+                value_hash,
+            }));
+        };
+        // None means that the key already exists
+        let crit = crit_bit(key, &self.walk(root, key)?.0);
+        self.insert_at(root, key, value_hash, crit)
+    }
+
+    fn insert_at(
+        &self,
+        hash: Hash,
+        key: &[u8],
+        value_hash: Hash,
+        crit: Option<usize>,
+    ) -> Result<Hash> {
+        match self.get_node(&hash)? {
+            Node::Inner {
+                bit,
+                prefix,
+                left,
+                right,
+            } if !matches!(crit, Some(crit) if crit <= bit) => {
+                self.remove_node(&hash);
+                let (left, right) = if key_bit(key, bit) {
+                    (left, self.insert_at(right, key, value_hash, crit)?)
+                } else {
+                    (self.insert_at(left, key, value_hash, crit)?, right)
+                };
+                Ok(self.put_node(&Node::Inner {
+                    bit,
+                    prefix,
+                    left,
+                    right,
+                }))
+            }
+            _ => {
+                let Some(crit) = crit else {
+                    // replace the existing leaf
+                    self.remove_node(&hash);
+                    return Ok(self.put_node(&Node::Leaf {
+                        key: key.to_vec(),
+                        value_hash,
+                    }));
+                };
+                let leaf = self.put_node(&Node::Leaf {
+                    key: key.to_vec(),
+                    value_hash,
+                });
+                let (left, right) = if key_bit(key, crit) {
+                    (hash, leaf)
+                } else {
+                    (leaf, hash)
+                };
+                Ok(self.put_node(&Node::Inner {
+                    bit: crit,
+                    prefix: key[..key.len().min(crit.div_ceil(9))].to_vec(),
+                    left,
+                    right,
+                }))
+            }
+        }
+    }
+
+    fn remove_key(&self, root: Option<Hash>, key: &[u8]) -> Result<Option<Hash>> {
+        match root {
+            Some(root) if self.find_leaf(root, key)?.is_some() => self.remove_at(root, key),
+            _ => Ok(root),
+        }
+    }
+
+    fn remove_at(&self, hash: Hash, key: &[u8]) -> Result<Option<Hash>> {
+        let node = self.get_node(&hash)?;
+        self.remove_node(&hash);
+        let (bit, prefix, left, right) = match node {
+            Node::Leaf { .. } => return Ok(None),
+            Node::Inner {
+                bit,
+                prefix,
+                left,
+                right,
+            } => (bit, prefix, left, right),
+        };
+        let (left, right) = if key_bit(key, bit) {
+            match self.remove_at(right, key)? {
+                Some(right) => (left, right),
+                None => return Ok(Some(left)),
+            }
+        } else {
+            match self.remove_at(left, key)? {
+                Some(left) => (left, right),
+                None => return Ok(Some(right)),
+            }
+        };
+        Ok(Some(self.put_node(&Node::Inner {
+            bit,
+            prefix,
+            left,
+            right,
+        })))
+    }
+
+    /// `collect_leaves` collects the leaves whose keys are in `[start, end)` in `order` up to `limit`
+    fn collect_leaves(
+        &self,
+        hash: Hash,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: usize,
+        leaves: &mut Vec<(Vec<u8>, Hash)>,
+    ) -> Result<()> {
+        if leaves.len() >= limit {
+            return Ok(());
+        }
+        match self.get_node(&hash)? {
+            Node::Leaf { key, value_hash } => {
+                if !matches!(start, Some(start) if key.as_slice() < start)
+                    && !matches!(end, Some(end) if key.as_slice() >= end)
+                {
+                    leaves.push((key, value_hash));
+                }
+            }
+            Node::Inner {
+                bit,
+                prefix,
+                left,
+                right,
+            } => {
+                let [left_bounds, right_bounds] = child_bounds(bit, &prefix, start, end);
+                let children = match order {
+                    Order::Ascending => [(left, left_bounds), (right, right_bounds)],
+                    Order::Descending => [(right, right_bounds), (left, left_bounds)],
+                };
+                for (child, bounds) in children {
+                    if let Some((start, end)) = bounds {
+                        self.collect_leaves(child, start, end, order, limit, leaves)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, S: KVStore> KVStore for AuthenticatedKVS<'a, S> {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        if let Err(e) = self.try_set(key, value) {
+            self.record_error(e);
+        }
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.try_get(key).unwrap_or_else(|e| {
+            self.record_error(e);
+            None
+        })
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        if let Err(e) = self.try_remove(key) {
+            self.record_error(e);
+        }
+    }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.try_range(start, end, order, limit)
+            .unwrap_or_else(|e| {
+                self.record_error(e);
+                vec![]
+            })
+    }
+}
+
+impl<'a, S: TxKVStore> TxKVStore for AuthenticatedKVS<'a, S> {
+    fn finalize(mut self: Box<Self>, commit: bool) -> Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        if commit {
+            // the new roots become the latest ones after the host commits them
+            let updates = self.write_roots()?;
+            if !updates.is_empty() {
+                self.registry.prepare(updates)?;
+            }
+        }
+        let this = *self;
+        Box::new(this.parent.into_inner()).finalize(commit)
+    }
+}
+
+/// `Node` is a node of a crit-bit Merkle trie
+///
+/// The bits of a key are defined by an order-preserving and prefix-free encoding of the key (see `key_bit`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Leaf {
+        key: Vec<u8>,
+        value_hash: Hash,
+    },
+    /// All keys under the node share the first `bit` bits of `prefix`,
+    /// and the keys under `left` (resp. `right`) have 0 (resp. 1) at `bit`.
+    Inner {
+        bit: usize,
+        prefix: Vec<u8>,
+        left: Hash,
+        right: Hash,
+    },
+}
+
+impl Node {
+    fn encode(&self) -> Vec<u8> {
+        let mut bz = Vec::new();
+        match self {
+            Node::Leaf { key, value_hash } => {
+                bz.push(0);
+                put_bytes(&mut bz, key);
+                bz.extend(value_hash);
+            }
+            Node::Inner {
+                bit,
+                prefix,
+                left,
+                right,
+            } => {
+                bz.push(1);
+                bz.extend((*bit as u64).to_be_bytes());
+                put_bytes(&mut bz, prefix);
+                bz.extend(left);
+                bz.extend(right);
+            }
+        }
+        bz
+    }
+
+    fn decode(bz: &[u8]) -> Result<Self> {
+        let mut r = Reader(bz);
+        let node = match r.take(1)?[0] {
+            0 => Node::Leaf {
+                key: r.bytes()?.to_vec(),
+                value_hash: r.hash()?,
+            },
+            1 => Node::Inner {
+                bit: r.u64()? as usize,
+                prefix: r.bytes()?.to_vec(),
+                left: r.hash()?,
+                right: r.hash()?,
+            },
+            tag => {
+                return Err(Error::invalid_authenticated_data(format!(
+                    "unknown node tag: {}",
+                    tag
+                )))
+            }
+        };
+        r.finish()?;
+        Ok(node)
+    }
+}
+
+/// `key_bit` returns the bit at `index` in the encoding of `key`,
+/// where each byte is preceded by a 1 bit and the key is terminated by a 0 bit
+fn key_bit(key: &[u8], index: usize) -> bool {
+    match key.get(index / 9) {
+        Some(b) => match index % 9 {
+            0 => true,
+            i => (b >> (8 - i)) & 1 == 1,
+        },
+        None => false,
+    }
+}
+
+/// `crit_bit` returns the index of the first different bit between the encodings of `a` and `b`, or None if they are equal
+fn crit_bit(a: &[u8], b: &[u8]) -> Option<usize> {
+    let n = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    match (a.get(n), b.get(n)) {
+        (Some(x), Some(y)) => Some(n * 9 + 1 + (x ^ y).leading_zeros() as usize),
+        (None, None) => None,
+        _ => Some(n * 9),
+    }
+}
+
+/// `BoundPosition` is the position of a bound relative to the keys under an inner node
+enum BoundPosition {
+    /// the bound is less than all keys
+    Below,
+    /// the bound is greater than all keys
+    Above,
+    /// the bound shares the prefix with the keys and has the given bit at the node's bit
+    Within(bool),
+}
+
+fn bound_position(bound: &[u8], bit: usize, prefix: &[u8]) -> BoundPosition {
+    match crit_bit(bound, prefix) {
+        Some(d) if d < bit => {
+            if key_bit(bound, d) {
+                BoundPosition::Above
+            } else {
+                BoundPosition::Below
+            }
+        }
+        _ => BoundPosition::Within(key_bit(bound, bit)),
+    }
+}
+
+type Bounds<'b> = (Option<&'b [u8]>, Option<&'b [u8]>);
+
+/// `child_bounds` returns the bounds to apply to the left and right children of an inner node,
+/// or None for a child whose keys are all out of `[start, end)`
+fn child_bounds<'b>(
+    bit: usize,
+    prefix: &[u8],
+    start: Option<&'b [u8]>,
+    end: Option<&'b [u8]>,
+) -> [Option<Bounds<'b>>; 2] {
+    let mut bounds = [Some((start, end)), Some((start, end))];
+    if let Some(start) = start {
+        match bound_position(start, bit, prefix) {
+            BoundPosition::Below => bounds.iter_mut().flatten().for_each(|b| b.0 = None),
+            BoundPosition::Above => return [None, None],
+            BoundPosition::Within(false) => {
+                if let Some(b) = bounds[1].as_mut() {
+                    b.0 = None;
+                }
+            }
+            BoundPosition::Within(true) => bounds[0] = None,
+        }
+    }
+    if let Some(end) = end {
+        match bound_position(end, bit, prefix) {
+            BoundPosition::Below => return [None, None],
+            BoundPosition::Above => bounds.iter_mut().flatten().for_each(|b| b.1 = None),
+            BoundPosition::Within(false) => bounds[1] = None,
+            BoundPosition::Within(true) => {
+                if let Some(b) = bounds[0].as_mut() {
+                    b.1 = None;
+                }
+            }
+        }
+    }
+    bounds
+}
+
+/// `tree_id` returns the id of the tree that `key` belongs to
+fn tree_id(key: &[u8]) -> Result<&[u8]> {
+    if key.starts_with(AUTHENTICATED_KEY_PREFIX) {
+        return Err(Error::reserved_key(key.to_vec()));
+    }
+    match key
+        .strip_prefix(CLIENT_KEY_PREFIX)
+        .and_then(|rest| rest.iter().position(|b| *b == b'/'))
+    {
+        Some(pos) => Ok(&key[..CLIENT_KEY_PREFIX.len() + pos + 1]),
+        None => Ok(&[]),
+    }
+}
+
+/// `range_tree_id` returns the id of the client tree that contains all keys in `[start, end)`
+fn range_tree_id<'k>(start: Option<&'k [u8]>, end: Option<&[u8]>) -> Result<&'k [u8]> {
+    let (Some(start), Some(end)) = (start, end) else {
+        return Err(Error::unsupported_range());
+    };
+    let tree_id = tree_id(start)?;
+    match crate::prefix_end(tree_id) {
+        Some(tree_end) if !tree_id.is_empty() && end <= tree_end.as_slice() => Ok(tree_id),
+        _ => Err(Error::unsupported_range()),
+    }
+}
+
+fn node_key(hash: &Hash) -> Vec<u8> {
+    [NODE_KEY_PREFIX, hash.as_slice()].concat()
+}
+
+fn root_key(tree_id: &[u8]) -> Vec<u8> {
+    [ROOT_KEY_PREFIX, tree_id].concat()
+}
+
+fn sha256(bz: &[u8]) -> Hash {
+    Sha256::digest(bz).into()
+}
+
+fn put_bytes(buf: &mut Vec<u8>, bz: &[u8]) {
+    buf.extend((bz.len() as u32).to_be_bytes());
+    buf.extend(bz);
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(Error::invalid_authenticated_data(format!(
+                "unexpected end of data: expected={} actual={}",
+                n,
+                self.0.len()
+            )));
+        }
+        let (bz, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(bz)
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn hash(&mut self) -> Result<Hash> {
+        Ok(self.take(32)?.try_into().unwrap())
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = u32::from_be_bytes(self.take(4)?.try_into().unwrap());
+        self.take(len as usize)
+    }
+
+    fn finish(&self) -> Result<()> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::invalid_authenticated_data(format!(
+                "unexpected trailing data: length={}",
+                self.0.len()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btree_range;
+    use alloc::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct MockStore {
+        db: BTreeMap<Vec<u8>, Vec<u8>>,
+    }
+
+    impl KVStore for MockStore {
+        fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+            self.db.insert(key, value);
+        }
+
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.db.get(key).cloned()
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.db.remove(key);
+        }
+
+        fn range(
+            &self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+            limit: Option<usize>,
+        ) -> Vec<KVPair> {
+            btree_range(&self.db, start, end, order, limit)
+        }
+    }
+
+    struct MockSealer;
+
+    impl RootSealer for MockSealer {
+        fn seal(&self, digest: &Hash) -> Result<Vec<u8>> {
+            Ok(digest.to_vec())
+        }
+
+        fn unseal(&self, sealed: &[u8]) -> Result<Hash> {
+            sealed
+                .try_into()
+                .map_err(|_| Error::unseal_root("invalid length".to_string()))
+        }
+    }
+
+    #[allow(non_snake_case)]
+    fn B(s: &str) -> Vec<u8> {
+        s.as_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_key_encoding() {
+        let mut keys = [
+            vec![],
+            vec![0x00],
+            vec![0x00, 0x00],
+            vec![0x01],
+            vec![0x7f, 0xff],
+            vec![0x80],
+            vec![0xff],
+            vec![0xff, 0x00],
+        ];
+        keys.sort();
+        for pair in keys.windows(2) {
+            // the encoding preserves the order of the keys
+            let crit = crit_bit(&pair[0], &pair[1]).unwrap();
+            assert!(!key_bit(&pair[0], crit) && key_bit(&pair[1], crit));
+            assert!((0..crit).all(|i| key_bit(&pair[0], i) == key_bit(&pair[1], i)));
+        }
+        assert_eq!(crit_bit(b"abc", b"abc"), None);
+    }
+
+    #[test]
+    fn test_authenticated_kvs() {
+        let parent = Rc::new(RefCell::new(MockStore::default()));
+        let registry = RefCell::new(RootTable::default());
+        let mut model = BTreeMap::new();
+
+        // generate keys with common prefixes in a deterministic order
+        let mut keys = Vec::new();
+        let mut seed = 7u64;
+        for i in 0..200u64 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let mut key = B("clients/07-tendermint-0/");
+            key.extend(&((seed >> 32) as u32).to_be_bytes()[..(i % 4) as usize]);
+            keys.push(key);
+        }
+
+        let mut store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        for (i, key) in keys.iter().enumerate() {
+            store.set(key.clone(), i.to_be_bytes().to_vec());
+            model.insert(key.clone(), i.to_be_bytes().to_vec());
+        }
+        for key in keys.iter().step_by(3) {
+            store.remove(key);
+            model.remove(key);
+        }
+        assert!(model.len() > 50);
+        for key in keys.iter() {
+            assert_eq!(store.get(key), model.get(key).cloned());
+        }
+        let prefix = B("clients/07-tendermint-0/");
+        let end = crate::prefix_end(&prefix).unwrap();
+        for (start, end) in [
+            (prefix.clone(), end.clone()),
+            (keys[10].clone(), keys[20].clone()),
+            (keys[20].clone(), end.clone()),
+        ] {
+            for (order, limit) in [
+                (Order::Ascending, None),
+                (Order::Descending, None),
+                (Order::Ascending, Some(3)),
+                (Order::Descending, Some(3)),
+            ] {
+                assert_eq!(
+                    store.range(Some(&start), Some(&end), order, limit),
+                    btree_range(&model, Some(&start), Some(&end), order, limit)
+                );
+            }
+        }
+        assert!(Box::new(store).finalize(true).is_ok());
+        // the new root is pending until it is read from the host's store
+        assert_eq!(
+            registry.borrow().get(&prefix).map(|r| r.latest.version),
+            Some(0)
+        );
+
+        // the committed values can be read in a new transaction
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(
+            store.prefix_range(&prefix, Order::Ascending, None),
+            model.clone().into_iter().collect::<Vec<_>>()
+        );
+        assert!(Box::new(store).finalize(true).is_ok());
+        assert_eq!(
+            registry.borrow().get(&prefix).map(|r| r.latest.version),
+            Some(1)
+        );
+
+        // the replaced nodes are removed, so only the nodes of the latest tree remain
+        let nodes = parent
+            .borrow()
+            .prefix_range(NODE_KEY_PREFIX, Order::Ascending, None);
+        assert_eq!(nodes.len(), 2 * model.len() - 1);
+
+        // a range across multiple trees is not supported
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        store.prefix_range(b"clients/", Order::Ascending, None);
+        assert!(Box::new(store).finalize(false).is_err());
+    }
+
+    #[test]
+    fn test_authenticated_kvs_tampering() {
+        let parent = Rc::new(RefCell::new(MockStore::default()));
+        let registry = RefCell::new(RootTable::default());
+
+        let mut store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        store.set(B("clients/c-0/k1"), B("v1"));
+        store.set(B("clients/c-0/k2"), B("v2"));
+        assert!(Box::new(store).finalize(true).is_ok());
+        let snapshot = parent.borrow().clone();
+
+        let mut store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        store.set(B("clients/c-0/k1"), B("v3"));
+        assert!(Box::new(store).finalize(true).is_ok());
+
+        // case1: the value is modified by the host
+        parent.borrow_mut().set(B("clients/c-0/k2"), B("x"));
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k2")), None);
+        assert!(Box::new(store).finalize(false).is_err());
+        parent.borrow_mut().set(B("clients/c-0/k2"), B("v2"));
+
+        // case2: an unauthenticated value is inserted by the host
+        parent.borrow_mut().set(B("clients/c-0/k3"), B("v3"));
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k3")), None);
+        assert!(Box::new(store).finalize(false).is_err());
+        parent.borrow_mut().remove(&B("clients/c-0/k3"));
+
+        // case3: the whole store is rolled back by the host
+        let latest = parent.replace(snapshot);
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k1")), None);
+        let res = Box::new(store).finalize(false);
+        assert!(
+            matches!(
+                res.as_ref().map_err(|e| e.detail()),
+                Err(crate::errors::ErrorDetail::RootVersionMismatch(_))
+            ),
+            "res={:?}",
+            res
+        );
+        // NOTE: the rollback is not detected after the enclave restarts, since the registry is lost
+        let restarted = RefCell::new(RootTable::default());
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &restarted, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k1")), Some(B("v1")));
+        assert!(Box::new(store).finalize(false).is_ok());
+        parent.replace(latest);
+
+        // case4: the keys of other trees are unaffected
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k1")), Some(B("v3")));
+        assert_eq!(store.get(&B("clients/c-1/k1")), None);
+        assert!(Box::new(store).finalize(false).is_ok());
+
+        // case5: the reserved keys cannot be written
+        let mut store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        store.set(B("authenticated/roots/clients/c-0/"), B("x"));
+        assert!(Box::new(store).finalize(true).is_err());
+    }
+
+    #[test]
+    fn test_authenticated_kvs_conflict() {
+        let parent = Rc::new(RefCell::new(MockStore::default()));
+        let registry = RefCell::new(RootTable::default());
+
+        let mut store1 =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        let mut store2 =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        store1.set(B("clients/c-0/k1"), B("v1"));
+        store2.set(B("clients/c-0/k2"), B("v2"));
+        assert!(Box::new(store1).finalize(true).is_ok());
+        // the root written by store1 becomes the latest one
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k1")), Some(B("v1")));
+        assert!(Box::new(store).finalize(false).is_ok());
+        // store2 is based on the root that has already been replaced
+        assert!(Box::new(store2).finalize(true).is_err());
+    }
+
+    #[test]
+    fn test_authenticated_kvs_uncommitted() {
+        let parent = Rc::new(RefCell::new(MockStore::default()));
+        let registry = RefCell::new(RootTable::default());
+
+        let mut store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        store.set(B("clients/c-0/k1"), B("v1"));
+        assert!(Box::new(store).finalize(true).is_ok());
+        let snapshot = parent.borrow().clone();
+
+        // the host fails to commit the transaction
+        let mut store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        store.set(B("clients/c-0/k1"), B("v2"));
+        assert!(Box::new(store).finalize(true).is_ok());
+        parent.replace(snapshot);
+
+        // the tree can still be read and updated from the committed root
+        let mut store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k1")), Some(B("v1")));
+        store.set(B("clients/c-0/k1"), B("v3"));
+        assert!(Box::new(store).finalize(true).is_ok());
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k1")), Some(B("v3")));
+        assert!(Box::new(store).finalize(false).is_ok());
+        assert_eq!(
+            registry
+                .borrow()
+                .get(b"clients/c-0/")
+                .map(|r| (r.latest.version, r.pending.len())),
+            Some((2, 0))
+        );
+    }
+
+    #[test]
+    fn test_authenticated_kvs_migration() {
+        let parent = Rc::new(RefCell::new(MockStore::default()));
+        let registry = RefCell::new(RootTable::default());
+        let migration = AuthenticationConfig {
+            migrate_existing: true,
+        };
+        // the values written before enabling the authentication
+        for (key, value) in [
+            ("clients/c-0/k1", "v1"),
+            ("clients/c-0/k2", "v2"),
+            ("clients/c-1/k1", "v3"),
+            ("global", "v4"),
+        ] {
+            parent.borrow_mut().set(B(key), B(value));
+        }
+
+        // they cannot be read without the migration
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k1")), None);
+        assert!(Box::new(store).finalize(false).is_err());
+
+        let registry = RefCell::new(RootTable::default());
+        let mut store = AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, migration);
+        assert_eq!(store.get(&B("clients/c-0/k1")), Some(B("v1")));
+        assert_eq!(store.get(&B("global")), Some(B("v4")));
+        store.set(B("clients/c-1/k2"), B("v5"));
+        assert!(Box::new(store).finalize(true).is_ok());
+
+        // the migrated trees are authenticated after disabling the migration
+        parent.borrow_mut().set(B("clients/c-0/k2"), B("x"));
+        let store =
+            AuthenticatedKVS::new(parent.clone(), &registry, &MockSealer, Default::default());
+        assert_eq!(store.get(&B("clients/c-0/k1")), Some(B("v1")));
+        assert_eq!(
+            store.prefix_range(b"clients/c-1/", Order::Ascending, None),
+            vec![
+                (B("clients/c-1/k1"), B("v3")),
+                (B("clients/c-1/k2"), B("v5"))
+            ]
+        );
+        assert_eq!(store.get(&B("global")), Some(B("v4")));
+        assert_eq!(store.get(&B("clients/c-0/k2")), None);
+        assert!(Box::new(store).finalize(false).is_err());
+    }
+}
@@ -1,12 +1,15 @@
 use crate::prelude::*;
-use crate::KVStore;
-use alloc::collections::BTreeMap;
+use crate::store::overlay_range;
+use crate::{KVPair, KVStore, Order, Result, TxKVStore, WriteBatch};
+use alloc::collections::{BTreeMap, BTreeSet};
 use core::cell::RefCell;
 
+type Cache = RefCell<BTreeMap<Vec<u8>, Option<Vec<u8>>>>;
+
 /// A key-value store that caches reads in memory
 pub struct CacheKVS<S: KVStore> {
     parent: S,
-    cache: RefCell<BTreeMap<Vec<u8>, Option<Vec<u8>>>>,
+    cache: Cache,
 }
 
 impl<S: KVStore> CacheKVS<S> {
@@ -25,24 +28,114 @@ impl<S: KVStore> KVStore for CacheKVS<S> {
     }
 
     fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
-        let cache = self.cache.borrow();
-        let res = cache.get(key);
-        match res {
-            Some(Some(v)) => Some(v.clone()),
-            Some(None) => None,
-            None => {
-                drop(cache);
-                let v = self.parent.get(key);
-                self.cache.borrow_mut().insert(key.to_vec(), v.clone());
-                v
-            }
-        }
+        cached_get(&self.cache, &self.parent, key)
     }
 
     fn remove(&mut self, key: &[u8]) {
         self.parent.remove(key);
         self.cache.borrow_mut().insert(key.to_vec(), None);
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        cached_range(&self.cache, &self.parent, start, end, order, limit)
+    }
+}
+
+/// A key-value store that caches reads and buffers writes in memory
+///
+/// The buffered writes are applied to the parent store by a single `write_batch` call when the store is finalized with `commit`.
+pub struct WriteBackKVS<S: KVStore> {
+    parent: S,
+    cache: Cache,
+    dirty: BTreeSet<Vec<u8>>,
+}
+
+impl<S: KVStore> WriteBackKVS<S> {
+    pub fn new(parent: S) -> Self {
+        Self {
+            parent,
+            cache: RefCell::new(BTreeMap::new()),
+            dirty: BTreeSet::new(),
+        }
+    }
+}
+
+impl<S: KVStore> KVStore for WriteBackKVS<S> {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.cache.borrow_mut().insert(key.clone(), Some(value));
+        self.dirty.insert(key);
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        cached_get(&self.cache, &self.parent, key)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.cache.borrow_mut().insert(key.to_vec(), None);
+        self.dirty.insert(key.to_vec());
+    }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        cached_range(&self.cache, &self.parent, start, end, order, limit)
+    }
+}
+
+impl<S: KVStore> TxKVStore for WriteBackKVS<S> {
+    fn finalize(mut self: Box<Self>, commit: bool) -> Result<()> {
+        if !commit || self.dirty.is_empty() {
+            return Ok(());
+        }
+        let mut cache = self.cache.take();
+        let batch: WriteBatch = core::mem::take(&mut self.dirty)
+            .into_iter()
+            .map(|key| {
+                let value = cache.remove(&key).flatten();
+                (key, value)
+            })
+            .collect();
+        self.parent.write_batch(batch);
+        Ok(())
+    }
+}
+
+fn cached_get<S: KVStore>(cache: &Cache, parent: &S, key: &[u8]) -> Option<Vec<u8>> {
+    if let Some(v) = cache.borrow().get(key) {
+        return v.clone();
+    }
+    let v = parent.get(key);
+    cache.borrow_mut().insert(key.to_vec(), v.clone());
+    v
+}
+
+fn cached_range<S: KVStore>(
+    cache: &Cache,
+    parent: &S,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: Order,
+    limit: Option<usize>,
+) -> Vec<KVPair> {
+    let pairs = overlay_range(&cache.borrow(), start, end, order, limit, |limit| {
+        parent.range(start, end, order, limit)
+    });
+    // cache the values so that subsequent reads are consistent with the result
+    let mut cache = cache.borrow_mut();
+    for (k, v) in pairs.iter() {
+        cache.entry(k.clone()).or_insert_with(|| Some(v.clone()));
+    }
+    pairs
 }
 
 #[cfg(test)]
@@ -53,12 +146,14 @@ mod tests {
 
     pub struct MockStore {
         db: BTreeMap<Vec<u8>, Vec<u8>>,
+        write_batch_count: usize,
     }
 
     impl MockStore {
         pub fn new() -> Self {
             Self {
                 db: BTreeMap::new(),
+                write_batch_count: 0,
             }
         }
     }
@@ -75,6 +170,26 @@ mod tests {
         fn remove(&mut self, key: &[u8]) {
             self.db.remove(key);
         }
+
+        fn range(
+            &self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+            limit: Option<usize>,
+        ) -> Vec<KVPair> {
+            crate::btree_range(&self.db, start, end, order, limit)
+        }
+
+        fn write_batch(&mut self, batch: WriteBatch) {
+            self.write_batch_count += 1;
+            for (key, value) in batch {
+                match value {
+                    Some(value) => self.db.insert(key, value),
+                    None => self.db.remove(&key),
+                };
+            }
+        }
     }
 
     #[allow(non_snake_case)]
@@ -119,4 +234,86 @@ mod tests {
         cache.set(B("k4"), B("v8"));
         assert_eq!(cache.get(&B("k4")), Some(B("v8")));
     }
+
+    #[test]
+    fn test_cache_kvs_range() {
+        let mut mock = Rc::new(RefCell::new(MockStore::new()));
+        for k in ["a1", "a2", "a3", "b1"] {
+            mock.set(B(k), B(k));
+        }
+
+        let mut cache = CacheKVS::new(mock.clone());
+        // the cached value takes precedence over the parent's one
+        assert_eq!(cache.get(&B("a2")), Some(B("a2")));
+        mock.set(B("a2"), B("x"));
+        // the removed key is hidden from the result
+        cache.remove(&B("a1"));
+        cache.set(B("a4"), B("a4"));
+
+        assert_eq!(
+            cache.prefix_range(&B("a"), Order::Ascending, None),
+            vec![(B("a2"), B("a2")), (B("a3"), B("a3")), (B("a4"), B("a4"))]
+        );
+        assert_eq!(
+            cache.prefix_range(&B("a"), Order::Descending, Some(2)),
+            vec![(B("a4"), B("a4")), (B("a3"), B("a3"))]
+        );
+        assert_eq!(
+            cache.range(None, Some(&B("a3")), Order::Ascending, Some(1)),
+            vec![(B("a2"), B("a2"))]
+        );
+        assert_eq!(
+            cache.range(Some(&B("a3")), None, Order::Ascending, None),
+            vec![(B("a3"), B("a3")), (B("a4"), B("a4")), (B("b1"), B("b1"))]
+        );
+        assert!(cache
+            .range(Some(&B("b")), Some(&B("a")), Order::Ascending, None)
+            .is_empty());
+
+        // the values read by the range query are cached
+        mock.set(B("a3"), B("y"));
+        assert_eq!(cache.get(&B("a3")), Some(B("a3")));
+    }
+
+    #[test]
+    fn test_write_back_kvs() {
+        let mut mock = Rc::new(RefCell::new(MockStore::new()));
+        for k in ["a1", "a2", "a3"] {
+            mock.set(B(k), B(k));
+        }
+
+        let mut store = WriteBackKVS::new(mock.clone());
+        store.set(B("a2"), B("x"));
+        store.remove(&B("a3"));
+        store.set(B("a4"), B("a4"));
+        store.set(B("a5"), B("a5"));
+        store.remove(&B("a5"));
+
+        // the writes are buffered
+        assert_eq!(mock.get(&B("a2")), Some(B("a2")));
+        assert_eq!(mock.get(&B("a4")), None);
+        assert_eq!(store.get(&B("a1")), Some(B("a1")));
+        assert_eq!(store.get(&B("a2")), Some(B("x")));
+        assert_eq!(store.get(&B("a3")), None);
+        assert_eq!(store.get(&B("a5")), None);
+        assert_eq!(
+            store.prefix_range(&B("a"), Order::Ascending, None),
+            vec![(B("a1"), B("a1")), (B("a2"), B("x")), (B("a4"), B("a4"))]
+        );
+
+        // the writes are discarded if not committed
+        let mut store2 = WriteBackKVS::new(mock.clone());
+        store2.set(B("a1"), B("y"));
+        assert!(Box::new(store2).finalize(false).is_ok());
+        assert_eq!(mock.get(&B("a1")), Some(B("a1")));
+        assert_eq!(mock.borrow().write_batch_count, 0);
+
+        // the writes are applied at once
+        assert!(Box::new(store).finalize(true).is_ok());
+        assert_eq!(mock.borrow().write_batch_count, 1);
+        assert_eq!(
+            mock.prefix_range(&B("a"), Order::Ascending, None),
+            vec![(B("a1"), B("a1")), (B("a2"), B("x")), (B("a4"), B("a4"))]
+        );
+    }
 }
@@ -0,0 +1,461 @@
+use crate::prelude::*;
+use crate::{Error, KVPair, KVStore, Order, Result, TxKVStore};
+use core::cell::{OnceCell, RefCell};
+
+/// The header of the encrypted values, which distinguishes them from plaintext values
+pub const ENCRYPTED_VALUE_HEADER: &[u8] = b"\xffenc1";
+/// The prefix of the keys reserved by the encrypted store, which includes the MACed keys
+pub const MACED_KEY_PREFIX: &[u8] = b"encrypted/";
+/// The key under which the data encryption key sealed by the enclave is stored
+pub const SEALED_DATA_KEY: &[u8] = b"encrypted/sealed-data-key";
+
+/// `StoreCipher` encrypts the values and MACs the keys stored by `EncryptedKVS`
+pub trait StoreCipher {
+    /// `encrypt` encrypts `plaintext` with an AEAD that authenticates `aad` together
+    fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>>;
+    /// `decrypt` decrypts `ciphertext` returned by `encrypt` with the same `aad`
+    fn decrypt(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>>;
+    /// `mac` returns a deterministic MAC of `key`
+    fn mac(&self, key: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// `StoreKeyring` keeps the cipher of the data encryption key used by `EncryptedKVS`
+///
+/// The key is generated randomly and stored in the host's store after being sealed by the enclave.
+/// Unlike a key derived from the current sealing key, it can still be unsealed after a TCB update.
+pub trait StoreKeyring {
+    /// `load` returns the cipher and the sealed key
+    ///
+    /// If no key has been loaded yet, it unseals `sealed` read from the host's store, or generates a new key if it is None.
+    fn load(&self, sealed: Option<&[u8]>) -> Result<(&dyn StoreCipher, &[u8])>;
+}
+
+/// `EncryptionConfig` is the configuration of `EncryptedKVS`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EncryptionConfig {
+    /// If true, the keys are also hidden by replacing them with their MACs.
+    /// Range queries are not supported in this mode.
+    pub mac_keys: bool,
+    /// If true, the plaintext values written before enabling the encryption can be read,
+    /// and they are encrypted when they are written again.
+    ///
+    /// NOTE: This allows the host to insert plaintext values, so it should be disabled after the migration.
+    pub migrate_plaintext: bool,
+}
+
+/// `EncryptedKVS` is a key-value store that encrypts the values written to the untrusted `parent` store
+///
+/// Each value is encrypted with the key under which it is stored as the associated data,
+/// so a value moved to another key by the host cannot be decrypted.
+/// The cipher is loaded from `keyring` on the first use, and the sealed key is written to `parent`
+/// when the transaction is committed if it is not stored yet.
+///
+/// Since `KVStore` methods cannot return an error, the first error is recorded and returned by `finalize`.
+pub struct EncryptedKVS<'a, S: KVStore> {
+    parent: S,
+    keyring: &'a dyn StoreKeyring,
+    config: EncryptionConfig,
+    /// The loaded cipher and the sealed key to write to `parent` if it is not stored yet
+    cipher: OnceCell<(&'a dyn StoreCipher, Option<Vec<u8>>)>,
+    error: RefCell<Option<Error>>,
+}
+
+impl<'a, S: KVStore> EncryptedKVS<'a, S> {
+    pub fn new(parent: S, keyring: &'a dyn StoreKeyring, config: EncryptionConfig) -> Self {
+        Self {
+            parent,
+            keyring,
+            config,
+            cipher: Default::default(),
+            error: Default::default(),
+        }
+    }
+
+    fn record_error(&self, err: Error) {
+        self.error.borrow_mut().get_or_insert(err);
+    }
+
+    fn cipher(&self) -> Result<&'a dyn StoreCipher> {
+        if let Some((cipher, _)) = self.cipher.get() {
+            return Ok(*cipher);
+        }
+        let stored = self.parent.get(SEALED_DATA_KEY);
+        let (cipher, sealed) = self.keyring.load(stored.as_deref())?;
+        let unstored = (stored.as_deref() != Some(sealed)).then(|| sealed.to_vec());
+        Ok(self.cipher.get_or_init(|| (cipher, unstored)).0)
+    }
+
+    /// `stored_key` returns the key under which the value of `key` is stored in `parent`
+    fn stored_key(&self, key: &[u8]) -> Result<Vec<u8>> {
+        if key.starts_with(MACED_KEY_PREFIX) {
+            return Err(Error::reserved_key(key.to_vec()));
+        } else if !self.config.mac_keys {
+            return Ok(key.to_vec());
+        }
+        Ok([MACED_KEY_PREFIX, &self.cipher()?.mac(key)?].concat())
+    }
+
+    fn open_value(&self, stored_key: &[u8], value: Vec<u8>) -> Result<Vec<u8>> {
+        match value.strip_prefix(ENCRYPTED_VALUE_HEADER) {
+            Some(ciphertext) => self.cipher()?.decrypt(ciphertext, stored_key),
+            None if self.config.migrate_plaintext => Ok(value),
+            None => Err(Error::unencrypted_value(stored_key.to_vec())),
+        }
+    }
+
+    fn try_get(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let stored_key = self.stored_key(key)?;
+        match self.parent.get(&stored_key) {
+            Some(value) => self.open_value(&stored_key, value).map(Some),
+            // the value may not have been migrated to the MACed key yet
+            None if self.config.mac_keys && self.config.migrate_plaintext => self
+                .parent
+                .get(key)
+                .map(|value| self.open_value(key, value))
+                .transpose(),
+            None => Ok(None),
+        }
+    }
+
+    fn try_set(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
+        let stored_key = self.stored_key(&key)?;
+        let value = [
+            ENCRYPTED_VALUE_HEADER,
+            &self.cipher()?.encrypt(&value, &stored_key)?,
+        ]
+        .concat();
+        if self.config.mac_keys && self.config.migrate_plaintext {
+            self.parent.remove(&key);
+        }
+        self.parent.set(stored_key, value);
+        Ok(())
+    }
+
+    fn try_remove(&mut self, key: &[u8]) -> Result<()> {
+        let stored_key = self.stored_key(key)?;
+        if self.config.mac_keys && self.config.migrate_plaintext {
+            self.parent.remove(key);
+        }
+        self.parent.remove(&stored_key);
+        Ok(())
+    }
+
+    fn try_range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Result<Vec<KVPair>> {
+        if self.config.mac_keys {
+            return Err(Error::maced_key_range());
+        }
+        // NOTE: the reserved keys are excluded after applying `limit`,
+        // but the callers never query a range that contains them.
+        self.parent
+            .range(start, end, order, limit)
+            .into_iter()
+            .filter(|(key, _)| !key.starts_with(MACED_KEY_PREFIX))
+            .map(|(key, value)| {
+                let value = self.open_value(&key, value)?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+}
+
+impl<'a, S: KVStore> KVStore for EncryptedKVS<'a, S> {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        if let Err(e) = self.try_set(key, value) {
+            self.record_error(e);
+        }
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.try_get(key).unwrap_or_else(|e| {
+            self.record_error(e);
+            None
+        })
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        if let Err(e) = self.try_remove(key) {
+            self.record_error(e);
+        }
+    }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.try_range(start, end, order, limit)
+            .unwrap_or_else(|e| {
+                self.record_error(e);
+                vec![]
+            })
+    }
+}
+
+impl<'a, S: TxKVStore> TxKVStore for EncryptedKVS<'a, S> {
+    fn finalize(mut self: Box<Self>, commit: bool) -> Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        if let Some((_, Some(sealed))) = self.cipher.take() {
+            if commit {
+                self.parent.set(SEALED_DATA_KEY.to_vec(), sealed);
+            }
+        }
+        Box::new(self.parent).finalize(commit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btree_range;
+    use alloc::collections::BTreeMap;
+    use alloc::rc::Rc;
+    use sha2::{Digest, Sha256};
+
+    #[derive(Default)]
+    struct MockStore {
+        db: BTreeMap<Vec<u8>, Vec<u8>>,
+    }
+
+    impl KVStore for MockStore {
+        fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+            self.db.insert(key, value);
+        }
+
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.db.get(key).cloned()
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.db.remove(key);
+        }
+
+        fn range(
+            &self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+            limit: Option<usize>,
+        ) -> Vec<KVPair> {
+            btree_range(&self.db, start, end, order, limit)
+        }
+    }
+
+    /// `MockCipher` is an insecure cipher that only detects modifications
+    struct MockCipher;
+
+    impl MockCipher {
+        fn tag(plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+            let mut hasher = Sha256::new();
+            hasher.update((aad.len() as u64).to_be_bytes());
+            hasher.update(aad);
+            hasher.update(plaintext);
+            hasher.finalize().to_vec()
+        }
+    }
+
+    impl StoreCipher for MockCipher {
+        fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+            let mut ciphertext = Self::tag(plaintext, aad);
+            ciphertext.extend(plaintext.iter().map(|b| b ^ 0x5a));
+            Ok(ciphertext)
+        }
+
+        fn decrypt(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+            if ciphertext.len() < 32 {
+                return Err(Error::cipher("too short ciphertext".to_string()));
+            }
+            let (tag, ciphertext) = ciphertext.split_at(32);
+            let plaintext: Vec<u8> = ciphertext.iter().map(|b| b ^ 0x5a).collect();
+            if Self::tag(&plaintext, aad) != tag {
+                return Err(Error::cipher("tag mismatch".to_string()));
+            }
+            Ok(plaintext)
+        }
+
+        fn mac(&self, key: &[u8]) -> Result<Vec<u8>> {
+            Ok(Self::tag(key, b"mac"))
+        }
+    }
+
+    /// `MockKeyring` loads `MockCipher` with a fixed sealed key
+    #[derive(Default)]
+    struct MockKeyring {
+        sealed: OnceCell<Vec<u8>>,
+    }
+
+    impl StoreKeyring for MockKeyring {
+        fn load(&self, sealed: Option<&[u8]>) -> Result<(&dyn StoreCipher, &[u8])> {
+            let sealed = self
+                .sealed
+                .get_or_init(|| sealed.map_or_else(|| B("sealed"), |s| s.to_vec()));
+            Ok((&MockCipher, sealed))
+        }
+    }
+
+    #[allow(non_snake_case)]
+    fn B(s: &str) -> Vec<u8> {
+        s.as_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_encrypted_kvs() {
+        for mac_keys in [false, true] {
+            let config = EncryptionConfig {
+                mac_keys,
+                migrate_plaintext: false,
+            };
+            let parent = Rc::new(RefCell::new(MockStore::default()));
+            let keyring = MockKeyring::default();
+            let mut store = EncryptedKVS::new(parent.clone(), &keyring, config);
+            store.set(B("k1"), B("v1"));
+            store.set(B("k2"), B("v2"));
+            store.set(B("k3"), B("v3"));
+            store.remove(&B("k3"));
+            assert_eq!(store.get(&B("k1")), Some(B("v1")));
+            assert_eq!(store.get(&B("k2")), Some(B("v2")));
+            assert_eq!(store.get(&B("k3")), None);
+            assert!(Box::new(store).finalize(true).is_ok());
+
+            // the sealed key is stored with the encrypted values
+            assert_eq!(parent.borrow().get(SEALED_DATA_KEY), Some(B("sealed")));
+            // the host cannot read the plaintext
+            for (key, value) in parent.borrow().db.iter() {
+                if key == SEALED_DATA_KEY {
+                    continue;
+                }
+                assert!(value.starts_with(ENCRYPTED_VALUE_HEADER));
+                assert!(!value.ends_with(b"v1") && !value.ends_with(b"v2"));
+                assert_eq!(key.starts_with(MACED_KEY_PREFIX), mac_keys);
+            }
+
+            let store = EncryptedKVS::new(parent.clone(), &keyring, config);
+            if mac_keys {
+                assert!(store.range(None, None, Order::Ascending, None).is_empty());
+                assert!(Box::new(store).finalize(false).is_err());
+            } else {
+                assert_eq!(
+                    store.range(None, None, Order::Descending, None),
+                    vec![(B("k2"), B("v2")), (B("k1"), B("v1"))]
+                );
+                assert!(Box::new(store).finalize(false).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn test_encrypted_kvs_tampering() {
+        let config = EncryptionConfig::default();
+        let parent = Rc::new(RefCell::new(MockStore::default()));
+        let keyring = MockKeyring::default();
+        let mut store = EncryptedKVS::new(parent.clone(), &keyring, config);
+        store.set(B("k1"), B("v1"));
+        store.set(B("k2"), B("v2"));
+        assert!(Box::new(store).finalize(true).is_ok());
+
+        // case1: the encrypted value is moved to another key by the host
+        let v1 = parent.borrow().get(&B("k1")).unwrap();
+        parent.borrow_mut().set(B("k2"), v1);
+        let store = EncryptedKVS::new(parent.clone(), &keyring, config);
+        assert_eq!(store.get(&B("k2")), None);
+        assert!(Box::new(store).finalize(false).is_err());
+
+        // case2: a plaintext value is inserted by the host
+        parent.borrow_mut().set(B("k3"), B("v3"));
+        let store = EncryptedKVS::new(parent.clone(), &keyring, config);
+        assert_eq!(store.get(&B("k3")), None);
+        assert!(Box::new(store).finalize(false).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_kvs_migration() {
+        for mac_keys in [false, true] {
+            let parent = Rc::new(RefCell::new(MockStore::default()));
+            parent.borrow_mut().set(B("k1"), B("v1"));
+            parent.borrow_mut().set(B("k2"), B("v2"));
+            let keyring = MockKeyring::default();
+
+            // the plaintext values cannot be read without the migration
+            let config = EncryptionConfig {
+                mac_keys,
+                migrate_plaintext: false,
+            };
+            let store = EncryptedKVS::new(parent.clone(), &keyring, config);
+            let res = store.get(&B("k1"));
+            if mac_keys {
+                assert_eq!(res, None);
+                assert!(Box::new(store).finalize(false).is_ok());
+            } else {
+                assert!(Box::new(store).finalize(false).is_err());
+            }
+
+            let config = EncryptionConfig {
+                mac_keys,
+                migrate_plaintext: true,
+            };
+            let mut store = EncryptedKVS::new(parent.clone(), &keyring, config);
+            assert_eq!(store.get(&B("k1")), Some(B("v1")));
+            assert_eq!(store.get(&B("k2")), Some(B("v2")));
+            store.set(B("k1"), B("v3"));
+            store.remove(&B("k2"));
+            assert_eq!(store.get(&B("k1")), Some(B("v3")));
+            assert_eq!(store.get(&B("k2")), None);
+            assert!(Box::new(store).finalize(true).is_ok());
+
+            // the migrated values don't depend on the plaintext values
+            let config = EncryptionConfig {
+                mac_keys,
+                migrate_plaintext: false,
+            };
+            let store = EncryptedKVS::new(parent.clone(), &keyring, config);
+            assert_eq!(store.get(&B("k1")), Some(B("v3")));
+            assert_eq!(store.get(&B("k2")), None);
+            assert!(Box::new(store).finalize(false).is_ok());
+            assert_eq!(parent.borrow().db.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_encrypted_kvs_sealed_key() {
+        let config = EncryptionConfig::default();
+        let parent = Rc::new(RefCell::new(MockStore::default()));
+        let keyring = MockKeyring::default();
+
+        // the sealed key is not written if the transaction is not committed
+        let mut store = EncryptedKVS::new(parent.clone(), &keyring, config);
+        store.set(B("k1"), B("v1"));
+        assert!(Box::new(store).finalize(false).is_ok());
+        assert_eq!(parent.borrow().get(SEALED_DATA_KEY), None);
+
+        // the sealed key is written again if it is missing in the host's store
+        let mut store = EncryptedKVS::new(parent.clone(), &keyring, config);
+        store.set(B("k1"), B("v1"));
+        assert!(Box::new(store).finalize(true).is_ok());
+        assert_eq!(parent.borrow().get(SEALED_DATA_KEY), Some(B("sealed")));
+
+        // a restarted enclave loads the sealed key from the host's store
+        parent
+            .borrow_mut()
+            .set(SEALED_DATA_KEY.to_vec(), B("resealed"));
+        let keyring = MockKeyring::default();
+        let store = EncryptedKVS::new(parent.clone(), &keyring, config);
+        assert_eq!(store.get(&B("k1")), Some(B("v1")));
+        assert!(Box::new(store).finalize(true).is_ok());
+        assert_eq!(keyring.sealed.get(), Some(&B("resealed")));
+
+        // the reserved keys cannot be written
+        let mut store = EncryptedKVS::new(parent.clone(), &keyring, config);
+        store.set(SEALED_DATA_KEY.to_vec(), B("x"));
+        assert!(Box::new(store).finalize(true).is_err());
+        assert_eq!(parent.borrow().get(SEALED_DATA_KEY), Some(B("resealed")));
+    }
+}
@@ -25,10 +25,55 @@ define_error! {
         WaitMutex { descr: String }
         |e| { format_args!("Wait mutex error: {}", e.descr) },
 
+        Iterate { descr: String }
+        |e| { format_args!("Iterate error: {}", e.descr) },
+
         NotSupportedOperation { descr: String }
         |e| { format_args!("The tx doesn't support an operation {}", e.descr) },
 
         InvalidUpdateKeyLength { length: usize }
-        |e| { format_args!("Invalid UpdateKey length: {}", e.length) }
+        |e| { format_args!("Invalid UpdateKey length: {}", e.length) },
+
+        ReservedKey { key: Vec<u8> }
+        |e| { format_args!("the key is reserved by the authenticated store: key={}", String::from_utf8_lossy(&e.key)) },
+
+        UnsupportedRange
+        |_| "the range must be within the keys of a single client",
+
+        AuthenticatedNodeNotFound { hash: Vec<u8> }
+        |e| { format_args!("authenticated node not found: hash={:?}", e.hash) },
+
+        InvalidAuthenticatedData { descr: String }
+        |e| { format_args!("invalid authenticated data: {}", e.descr) },
+
+        UnauthenticatedValue { key: Vec<u8> }
+        |e| { format_args!("the value doesn't match the authenticated one: key={}", String::from_utf8_lossy(&e.key)) },
+
+        RootVersionMismatch { tree_id: Vec<u8>, expected: u64, actual: u64 }
+        |e| {
+            format_args!("the root stored in the host doesn't match the latest one: tree_id={} expected_version={} actual_version={}",
+                String::from_utf8_lossy(&e.tree_id), e.expected, e.actual)
+        },
+
+        RootConflict { tree_id: Vec<u8>, version: u64 }
+        |e| {
+            format_args!("the root has been updated by another transaction: tree_id={} version={}",
+                String::from_utf8_lossy(&e.tree_id), e.version)
+        },
+
+        SealRoot { descr: String }
+        |e| { format_args!("failed to seal the root: {}", e.descr) },
+
+        UnsealRoot { descr: String }
+        |e| { format_args!("failed to unseal the root: {}", e.descr) },
+
+        Cipher { descr: String }
+        |e| { format_args!("cipher error: {}", e.descr) },
+
+        UnencryptedValue { key: Vec<u8> }
+        |e| { format_args!("the value is not encrypted: key={}", String::from_utf8_lossy(&e.key)) },
+
+        MacedKeyRange
+        |_| "range queries are not supported when the keys are MACed"
     }
 }
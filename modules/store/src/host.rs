@@ -7,6 +7,8 @@ use crate::{KVStore, Result, TxId};
 pub enum HostStore {
     #[cfg(feature = "rocksdb")]
     RocksDB(crate::rocksdb::RocksDBStore),
+    #[cfg(feature = "sqlite")]
+    SQLite(crate::sqlite::SQLiteStore),
     Memory(crate::memory::MemStore),
 }
 
@@ -26,6 +28,16 @@ impl IntoCommitStore<crate::rocksdb::RocksDBStore> for HostStore {
     }
 }
 
+#[cfg(feature = "sqlite")]
+impl IntoCommitStore<crate::sqlite::SQLiteStore> for HostStore {
+    fn apply<T>(&mut self, f: impl FnOnce(&mut crate::sqlite::SQLiteStore) -> T) -> T {
+        match self {
+            HostStore::SQLite(store) => f(store),
+            _ => unreachable!(),
+        }
+    }
+}
+
 impl IntoCommitStore<MemStore> for HostStore {
     fn apply<T>(&mut self, f: impl FnOnce(&mut MemStore) -> T) -> T {
         match self {
@@ -41,6 +53,8 @@ impl TxAccessor for HostStore {
         match self {
             #[cfg(feature = "rocksdb")]
             HostStore::RocksDB(store) => store.run_in_tx(tx_id, f),
+            #[cfg(feature = "sqlite")]
+            HostStore::SQLite(store) => store.run_in_tx(tx_id, f),
             HostStore::Memory(store) => store.run_in_tx(tx_id, f),
         }
     }
@@ -53,6 +67,8 @@ impl TxAccessor for HostStore {
         match self {
             #[cfg(feature = "rocksdb")]
             HostStore::RocksDB(store) => store.run_in_mut_tx(tx_id, f),
+            #[cfg(feature = "sqlite")]
+            HostStore::SQLite(store) => store.run_in_mut_tx(tx_id, f),
             HostStore::Memory(store) => store.run_in_mut_tx(tx_id, f),
         }
     }
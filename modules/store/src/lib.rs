@@ -21,16 +21,23 @@ mod prelude {
 }
 
 pub use crate::errors::{Error, Result};
-pub use crate::store::{KVStore, TxId};
+pub use crate::store::{
+    btree_range, prefix_end, KVPair, KVStore, Order, TxId, TxKVStore, WriteBatch,
+};
 
+pub mod authenticated;
 pub mod cache;
+pub mod encrypted;
 mod errors;
 #[cfg(feature = "std")]
 pub mod host;
 #[cfg(feature = "std")]
 pub mod memory;
+pub mod protected;
 #[cfg(feature = "rocksdb")]
 pub mod rocksdb;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 mod store;
 #[cfg(feature = "std")]
 pub mod transaction;
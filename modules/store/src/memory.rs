@@ -1,8 +1,8 @@
 use crate::prelude::*;
-use crate::store::TxId;
+use crate::store::{overlay_range, TxId};
 use crate::transaction::{CommitStore, CreatedTx, Tx, TxAccessor};
-use crate::{KVStore, Result};
-use std::collections::HashMap;
+use crate::{btree_range, KVPair, KVStore, Order, Result};
+use std::collections::BTreeMap;
 use std::sync::Mutex;
 
 // MemStore is only available for testing purposes
@@ -21,6 +21,16 @@ impl KVStore for MemStore {
     fn remove(&mut self, key: &[u8]) {
         self.0.lock().unwrap().remove(key)
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.0.lock().unwrap().range(start, end, order, limit)
+    }
 }
 
 impl TxAccessor for MemStore {
@@ -64,8 +74,8 @@ impl CommitStore for MemStore {
 pub struct InnerMemStore {
     running_tx_exists: bool,
     latest_tx_id: TxId,
-    uncommitted_data: HashMap<Vec<u8>, Option<Vec<u8>>>,
-    committed_data: HashMap<Vec<u8>, Vec<u8>>,
+    uncommitted_data: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+    committed_data: BTreeMap<Vec<u8>, Vec<u8>>,
 }
 
 impl KVStore for InnerMemStore {
@@ -95,6 +105,22 @@ impl KVStore for InnerMemStore {
             self.committed_data.remove(key);
         }
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        if self.running_tx_exists {
+            overlay_range(&self.uncommitted_data, start, end, order, limit, |limit| {
+                btree_range(&self.committed_data, start, end, order, limit)
+            })
+        } else {
+            btree_range(&self.committed_data, start, end, order, limit)
+        }
+    }
 }
 
 impl TxAccessor for InnerMemStore {
@@ -131,7 +157,7 @@ impl CommitStore for InnerMemStore {
     fn commit(&mut self, _tx: <Self::Tx as CreatedTx>::PreparedTx) -> Result<()> {
         assert!(self.running_tx_exists);
         self.running_tx_exists = false;
-        let data = HashMap::<Vec<u8>, Option<Vec<u8>>>::default();
+        let data = BTreeMap::<Vec<u8>, Option<Vec<u8>>>::default();
         let uncommitted_data = std::mem::replace(&mut self.uncommitted_data, data);
         for it in uncommitted_data {
             match it.1 {
@@ -0,0 +1,187 @@
+use crate::authenticated::{AuthenticatedKVS, AuthenticationConfig, RootRegistry, RootSealer};
+use crate::encrypted::{EncryptedKVS, EncryptionConfig, StoreKeyring};
+use crate::prelude::*;
+use crate::TxKVStore;
+
+/// `StoreEncryption` enables `EncryptedKVS` in `StoreProtection`
+pub struct StoreEncryption<'a> {
+    pub config: EncryptionConfig,
+    pub keyring: &'a dyn StoreKeyring,
+}
+
+/// `StoreAuthentication` enables `AuthenticatedKVS` in `StoreProtection`
+pub struct StoreAuthentication<'a> {
+    pub config: AuthenticationConfig,
+    pub registry: &'a dyn RootRegistry,
+    pub sealer: &'a dyn RootSealer,
+}
+
+/// `StoreProtection` is the set of the layers that protect the state stored in the host
+#[derive(Default)]
+pub struct StoreProtection<'a> {
+    pub encryption: Option<StoreEncryption<'a>>,
+    pub authentication: Option<StoreAuthentication<'a>>,
+}
+
+impl<'a> StoreProtection<'a> {
+    /// `wrap` wraps `store` with the enabled layers
+    ///
+    /// The authentication layer is put on top of the encryption layer,
+    /// so the trees are built from the plaintext values and their nodes are encrypted too.
+    pub fn wrap(&self, store: Box<dyn TxKVStore + 'a>) -> Box<dyn TxKVStore + 'a> {
+        let store: Box<dyn TxKVStore + 'a> = match self.encryption.as_ref() {
+            Some(enc) => Box::new(EncryptedKVS::new(store, enc.keyring, enc.config)),
+            None => store,
+        };
+        match self.authentication.as_ref() {
+            Some(auth) => Box::new(AuthenticatedKVS::new(
+                store,
+                auth.registry,
+                auth.sealer,
+                auth.config,
+            )),
+            None => store,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::authenticated::{Hash, RootTable};
+    use crate::encrypted::{StoreCipher, ENCRYPTED_VALUE_HEADER};
+    use crate::{btree_range, Error, KVPair, KVStore, Order, Result};
+    use alloc::collections::BTreeMap;
+    use alloc::rc::Rc;
+    use core::cell::RefCell;
+
+    #[derive(Default)]
+    struct MockStore {
+        db: BTreeMap<Vec<u8>, Vec<u8>>,
+    }
+
+    impl KVStore for MockStore {
+        fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+            self.db.insert(key, value);
+        }
+
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.db.get(key).cloned()
+        }
+
+        fn remove(&mut self, key: &[u8]) {
+            self.db.remove(key);
+        }
+
+        fn range(
+            &self,
+            start: Option<&[u8]>,
+            end: Option<&[u8]>,
+            order: Order,
+            limit: Option<usize>,
+        ) -> Vec<KVPair> {
+            btree_range(&self.db, start, end, order, limit)
+        }
+    }
+
+    struct MockSealer;
+
+    impl RootSealer for MockSealer {
+        fn seal(&self, digest: &Hash) -> Result<Vec<u8>> {
+            Ok(digest.to_vec())
+        }
+
+        fn unseal(&self, sealed: &[u8]) -> Result<Hash> {
+            sealed
+                .try_into()
+                .map_err(|_| Error::unseal_root("invalid length".to_string()))
+        }
+    }
+
+    /// `MockCipher` is an insecure cipher that inverts the bits and doesn't detect modifications
+    struct MockCipher;
+
+    impl StoreCipher for MockCipher {
+        fn encrypt(&self, plaintext: &[u8], _aad: &[u8]) -> Result<Vec<u8>> {
+            Ok(plaintext.iter().map(|b| !b).collect())
+        }
+
+        fn decrypt(&self, ciphertext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+            self.encrypt(ciphertext, aad)
+        }
+
+        fn mac(&self, key: &[u8]) -> Result<Vec<u8>> {
+            Ok(key.iter().rev().cloned().collect())
+        }
+    }
+
+    struct MockKeyring;
+
+    impl StoreKeyring for MockKeyring {
+        fn load(&self, _sealed: Option<&[u8]>) -> Result<(&dyn StoreCipher, &[u8])> {
+            Ok((&MockCipher, b"sealed"))
+        }
+    }
+
+    #[allow(non_snake_case)]
+    fn B(s: &str) -> Vec<u8> {
+        s.as_bytes().to_vec()
+    }
+
+    fn protection(registry: &RefCell<RootTable>, migration: bool) -> StoreProtection<'_> {
+        StoreProtection {
+            encryption: Some(StoreEncryption {
+                config: EncryptionConfig {
+                    mac_keys: false,
+                    migrate_plaintext: migration,
+                },
+                keyring: &MockKeyring,
+            }),
+            authentication: Some(StoreAuthentication {
+                config: AuthenticationConfig {
+                    migrate_existing: migration,
+                },
+                registry,
+                sealer: &MockSealer,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_store_protection_migration() {
+        let parent = Rc::new(RefCell::new(MockStore::default()));
+        let registry = RefCell::new(RootTable::default());
+        // the state written without any protection
+        parent.borrow_mut().set(B("clients/c-0/k1"), B("v1"));
+        parent.borrow_mut().set(B("clients/c-0/k2"), B("v2"));
+
+        // the existing state can be read while both migrations are enabled
+        let migration = protection(&registry, true);
+        let mut store = migration.wrap(Box::new(parent.clone()));
+        assert_eq!(store.get(&B("clients/c-0/k1")), Some(B("v1")));
+        store.set(B("clients/c-0/k2"), B("v3"));
+        store.set(B("clients/c-0/k4"), B("v4"));
+        assert!(store.finalize(true).is_ok());
+
+        // the migrated state is authenticated after disabling the migrations
+        let protection = protection(&registry, false);
+        let store = protection.wrap(Box::new(parent.clone()));
+        assert_eq!(store.get(&B("clients/c-0/k2")), Some(B("v3")));
+        assert_eq!(store.get(&B("clients/c-0/k4")), Some(B("v4")));
+        // the plaintext value that has not been written again cannot be read
+        assert_eq!(store.get(&B("clients/c-0/k1")), None);
+        assert!(store.finalize(false).is_err());
+
+        // the values written after the migration are encrypted
+        let mut store = protection.wrap(Box::new(parent.clone()));
+        store.set(B("clients/c-0/k1"), B("v5"));
+        assert!(store.finalize(true).is_ok());
+        for key in ["clients/c-0/k1", "clients/c-0/k2", "clients/c-0/k4"] {
+            let value = parent.borrow().get(&B(key)).unwrap();
+            assert!(value.starts_with(ENCRYPTED_VALUE_HEADER));
+        }
+        let store = protection.wrap(Box::new(parent.clone()));
+        assert_eq!(store.get(&B("clients/c-0/k1")), Some(B("v5")));
+        assert!(store.finalize(false).is_ok());
+    }
+}
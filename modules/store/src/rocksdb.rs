@@ -1,13 +1,14 @@
+use crate::store::overlay_range;
 use crate::transaction::{CommitStore, CreatedTx, Tx, TxAccessor, UpdateKey};
-use crate::{Error, KVStore, Result, TxId};
+use crate::{Error, KVPair, KVStore, Order, Result, TxId};
 use core::marker::PhantomData;
 use log::*;
 use ouroboros::self_referencing;
 use rocksdb::{
-    Error as RocksDBError, SnapshotWithThreadMode, Transaction, TransactionDB, TransactionOptions,
-    WriteOptions, DB,
+    Error as RocksDBError, IteratorMode, ReadOptions, SnapshotWithThreadMode, Transaction,
+    TransactionDB, TransactionOptions, WriteOptions, DB,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::rc::Rc;
 use std::sync::{Mutex, MutexGuard};
@@ -59,6 +60,26 @@ impl RocksDBStore {
         .build()
     }
 
+    /// `for_each` calls `f` with every key-value pair in the database in ascending order of the keys
+    ///
+    /// The pairs are read from the implicit snapshot taken when the iteration starts,
+    /// so the writes committed during the iteration are not visible to `f`.
+    pub fn for_each<E: From<Error>>(
+        &self,
+        mut f: impl FnMut(&[u8], &[u8]) -> core::result::Result<(), E>,
+    ) -> core::result::Result<(), E> {
+        type Item = core::result::Result<(Box<[u8]>, Box<[u8]>), RocksDBError>;
+        let iter: Box<dyn Iterator<Item = Item>> = match self.borrow_db() {
+            InnerDB::TransactionDB(db) => Box::new(db.iterator(IteratorMode::Start)),
+            InnerDB::ReadOnlyDB(db) => Box::new(db.iterator(IteratorMode::Start)),
+        };
+        for res in iter {
+            let (key, value) = res.map_err(|e| Error::iterate(e.to_string()))?;
+            f(&key, &value)?;
+        }
+        Ok(())
+    }
+
     pub fn finalize_tx<T>(
         &mut self,
         tx: RocksDBTx<PreparedRocksDBTx>,
@@ -95,6 +116,16 @@ impl KVStore for RocksDBStore {
     fn remove(&mut self, key: &[u8]) {
         self.borrow_db().remove(key)
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.borrow_db().range(start, end, order, limit)
+    }
 }
 
 impl TxAccessor for RocksDBStore {
@@ -166,7 +197,7 @@ impl CommitStore for RocksDBStore {
             let stx = match fields.db {
                 InnerDB::ReadOnlyDB(db) => StoreTransaction::ReadSnapshot(ReadSnapshot {
                     snapshot: db.snapshot(),
-                    buffer: HashMap::default(),
+                    buffer: BTreeMap::default(),
                 }),
                 InnerDB::TransactionDB(db) => {
                     if tx.is_update_tx() {
@@ -180,7 +211,7 @@ impl CommitStore for RocksDBStore {
                     } else {
                         StoreTransaction::Read(ReadTransaction {
                             snapshot: db.snapshot(),
-                            buffer: HashMap::default(),
+                            buffer: BTreeMap::default(),
                         })
                     }
                 }
@@ -233,6 +264,57 @@ impl InnerDB {
             Self::ReadOnlyDB(db) => db.delete(key).unwrap(),
         }
     }
+
+    pub(crate) fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        match self {
+            Self::TransactionDB(db) => range_iterator(start, end, order, limit, |mode, opts| {
+                db.iterator_opt(mode, opts)
+            }),
+            Self::ReadOnlyDB(db) => range_iterator(start, end, order, limit, |mode, opts| {
+                db.iterator_opt(mode, opts)
+            }),
+        }
+    }
+}
+
+/// `range_iterator` collects at most `limit` key-value pairs from the iterator created by `f` with the bounds `[start, end)`
+fn range_iterator<I>(
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: Order,
+    limit: Option<usize>,
+    f: impl FnOnce(IteratorMode<'static>, ReadOptions) -> I,
+) -> Vec<KVPair>
+where
+    I: Iterator<Item = core::result::Result<(Box<[u8]>, Box<[u8]>), RocksDBError>>,
+{
+    if matches!((start, end), (Some(start), Some(end)) if start >= end) {
+        return vec![];
+    }
+    let mut opts = ReadOptions::default();
+    if let Some(start) = start {
+        opts.set_iterate_lower_bound(start);
+    }
+    if let Some(end) = end {
+        opts.set_iterate_upper_bound(end);
+    }
+    let mode = match order {
+        Order::Ascending => IteratorMode::Start,
+        Order::Descending => IteratorMode::End,
+    };
+    f(mode, opts)
+        .take(limit.unwrap_or(usize::MAX))
+        .map(|res| {
+            let (k, v) = res.unwrap();
+            (k.into_vec(), v.into_vec())
+        })
+        .collect()
 }
 
 /// StoreTransaction implements multiple transaction types
@@ -283,6 +365,20 @@ impl<'a> KVStore for StoreTransaction<'a> {
             StoreTransaction::ReadSnapshot(stx) => stx.remove(key),
         }
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        match self {
+            StoreTransaction::Read(stx) => stx.range(start, end, order, limit),
+            StoreTransaction::Update(stx) => stx.range(start, end, order, limit),
+            StoreTransaction::ReadSnapshot(stx) => stx.range(start, end, order, limit),
+        }
+    }
 }
 
 /// ReadTransaction is a `read-only` transaction.
@@ -291,7 +387,7 @@ impl<'a> KVStore for StoreTransaction<'a> {
 /// All write operations are applied to the transaction's buffer, but they are never committed to the DB.
 pub struct ReadTransaction<'a> {
     snapshot: SnapshotWithThreadMode<'a, TransactionDB>,
-    buffer: HashMap<Vec<u8>, Option<Vec<u8>>>,
+    buffer: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
 }
 
 impl<'a> KVStore for ReadTransaction<'a> {
@@ -310,6 +406,20 @@ impl<'a> KVStore for ReadTransaction<'a> {
     fn remove(&mut self, key: &[u8]) {
         self.buffer.insert(key.to_vec(), None);
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        overlay_range(&self.buffer, start, end, order, limit, |limit| {
+            range_iterator(start, end, order, limit, |mode, opts| {
+                self.snapshot.iterator_opt(mode, opts)
+            })
+        })
+    }
 }
 
 /// UpdateTransaction is a `writable` transaction
@@ -349,6 +459,20 @@ impl<'a> KVStore for UpdateTransaction<'a> {
     fn remove(&mut self, key: &[u8]) {
         self.with_tx(|tx| tx.delete(key)).unwrap()
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.with_snapshot(|snapshot| {
+            range_iterator(start, end, order, limit, |mode, opts| {
+                snapshot.iterator_opt(mode, opts)
+            })
+        })
+    }
 }
 
 /// ReadSnapshot is a `read-only` transaction.
@@ -357,7 +481,7 @@ impl<'a> KVStore for UpdateTransaction<'a> {
 /// All write operations are applied to the transaction's buffer, but they are never committed to the DB.
 pub struct ReadSnapshot<'a> {
     snapshot: SnapshotWithThreadMode<'a, DB>,
-    buffer: HashMap<Vec<u8>, Option<Vec<u8>>>,
+    buffer: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
 }
 
 impl<'a> KVStore for ReadSnapshot<'a> {
@@ -376,6 +500,20 @@ impl<'a> KVStore for ReadSnapshot<'a> {
     fn remove(&mut self, key: &[u8]) {
         self.buffer.insert(key.to_vec(), None);
     }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        overlay_range(&self.buffer, start, end, order, limit, |limit| {
+            range_iterator(start, end, order, limit, |mode, opts| {
+                self.snapshot.iterator_opt(mode, opts)
+            })
+        })
+    }
 }
 
 /// RocksDBTx is a transaction handle corresponding to `StoreTransaction`
@@ -557,6 +695,68 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_range() {
+        let _ = env_logger::try_init();
+        let tmp_dir = TempDir::new().unwrap();
+        let mut store = RocksDBStore::open(tmp_dir.as_ref());
+        for i in 0..4 {
+            store.set(key(i), value(i));
+        }
+
+        // case1: range in update tx reflects the uncommitted changes
+        {
+            let tx = store.create_transaction(Some("test".into())).unwrap();
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            store.tx_remove(tx.get_id(), &key(1)).unwrap();
+            store.tx_set(tx.get_id(), key(4), value(4)).unwrap();
+            assert_eq!(
+                store
+                    .tx_range(tx.get_id(), Some(&key(1)), None, Order::Ascending, None)
+                    .unwrap(),
+                vec![(key(2), value(2)), (key(3), value(3)), (key(4), value(4))]
+            );
+            assert_eq!(
+                store
+                    .tx_range(tx.get_id(), None, Some(&key(3)), Order::Descending, Some(2))
+                    .unwrap(),
+                vec![(key(2), value(2)), (key(0), value(0))]
+            );
+            store.commit(tx).unwrap();
+        }
+
+        // case2: range in read tx reflects the buffered changes
+        {
+            let tx = store.create_transaction(None).unwrap();
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            store.tx_remove(tx.get_id(), &key(2)).unwrap();
+            store.tx_set(tx.get_id(), key(1), value(1)).unwrap();
+            assert_eq!(
+                store
+                    .tx_prefix_range(tx.get_id(), b"k", Order::Descending, Some(3))
+                    .unwrap(),
+                vec![(key(4), value(4)), (key(3), value(3)), (key(1), value(1))]
+            );
+            store.commit(tx).unwrap();
+        }
+
+        // case3: the changes in read tx are never committed
+        assert_eq!(
+            store.prefix_range(b"k", Order::Ascending, None),
+            vec![
+                (key(0), value(0)),
+                (key(2), value(2)),
+                (key(3), value(3)),
+                (key(4), value(4))
+            ]
+        );
+        assert!(store
+            .range(Some(&key(3)), Some(&key(3)), Order::Ascending, None)
+            .is_empty());
+    }
+
     #[test]
     fn test_concurrent_write_tx_with_same_update_key_1() {
         let (_tmp_dir, store, [r1, r2]) = get_test_helpers::<2>(vec![]);
@@ -0,0 +1,1076 @@
+use crate::store::overlay_range;
+use crate::transaction::{CommitStore, CreatedTx, Tx, TxAccessor, UpdateKey};
+use crate::{Error, KVPair, KVStore, Order, Result, TxId};
+use core::marker::PhantomData;
+use core::time::Duration;
+use log::*;
+use rusqlite::{
+    params, params_from_iter, Connection, OpenFlags, OptionalExtension, ToSql, TransactionBehavior,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+
+/// The duration to wait for a lock held by another connection
+const BUSY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `SQLiteStore` is a store implementation with SQLite
+///
+/// The database is opened in WAL mode, and each transaction reads from a snapshot taken by its own connection.
+/// The writes of an update transaction are buffered and applied to the database in a single SQLite transaction on commit.
+pub struct SQLiteStore {
+    path: PathBuf,
+    read_only: bool,
+    conn: Mutex<Connection>,
+    latest_tx_id: TxId,
+    txs: HashMap<TxId, SQLiteTransaction>,
+    mutex: HashMap<UpdateKey, Arc<UpdateLock>>,
+}
+
+impl SQLiteStore {
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        if !path.as_ref().exists() {
+            info!("initialize a database: {:?}", path.as_ref());
+        }
+        let conn = connect(path.as_ref(), false).unwrap();
+        conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))
+            .unwrap();
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS kvs (
+                key BLOB PRIMARY KEY,
+                value BLOB NOT NULL
+            ) WITHOUT ROWID",
+        )
+        .unwrap();
+        Self::create(path.as_ref(), false, conn)
+    }
+
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Self {
+        if !path.as_ref().exists() {
+            let store = Self::open(path.as_ref());
+            drop(store);
+        }
+        let conn = connect(path.as_ref(), true).unwrap();
+        Self::create(path.as_ref(), true, conn)
+    }
+
+    fn create(path: &Path, read_only: bool, conn: Connection) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            read_only,
+            conn: Mutex::new(conn),
+            latest_tx_id: Default::default(),
+            txs: Default::default(),
+            mutex: Default::default(),
+        }
+    }
+
+    /// `for_each` calls `f` with every key-value pair in the database in ascending order of the keys
+    ///
+    /// The pairs are read by a single statement, so the writes committed during the iteration are not visible to `f`.
+    pub fn for_each<E: From<Error>>(
+        &self,
+        mut f: impl FnMut(&[u8], &[u8]) -> core::result::Result<(), E>,
+    ) -> core::result::Result<(), E> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| Error::wait_mutex(e.to_string()))?;
+        let mut stmt = conn
+            .prepare("SELECT key, value FROM kvs ORDER BY key ASC")
+            .map_err(|e| Error::iterate(e.to_string()))?;
+        let mut rows = stmt.query([]).map_err(|e| Error::iterate(e.to_string()))?;
+        while let Some(row) = rows.next().map_err(|e| Error::iterate(e.to_string()))? {
+            let (key, value) = row
+                .get_ref(0)
+                .and_then(|key| Ok((key.as_blob()?, row.get_ref(1)?.as_blob()?)))
+                .map_err(|e| Error::iterate(e.to_string()))?;
+            f(key, value)?;
+        }
+        Ok(())
+    }
+
+    pub fn finalize_tx<T>(
+        &mut self,
+        tx: SQLiteTx<PreparedSQLiteTx>,
+        f: impl FnOnce(&Mutex<Connection>, SQLiteTransaction) -> T,
+    ) -> T {
+        if let Some(update_key) = tx.update_key.as_ref() {
+            let v = self.mutex.get(update_key).expect("invariant violation");
+            if Arc::strong_count(v) == 2 {
+                // "2" indicates `v` and an entry of `mutex` only exist
+                // so, remove the entry
+                self.mutex.remove_entry(update_key);
+            }
+        }
+        let (_, stx) = self.txs.remove_entry(&tx.id).expect("tx not found");
+        // NOTE: `tx` releases the lock of the update key after `f` returns
+        f(&self.conn, stx)
+    }
+}
+
+impl KVStore for SQLiteStore {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "REPLACE INTO kvs (key, value) VALUES (?1, ?2)",
+                params![key, value],
+            )
+            .unwrap();
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        get(&self.conn.lock().unwrap(), key)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.conn
+            .lock()
+            .unwrap()
+            .execute("DELETE FROM kvs WHERE key = ?1", params![key])
+            .unwrap();
+    }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        range(&self.conn.lock().unwrap(), start, end, order, limit)
+    }
+}
+
+impl TxAccessor for SQLiteStore {
+    fn run_in_tx<T>(&self, tx_id: TxId, f: impl FnOnce(&dyn KVStore) -> T) -> Result<T> {
+        let stx = self
+            .txs
+            .get(&tx_id)
+            .ok_or_else(|| Error::tx_id_not_found(tx_id))?;
+        Ok(f(stx))
+    }
+
+    fn run_in_mut_tx<T>(
+        &mut self,
+        tx_id: TxId,
+        f: impl FnOnce(&mut dyn KVStore) -> T,
+    ) -> Result<T> {
+        let stx = self
+            .txs
+            .get_mut(&tx_id)
+            .ok_or_else(|| Error::tx_id_not_found(tx_id))?;
+        Ok(f(stx))
+    }
+}
+
+impl CommitStore for SQLiteStore {
+    type Tx = SQLiteTx<CreatedSQLiteTx>;
+
+    fn create_transaction(&mut self, update_key: Option<UpdateKey>) -> Result<Self::Tx> {
+        debug!("create tx: {:?}", update_key);
+        self.latest_tx_id.safe_incr()?;
+        match update_key {
+            // NOTE: ignore `update_key` if the store is read-only
+            Some(update_key) if !self.read_only => {
+                if update_key.is_empty() {
+                    return Err(Error::invalid_update_key_length(0));
+                }
+                let lock = self.mutex.entry(update_key.clone()).or_default().clone();
+                Ok(SQLiteTx::new_update_tx(self.latest_tx_id, update_key, lock))
+            }
+            _ => Ok(SQLiteTx::new_read_tx(self.latest_tx_id)),
+        }
+    }
+
+    fn begin(&mut self, tx: &<Self::Tx as CreatedTx>::PreparedTx) -> Result<()> {
+        debug!("begin tx: {:?}", tx.get_id());
+        let stx = SQLiteTransaction::begin(&self.path, self.read_only, tx.is_update_tx())
+            .map_err(|e| Error::begin_tx(e.to_string()))?;
+        self.txs.insert(tx.get_id(), stx);
+        Ok(())
+    }
+
+    fn commit(&mut self, tx: <Self::Tx as CreatedTx>::PreparedTx) -> Result<()> {
+        debug!("commit tx: {:?}", tx.get_id());
+        self.finalize_tx(tx, |conn, stx| stx.commit(conn))
+    }
+
+    fn rollback(&mut self, tx: <Self::Tx as CreatedTx>::PreparedTx) {
+        debug!("rollback tx: {:?}", tx.get_id());
+        self.finalize_tx(tx, |_, stx| stx.rollback())
+    }
+}
+
+fn connect(path: &Path, read_only: bool) -> rusqlite::Result<Connection> {
+    let flags = if read_only {
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX
+    } else {
+        OpenFlags::default()
+    };
+    let conn = Connection::open_with_flags(path, flags)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
+fn get(conn: &Connection, key: &[u8]) -> Option<Vec<u8>> {
+    conn.prepare_cached("SELECT value FROM kvs WHERE key = ?1")
+        .unwrap()
+        .query_row(params![key], |row| row.get(0))
+        .optional()
+        .unwrap()
+}
+
+/// `range` returns at most `limit` key-value pairs whose keys are in `[start, end)` sorted by key in `order`
+///
+/// The keys are compared with `memcmp()` as BLOBs, which matches the ordering of the other stores.
+fn range(
+    conn: &Connection,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: Order,
+    limit: Option<usize>,
+) -> Vec<KVPair> {
+    if matches!((start, end), (Some(start), Some(end)) if start >= end) {
+        return vec![];
+    }
+    let mut conditions = vec![];
+    let mut params: Vec<&dyn ToSql> = vec![];
+    if let Some(start) = start.as_ref() {
+        conditions.push("key >= ?");
+        params.push(start);
+    }
+    if let Some(end) = end.as_ref() {
+        conditions.push("key < ?");
+        params.push(end);
+    }
+    let mut sql = "SELECT key, value FROM kvs".to_owned();
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+    sql.push_str(match order {
+        Order::Ascending => " ORDER BY key ASC",
+        Order::Descending => " ORDER BY key DESC",
+    });
+    // a negative LIMIT means no limit
+    let limit = limit.map_or(-1, |limit| i64::try_from(limit).unwrap_or(i64::MAX));
+    sql.push_str(" LIMIT ?");
+    params.push(&limit);
+
+    conn.prepare_cached(&sql)
+        .unwrap()
+        .query_map(params_from_iter(params), |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap()
+}
+
+/// `write_buffer` applies the writes in `buffer` to the database in a single transaction
+fn write_buffer(
+    conn: &mut Connection,
+    buffer: &BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+) -> rusqlite::Result<()> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    {
+        let mut set = tx.prepare_cached("REPLACE INTO kvs (key, value) VALUES (?1, ?2)")?;
+        let mut remove = tx.prepare_cached("DELETE FROM kvs WHERE key = ?1")?;
+        for (key, value) in buffer.iter() {
+            match value {
+                Some(value) => set.execute(params![key, value])?,
+                None => remove.execute(params![key])?,
+            };
+        }
+    }
+    tx.commit()
+}
+
+/// SQLiteTransaction is a transaction that reads from a snapshot of the database
+///
+/// All write operations are applied to the transaction's buffer.
+/// The buffer of an update transaction is written to the database on commit, and that of a read transaction is never committed.
+pub struct SQLiteTransaction {
+    conn: Mutex<Connection>,
+    update: bool,
+    buffer: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl SQLiteTransaction {
+    fn begin(path: &Path, read_only: bool, update: bool) -> rusqlite::Result<Self> {
+        let conn = connect(path, read_only)?;
+        conn.execute_batch("BEGIN")?;
+        // a deferred transaction takes a snapshot on the first read
+        conn.query_row("SELECT 1 FROM kvs LIMIT 1", [], |_| Ok(()))
+            .optional()?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            update,
+            buffer: Default::default(),
+        })
+    }
+
+    fn commit(self, conn: &Mutex<Connection>) -> Result<()> {
+        self.end().map_err(|e| Error::commit_tx(e.to_string()))?;
+        if !self.update || self.buffer.is_empty() {
+            return Ok(());
+        }
+        let mut conn = conn.lock().map_err(|e| Error::wait_mutex(e.to_string()))?;
+        write_buffer(&mut conn, &self.buffer).map_err(|e| Error::commit_tx(e.to_string()))
+    }
+
+    fn rollback(&self) {
+        // NOTE: the buffer is discarded anyway, so the failure to release the snapshot is not fatal
+        if let Err(e) = self.end() {
+            error!("failed to rollback the transaction: {}", e);
+        }
+    }
+
+    /// `end` releases the snapshot of the transaction
+    fn end(&self) -> rusqlite::Result<()> {
+        self.conn.lock().unwrap().execute_batch("ROLLBACK")
+    }
+}
+
+impl KVStore for SQLiteTransaction {
+    fn set(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.buffer.insert(key, Some(value));
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.buffer.get(key) {
+            Some(Some(v)) => Some(v.to_vec()),
+            Some(None) => None, // already removed in the tx
+            None => get(&self.conn.lock().unwrap(), key),
+        }
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.buffer.insert(key.to_vec(), None);
+    }
+
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        overlay_range(&self.buffer, start, end, order, limit, |limit| {
+            range(&self.conn.lock().unwrap(), start, end, order, limit)
+        })
+    }
+}
+
+/// `UpdateLock` serializes the update transactions with the same update key
+#[derive(Default)]
+pub struct UpdateLock {
+    locked: Mutex<bool>,
+    cond: Condvar,
+}
+
+impl UpdateLock {
+    fn lock(&self) -> Result<()> {
+        let locked = self
+            .locked
+            .lock()
+            .map_err(|e| Error::wait_mutex(e.to_string()))?;
+        let mut locked = self
+            .cond
+            .wait_while(locked, |locked| *locked)
+            .map_err(|e| Error::wait_mutex(e.to_string()))?;
+        *locked = true;
+        Ok(())
+    }
+
+    fn unlock(&self) {
+        // the flag is only updated while the mutex is held, so it is valid even if the mutex is poisoned
+        *self.locked.lock().unwrap_or_else(|e| e.into_inner()) = false;
+        self.cond.notify_one();
+    }
+}
+
+/// SQLiteTx is a transaction handle corresponding to `SQLiteTransaction`
+pub struct SQLiteTx<T> {
+    pub id: TxId,
+    pub update_key: Option<UpdateKey>,
+    lock: Option<Arc<UpdateLock>>,
+    locked: bool,
+    marker: PhantomData<T>,
+}
+
+/// CreatedSQLiteTx represents a type of transaction that cannot be begun yet
+pub struct CreatedSQLiteTx;
+
+/// PreparedSQLiteTx represents a type of transaction that has been begun or can be begun
+pub struct PreparedSQLiteTx;
+
+impl<T> Tx for SQLiteTx<T> {
+    fn get_id(&self) -> TxId {
+        self.id
+    }
+}
+
+impl CreatedTx for SQLiteTx<CreatedSQLiteTx> {
+    type PreparedTx = SQLiteTx<PreparedSQLiteTx>;
+
+    fn prepare(mut self) -> Result<Self::PreparedTx> {
+        let lock = self.lock.take();
+        if let Some(lock) = lock.as_ref() {
+            lock.lock()?;
+        }
+        Ok(SQLiteTx {
+            id: self.id,
+            update_key: self.update_key.take(),
+            locked: lock.is_some(),
+            lock,
+            marker: Default::default(),
+        })
+    }
+}
+
+impl<T> SQLiteTx<T> {
+    pub fn new_read_tx(id: TxId) -> Self {
+        Self {
+            id,
+            update_key: None,
+            lock: None,
+            locked: false,
+            marker: Default::default(),
+        }
+    }
+
+    pub fn new_update_tx(id: TxId, update_key: UpdateKey, lock: Arc<UpdateLock>) -> Self {
+        Self {
+            id,
+            update_key: Some(update_key),
+            lock: Some(lock),
+            locked: false,
+            marker: Default::default(),
+        }
+    }
+
+    pub fn is_update_tx(&self) -> bool {
+        self.update_key.is_some()
+    }
+}
+
+impl<T> Drop for SQLiteTx<T> {
+    fn drop(&mut self) {
+        if self.locked {
+            self.lock.as_ref().unwrap().unlock();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::sync::Arc;
+    use core::time::Duration;
+    use std::{
+        collections::HashSet,
+        sync::{Condvar, RwLock},
+        thread,
+        time::SystemTime,
+    };
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_store() {
+        let _ = env_logger::try_init();
+        let tmp_dir = TempDir::new().unwrap();
+        let mut store = SQLiteStore::open(db_path(&tmp_dir));
+
+        // case1: set key-value pair simply in update tx
+        // pre:  initial state
+        // post: k0 -> v0
+        {
+            let tx = store.create_transaction(Some("test".into())).unwrap();
+            assert_eq!(store.mutex.len(), 1);
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            assert!(store.tx_set(tx.get_id(), key(0), value(0)).is_ok());
+            assert!(store
+                .tx_get(tx.get_id(), &key(0))
+                .unwrap()
+                .eq(&Some(value(0))));
+            store.commit(tx).unwrap();
+            assert_eq!(store.mutex.len(), 0);
+        }
+
+        // case2: get key-value pair simply in read tx
+        // post: k0 -> v0
+        {
+            let tx = store.create_transaction(None).unwrap();
+            assert_eq!(store.mutex.len(), 0);
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            assert!(store
+                .tx_get(tx.get_id(), &key(0))
+                .unwrap()
+                .eq(&Some(value(0))));
+            assert!(store.tx_set(tx.get_id(), key(0), value(1)).is_ok());
+            assert!(store
+                .tx_get(tx.get_id(), &key(0))
+                .unwrap()
+                .eq(&Some(value(1))));
+            store.commit(tx).unwrap();
+            assert_eq!(store.mutex.len(), 0);
+        }
+
+        // case3: remove key-value pair simply in read tx
+        // post: k0 -> v0
+        {
+            let tx = store.create_transaction(None).unwrap();
+            assert_eq!(store.mutex.len(), 0);
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            store.tx_remove(tx.get_id(), &key(0)).unwrap();
+            assert!(store.tx_get(tx.get_id(), &key(0)).unwrap().eq(&None));
+            store.commit(tx).unwrap();
+            assert_eq!(store.mutex.len(), 0);
+            assert!(store.get(&key(0)).ne(&None));
+        }
+
+        // case4: remove key-value pair simply in update tx
+        // post: empty
+        {
+            let tx = store.create_transaction(Some("test".into())).unwrap();
+            assert_eq!(store.mutex.len(), 1);
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            store.tx_remove(tx.get_id(), &key(0)).unwrap();
+            assert!(store.tx_get(tx.get_id(), &key(0)).unwrap().eq(&None));
+            store.commit(tx).unwrap();
+            assert_eq!(store.mutex.len(), 0);
+            assert!(store.get(&key(0)).eq(&None));
+        }
+
+        // case5: set key-value pair but rollback it
+        {
+            let tx = store.create_transaction(Some("test".into())).unwrap();
+            assert_eq!(store.mutex.len(), 1);
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            assert!(store.tx_set(tx.get_id(), key(0), value(0)).is_ok());
+            store.rollback(tx);
+            assert_eq!(store.mutex.len(), 0);
+            assert!(store.get(&key(0)).eq(&None));
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let _ = env_logger::try_init();
+        let tmp_dir = TempDir::new().unwrap();
+        let mut store = SQLiteStore::open(db_path(&tmp_dir));
+        for i in 0..4 {
+            store.set(key(i), value(i));
+        }
+
+        // case1: range in update tx reflects the uncommitted changes
+        {
+            let tx = store.create_transaction(Some("test".into())).unwrap();
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            store.tx_remove(tx.get_id(), &key(1)).unwrap();
+            store.tx_set(tx.get_id(), key(4), value(4)).unwrap();
+            assert_eq!(
+                store
+                    .tx_range(tx.get_id(), Some(&key(1)), None, Order::Ascending, None)
+                    .unwrap(),
+                vec![(key(2), value(2)), (key(3), value(3)), (key(4), value(4))]
+            );
+            assert_eq!(
+                store
+                    .tx_range(tx.get_id(), None, Some(&key(3)), Order::Descending, Some(2))
+                    .unwrap(),
+                vec![(key(2), value(2)), (key(0), value(0))]
+            );
+            store.commit(tx).unwrap();
+        }
+
+        // case2: range in read tx reflects the buffered changes
+        {
+            let tx = store.create_transaction(None).unwrap();
+            let tx = tx.prepare().unwrap();
+            store.begin(&tx).unwrap();
+            store.tx_remove(tx.get_id(), &key(2)).unwrap();
+            store.tx_set(tx.get_id(), key(1), value(1)).unwrap();
+            assert_eq!(
+                store
+                    .tx_prefix_range(tx.get_id(), b"k", Order::Descending, Some(3))
+                    .unwrap(),
+                vec![(key(4), value(4)), (key(3), value(3)), (key(1), value(1))]
+            );
+            store.commit(tx).unwrap();
+        }
+
+        // case3: the changes in read tx are never committed
+        assert_eq!(
+            store.prefix_range(b"k", Order::Ascending, None),
+            vec![
+                (key(0), value(0)),
+                (key(2), value(2)),
+                (key(3), value(3)),
+                (key(4), value(4))
+            ]
+        );
+        assert!(store
+            .range(Some(&key(3)), Some(&key(3)), Order::Ascending, None)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_for_each() {
+        let tmp_dir = TempDir::new().unwrap();
+        let mut store = SQLiteStore::open(db_path(&tmp_dir));
+        for i in (0..4).rev() {
+            store.set(key(i), value(i));
+        }
+        let mut pairs = vec![];
+        store
+            .for_each(|k, v| -> Result<()> {
+                pairs.push((k.to_vec(), v.to_vec()));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            pairs,
+            (0..4).map(|i| (key(i), value(i))).collect::<Vec<_>>()
+        );
+
+        // an error returned by `f` stops the iteration
+        let mut count = 0;
+        let res = store.for_each(|_, _| {
+            count += 1;
+            Err(Error::iterate("stop".into()))
+        });
+        assert!(res.is_err());
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_concurrent_write_tx_with_same_update_key_1() {
+        let (_tmp_dir, store, [r1, r2]) = get_test_helpers::<2>(vec![]);
+
+        // r1: create&prepare -> begin  -> commit
+        //                     \                   \
+        // r2:                   create -> prepare(blocking) -> begin&commit
+
+        let th1 = thread::spawn(move || {
+            r1.create(Some("test"))
+                .emit_event(1)
+                .prepare()
+                .emit_event(2)
+                .begin()
+                .set(key_s(0), value_s(0))
+                .commit()
+        });
+
+        let th2 = thread::spawn(move || {
+            r2.block_on(1, 1)
+                .create(Some("test"))
+                .block_on(1, 2)
+                .prepare()
+                .begin()
+                .get(key_s(0), Some(value_s(0)))
+                .set(key_s(0), value_s(1))
+                .commit()
+        });
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        assert!(store.read().unwrap().get(&key(0)).eq(&Some(value(1))));
+    }
+
+    #[test]
+    fn test_concurrent_write_tx_with_same_update_key_2() {
+        let (_tmp_dir, store, [r1, r2]) = get_test_helpers::<2>(vec![]);
+
+        // r1:        create -> prepare -> begin -> commit
+        //           /                                    \
+        // r2: create --------> prepare(blocking) ---------> begin&commit
+
+        let th1 = thread::spawn(move || {
+            r1.block_on(2, 1)
+                .create(Some("test"))
+                .prepare()
+                .emit_event(1)
+                .begin()
+                .set(key_s(0), value_s(0))
+                .commit()
+        });
+
+        let th2 = thread::spawn(move || {
+            r2.create(Some("test"))
+                .emit_event(1)
+                .block_on(1, 1)
+                .prepare()
+                .begin()
+                .get(key_s(0), Some(value_s(0)))
+                .set(key_s(0), value_s(1))
+                .commit()
+        });
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        assert!(store.read().unwrap().get(&key(0)).eq(&Some(value(1))));
+    }
+
+    #[test]
+    fn test_concurrent_read_tx() {
+        let (_tmp_dir, store, runners) = get_test_helpers::<8>(vec![]);
+
+        let mut ths = vec![];
+        let start = SystemTime::now();
+        for runner in runners.into_iter() {
+            let th = thread::spawn(move || {
+                runner
+                    .create(None)
+                    .prepare()
+                    .begin()
+                    .get(key_s(0), None)
+                    .execute(|| thread::sleep(Duration::from_secs(1)))
+                    .commit()
+            });
+            ths.push(th);
+        }
+        for th in ths.into_iter() {
+            th.join().unwrap();
+        }
+        let end = SystemTime::now().duration_since(start).unwrap();
+        assert!(Duration::from_secs(1) * 2 > end);
+        assert_eq!(store.read().unwrap().mutex.len(), 0);
+    }
+
+    #[test]
+    fn test_concurrent_include_rollback() {
+        let (_tmp_dir, store, [r1, r2]) = get_test_helpers::<2>(vec![]);
+
+        let th1 = thread::spawn(move || {
+            r1.create(Some("test"))
+                .prepare()
+                .emit_event(1)
+                .block_on(2, 1)
+                .begin()
+                .set(key_s(0), value_s(0))
+                .commit()
+        });
+
+        let th2 = thread::spawn(move || {
+            r2.create(Some("test"))
+                .block_on(1, 1)
+                .emit_event(1)
+                .prepare()
+                .begin()
+                .get(key_s(0), Some(value_s(0)))
+                .set(key_s(0), value_s(1))
+                .rollback()
+        });
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+
+        assert!(store.read().unwrap().get(&key(0)).eq(&Some(value(0))));
+    }
+
+    #[test]
+    fn test_concurrent_write_different_update_keys() {
+        let (_tmp_dir, store, runners) = get_test_helpers::<8>(vec![]);
+
+        let mut ths = vec![];
+        let start = SystemTime::now();
+        for r in runners {
+            let th = thread::spawn(move || {
+                let key = key_s(r.id);
+                r.create(Some(key.as_str()))
+                    .prepare()
+                    .begin()
+                    .set(key, value_s(0))
+                    .execute(|| thread::sleep(Duration::from_secs(1)))
+                    .commit()
+            });
+            ths.push(th);
+        }
+        for th in ths.into_iter() {
+            th.join().unwrap();
+        }
+        let end = SystemTime::now().duration_since(start).unwrap();
+        assert!(Duration::from_secs(1) * 2 > end);
+        assert_eq!(store.read().unwrap().mutex.len(), 0);
+    }
+
+    #[test]
+    fn test_write_and_snapshot() {
+        let (_tmp_dir, store, [r1, r2, r3]) = get_test_helpers::<3>(vec![3]);
+
+        // r1: create -> prepare -> begin -> set -----------> commit
+        //                                      \           /      \
+        // r2:                                   create -> begin -> get(before commit) -> get(after commit)
+        //                                                                                  \
+        // r3(read-only):                                                                    create -> begin -> get
+
+        let th1 = thread::spawn(move || {
+            r1.create(Some("test"))
+                .prepare()
+                .begin()
+                .set(key_s(0), value_s(0))
+                .emit_event(1)
+                .block_on(2, 1)
+                .commit()
+                .emit_event(2)
+        });
+
+        let th2 = thread::spawn(move || {
+            r2.block_on(1, 1)
+                .create(None)
+                .prepare()
+                .begin()
+                .get(key_s(0), None)
+                .emit_event(1)
+                .block_on(1, 2)
+                // snapshot does not reflect the update
+                .get(key_s(0), None)
+                .commit()
+        });
+
+        // the update key is ignored in read-only store, so the changes are never committed
+        let th3 = thread::spawn(move || {
+            r3.block_on(1, 2)
+                .create(Some("test"))
+                .prepare()
+                .begin()
+                .get(key_s(0), Some(value_s(0)))
+                .set(key_s(0), value_s(1))
+                .commit()
+        });
+
+        th1.join().unwrap();
+        th2.join().unwrap();
+        th3.join().unwrap();
+
+        assert!(store.read().unwrap().get(&key(0)).eq(&Some(value(0))));
+        assert_eq!(store.read().unwrap().mutex.len(), 0);
+    }
+
+    fn db_path(tmp_dir: &TempDir) -> std::path::PathBuf {
+        tmp_dir.path().join("store.sqlite")
+    }
+
+    fn key(idx: u64) -> Vec<u8> {
+        key_s(idx).into_bytes()
+    }
+
+    fn value(idx: u64) -> Vec<u8> {
+        value_s(idx).into_bytes()
+    }
+
+    fn key_s(idx: u64) -> String {
+        format!("k{}", idx)
+    }
+
+    fn value_s(idx: u64) -> String {
+        format!("v{}", idx)
+    }
+
+    fn get_test_helpers<const S: usize>(
+        read_only_ids: Vec<usize>,
+    ) -> (TempDir, Arc<RwLock<SQLiteStore>>, [TxRunner; S]) {
+        let _ = env_logger::try_init();
+        let tmp_dir = TempDir::new().unwrap();
+        let store = Arc::new(RwLock::new(SQLiteStore::open(db_path(&tmp_dir))));
+        let r_store = Arc::new(RwLock::new(SQLiteStore::open_read_only(db_path(&tmp_dir))));
+        let cond = Arc::new(Condvar::new());
+        let events = Arc::new(Mutex::new(HashSet::new()));
+
+        let runners = {
+            let mut arr: [std::mem::MaybeUninit<TxRunner>; S] =
+                unsafe { std::mem::MaybeUninit::uninit().assume_init() };
+            for (i, elem) in arr.iter_mut().enumerate() {
+                if read_only_ids.contains(&(i + 1)) {
+                    unsafe {
+                        std::ptr::write(
+                            elem.as_mut_ptr(),
+                            TxRunner::new(
+                                (i + 1) as u64,
+                                r_store.clone(),
+                                cond.clone(),
+                                events.clone(),
+                            ),
+                        );
+                    }
+                } else {
+                    unsafe {
+                        std::ptr::write(
+                            elem.as_mut_ptr(),
+                            TxRunner::new(
+                                (i + 1) as u64,
+                                store.clone(),
+                                cond.clone(),
+                                events.clone(),
+                            ),
+                        );
+                    }
+                }
+            }
+            let ptr = &mut arr as *mut _ as *mut [TxRunner; S];
+            let res = unsafe { ptr.read() };
+            #[allow(clippy::forget_non_drop)]
+            core::mem::forget(arr);
+            res
+        };
+        (tmp_dir, store, runners)
+    }
+
+    struct TxRunner {
+        id: u64,
+        channel: EventChannel,
+        store: Arc<RwLock<SQLiteStore>>,
+        created_tx: Option<SQLiteTx<CreatedSQLiteTx>>,
+        prepared_tx: Option<SQLiteTx<PreparedSQLiteTx>>,
+    }
+
+    struct EventChannel {
+        self_rid: u64,
+        cond: Arc<Condvar>,
+        events: Arc<Mutex<HashSet<(u64, u64)>>>, // (rid, eid)
+    }
+
+    impl EventChannel {
+        fn emit_event(&self, eid: u64) {
+            debug!("emit: runner_id={} eid={}", self.self_rid, eid);
+            self.events.lock().unwrap().insert((self.self_rid, eid));
+            self.cond.notify_all();
+        }
+        #[allow(unused_must_use)]
+        fn block_on(&self, rid: u64, eid: u64) {
+            let events = self.events.lock().unwrap();
+            self.cond
+                .wait_while(events, |events| {
+                    debug!(
+                        "wait: self_runner_id={} target_runner_id={} eid={}",
+                        self.self_rid, rid, eid
+                    );
+                    !events.contains(&(rid, eid))
+                })
+                .unwrap();
+        }
+    }
+
+    impl TxRunner {
+        fn new(
+            id: u64,
+            store: Arc<RwLock<SQLiteStore>>,
+            cond: Arc<Condvar>,
+            events: Arc<Mutex<HashSet<(u64, u64)>>>,
+        ) -> Self {
+            Self {
+                id,
+                channel: EventChannel {
+                    self_rid: id,
+                    cond,
+                    events,
+                },
+                store,
+                created_tx: None,
+                prepared_tx: None,
+            }
+        }
+
+        fn emit_event(self, eid: u64) -> Self {
+            self.channel.emit_event(eid);
+            self
+        }
+
+        fn block_on(self, rid: u64, eid: u64) -> Self {
+            self.channel.block_on(rid, eid);
+            self
+        }
+
+        fn create(mut self, update_key: Option<&str>) -> Self {
+            debug!("create: id={} update_key={:?}", self.id, update_key);
+            let tx = self
+                .store
+                .write()
+                .unwrap()
+                .create_transaction(update_key.map(|s| s.into()))
+                .unwrap();
+            self.created_tx = Some(tx);
+            self
+        }
+
+        fn prepare(mut self) -> Self {
+            debug!("prepare: id={}", self.id);
+            self.prepared_tx = Some(self.created_tx.take().unwrap().prepare().unwrap());
+            self
+        }
+
+        fn begin(self) -> Self {
+            debug!("begin: id={}", self.id);
+            self.store
+                .write()
+                .unwrap()
+                .begin(self.prepared_tx.as_ref().unwrap())
+                .unwrap();
+            self
+        }
+
+        fn get<S: Into<String>>(self, key: S, expected_value: Option<S>) -> Self {
+            let v = self
+                .store
+                .read()
+                .unwrap()
+                .tx_get(
+                    self.prepared_tx.as_ref().unwrap().get_id(),
+                    (key.into() as String).as_bytes(),
+                )
+                .unwrap();
+
+            assert_eq!(
+                v.map(|s| String::from_utf8(s).unwrap()),
+                expected_value.map(|s| s.into())
+            );
+            self
+        }
+
+        fn set<S: Into<String>>(self, key: S, value: S) -> Self {
+            self.store
+                .write()
+                .unwrap()
+                .tx_set(
+                    self.prepared_tx.as_ref().unwrap().get_id(),
+                    (key.into() as String).into_bytes(),
+                    (value.into() as String).into_bytes(),
+                )
+                .unwrap();
+            self
+        }
+
+        fn execute(self, f: impl FnOnce()) -> Self {
+            debug!("execute: id={}", self.id);
+            f();
+            self
+        }
+
+        fn commit(mut self) -> Self {
+            debug!("commit: id={}", self.id);
+            let tx = self.prepared_tx.take().unwrap();
+            self.store.write().unwrap().commit(tx).unwrap();
+            self
+        }
+
+        fn rollback(mut self) -> Self {
+            debug!("rollback: id={}", self.id);
+            let tx = self.prepared_tx.take().unwrap();
+            self.store.write().unwrap().rollback(tx);
+            self
+        }
+    }
+}
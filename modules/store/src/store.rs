@@ -1,15 +1,65 @@
 use crate::prelude::*;
 use crate::{Error, Result};
+use alloc::collections::BTreeMap;
 use alloc::rc::Rc;
 use core::cell::RefCell;
 use core::fmt::Display;
-use core::ops::Deref;
+use core::ops::{Bound, Deref};
 use serde::{Deserialize, Serialize};
 
+/// `KVPair` is a key-value pair returned by a range query
+pub type KVPair = (Vec<u8>, Vec<u8>);
+
+/// `WriteBatch` is a list of writes applied in order, where `None` removes the key
+pub type WriteBatch = Vec<(Vec<u8>, Option<Vec<u8>>)>;
+
+/// `Order` is the order of key-value pairs returned by a range query
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
 pub trait KVStore {
     fn set(&mut self, key: Vec<u8>, value: Vec<u8>);
     fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
     fn remove(&mut self, key: &[u8]);
+
+    /// `range` returns at most `limit` key-value pairs whose keys are in `[start, end)` sorted by key in `order`
+    ///
+    /// `None` for `start` or `end` means that the range is unbounded on that side.
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair>;
+
+    /// `prefix_range` returns at most `limit` key-value pairs whose keys start with `prefix` sorted by key in `order`
+    fn prefix_range(&self, prefix: &[u8], order: Order, limit: Option<usize>) -> Vec<KVPair> {
+        let end = prefix_end(prefix);
+        self.range(Some(prefix), end.as_deref(), order, limit)
+    }
+
+    /// `write_batch` applies the writes in `batch` in order
+    ///
+    /// A store that can apply the writes at once (e.g. in a single ocall) should override this.
+    fn write_batch(&mut self, batch: WriteBatch) {
+        for (key, value) in batch {
+            match value {
+                Some(value) => self.set(key, value),
+                None => self.remove(&key),
+            }
+        }
+    }
+}
+
+/// `TxKVStore` is a `KVStore` bound to a transaction, which must be finalized before the transaction is committed
+pub trait TxKVStore: KVStore {
+    /// `finalize` returns the error that occurred in the store operations if any,
+    /// and applies the changes to the state of the store if `commit` is true
+    fn finalize(self: Box<Self>, commit: bool) -> Result<()>;
 }
 
 impl KVStore for Box<dyn KVStore> {
@@ -22,6 +72,55 @@ impl KVStore for Box<dyn KVStore> {
     fn remove(&mut self, key: &[u8]) {
         self.as_mut().remove(key)
     }
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.as_ref().range(start, end, order, limit)
+    }
+    fn write_batch(&mut self, batch: WriteBatch) {
+        self.as_mut().write_batch(batch)
+    }
+}
+
+impl<'a> KVStore for Box<dyn TxKVStore + 'a> {
+    fn get(&self, k: &[u8]) -> Option<Vec<u8>> {
+        self.as_ref().get(k)
+    }
+    fn set(&mut self, k: Vec<u8>, v: Vec<u8>) {
+        self.as_mut().set(k, v)
+    }
+    fn remove(&mut self, key: &[u8]) {
+        self.as_mut().remove(key)
+    }
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.as_ref().range(start, end, order, limit)
+    }
+    fn write_batch(&mut self, batch: WriteBatch) {
+        self.as_mut().write_batch(batch)
+    }
+}
+
+impl<'a> TxKVStore for Box<dyn TxKVStore + 'a> {
+    fn finalize(self: Box<Self>, commit: bool) -> Result<()> {
+        (*self).finalize(commit)
+    }
+}
+
+/// A plain `KVStore` has no state to finalize
+impl TxKVStore for Box<dyn KVStore> {
+    fn finalize(self: Box<Self>, _commit: bool) -> Result<()> {
+        Ok(())
+    }
 }
 
 impl<T: KVStore> KVStore for Rc<RefCell<T>> {
@@ -34,6 +133,112 @@ impl<T: KVStore> KVStore for Rc<RefCell<T>> {
     fn remove(&mut self, key: &[u8]) {
         self.borrow_mut().remove(key)
     }
+    fn range(
+        &self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Vec<KVPair> {
+        self.borrow().range(start, end, order, limit)
+    }
+    fn write_batch(&mut self, batch: WriteBatch) {
+        self.borrow_mut().write_batch(batch)
+    }
+}
+
+impl<T: KVStore> TxKVStore for Rc<RefCell<T>> {
+    fn finalize(self: Box<Self>, _commit: bool) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// `prefix_end` returns the smallest key that is greater than all keys starting with `prefix`
+///
+/// If such a key does not exist (e.g. `prefix` is empty or consists of only `0xff`), it returns None.
+pub fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
+
+/// `btree_range` returns at most `limit` key-value pairs in `map` whose keys are in `[start, end)` sorted by key in `order`
+pub fn btree_range(
+    map: &BTreeMap<Vec<u8>, Vec<u8>>,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: Order,
+    limit: Option<usize>,
+) -> Vec<KVPair> {
+    let limit = limit.unwrap_or(usize::MAX);
+    let Some(bounds) = range_bounds(start, end) else {
+        return vec![];
+    };
+    let it = map.range::<[u8], _>(bounds);
+    match order {
+        Order::Ascending => it
+            .take(limit)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        Order::Descending => it
+            .rev()
+            .take(limit)
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+    }
+}
+
+/// `overlay_range` returns at most `limit` key-value pairs whose keys are in `[start, end)` sorted by key in `order`,
+/// where the entries of `overlay` take precedence over the pairs returned by `base`.
+///
+/// `None` in `overlay` indicates that the key has been removed.
+/// `base` is called with the number of pairs to fetch from the underlying store.
+pub(crate) fn overlay_range(
+    overlay: &BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+    start: Option<&[u8]>,
+    end: Option<&[u8]>,
+    order: Order,
+    limit: Option<usize>,
+    base: impl FnOnce(Option<usize>) -> Vec<KVPair>,
+) -> Vec<KVPair> {
+    let Some(bounds) = range_bounds(start, end) else {
+        return vec![];
+    };
+    let entries: Vec<_> = overlay.range::<[u8], _>(bounds).collect();
+    // each overlay entry hides at most one pair of `base`,
+    // so fetching `limit + entries.len()` pairs is enough to fill `limit` pairs
+    let mut merged: BTreeMap<Vec<u8>, Option<Vec<u8>>> =
+        base(limit.map(|l| l.saturating_add(entries.len())))
+            .into_iter()
+            .map(|(k, v)| (k, Some(v)))
+            .collect();
+    for (k, v) in entries {
+        merged.insert(k.clone(), v.clone());
+    }
+    let limit = limit.unwrap_or(usize::MAX);
+    let it = merged.into_iter().filter_map(|(k, v)| v.map(|v| (k, v)));
+    match order {
+        Order::Ascending => it.take(limit).collect(),
+        Order::Descending => it.rev().take(limit).collect(),
+    }
+}
+
+type KeyBounds<'a> = (Bound<&'a [u8]>, Bound<&'a [u8]>);
+
+/// `range_bounds` converts `[start, end)` into bounds. It returns None if the range is empty.
+fn range_bounds<'a>(start: Option<&'a [u8]>, end: Option<&'a [u8]>) -> Option<KeyBounds<'a>> {
+    match (start, end) {
+        (Some(start), Some(end)) if start >= end => None,
+        _ => Some((
+            start.map_or(Bound::Unbounded, Bound::Included),
+            end.map_or(Bound::Unbounded, Bound::Excluded),
+        )),
+    }
 }
 
 #[derive(
@@ -65,3 +270,16 @@ impl TxId {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_end() {
+        assert_eq!(prefix_end(b"ab"), Some(b"ac".to_vec()));
+        assert_eq!(prefix_end(&[0x01, 0xff, 0xff]), Some(vec![0x02]));
+        assert_eq!(prefix_end(&[0xff, 0xff]), None);
+        assert_eq!(prefix_end(&[]), None);
+    }
+}
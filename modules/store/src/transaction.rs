@@ -1,5 +1,5 @@
 use crate::prelude::*;
-use crate::{KVStore, Result, TxId};
+use crate::{KVPair, KVStore, Order, Result, TxId, WriteBatch};
 
 /// `UpdateKey` is a hint to the store to control concurrent transactions
 pub type UpdateKey = String;
@@ -60,4 +60,32 @@ pub trait TxAccessor {
     fn tx_remove(&mut self, tx_id: TxId, key: &[u8]) -> Result<()> {
         self.run_in_mut_tx(tx_id, |tx| tx.remove(key))
     }
+
+    /// `tx_write_batch` applies the writes in `batch` in a specified transaction
+    fn tx_write_batch(&mut self, tx_id: TxId, batch: WriteBatch) -> Result<()> {
+        self.run_in_mut_tx(tx_id, |tx| tx.write_batch(batch))
+    }
+
+    /// `tx_range` returns at most `limit` key-value pairs whose keys are in `[start, end)` sorted by key in `order` in a specified transaction
+    fn tx_range(
+        &self,
+        tx_id: TxId,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+        limit: Option<usize>,
+    ) -> Result<Vec<KVPair>> {
+        self.run_in_tx(tx_id, |tx| tx.range(start, end, order, limit))
+    }
+
+    /// `tx_prefix_range` returns at most `limit` key-value pairs whose keys start with `prefix` sorted by key in `order` in a specified transaction
+    fn tx_prefix_range(
+        &self,
+        tx_id: TxId,
+        prefix: &[u8],
+        order: Order,
+        limit: Option<usize>,
+    ) -> Result<Vec<KVPair>> {
+        self.run_in_tx(tx_id, |tx| tx.prefix_range(prefix, order, limit))
+    }
 }
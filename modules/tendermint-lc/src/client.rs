@@ -89,6 +89,7 @@ impl LightClient for TendermintLightClient {
                 timestamp,
                 context: ValidationContext::Empty,
                 emitted_states: vec![EmittedState(height, any_client_state)],
+                emitted_states_root: None,
             }
             .into(),
             prove: false,
@@ -318,6 +319,7 @@ impl TendermintLightClient {
                 )
                 .into(),
                 emitted_states: Default::default(),
+                emitted_states_root: None,
             },
             prove: true,
         })
@@ -17,7 +17,7 @@ use ibc::{
 };
 use lcp_proto::google::protobuf::Any;
 use tendermint_light_client_verifier::{
-    types::{TrustedBlockState, UntrustedBlockState},
+    types::{Time, TrustedBlockState, UntrustedBlockState},
     ProdVerifier, Verdict, Verifier,
 };
 
@@ -46,12 +46,7 @@ pub(crate) fn check_header_and_update_state(
         ClientConsensusStatePath::new(&client_id, &header.trusted_height);
     let trusted_consensus_state = downcast_tm_consensus_state(
         ctx.consensus_state(&trusted_client_cons_state_path)
-            .map_err(|e| match e {
-                ContextError::ClientError(e) => e,
-                _ => ClientError::Other {
-                    description: e.to_string(),
-                },
-            })?
+            .map_err(context_error)?
             .as_ref(),
     )?;
 
@@ -96,12 +91,82 @@ pub(crate) fn check_header_and_update_state(
         Verdict::Invalid(detail) => Err(Error::VerificationError { detail }),
     }?;
 
+    // Monotonicity checks for timestamps for in-the-middle updates
+    // (cs-new, cs-next, cs-latest)
+    let next_cs = if header.height() < client_state.latest_height() {
+        ctx.next_consensus_state(&client_id, &header.height())
+            .map_err(context_error)?
+            .as_ref()
+            .map(|cs| downcast_tm_consensus_state(cs.as_ref()))
+            .transpose()?
+    } else {
+        None
+    };
+    // (cs-trusted, cs-prev, cs-new)
+    let prev_cs = if header.trusted_height < header.height() {
+        ctx.prev_consensus_state(&client_id, &header.height())
+            .map_err(context_error)?
+            .as_ref()
+            .map(|cs| downcast_tm_consensus_state(cs.as_ref()))
+            .transpose()?
+    } else {
+        None
+    };
+    check_timestamp_monotonicity(
+        header.signed_header.header().time,
+        prev_cs.map(|cs| cs.timestamp),
+        next_cs.map(|cs| cs.timestamp),
+    )?;
+
     Ok(UpdatedState {
         client_state: client_state.with_header(header.clone())?.into_box(),
         consensus_state: TmConsensusState::from(header).into_box(),
     })
 }
 
+/// check_timestamp_monotonicity checks that the header timestamp is strictly after the timestamp of the previous consensus state
+/// and strictly before the timestamp of the next consensus state
+fn check_timestamp_monotonicity(
+    header_time: Time,
+    prev_timestamp: Option<Time>,
+    next_timestamp: Option<Time>,
+) -> Result<(), ClientError> {
+    if let Some(next_timestamp) = next_timestamp {
+        // New (untrusted) header timestamp cannot occur at or after next
+        // consensus state's height
+        if header_time >= next_timestamp {
+            return Err(ClientError::ClientSpecific {
+                description: format!(
+                    "header timestamp must be less than the timestamp of the next consensus state: actual={} next={}",
+                    header_time, next_timestamp
+                ),
+            });
+        }
+    }
+    if let Some(prev_timestamp) = prev_timestamp {
+        // New (untrusted) header timestamp cannot occur at or before the
+        // previous consensus state's height
+        if header_time <= prev_timestamp {
+            return Err(ClientError::ClientSpecific {
+                description: format!(
+                    "header timestamp must be greater than the timestamp of the previous consensus state: actual={} prev={}",
+                    header_time, prev_timestamp
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn context_error(e: ContextError) -> ClientError {
+    match e {
+        ContextError::ClientError(e) => e,
+        _ => ClientError::Other {
+            description: e.to_string(),
+        },
+    }
+}
+
 fn downcast_tm_client_state(cs: &dyn Ics2ClientState) -> Result<&ClientState, ClientError> {
     cs.as_any()
         .downcast_ref::<ClientState>()
@@ -118,3 +183,34 @@ fn downcast_tm_consensus_state(cs: &dyn ConsensusState) -> Result<TmConsensusSta
         })
         .cloned()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_timestamp_monotonicity() {
+        let time = |secs| Time::from_unix_timestamp(secs, 0).unwrap();
+
+        assert!(check_timestamp_monotonicity(time(20), None, None).is_ok());
+        assert!(check_timestamp_monotonicity(time(20), Some(time(10)), Some(time(30))).is_ok());
+        // the header must not have the same timestamp as its neighbors
+        assert!(check_timestamp_monotonicity(time(20), Some(time(20)), Some(time(30))).is_err());
+        assert!(check_timestamp_monotonicity(time(20), Some(time(10)), Some(time(20))).is_err());
+        assert!(check_timestamp_monotonicity(time(20), Some(time(20)), None).is_err());
+        assert!(check_timestamp_monotonicity(time(20), None, Some(time(20))).is_err());
+
+        // the header timestamp is after the next consensus state
+        assert!(matches!(
+            check_timestamp_monotonicity(time(31), Some(time(10)), Some(time(30))),
+            Err(ClientError::ClientSpecific { .. })
+        ));
+        assert!(check_timestamp_monotonicity(time(31), None, Some(time(30))).is_err());
+        // the header timestamp is before the previous consensus state
+        assert!(matches!(
+            check_timestamp_monotonicity(time(9), Some(time(10)), Some(time(30))),
+            Err(ClientError::ClientSpecific { .. })
+        ));
+        assert!(check_timestamp_monotonicity(time(9), Some(time(10)), None).is_err());
+    }
+}
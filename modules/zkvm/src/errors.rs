@@ -38,6 +38,14 @@ define_error! {
             format_args!("Groth16 verification error: {}", e.descr)
         },
 
+        Sp1VerificationError
+        {
+            descr: String
+        }
+        |e| {
+            format_args!("SP1 verification error: {}", e.descr)
+        },
+
         UnexpectedSelector
         {
             expected: Vec<u8>,
@@ -20,3 +20,23 @@ pub fn verify_groth16_proof(
         .verify_integrity()
         .map_err(|e| Error::groth16_verification_error(e.to_string()))
 }
+
+/// Verifies a SP1 Groth16 proof of the program identified by `vkey_hash`.
+///
+/// The first 4 bytes of `proof` must be the prefix of the Groth16 verification key hash, which is checked by the verifier.
+pub fn verify_sp1_groth16_proof(
+    proof: &[u8],
+    vkey_hash: [u8; 32],
+    public_values: &[u8],
+) -> Result<(), Error> {
+    let vkey_hash = vkey_hash
+        .iter()
+        .fold(String::from("0x"), |acc, b| acc + &format!("{:02x}", b));
+    sp1_verifier::Groth16Verifier::verify(
+        proof,
+        public_values,
+        &vkey_hash,
+        *sp1_verifier::GROTH16_VK_BYTES,
+    )
+    .map_err(|e| Error::sp1_verification_error(format!("{:?}", e)))
+}
@@ -44,6 +44,11 @@ pub mod ibc {
 }
 
 pub mod lcp {
+    pub mod commitments {
+        pub mod v1 {
+            include_proto!("lcp.commitments.v1.rs");
+        }
+    }
     pub mod service {
         pub mod enclave {
             pub mod v1 {
@@ -43,6 +43,39 @@ pub struct ZkdcapRegisterEnclaveKeyMessage {
     #[prost(bytes = "vec", tag = "4")]
     pub operator_signature: ::prost::alloc::vec::Vec<u8>,
 }
+/// A message to verify a DCAP quote and its collateral for the enclave key registration
+///
+/// Unlike `ZKDCAPRegisterEnclaveKeyMessage`, the quote is verified directly by the client.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DcapRegisterEnclaveKeyMessage {
+    /// A raw DCAP quote (version 3)
+    #[prost(bytes = "vec", tag = "1")]
+    pub quote: ::prost::alloc::vec::Vec<u8>,
+    /// Collateral used to verify the quote
+    #[prost(message, optional, tag = "2")]
+    pub collateral: ::core::option::Option<DcapCollateral>,
+    /// An operator's signature of the EIP-712 message `DCAPRegisterEnclaveKey`
+    #[prost(bytes = "vec", tag = "3")]
+    pub operator_signature: ::prost::alloc::vec::Vec<u8>,
+}
+/// Collateral for the DCAP quote verification, which is obtained from Intel PCS
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DcapCollateral {
+    #[prost(string, tag = "1")]
+    pub tcb_info_json: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub qe_identity_json: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "3")]
+    pub sgx_intel_root_ca_der: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub sgx_tcb_signing_der: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "5")]
+    pub sgx_intel_root_ca_crl_der: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub sgx_pck_crl_der: ::prost::alloc::vec::Vec<u8>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct UpdateOperatorsMessage {
@@ -62,6 +95,40 @@ pub struct UpdateOperatorsMessage {
     #[prost(bytes = "vec", repeated, tag = "5")]
     pub signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
 }
+/// A message to revoke an enclave key registered in the client
+///
+/// Once revoked, the enclave key is never accepted by the client even if it has not expired yet.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RevokeEnclaveKeyMessage {
+    /// An address of the enclave key to be revoked
+    #[prost(bytes = "vec", tag = "1")]
+    pub enclave_key: ::prost::alloc::vec::Vec<u8>,
+    /// Signatures of the EIP-712 message `RevokeEnclaveKey` by the current operators
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+/// A message to update the MRENCLAVE that the client accepts
+///
+/// The message shares the nonce with `UpdateOperatorsMessage`, i.e. `nonce` must be `operators_nonce + 1`.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateMrenclaveMessage {
+    /// A nonce for this update
+    #[prost(uint64, tag = "1")]
+    pub nonce: u64,
+    /// A new MRENCLAVE value
+    #[prost(bytes = "vec", tag = "2")]
+    pub new_mrenclave: ::prost::alloc::vec::Vec<u8>,
+    /// A period (in seconds) during which the current MRENCLAVE is still accepted after the update
+    ///
+    /// If this is zero, the current MRENCLAVE is no longer accepted immediately.
+    #[prost(uint64, tag = "3")]
+    pub transition_period: u64,
+    /// Signatures of the EIP-712 message `UpdateMrenclave` by the current operators
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub signatures: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ClientState {
@@ -189,15 +256,81 @@ pub struct ClientState {
     /// - First byte (0): zkVM type identifier.
     /// - Remaining bytes (1–N): zkVM-specific data.
     ///
-    /// Currently, only RISC Zero zkVM (type=1) is supported, with the following format:
+    /// Currently, RISC Zero zkVM (type=1) and SP1 zkVM (type=2) are supported, with the following format:
     ///
-    /// | Byte(s) | Description                 |
-    /// |---------|-----------------------------|
-    /// | 0       | zkVM type (fixed as 1)      |
-    /// | 1–31    | Reserved (set as zero)      |
-    /// | 32–63   | Image ID                    |
+    /// | Byte(s) | Description                                           |
+    /// |---------|-------------------------------------------------------|
+    /// | 0       | zkVM type (1 or 2)                                    |
+    /// | 1–31    | Reserved (set as zero)                                |
+    /// | 32–63   | Image ID (RISC Zero) or verification key hash (SP1)   |
+    ///
+    /// Multiple verifiers can be listed, and a proof is accepted if any of the verifiers of the message's zkVM type accepts it.
+    /// The same verifier info must not be listed more than once.
     #[prost(bytes = "vec", repeated, tag = "15")]
     pub zkdcap_verifier_infos: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    /// The MRENCLAVE that was replaced by the last `UpdateMrenclaveMessage`
+    ///
+    /// New EKs with this value can be registered until `previous_mrenclave_expiration_time`.
+    /// The EKs registered with it, including the ones registered before the update, are rejected from
+    /// `previous_mrenclave_expiration_time` even if they have not expired yet. If the update had no
+    /// transition period, they are rejected immediately.
+    #[prost(bytes = "vec", tag = "16")]
+    pub previous_mrenclave: ::prost::alloc::vec::Vec<u8>,
+    /// The time until which `previous_mrenclave` is accepted (UNIX time seconds)
+    ///
+    /// Notes:
+    /// - Must be zero if and only if `previous_mrenclave` is empty.
+    #[prost(uint64, tag = "17")]
+    pub previous_mrenclave_expiration_time: u64,
+    /// The maximum number of consensus states that the client retains
+    ///
+    /// When the number of consensus states exceeds this value, the ones with the lowest heights are pruned during the client update.
+    /// If this is zero, the consensus states are not pruned by count.
+    #[prost(uint64, tag = "18")]
+    pub max_consensus_states: u64,
+    /// The period (in seconds) for which the client retains a consensus state
+    ///
+    /// A consensus state whose timestamp is older than the current time minus this period is pruned during the client update.
+    /// If this is zero, the consensus states are not pruned by age.
+    /// Note that the consensus state at the latest height is never pruned.
+    #[prost(uint64, tag = "19")]
+    pub consensus_state_retention_period: u64,
+    /// Additional MRENCLAVE values that the client accepts besides `mrenclave`
+    ///
+    /// This allows operators to run multiple enclave builds side by side.
+    /// Each value must be unique and must not be equal to `mrenclave`.
+    #[prost(message, repeated, tag = "20")]
+    pub additional_mrenclaves: ::prost::alloc::vec::Vec<AllowedMrenclave>,
+    /// The chain id of the chain on which the client is deployed
+    ///
+    /// If non-empty, the client only accepts proxy messages of schema version 2
+    /// that are bound to this chain id and the client's own client id.
+    /// If empty, the client also accepts proxy messages of schema version 1.
+    #[prost(string, tag = "21")]
+    pub chain_id: ::prost::alloc::string::String,
+    /// The chain id of the EIP-712 domain used for the operator signatures
+    ///
+    /// If both `eip712_chain_id` and `eip712_verifying_contract` are zero, the default domain is used.
+    #[prost(uint64, tag = "22")]
+    pub eip712_chain_id: u64,
+    /// The verifying contract address of the EIP-712 domain used for the operator signatures
+    ///
+    /// If empty, the zero address is used.
+    #[prost(bytes = "vec", tag = "23")]
+    pub eip712_verifying_contract: ::prost::alloc::vec::Vec<u8>,
+}
+/// An MRENCLAVE value allowed by the client
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AllowedMrenclave {
+    /// The MRENCLAVE value
+    #[prost(bytes = "vec", tag = "1")]
+    pub mrenclave: ::prost::alloc::vec::Vec<u8>,
+    /// The key expiration (in seconds) for the EKs registered with this MRENCLAVE
+    ///
+    /// If this is zero, the client's `key_expiration` is used.
+    #[prost(uint64, tag = "2")]
+    pub key_expiration: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -211,4 +344,8 @@ pub struct ConsensusState {
     /// expressed in UNIX time (seconds).
     #[prost(uint64, tag = "2")]
     pub timestamp: u64,
+    /// The merkle root of the states emitted by the update at the consensus height;
+    /// empty if the update carried the emitted states inline.
+    #[prost(bytes = "vec", tag = "3")]
+    pub emitted_states_root: ::prost::alloc::vec::Vec<u8>,
 }
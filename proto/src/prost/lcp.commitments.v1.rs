@@ -0,0 +1,209 @@
+/// HeaderedProxyMessage is the protobuf encoding of a proxy message.
+///
+/// The header has the same layout as the EthABI encoding:
+/// 0-1: schema version, 2-3: message type, 4-5: message encoding, 6-31: reserved.
+/// `message` is one of the proxy messages below, or a `DomainBoundMessage` if the schema version is 2.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HeaderedProxyMessage {
+    #[prost(bytes = "vec", tag = "1")]
+    pub header: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+}
+/// DomainBoundMessage binds a proxy message to the LCP client that verifies it.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DomainBoundMessage {
+    #[prost(string, tag = "1")]
+    pub chain_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "3")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateStateProxyMessage {
+    /// unset if the update does not have a previous state
+    #[prost(message, optional, tag = "1")]
+    pub prev_height: ::core::option::Option<
+        super::super::super::ibc::core::client::v1::Height,
+    >,
+    /// empty if the update does not have a previous state
+    #[prost(bytes = "vec", tag = "2")]
+    pub prev_state_id: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub post_height: ::core::option::Option<
+        super::super::super::ibc::core::client::v1::Height,
+    >,
+    #[prost(bytes = "vec", tag = "4")]
+    pub post_state_id: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "5")]
+    pub timestamp: ::core::option::Option<
+        super::super::super::google::protobuf::Timestamp,
+    >,
+    #[prost(message, optional, tag = "6")]
+    pub context: ::core::option::Option<ValidationContext>,
+    #[prost(message, repeated, tag = "7")]
+    pub emitted_states: ::prost::alloc::vec::Vec<EmittedState>,
+    /// merkle root of the emitted states; empty if the emitted states are carried inline
+    #[prost(bytes = "vec", tag = "8")]
+    pub emitted_states_root: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EmittedState {
+    #[prost(message, optional, tag = "1")]
+    pub height: ::core::option::Option<
+        super::super::super::ibc::core::client::v1::Height,
+    >,
+    #[prost(message, optional, tag = "2")]
+    pub state: ::core::option::Option<super::super::super::google::protobuf::Any>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembershipProxyMessage {
+    #[prost(bytes = "vec", tag = "1")]
+    pub prefix: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub path: ::prost::alloc::string::String,
+    /// hash of the value, empty for non-membership
+    #[prost(bytes = "vec", tag = "3")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "4")]
+    pub height: ::core::option::Option<
+        super::super::super::ibc::core::client::v1::Height,
+    >,
+    #[prost(bytes = "vec", tag = "5")]
+    pub state_id: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembershipBatchProxyMessage {
+    #[prost(bytes = "vec", tag = "1")]
+    pub prefix: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, repeated, tag = "2")]
+    pub entries: ::prost::alloc::vec::Vec<MembershipEntry>,
+    #[prost(message, optional, tag = "3")]
+    pub height: ::core::option::Option<
+        super::super::super::ibc::core::client::v1::Height,
+    >,
+    #[prost(bytes = "vec", tag = "4")]
+    pub state_id: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MembershipEntry {
+    #[prost(string, tag = "1")]
+    pub path: ::prost::alloc::string::String,
+    /// hash of the value, empty for non-membership
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MisbehaviourProxyMessage {
+    #[prost(message, repeated, tag = "1")]
+    pub prev_states: ::prost::alloc::vec::Vec<PrevState>,
+    #[prost(message, optional, tag = "2")]
+    pub context: ::core::option::Option<ValidationContext>,
+    #[prost(message, optional, tag = "3")]
+    pub client_message: ::core::option::Option<
+        super::super::super::google::protobuf::Any,
+    >,
+}
+/// ClientMessages is the client message of a misbehaviour message that aggregates the evidence of multiple misbehaviour messages
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClientMessages {
+    #[prost(message, repeated, tag = "1")]
+    pub client_messages: ::prost::alloc::vec::Vec<
+        super::super::super::google::protobuf::Any,
+    >,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PrevState {
+    #[prost(message, optional, tag = "1")]
+    pub height: ::core::option::Option<
+        super::super::super::ibc::core::client::v1::Height,
+    >,
+    #[prost(bytes = "vec", tag = "2")]
+    pub state_id: ::prost::alloc::vec::Vec<u8>,
+}
+/// ValidationContext is empty if no context is set.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidationContext {
+    #[prost(oneof = "validation_context::Context", tags = "1, 2")]
+    pub context: ::core::option::Option<validation_context::Context>,
+}
+/// Nested message and enum types in `ValidationContext`.
+pub mod validation_context {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Context {
+        #[prost(message, tag = "1")]
+        TrustingPeriod(super::TrustingPeriodContext),
+        #[prost(message, tag = "2")]
+        HostHeight(super::HostHeightContext),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TrustingPeriodContext {
+    #[prost(message, optional, tag = "1")]
+    pub trusting_period: ::core::option::Option<
+        super::super::super::google::protobuf::Duration,
+    >,
+    #[prost(message, optional, tag = "2")]
+    pub clock_drift: ::core::option::Option<
+        super::super::super::google::protobuf::Duration,
+    >,
+    #[prost(message, optional, tag = "3")]
+    pub untrusted_header_timestamp: ::core::option::Option<
+        super::super::super::google::protobuf::Timestamp,
+    >,
+    #[prost(message, optional, tag = "4")]
+    pub trusted_state_timestamp: ::core::option::Option<
+        super::super::super::google::protobuf::Timestamp,
+    >,
+}
+/// HostHeightContext bounds the validity of a message by the height of the host chain
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HostHeightContext {
+    /// the last height of the host chain at which the message is valid
+    #[prost(message, optional, tag = "1")]
+    pub valid_until: ::core::option::Option<
+        super::super::super::ibc::core::client::v1::Height,
+    >,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CommitmentProof {
+    #[prost(bytes = "vec", tag = "1")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
+/// MerkleProof is an inclusion proof of an emitted state in the emitted states root
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MerkleProof {
+    /// sibling hashes from the leaf to the root
+    #[prost(message, repeated, tag = "1")]
+    pub path: ::prost::alloc::vec::Vec<MerkleProofNode>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MerkleProofNode {
+    /// true if the sibling is the left child of the parent
+    #[prost(bool, tag = "1")]
+    pub left: bool,
+    #[prost(bytes = "vec", tag = "2")]
+    pub sibling: ::prost::alloc::vec::Vec<u8>,
+}
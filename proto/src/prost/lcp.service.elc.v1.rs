@@ -316,6 +316,15 @@ pub struct MsgUpdateClient {
     /// enclave key for signing
     #[prost(bytes = "vec", tag = "4")]
     pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// domain that the proxy message is bound to (optional)
+    #[prost(message, optional, tag = "5")]
+    pub domain: ::core::option::Option<MessageDomain>,
+    /// encoding of the proxy message body
+    #[prost(enumeration = "MessageEncoding", tag = "6")]
+    pub encoding: i32,
+    /// if true, the emitted states are committed by their merkle root instead of inline
+    #[prost(bool, tag = "7")]
+    pub commit_emitted_states_root: bool,
 }
 /// MsgUpdateClientResponse defines the Msg/UpdateClient response type.
 #[derive(::serde::Serialize, ::serde::Deserialize)]
@@ -326,6 +335,11 @@ pub struct MsgUpdateClientResponse {
     pub message: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "2")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// inclusion proofs of the emitted states in the emitted states root; empty if the states are inline
+    #[prost(message, repeated, tag = "3")]
+    pub emitted_state_proofs: ::prost::alloc::vec::Vec<
+        super::super::super::commitments::v1::MerkleProof,
+    >,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -346,6 +360,11 @@ pub struct MsgAggregateMessagesResponse {
     pub message: ::prost::alloc::vec::Vec<u8>,
     #[prost(bytes = "vec", tag = "2")]
     pub signature: ::prost::alloc::vec::Vec<u8>,
+    /// inclusion proofs of each message's emitted states root in the aggregated root
+    #[prost(message, repeated, tag = "3")]
+    pub emitted_states_root_proofs: ::prost::alloc::vec::Vec<
+        super::super::super::commitments::v1::MerkleProof,
+    >,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -368,6 +387,12 @@ pub struct MsgVerifyMembership {
     /// enclave key for signing
     #[prost(bytes = "vec", tag = "7")]
     pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// domain that the proxy message is bound to (optional)
+    #[prost(message, optional, tag = "8")]
+    pub domain: ::core::option::Option<MessageDomain>,
+    /// encoding of the proxy message body
+    #[prost(enumeration = "MessageEncoding", tag = "9")]
+    pub encoding: i32,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -397,6 +422,12 @@ pub struct MsgVerifyNonMembership {
     /// enclave key for signing
     #[prost(bytes = "vec", tag = "6")]
     pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// domain that the proxy message is bound to (optional)
+    #[prost(message, optional, tag = "7")]
+    pub domain: ::core::option::Option<MessageDomain>,
+    /// encoding of the proxy message body
+    #[prost(enumeration = "MessageEncoding", tag = "8")]
+    pub encoding: i32,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -410,6 +441,72 @@ pub struct MsgVerifyNonMembershipResponse {
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgVerifyMembershipBatch {
+    #[prost(string, tag = "1")]
+    pub client_id: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub prefix: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub proof_height: ::core::option::Option<
+        super::super::super::super::ibc::core::client::v1::Height,
+    >,
+    /// commitments to be verified at `proof_height`
+    #[prost(message, repeated, tag = "4")]
+    pub items: ::prost::alloc::vec::Vec<VerifyMembershipBatchItem>,
+    /// enclave key for signing
+    #[prost(bytes = "vec", tag = "5")]
+    pub signer: ::prost::alloc::vec::Vec<u8>,
+    /// domain that the proxy message is bound to (optional)
+    #[prost(message, optional, tag = "6")]
+    pub domain: ::core::option::Option<MessageDomain>,
+    /// encoding of the proxy message body
+    #[prost(enumeration = "MessageEncoding", tag = "7")]
+    pub encoding: i32,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyMembershipBatchItem {
+    #[prost(string, tag = "1")]
+    pub path: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub proof: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgVerifyMembershipBatchResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
+/// MsgCountersignUpdateClient defines a message to re-verify a proxy message produced by another LCP node and countersign it
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgCountersignUpdateClient {
+    /// the update request processed by the other LCP node; `signer` is the enclave key of this node
+    #[prost(message, optional, tag = "1")]
+    pub update: ::core::option::Option<MsgUpdateClient>,
+    /// the proxy message produced by the other LCP node
+    #[prost(bytes = "vec", tag = "2")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgCountersignUpdateClientResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub message: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MsgUpdateClientStreamChunk {
     #[prost(oneof = "msg_update_client_stream_chunk::Chunk", tags = "1, 2")]
     pub chunk: ::core::option::Option<msg_update_client_stream_chunk::Chunk>,
@@ -438,6 +535,15 @@ pub struct UpdateClientStreamInit {
     pub signer: ::prost::alloc::vec::Vec<u8>,
     #[prost(string, tag = "4")]
     pub type_url: ::prost::alloc::string::String,
+    /// domain that the proxy message is bound to (optional)
+    #[prost(message, optional, tag = "5")]
+    pub domain: ::core::option::Option<MessageDomain>,
+    /// encoding of the proxy message body
+    #[prost(enumeration = "MessageEncoding", tag = "6")]
+    pub encoding: i32,
+    /// if true, the emitted states are committed by their merkle root instead of inline
+    #[prost(bool, tag = "7")]
+    pub commit_emitted_states_root: bool,
 }
 #[derive(::serde::Serialize, ::serde::Deserialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -446,6 +552,48 @@ pub struct UpdateClientStreamHeaderChunk {
     #[prost(bytes = "vec", tag = "1")]
     pub data: ::prost::alloc::vec::Vec<u8>,
 }
+/// MessageDomain identifies the LCP client on the counterparty chain that verifies the proxy message.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MessageDomain {
+    /// chain id of the counterparty chain
+    #[prost(string, tag = "1")]
+    pub chain_id: ::prost::alloc::string::String,
+    /// client id of the LCP client on the counterparty chain
+    #[prost(string, tag = "2")]
+    pub client_id: ::prost::alloc::string::String,
+}
+/// MessageEncoding is the encoding of the proxy message body.
+#[derive(::serde::Serialize, ::serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum MessageEncoding {
+    /// Ethereum ABI encoding
+    Ethabi = 0,
+    /// Protocol Buffers encoding
+    Protobuf = 1,
+}
+impl MessageEncoding {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            MessageEncoding::Ethabi => "MESSAGE_ENCODING_ETHABI",
+            MessageEncoding::Protobuf => "MESSAGE_ENCODING_PROTOBUF",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "MESSAGE_ENCODING_ETHABI" => Some(Self::Ethabi),
+            "MESSAGE_ENCODING_PROTOBUF" => Some(Self::Protobuf),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 #[cfg(feature = "client")]
 pub mod msg_client {
@@ -647,6 +795,52 @@ pub mod msg_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// VerifyMembershipBatch defines a rpc handler method for MsgVerifyMembershipBatch
+        pub async fn verify_membership_batch(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MsgVerifyMembershipBatch>,
+        ) -> Result<
+            tonic::Response<super::MsgVerifyMembershipBatchResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/lcp.service.elc.v1.Msg/VerifyMembershipBatch",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// CountersignUpdateClient defines a rpc handler method for MsgCountersignUpdateClient
+        pub async fn countersign_update_client(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MsgCountersignUpdateClient>,
+        ) -> Result<
+            tonic::Response<super::MsgCountersignUpdateClientResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/lcp.service.elc.v1.Msg/CountersignUpdateClient",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -690,6 +884,22 @@ pub mod msg_server {
             tonic::Response<super::MsgVerifyNonMembershipResponse>,
             tonic::Status,
         >;
+        /// VerifyMembershipBatch defines a rpc handler method for MsgVerifyMembershipBatch
+        async fn verify_membership_batch(
+            &self,
+            request: tonic::Request<super::MsgVerifyMembershipBatch>,
+        ) -> Result<
+            tonic::Response<super::MsgVerifyMembershipBatchResponse>,
+            tonic::Status,
+        >;
+        /// CountersignUpdateClient defines a rpc handler method for MsgCountersignUpdateClient
+        async fn countersign_update_client(
+            &self,
+            request: tonic::Request<super::MsgCountersignUpdateClient>,
+        ) -> Result<
+            tonic::Response<super::MsgCountersignUpdateClientResponse>,
+            tonic::Status,
+        >;
     }
     /// Msg defines the ELC Msg service.
     #[derive(Debug)]
@@ -986,6 +1196,86 @@ pub mod msg_server {
                     };
                     Box::pin(fut)
                 }
+                "/lcp.service.elc.v1.Msg/VerifyMembershipBatch" => {
+                    #[allow(non_camel_case_types)]
+                    struct VerifyMembershipBatchSvc<T: Msg>(pub Arc<T>);
+                    impl<
+                        T: Msg,
+                    > tonic::server::UnaryService<super::MsgVerifyMembershipBatch>
+                    for VerifyMembershipBatchSvc<T> {
+                        type Response = super::MsgVerifyMembershipBatchResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MsgVerifyMembershipBatch>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).verify_membership_batch(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = VerifyMembershipBatchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/lcp.service.elc.v1.Msg/CountersignUpdateClient" => {
+                    #[allow(non_camel_case_types)]
+                    struct CountersignUpdateClientSvc<T: Msg>(pub Arc<T>);
+                    impl<
+                        T: Msg,
+                    > tonic::server::UnaryService<super::MsgCountersignUpdateClient>
+                    for CountersignUpdateClientSvc<T> {
+                        type Response = super::MsgCountersignUpdateClientResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MsgCountersignUpdateClient>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).countersign_update_client(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CountersignUpdateClientSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -346,6 +346,9 @@ mod tests {
                 any_header: target_header,
                 include_state: true,
                 signer,
+                domain: None,
+                encoding: Default::default(),
+                commit_emitted_states_root: false,
             })?;
             info!("update_client's result is {:?}", res);
             assert!(res.0.is_proven());
@@ -375,6 +378,8 @@ mod tests {
                 value: res.0.encode_vec()?,
                 proof: CommitmentProofPair(res.2.into(), merkle_proof_to_bytes(res.1)?),
                 signer,
+                domain: None,
+                encoding: Default::default(),
             })?;
         }
 
@@ -389,6 +394,9 @@ mod tests {
                     any_header: target_header,
                     include_state: false,
                     signer,
+                    domain: None,
+                    encoding: Default::default(),
+                    commit_emitted_states_root: false,
                 })?;
                 info!("update_client's result is {:?}", res);
                 lh = target_height;